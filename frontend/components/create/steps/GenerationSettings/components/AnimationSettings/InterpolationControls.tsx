@@ -4,15 +4,22 @@ import { InfoIcon } from '@/components/icons';
 import { NumericInputWithButtons } from '@/components/shared/NumericInputWithButtons';
 import Dropdown from '@/components/shared/Dropdown';
 import CheckboxWithLabel from '@/components/shared/CheckboxWithLabel';
-import type { WebMSettings, MP4Settings, WebPSettings, GIFSettings } from '@/types/effect';
+import type {
+  WebMSettings,
+  MP4Settings,
+  WebPSettings,
+  GIFSettings,
+  APNGSettings,
+} from '@/types/effect';
 
-type FormatType = 'webm' | 'mp4' | 'webp' | 'gif';
+type FormatType = 'webm' | 'mp4' | 'webp' | 'gif' | 'apng';
 
 interface FormatSpecificSettings {
   webm: WebMSettings;
   mp4: MP4Settings;
   webp: WebPSettings;
   gif: GIFSettings;
+  apng: APNGSettings;
 }
 
 const interpolationMethods = [
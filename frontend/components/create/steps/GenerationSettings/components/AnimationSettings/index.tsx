@@ -6,10 +6,12 @@ import type {
   MP4Settings,
   WebMSettings,
   WebPSettings,
+  APNGSettings,
   AnimationQualityConfig,
 } from '@/types/effect';
 
 import { useGenerationSettingsStore } from '@/components/store/generationsettings';
+import { defaultAnimationQuality } from '@/components/store/generationsettings/default/defaultAnimationQuality';
 import { useLayerOrder } from '@/components/store/layerOrder/hook';
 import { useProjectSetup } from '@/components/store/projectSetup/hook';
 
@@ -20,7 +22,7 @@ import { InfoIcon } from '@/components/icons';
 import { DitheringControls } from './DitheringControls';
 import { InterpolationControls } from './InterpolationControls';
 
-type FormatType = 'webm' | 'mp4' | 'webp' | 'gif';
+type FormatType = 'webm' | 'mp4' | 'webp' | 'gif' | 'apng';
 
 export const AnimationSettings: React.FC<{
   transitionVariants: {
@@ -52,6 +54,7 @@ export const AnimationSettings: React.FC<{
       | MP4Settings
       | WebPSettings
       | GIFSettings
+      | APNGSettings
       | undefined;
 
     if (currentSettings?.interpolation?.enabled) {
@@ -75,8 +78,11 @@ export const AnimationSettings: React.FC<{
       key: string,
       value: boolean | string | number | Record<string, unknown>
     ) => {
-      const currentSettings = animationQuality?.formatSpecificSettings[format];
-      if (currentSettings) {
+      // Projects saved before APNG was supported have no settings for it yet.
+      const currentSettings =
+        animationQuality?.formatSpecificSettings[format] ??
+        defaultAnimationQuality.formatSpecificSettings[format];
+      if (animationQuality && currentSettings) {
         const updatedSettings = { ...currentSettings, [key]: value };
         updateAnimationQuality({
           formatSpecificSettings: {
@@ -94,7 +100,9 @@ export const AnimationSettings: React.FC<{
     key: string,
     value: boolean | string | number
   ) => {
-    const currentSettings = animationQuality?.formatSpecificSettings[format];
+    const currentSettings =
+      animationQuality?.formatSpecificSettings[format] ??
+      defaultAnimationQuality.formatSpecificSettings[format];
     if (currentSettings?.interpolation) {
       const updatedInterpolation = {
         ...currentSettings.interpolation,
@@ -344,7 +352,9 @@ export const AnimationSettings: React.FC<{
       </motion.div>
 
       <AnimatePresence mode="wait">
-        {(['webm', 'mp4', 'webp', 'gif'] as const).includes(imageFormat as FormatType) && (
+        {(['webm', 'mp4', 'webp', 'gif', 'apng'] as const).includes(
+          imageFormat as FormatType
+        ) && (
           <motion.div
             key={`interpolation-${imageFormat}`}
             layout
@@ -355,7 +365,10 @@ export const AnimationSettings: React.FC<{
             className="mt-6"
           >
             <InterpolationControls
-              settings={animationQuality?.formatSpecificSettings[imageFormat as FormatType]}
+              settings={
+                animationQuality?.formatSpecificSettings[imageFormat as FormatType] ??
+                defaultAnimationQuality.formatSpecificSettings[imageFormat as FormatType]
+              }
               onSettingChange={(key, value) =>
                 handleFormatSpecificInterpolationChange(imageFormat as FormatType, key, value)
               }
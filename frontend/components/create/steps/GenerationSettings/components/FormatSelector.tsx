@@ -13,7 +13,7 @@ export const FormatSelector: React.FC<FormatSelectorProps> = ({ transitionVarian
   const { isAnimatedCollection } = useProjectSetup();
 
   const availableFormats = useMemo(() => {
-    return isAnimatedCollection ? ['mp4', 'webp', 'webm', 'gif'] : ['png', 'jpg', 'webp'];
+    return isAnimatedCollection ? ['mp4', 'webp', 'webm', 'gif', 'apng'] : ['png', 'jpg', 'webp'];
   }, [isAnimatedCollection]);
 
   useEffect(() => {
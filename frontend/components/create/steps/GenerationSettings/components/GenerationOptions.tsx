@@ -1,12 +1,15 @@
 import React from 'react';
 import { motion, Variants } from 'framer-motion';
 import Toggle from '@/components/shared/Toggle';
+import type { PackageFormat, PackagingConfig } from '@/types/effect';
 
 interface GenerationOptionsProps {
   shuffleSets: boolean;
   allowDuplicates: boolean;
   setShuffleSets: (value: boolean) => void;
   setAllowDuplicates: (value: boolean) => void;
+  packaging: PackagingConfig | undefined;
+  updatePackaging: (config: Partial<PackagingConfig>) => void;
   transitionVariants: Variants;
 }
 
@@ -15,8 +18,12 @@ export const GenerationOptions: React.FC<GenerationOptionsProps> = ({
   allowDuplicates,
   setShuffleSets,
   setAllowDuplicates,
+  packaging,
+  updatePackaging,
   transitionVariants,
 }) => {
+  const packagingEnabled = packaging?.enabled ?? false;
+
   return (
     <motion.div
       initial="hidden"
@@ -51,6 +58,40 @@ export const GenerationOptions: React.FC<GenerationOptionsProps> = ({
         />
         <span className="ml-3 font-bold text-gray-700 dark:text-gray-300">Allow Duplicates</span>
       </div>
+      <div className="flex items-center">
+        <Toggle
+          checked={packagingEnabled}
+          onChange={() => updatePackaging({ enabled: !packagingEnabled })}
+          size="md"
+          activeColor="bg-[rgb(var(--color-secondary))]"
+          inactiveColor="bg-gray-300 dark:bg-gray-600"
+          thumbColor="bg-[rgb(var(--color-primary))]"
+        />
+        <span className="ml-3 font-bold text-gray-700 dark:text-gray-300">Package Collection</span>
+      </div>
+      {packagingEnabled && (
+        <div className="flex items-center gap-2 pl-2">
+          <select
+            value={packaging?.format ?? 'zip'}
+            onChange={(e) => updatePackaging({ format: e.target.value as PackageFormat })}
+            className="px-3 py-1.5 rounded-sm"
+          >
+            <option value="zip">Zip</option>
+            <option value="tarZst">tar.zst</option>
+          </select>
+          <input
+            type="number"
+            min="1"
+            value={packaging?.maxVolumeMb ?? ''}
+            onChange={(e) => {
+              const value = parseInt(e.target.value);
+              updatePackaging({ maxVolumeMb: value > 0 ? value : undefined });
+            }}
+            placeholder="Max volume (MB)"
+            className="w-40 px-3 py-1.5 rounded-sm"
+          />
+        </div>
+      )}
     </motion.div>
   );
 };
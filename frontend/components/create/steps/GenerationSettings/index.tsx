@@ -45,6 +45,8 @@ const GenerationSetup: React.FC = () => {
       allowDuplicates: generationSettingsStore.allowDuplicates,
       setShuffleSets: generationSettingsStore.setShuffleSets,
       setAllowDuplicates: generationSettingsStore.setAllowDuplicates,
+      packaging: generationSettingsStore.packaging,
+      updatePackaging: generationSettingsStore.updatePackaging,
     }),
     [generationSettingsStore]
   );
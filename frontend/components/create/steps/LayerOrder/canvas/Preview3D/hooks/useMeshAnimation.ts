@@ -3,7 +3,7 @@ import { useRef } from 'react';
 import * as THREE from 'three';
 import { useLayerOrder } from '@/components/store/layerOrder/hook';
 import { useProjectSetup } from '@/components/store/projectSetup/hook';
-import { calculateTotalFrames } from '../../utils/spritesheetUtils';
+import { calculateTotalFrames, fitMeshToCell } from '../../utils/spritesheetUtils';
 
 interface UseMeshAnimationProps {
   meshesRef: React.RefObject<THREE.Mesh[]>;
@@ -86,6 +86,7 @@ export const useMeshAnimation = ({ meshesRef }: UseMeshAnimationProps) => {
           material.map.offset.set(col / cols, 1 - (row + 1) / rows);
           material.map.repeat.set(1 / cols, 1 / rows);
           material.map.needsUpdate = true;
+          fitMeshToCell(mesh, currentSpritesheet);
         }
       }
     });
@@ -3,6 +3,7 @@ import * as THREE from 'three';
 import { useThree } from '@react-three/fiber';
 import { useLayerOrder } from '@/components/store/layerOrder/hook';
 import { useProjectSetup } from '@/components/store/projectSetup/hook';
+import { fitMeshToCell } from '../../utils/spritesheetUtils';
 
 interface UseMeshManagementProps {
   meshesRef: React.RefObject<THREE.Mesh[]>;
@@ -187,6 +188,7 @@ export const useMeshManagement = ({
                       texture.needsUpdate = true;
                       material.needsUpdate = true;
                     }
+                    fitMeshToCell(mesh, spritesheets[0]);
 
                     mesh.userData.currentFrame = 0;
                     mesh.userData.lastFrameTime = performance.now();
@@ -285,6 +287,7 @@ export const useMeshManagement = ({
                   texture.needsUpdate = true;
                   material.needsUpdate = true;
                 }
+                fitMeshToCell(mesh, spritesheets[0]);
               }
             }
           } else {
@@ -350,10 +353,17 @@ export const useMeshManagement = ({
           mesh.position.y = -offset.y / 100;
           mesh.userData.offsetX = offset.x;
           mesh.userData.offsetY = offset.y;
+
+          const spritesheets = isAnimatedCollection
+            ? framesByLayer[layerName]?.[traitName]
+            : undefined;
+          if (spritesheets?.length) {
+            fitMeshToCell(mesh, spritesheets[0]);
+          }
         }
       }
     }
-  }, [rarityConfig, meshesRef, getOffset]);
+  }, [rarityConfig, meshesRef, getOffset, isAnimatedCollection, framesByLayer]);
 
   return { setupComplete };
 };
@@ -6,6 +6,7 @@ import { useProjectSetup } from '@/components/store/projectSetup/hook';
 import { useLayerOrder } from '@/components/store/layerOrder/hook';
 
 import { useImageConfigs } from '../../hooks/useImageConfigs';
+import { getSpritesheetCell } from '../../utils/spritesheetUtils';
 
 export const AnimatedCanvas: React.FC<{
   images: PreviewImage[];
@@ -69,9 +70,10 @@ export const AnimatedCanvas: React.FC<{
 
       const col = localFrameNum % cols;
       const row = Math.floor(localFrameNum / cols);
+      const cell = getSpritesheetCell(currentSpritesheet.layout);
 
-      const sx = col * config.frameWidth;
-      const sy = row * config.frameHeight;
+      const sx = col * cell.width;
+      const sy = row * cell.height;
 
       const scaleX = Math.floor(config.destWidth / config.frameWidth);
       const scaleY = Math.floor(config.destHeight / config.frameHeight);
@@ -80,8 +82,8 @@ export const AnimatedCanvas: React.FC<{
       const scaledWidth = config.frameWidth * scale;
       const scaledHeight = config.frameHeight * scale;
 
-      const x = config.destX + (config.destWidth - scaledWidth) / 2;
-      const y = config.destY + (config.destHeight - scaledHeight) / 2;
+      const x = config.destX + (config.destWidth - scaledWidth) / 2 + cell.x * scale;
+      const y = config.destY + (config.destHeight - scaledHeight) / 2 + cell.y * scale;
 
       ctx.save();
       if (config.opacity !== 1) {
@@ -96,12 +98,12 @@ export const AnimatedCanvas: React.FC<{
           currentSpritesheet.image,
           sx,
           sy,
-          config.frameWidth,
-          config.frameHeight,
+          cell.width,
+          cell.height,
           x,
           y,
-          scaledWidth,
-          scaledHeight
+          cell.width * scale,
+          cell.height * scale
         );
       }
       ctx.restore();
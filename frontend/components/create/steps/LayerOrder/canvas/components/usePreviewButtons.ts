@@ -3,7 +3,7 @@ import { useLayerOrder } from '@/components/store/layerOrder/hook';
 import { useProjectSetup } from '@/components/store/projectSetup/hook';
 import { useLayerOrderZoomStore } from '@/components/windows/layerOrderZoom/store';
 import { listen, emit } from '@tauri-apps/api/event';
-import { calculateTotalFrames } from '../utils/spritesheetUtils';
+import { calculateTotalFrames, getSpritesheetCell } from '../utils/spritesheetUtils';
 
 interface UsePreviewButtonsProps {
   setViewMode: (mode: '2d' | '3d') => void;
@@ -115,6 +115,7 @@ export const usePreviewButtons = ({
               totalFrames,
               spritesheetCols: firstSpritesheet?.layout?.cols,
               spritesheetRows: firstSpritesheet?.layout?.rows,
+              spritesheetCell: firstSpritesheet && getSpritesheetCell(firstSpritesheet.layout),
               opacity: blendConfig?.opacity ?? 1,
               blendMode: blendConfig?.mode ?? 'source-over',
             };
@@ -186,6 +187,7 @@ export const usePreviewButtons = ({
           totalFrames,
           spritesheetCols: firstSpritesheet?.layout?.cols,
           spritesheetRows: firstSpritesheet?.layout?.rows,
+          spritesheetCell: firstSpritesheet && getSpritesheetCell(firstSpritesheet.layout),
           opacity: blendConfig?.opacity ?? 1,
           blendMode: blendConfig?.mode ?? 'source-over',
         };
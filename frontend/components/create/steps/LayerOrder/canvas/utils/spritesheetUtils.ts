@@ -1,4 +1,6 @@
-import { SpriteSheetData } from '@/types/cannevasTypes';
+import * as THREE from 'three';
+
+import { SpriteSheetData, SpriteSheetLayout, SpritesheetCell } from '@/types/cannevasTypes';
 
 export const calculateTotalFrames = (spritesheets: SpriteSheetData[] | undefined): number => {
   return spritesheets?.reduce((sum, sheet) => sum + sheet.frameCount, 0) ?? 0;
@@ -13,3 +15,33 @@ export const getFirstSpritesheet = (
 export const hasSpritesheets = (spritesheets: SpriteSheetData[] | undefined): boolean => {
   return (spritesheets?.length ?? 0) > 0;
 };
+
+// Part of the frame each cell holds. Layouts without cells hold the whole frame.
+export const getSpritesheetCell = (layout: SpriteSheetLayout): SpritesheetCell => {
+  if (!layout.cellWidth || !layout.cellHeight) {
+    return { x: 0, y: 0, width: layout.frameWidth, height: layout.frameHeight };
+  }
+
+  return {
+    x: layout.cellX ?? 0,
+    y: layout.cellY ?? 0,
+    width: layout.cellWidth,
+    height: layout.cellHeight,
+  };
+};
+
+// Shrinks a frame-sized mesh to the cell of its spritesheet and moves it to
+// where the cell sits on the frame, on top of the trait offset.
+export const fitMeshToCell = (mesh: THREE.Mesh, spritesheet: SpriteSheetData) => {
+  const { frameWidth, frameHeight } = spritesheet;
+  const cell = getSpritesheetCell(spritesheet.layout);
+  const width = (mesh.userData.width as number | undefined) ?? 0;
+  const height = (mesh.userData.height as number | undefined) ?? 0;
+  const offsetX = (mesh.userData.offsetX as number | undefined) ?? 0;
+  const offsetY = (mesh.userData.offsetY as number | undefined) ?? 0;
+
+  mesh.scale.x = cell.width / frameWidth;
+  mesh.scale.y = cell.height / frameHeight;
+  mesh.position.x = offsetX / 100 + ((cell.x + cell.width / 2) / frameWidth - 0.5) * width;
+  mesh.position.y = -offsetY / 100 - ((cell.y + cell.height / 2) / frameHeight - 0.5) * height;
+};
@@ -24,6 +24,7 @@ import type {
   AnimationQualityConfig,
   ResizeConfig,
   SolanaMetadataConfig,
+  PackagingConfig,
 } from '@/types/effect';
 import type { AppState, AppActions } from './types';
 
@@ -253,6 +254,7 @@ export const useGenerateStore = create<AppState & AppActions>((set, get) => ({
         includeSpritesheets,
         animationQuality,
         resizeConfig,
+        packaging,
       } = useGenerationSettingsStore.getState() as {
         imageFormat: string;
         baseWidth: number;
@@ -266,6 +268,7 @@ export const useGenerateStore = create<AppState & AppActions>((set, get) => ({
         includeSpritesheets: boolean;
         animationQuality: AnimationQualityConfig;
         resizeConfig: ResizeConfig;
+        packaging: PackagingConfig | undefined;
       };
 
       const { fps } = usePreview3DStore.getState();
@@ -323,6 +326,7 @@ export const useGenerateStore = create<AppState & AppActions>((set, get) => ({
         resizeConfig: resizeConfig ?? undefined,
         totalFramesCount: maxFrames,
         spritesheetLayout,
+        packaging,
       };
 
       const result = await api.startNFTGeneration(generationArgs);
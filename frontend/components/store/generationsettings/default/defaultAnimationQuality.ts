@@ -32,6 +32,14 @@ export const defaultAnimationQuality: AnimationQualityConfig = {
         factor: 1,
       },
     },
+    apng: {
+      autoloop: true,
+      interpolation: {
+        enabled: false,
+        method: 'LUCAS_KANADE',
+        factor: 1,
+      },
+    },
     gif: {
       colors: 256,
       dithering: true,
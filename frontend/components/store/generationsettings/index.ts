@@ -5,13 +5,20 @@ import { pipe } from 'effect/Function';
 import { api } from '@/services';
 
 import type { ImageSetupState, ImageSetupActions, ImageSetupPersistentState } from './types';
-import type { SolanaMetadataConfig, AnimationQualityConfig, ResizeConfig } from '@/types/effect';
+import type {
+  SolanaMetadataConfig,
+  AnimationQualityConfig,
+  ResizeConfig,
+  PackagingConfig,
+} from '@/types/effect';
 import { DEFAULT_BLEND_PROPERTIES, VALID_BLEND_MODES, BlendMode } from '@/types/blendModes';
 
 import { useLayerOrderStore } from '../layerOrder/main';
 import { useRarityStore } from '../rarityStore/main';
 import { useProjectSetupStore } from '../projectSetup/main';
 
+const DEFAULT_PACKAGING: PackagingConfig = { enabled: false, format: 'zip' };
+
 export const getIsAnimated = () => {
   const projectSetupStore = useProjectSetupStore.getState();
   return projectSetupStore.isAnimatedCollection;
@@ -40,6 +47,7 @@ export const useGenerationSettingsStore = create<ImageSetupState & ImageSetupAct
       solanaConfig: undefined,
       animationQuality: undefined,
       resizeConfig: undefined,
+      packaging: undefined,
     };
 
     const loadInitialData = async () => {
@@ -48,7 +56,7 @@ export const useGenerationSettingsStore = create<ImageSetupState & ImageSetupAct
         if (backendState) {
           const isAnimated = getIsAnimated();
           const availableFormats = isAnimated
-            ? ['mp4', 'webp', 'webm', 'gif']
+            ? ['mp4', 'webp', 'webm', 'gif', 'apng']
             : ['png', 'jpg', 'webp'];
 
           const newState = {
@@ -338,7 +346,7 @@ export const useGenerationSettingsStore = create<ImageSetupState & ImageSetupAct
               if (isValid) {
                 const isAnimated = getIsAnimated();
                 const availableFormats = isAnimated
-                  ? ['mp4', 'webp', 'webm', 'gif']
+                  ? ['mp4', 'webp', 'webm', 'gif', 'apng']
                   : ['png', 'jpg', 'webp'];
 
                 set({
@@ -383,6 +391,7 @@ export const useGenerationSettingsStore = create<ImageSetupState & ImageSetupAct
               solanaConfig: state.solanaConfig,
               animationQuality: state.animationQuality,
               resizeConfig: state.resizeConfig ?? undefined,
+              packaging: state.packaging,
             };
             return api.saveImageSetupState(persistentState);
           }),
@@ -567,7 +576,9 @@ export const useGenerationSettingsStore = create<ImageSetupState & ImageSetupAct
 
       updateFormats: () => {
         const isAnimated = getIsAnimated();
-        const newFormats = isAnimated ? ['mp4', 'webp', 'webm', 'gif'] : ['png', 'jpg', 'webp'];
+        const newFormats = isAnimated
+          ? ['mp4', 'webp', 'webm', 'gif', 'apng']
+          : ['png', 'jpg', 'webp'];
 
         set({
           imageFormats: newFormats,
@@ -617,6 +628,13 @@ export const useGenerationSettingsStore = create<ImageSetupState & ImageSetupAct
         });
         void get().saveState();
       },
+
+      updatePackaging: (config) => {
+        set((state) => ({
+          packaging: { ...DEFAULT_PACKAGING, ...state.packaging, ...config },
+        }));
+        void get().saveState();
+      },
     };
   }
 );
@@ -1,5 +1,6 @@
 import type {
   AnimationQualityConfig,
+  PackagingConfig,
   ResizeConfig,
   SolanaCreator,
   SolanaMetadataConfig,
@@ -54,6 +55,7 @@ export interface ImageSetupActions {
   setIncludeSpritesheets: (value: boolean) => void;
   updateAnimationQuality: (config: Partial<AnimationQualityConfig>) => void;
   updateResizeConfig: (config: Partial<ResizeConfig>) => void;
+  updatePackaging: (config: Partial<PackagingConfig>) => void;
   resetGenerationStore: () => Promise<void>;
   getMaxImageSize: () => number;
   readonly MIN_IMAGE_SIZE: number;
@@ -68,8 +68,15 @@ export const createImageSlice: StateCreator<ImageSliceState, [], [], ImageSlice>
 
       const traitNameWithoutExt = traitName.replace(/\.(gif|png|webp|mp4|webm|mov|avi|mkv)$/i, '');
 
+      // Sheets packed with trimmed cells have their own layout, older ones use
+      // the project layout.
+      const layout =
+        (await api
+          .getTraitSpritesheetLayout(projectId, `${layerName}/${traitNameWithoutExt}`)
+          .catch(() => null)) ?? spritesheetLayout;
+
       while (
-        sheetIndex < spritesheetLayout.totalSheets &&
+        sheetIndex < layout.totalSheets &&
         consecutiveFailures < MAX_CONSECUTIVE_FAILURES
       ) {
         try {
@@ -111,15 +118,15 @@ export const createImageSlice: StateCreator<ImageSliceState, [], [], ImageSlice>
 
           framesByLayer[layerName][traitName].push({
             texture,
-            frameCount: spritesheetLayout.framesPerSheet,
-            frameWidth: spritesheetLayout.frameWidth,
-            frameHeight: spritesheetLayout.frameHeight,
+            frameCount: layout.framesPerSheet,
+            frameWidth: layout.frameWidth,
+            frameHeight: layout.frameHeight,
             image: img,
-            layout: spritesheetLayout,
+            layout,
             sheetIndex,
           });
 
-          validFrames += spritesheetLayout.framesPerSheet;
+          validFrames += layout.framesPerSheet;
           sheetIndex++;
           consecutiveFailures = 0;
         } catch (error) {
@@ -138,7 +145,7 @@ export const createImageSlice: StateCreator<ImageSliceState, [], [], ImageSlice>
 
       if (validFrames === 0) {
         console.warn(
-          `No valid frames loaded for ${layerName}/${traitName}. Total expected sheets: ${spritesheetLayout.totalSheets}`
+          `No valid frames loaded for ${layerName}/${traitName}. Total expected sheets: ${layout.totalSheets}`
         );
         delete framesByLayer[layerName][traitName];
       } else {
@@ -236,7 +236,7 @@ export const useSaveLoadStore = create<SaveLoadState & SaveLoadActions>((set) =>
           errorMessage: null,
           isGenerateDisabled: false,
           imageFormats: safeConfig.isAnimatedCollection
-            ? ['mp4', 'webp', 'webm', 'gif']
+            ? ['mp4', 'webp', 'webm', 'gif', 'apng']
             : ['png', 'jpg', 'webp'],
         });
 
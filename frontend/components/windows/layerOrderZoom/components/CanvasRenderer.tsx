@@ -1,4 +1,5 @@
 import React, { useEffect } from 'react';
+import type { SpritesheetCell } from '@/types/cannevasTypes';
 
 interface ImageConfig {
   destWidth: number;
@@ -14,6 +15,7 @@ interface ImageConfig {
   totalFrames?: number;
   spritesheetCols?: number;
   spritesheetRows?: number;
+  spritesheetCell?: SpritesheetCell;
   opacity?: number;
   blendMode?: GlobalCompositeOperation;
   offsetX?: number;
@@ -117,20 +119,27 @@ export const CanvasRenderer: React.FC<CanvasRendererProps> = ({
 
             const scaledWidth = destWidth * zoom;
             const scaledHeight = destHeight * zoom;
+            const scale = scaledWidth / config.frameWidth;
+            const cell = config.spritesheetCell ?? {
+              x: 0,
+              y: 0,
+              width: config.frameWidth,
+              height: config.frameHeight,
+            };
 
             const x = (canvas.width - scaledWidth) / 2 + (config.offsetX ?? 0) * zoom;
             const y = (canvas.height - scaledHeight) / 2 + (config.offsetY ?? 0) * zoom;
 
             ctx.drawImage(
               img,
-              col * config.frameWidth,
-              row * config.frameHeight,
-              config.frameWidth,
-              config.frameHeight,
-              x,
-              y,
-              scaledWidth,
-              scaledHeight
+              col * cell.width,
+              row * cell.height,
+              cell.width,
+              cell.height,
+              x + cell.x * scale,
+              y + cell.y * scale,
+              cell.width * scale,
+              cell.height * scale
             );
           }
         } else {
@@ -1,6 +1,7 @@
 import { useEffect, useRef, useState, useMemo } from 'react';
 import { listen } from '@tauri-apps/api/event';
 import { api } from '@/services';
+import type { SpritesheetCell } from '@/types/cannevasTypes';
 
 interface Layer {
   id: string;
@@ -14,6 +15,7 @@ interface Layer {
   totalFrames?: number;
   spritesheetCols?: number;
   spritesheetRows?: number;
+  spritesheetCell?: SpritesheetCell;
   opacity?: number;
   blendMode?: GlobalCompositeOperation;
 }
@@ -1,5 +1,6 @@
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
+import type { SpritesheetCell } from '@/types/cannevasTypes';
 
 interface Layer {
   id: string;
@@ -13,6 +14,7 @@ interface Layer {
   totalFrames?: number;
   spritesheetCols?: number;
   spritesheetRows?: number;
+  spritesheetCell?: SpritesheetCell;
   opacity?: number;
   blendMode?: string;
 }
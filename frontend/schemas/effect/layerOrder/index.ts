@@ -4,6 +4,7 @@ import {
   SolanaMetadataConfigSchema,
   AnimationQualityConfigSchema,
   ResizeConfigSchema,
+  PackagingConfigSchema,
 } from '../metadataSchemas';
 
 // Schema for blend properties
@@ -48,6 +49,10 @@ export const SpritesheetLayoutSchema = S.mutable(
     totalSheets: S.Number.pipe(S.int(), S.nonNegative()),
     framesPerSheet: S.Number.pipe(S.int(), S.nonNegative()),
     totalFrames: S.Number.pipe(S.int(), S.nonNegative()),
+    cellX: S.optional(S.Number.pipe(S.int(), S.nonNegative())),
+    cellY: S.optional(S.Number.pipe(S.int(), S.nonNegative())),
+    cellWidth: S.optional(S.Number.pipe(S.int(), S.nonNegative())),
+    cellHeight: S.optional(S.Number.pipe(S.int(), S.nonNegative())),
   })
 );
 
@@ -152,12 +157,21 @@ export const TraitConfigSchema = S.mutable(
 );
 
 // Schema for layer config
+export const LayerPlaybackSchema = S.mutable(
+  S.Struct({
+    mode: S.optional(S.Literal('loop', 'pingPong', 'holdLastFrame')),
+    startOffset: S.optional(S.Number),
+    speed: S.optional(S.Number),
+  })
+);
+
 export const LayerConfigSchema = S.mutable(
   S.Struct({
     sets: S.optional(S.mutable(S.Record({ key: S.String, value: LayerSetConfigSchema }))),
     traits: S.optional(S.mutable(S.Record({ key: S.String, value: TraitConfigSchema }))),
     defaultBlend: S.optional(BlendPropertiesSchema),
     locked: S.optional(S.Boolean),
+    playback: S.optional(LayerPlaybackSchema),
   })
 );
 
@@ -305,6 +319,7 @@ export const NFTGenerationArgsSchema = S.Struct({
   animationQuality: S.optional(AnimationQualityConfigSchema),
   resizeConfig: S.optional(ResizeConfigSchema),
   spritesheetLayout: S.optional(SpritesheetLayoutSchema),
+  packaging: S.optional(PackagingConfigSchema),
 });
 
 // Schema for incompatibility side
@@ -406,6 +421,7 @@ export type {
   GIFSettings,
   MP4Settings,
   WebMSettings,
+  APNGSettings,
   ResizeFilter,
   ResizeAlgorithm,
   ResizeConfig,
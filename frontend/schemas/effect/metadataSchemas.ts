@@ -69,6 +69,12 @@ export const WebMSettingsSchema = S.Struct({
   autoloop: S.Boolean,
 });
 
+// Schema for APNG settings
+export const APNGSettingsSchema = S.Struct({
+  interpolation: AnimationInterpolationSettingsSchema,
+  autoloop: S.Boolean,
+});
+
 // Schema for resize filter
 export const ResizeFilterSchema = S.Union(
   S.Literal('NEAREST'),
@@ -95,6 +101,15 @@ export const ResizeConfigSchema = S.Struct({
   superSamplingFactor: S.optional(S.Number.pipe(S.int(), S.positive())),
 });
 
+// Schema for the archive written after a run
+export const PackageFormatSchema = S.Union(S.Literal('zip'), S.Literal('tarZst'));
+
+export const PackagingConfigSchema = S.Struct({
+  enabled: S.Boolean,
+  format: PackageFormatSchema,
+  maxVolumeMb: S.optional(S.Number.pipe(S.int(), S.positive())),
+});
+
 // Schema for animation quality config
 export const AnimationQualityConfigSchema = S.Struct({
   optimize: S.Boolean,
@@ -103,6 +118,7 @@ export const AnimationQualityConfigSchema = S.Struct({
     gif: GIFSettingsSchema,
     mp4: MP4SettingsSchema,
     webm: WebMSettingsSchema,
+    apng: S.optional(APNGSettingsSchema),
   }),
 });
 
@@ -117,7 +133,10 @@ export type WebPSettings = S.Schema.Type<typeof WebPSettingsSchema>;
 export type GIFSettings = S.Schema.Type<typeof GIFSettingsSchema>;
 export type MP4Settings = S.Schema.Type<typeof MP4SettingsSchema>;
 export type WebMSettings = S.Schema.Type<typeof WebMSettingsSchema>;
+export type APNGSettings = S.Schema.Type<typeof APNGSettingsSchema>;
 export type ResizeFilter = S.Schema.Type<typeof ResizeFilterSchema>;
 export type ResizeAlgorithm = S.Schema.Type<typeof ResizeAlgorithmSchema>;
 export type ResizeConfig = S.Schema.Type<typeof ResizeConfigSchema>;
+export type PackageFormat = S.Schema.Type<typeof PackageFormatSchema>;
+export type PackagingConfig = S.Schema.Type<typeof PackagingConfigSchema>;
 export type AnimationQualityConfig = S.Schema.Type<typeof AnimationQualityConfigSchema>;
@@ -4,6 +4,7 @@ import {
   SolanaMetadataConfigSchema,
   AnimationQualityConfigSchema,
   ResizeConfigSchema,
+  PackagingConfigSchema,
 } from '../metadataSchemas';
 
 /**
@@ -138,6 +139,7 @@ export const ImageSetupPersistentStateSchema = S.Struct({
   solanaConfig: S.optional(SolanaMetadataConfigSchema),
   animationQuality: S.optional(AnimationQualityConfigSchema),
   resizeConfig: S.optional(ResizeConfigSchema),
+  packaging: S.optional(PackagingConfigSchema),
 });
 
 /**
@@ -158,6 +160,7 @@ export const ImageSetupStateSchema = S.Struct({
   solanaConfig: S.optional(SolanaMetadataConfigSchema),
   animationQuality: S.optional(AnimationQualityConfigSchema),
   resizeConfig: S.optional(ResizeConfigSchema),
+  packaging: S.optional(PackagingConfigSchema),
 
   // Additional properties for the full state
   isLoading: S.optional(S.Boolean),
@@ -106,6 +106,16 @@ export class TauriApiService implements TauriApi {
     });
   }
 
+  async getTraitSpritesheetLayout(
+    projectId: string,
+    layerTraitPath: string
+  ): Promise<SpritesheetLayout | null> {
+    return await invoke<SpritesheetLayout | null>('get_trait_spritesheet_layout', {
+      projectId,
+      layerTraitPath,
+    });
+  }
+
   async cancelNFTGeneration(): Promise<{ success: boolean; message: string }> {
     return await invoke<{ success: boolean; message: string }>('cancel_nft_generation');
   }
@@ -13,6 +13,17 @@ export interface SpriteSheetLayout {
   totalSheets: number;
   framesPerSheet: number;
   totalFrames: number;
+  cellX?: number;
+  cellY?: number;
+  cellWidth?: number;
+  cellHeight?: number;
+}
+
+export interface SpritesheetCell {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
 }
 
 export interface SpriteSheetData {
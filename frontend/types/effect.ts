@@ -33,12 +33,14 @@ import {
   GIFSettingsSchema,
   MP4SettingsSchema,
   WebMSettingsSchema,
+  APNGSettingsSchema,
   AnimationQualityConfigSchema,
   SolanaCreatorSchema,
   SolanaMetadataConfigSchema,
   ResizeAlgorithmSchema,
   ResizeFilterSchema,
   ResizeConfigSchema,
+  PackagingConfigSchema,
 } from '../schemas/effect/metadataSchemas';
 
 import {
@@ -192,6 +194,7 @@ export type WebPSettings = S.Schema.Type<typeof WebPSettingsSchema>;
 export type GIFSettings = S.Schema.Type<typeof GIFSettingsSchema>;
 export type MP4Settings = S.Schema.Type<typeof MP4SettingsSchema>;
 export type WebMSettings = S.Schema.Type<typeof WebMSettingsSchema>;
+export type APNGSettings = S.Schema.Type<typeof APNGSettingsSchema>;
 
 export type AnimationQualityConfig = S.Schema.Type<typeof AnimationQualityConfigSchema>;
 export type SolanaCreator = S.Schema.Type<typeof SolanaCreatorSchema>;
@@ -265,3 +268,6 @@ export type ForcedCombinationStore = S.Schema.Type<typeof ForcedCombinationState
 export type ResizeAlgorithm = S.Schema.Type<typeof ResizeAlgorithmSchema>;
 export type ResizeFilter = S.Schema.Type<typeof ResizeFilterSchema>;
 export type ResizeConfig = S.Schema.Type<typeof ResizeConfigSchema>;
+
+// Packaging types
+export type PackagingConfig = S.Schema.Type<typeof PackagingConfigSchema>;
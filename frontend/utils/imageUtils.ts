@@ -15,6 +15,8 @@ export const getImageMimeType = (imageName: string): string => {
       return 'image/webp';
     case 'gif':
       return 'image/gif';
+    case 'apng':
+      return 'image/apng';
     case 'mp4':
       return 'video/mp4';
     case 'webm':
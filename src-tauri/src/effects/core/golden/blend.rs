@@ -0,0 +1,71 @@
+use super::harness::{gpu, load_fixture, GoldenSuite, Threshold};
+use crate::{effects::core::gpu::blend_modes_gpu::GpuBlendProcessor, types::BlendMode};
+
+const ALL_BLEND_MODES: [BlendMode; 17] = [
+    BlendMode::SourceOver,
+    BlendMode::Lighter,
+    BlendMode::Multiply,
+    BlendMode::Screen,
+    BlendMode::Overlay,
+    BlendMode::Darken,
+    BlendMode::Lighten,
+    BlendMode::ColorDodge,
+    BlendMode::ColorBurn,
+    BlendMode::HardLight,
+    BlendMode::SoftLight,
+    BlendMode::Difference,
+    BlendMode::Exclusion,
+    BlendMode::Hue,
+    BlendMode::Saturation,
+    BlendMode::Color,
+    BlendMode::Luminosity,
+];
+
+const OPACITIES: [f32; 4] = [0.0, 0.35, 0.7, 1.0];
+
+/// Blending only runs on the GPU. Drivers may flush denormals or fuse
+/// multiply-adds, which moves a few channels by one step after quantization.
+const GPU_THRESHOLD: Threshold = Threshold::within(2, 45.0);
+
+fn case_name(mode: BlendMode, opacity: f32) -> String {
+    let mode = serde_json::to_value(mode).unwrap();
+    format!(
+        "{}_{:03}",
+        mode.as_str().unwrap(),
+        (opacity * 100.0).round() as u32
+    )
+}
+
+#[test]
+fn blend_modes_match_goldens() {
+    let Some(context) = gpu() else {
+        println!("no GPU adapter, skipping the blend goldens");
+        return;
+    };
+    let manager = &context.manager;
+    let processor = pollster::block_on(GpuBlendProcessor::new()).unwrap();
+
+    let base = load_fixture("base");
+    let overlay = load_fixture("overlay");
+    let mut suite = GoldenSuite::new("blend");
+
+    for mode in ALL_BLEND_MODES {
+        for opacity in OPACITIES {
+            let case = case_name(mode, opacity);
+
+            match processor.blend_images(
+                manager.device(),
+                manager.queue(),
+                &base,
+                &overlay,
+                mode,
+                opacity,
+            ) {
+                Ok(output) => suite.check(&case, &output, GPU_THRESHOLD),
+                Err(e) => suite.fail(&case, e),
+            }
+        }
+    }
+
+    suite.finish();
+}
@@ -0,0 +1,242 @@
+use image::{DynamicImage, GenericImageView, ImageFormat};
+use once_cell::sync::Lazy;
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+use crate::effects::core::{gpu::GpuEffectManager, interpolate::InterpolationEngine};
+
+/// Set to rewrite every expected image from the production output instead of
+/// comparing.
+pub const UPDATE_ENV: &str = "GOLDEN_UPDATE";
+
+pub struct GpuContext {
+    pub manager: GpuEffectManager,
+    pub interpolation: Option<InterpolationEngine>,
+}
+
+/// Created once and never dropped: the blend shaders cache their pipelines
+/// against the global device, and on GL backends dropping a second instance
+/// tears down the display the first one still uses.
+static GPU: Lazy<Option<GpuContext>> = Lazy::new(|| {
+    let manager = pollster::block_on(GpuEffectManager::new()).ok()?;
+    let interpolation = pollster::block_on(InterpolationEngine::new()).ok();
+
+    Some(GpuContext {
+        manager,
+        interpolation,
+    })
+});
+
+pub fn gpu() -> Option<&'static GpuContext> {
+    GPU.as_ref()
+}
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+pub fn load_fixture(name: &str) -> DynamicImage {
+    let path = golden_dir().join("fixtures").join(format!("{}.png", name));
+    image::open(&path).unwrap_or_else(|e| panic!("missing fixture {}: {}", path.display(), e))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Threshold {
+    /// Largest per-channel difference that still counts as a match.
+    pub max_delta: u8,
+    /// Share of channels allowed to exceed `max_delta`.
+    pub max_outlier_ratio: f64,
+    pub min_psnr: f64,
+}
+
+impl Threshold {
+    pub const IDENTICAL: Self = Self {
+        max_delta: 0,
+        max_outlier_ratio: 0.0,
+        min_psnr: f64::INFINITY,
+    };
+
+    /// Same math on the same platform, allowing for rounding at the 8-bit store.
+    pub const EXACT: Self = Self {
+        max_delta: 1,
+        max_outlier_ratio: 0.0,
+        min_psnr: 50.0,
+    };
+
+    pub const fn within(max_delta: u8, min_psnr: f64) -> Self {
+        Self {
+            max_delta,
+            max_outlier_ratio: 0.0,
+            min_psnr,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ImageDiff {
+    pub max_delta: u8,
+    pub outlier_ratio: f64,
+    pub psnr: f64,
+}
+
+impl ImageDiff {
+    pub fn passes(&self, threshold: &Threshold) -> bool {
+        self.outlier_ratio <= threshold.max_outlier_ratio && self.psnr >= threshold.min_psnr
+    }
+}
+
+/// Per-channel comparison over RGBA8. Identical images have an infinite PSNR.
+pub fn diff_images(expected: &DynamicImage, actual: &DynamicImage, max_delta: u8) -> ImageDiff {
+    let expected = expected.to_rgba8();
+    let actual = actual.to_rgba8();
+
+    let mut worst = 0u8;
+    let mut outliers = 0u64;
+    let mut squared_error = 0f64;
+    for (a, b) in expected.as_raw().iter().zip(actual.as_raw()) {
+        let delta = a.abs_diff(*b);
+        worst = worst.max(delta);
+        if delta > max_delta {
+            outliers += 1;
+        }
+        squared_error += (delta as f64).powi(2);
+    }
+
+    let channels = expected.as_raw().len().max(1) as f64;
+    let mse = squared_error / channels;
+    let psnr = if mse == 0.0 {
+        f64::INFINITY
+    } else {
+        10.0 * (255.0f64.powi(2) / mse).log10()
+    };
+
+    ImageDiff {
+        max_delta: worst,
+        outlier_ratio: outliers as f64 / channels,
+        psnr,
+    }
+}
+
+/// Collects every mismatch of a test so one run reports all broken cases.
+pub struct GoldenSuite {
+    area: &'static str,
+    update: bool,
+    checked: usize,
+    failures: Vec<String>,
+}
+
+impl GoldenSuite {
+    pub fn new(area: &'static str) -> Self {
+        Self {
+            area,
+            update: env::var_os(UPDATE_ENV).is_some(),
+            checked: 0,
+            failures: Vec::new(),
+        }
+    }
+
+    fn expected_path(&self, case: &str) -> PathBuf {
+        golden_dir()
+            .join("expected")
+            .join(self.area)
+            .join(format!("{}.png", case))
+    }
+
+    /// Compares the output of the production path against the stored golden,
+    /// or stores it when updating.
+    pub fn check(&mut self, case: &str, actual: &DynamicImage, threshold: Threshold) {
+        let path = self.expected_path(case);
+
+        if self.update {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            actual
+                .to_rgba8()
+                .save_with_format(&path, ImageFormat::Png)
+                .unwrap_or_else(|e| panic!("failed to write {}: {}", path.display(), e));
+            return;
+        }
+
+        match image::open(&path) {
+            Ok(expected) => self.compare(case, &expected, actual, threshold),
+            Err(_) => self.failures.push(format!(
+                "{}: no golden at {}, run with {}=1 to create it",
+                case,
+                path.display(),
+                UPDATE_ENV
+            )),
+        }
+    }
+
+    /// Compares a second backend of the same operation against the stored
+    /// golden. It never updates goldens.
+    pub fn check_port(
+        &mut self,
+        case: &str,
+        backend: &str,
+        actual: &DynamicImage,
+        threshold: Threshold,
+    ) {
+        if self.update {
+            return;
+        }
+        if let Ok(expected) = image::open(self.expected_path(case)) {
+            let label = format!("{} ({})", case, backend);
+            self.compare(&label, &expected, actual, threshold);
+        }
+    }
+
+    pub fn fail(&mut self, case: &str, error: impl std::fmt::Display) {
+        self.failures.push(format!("{}: {}", case, error));
+    }
+
+    fn compare(
+        &mut self,
+        label: &str,
+        expected: &DynamicImage,
+        actual: &DynamicImage,
+        threshold: Threshold,
+    ) {
+        self.checked += 1;
+
+        if expected.dimensions() != actual.dimensions() {
+            self.failures.push(format!(
+                "{}: size {:?}, expected {:?}",
+                label,
+                actual.dimensions(),
+                expected.dimensions()
+            ));
+            return;
+        }
+
+        let diff = diff_images(expected, actual, threshold.max_delta);
+        if !diff.passes(&threshold) {
+            self.failures.push(format!(
+                "{}: max delta {}, {:.2}% over {}, PSNR {:.2} dB (min {:.1})",
+                label,
+                diff.max_delta,
+                diff.outlier_ratio * 100.0,
+                threshold.max_delta,
+                diff.psnr,
+                threshold.min_psnr
+            ));
+        }
+    }
+
+    pub fn finish(self) {
+        if self.update {
+            return;
+        }
+
+        assert!(
+            self.failures.is_empty(),
+            "{} {} golden checks failed ({} images compared):\n{}",
+            self.failures.len(),
+            self.area,
+            self.checked,
+            self.failures.join("\n")
+        );
+        assert!(self.checked > 0, "no {} goldens were compared", self.area);
+    }
+}
@@ -0,0 +1,53 @@
+use super::harness::{gpu, load_fixture, GoldenSuite, Threshold};
+use crate::{
+    effects::core::interpolate::{
+        cpu::CpuInterpolator,
+        verify::{tolerance_for, ALL_INTERPOLATION_METHODS},
+    },
+    types::InterpolationMethod,
+};
+
+const ALPHAS: [f32; 2] = [0.25, 0.5];
+
+fn gpu_threshold(method: InterpolationMethod) -> Threshold {
+    let tolerance = tolerance_for(method);
+    Threshold {
+        max_delta: tolerance.max_delta,
+        max_outlier_ratio: tolerance.max_outlier_ratio,
+        min_psnr: 30.0,
+    }
+}
+
+#[test]
+fn interpolation_matches_goldens() {
+    let frame1 = load_fixture("frame_a");
+    let frame2 = load_fixture("frame_b");
+    let cpu = CpuInterpolator::new();
+    let mut suite = GoldenSuite::new("interpolation");
+
+    let engine = gpu().and_then(|context| context.interpolation.as_ref());
+
+    for method in ALL_INTERPOLATION_METHODS {
+        for alpha in ALPHAS {
+            let case = format!(
+                "{}_{:03}",
+                method.to_string().to_lowercase(),
+                (alpha * 100.0).round() as u32
+            );
+
+            match cpu.interpolate_single_pair(&frame1, &frame2, alpha, method) {
+                Ok(output) => suite.check(&case, &output, Threshold::EXACT),
+                Err(e) => suite.fail(&case, e),
+            }
+
+            if let Some(engine) = engine {
+                match engine.interpolate_single_pair(&frame1, &frame2, alpha, method) {
+                    Ok(output) => suite.check_port(&case, "gpu", &output, gpu_threshold(method)),
+                    Err(e) => suite.fail(&case, e),
+                }
+            }
+        }
+    }
+
+    suite.finish();
+}
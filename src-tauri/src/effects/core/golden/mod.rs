@@ -0,0 +1,12 @@
+//! Golden-image regression tests for blending, resizing, offsets and frame
+//! interpolation. Fixtures and expected outputs live in `tests/golden`; run
+//! with `GOLDEN_UPDATE=1` to regenerate the expected images from the
+//! production path after an intentional change: the GPU shaders for blending
+//! and resizing, the CPU code for offsets, CPU resizing and interpolation.
+//! Tests that need a GPU skip without an adapter.
+
+mod blend;
+mod harness;
+mod interpolation;
+mod offset;
+mod resize;
@@ -0,0 +1,28 @@
+use super::harness::{load_fixture, GoldenSuite, Threshold};
+use crate::effects::core::transform::apply_offset;
+
+const OFFSETS: [(i32, i32); 6] = [(0, 0), (3, 0), (0, -2), (-5, 4), (7, 7), (16, 0)];
+
+fn case_name(x: i32, y: i32) -> String {
+    let signed = |v: i32| {
+        if v < 0 {
+            format!("m{}", -v)
+        } else {
+            format!("p{}", v)
+        }
+    };
+    format!("offset_{}_{}", signed(x), signed(y))
+}
+
+#[test]
+fn offsets_match_goldens() {
+    let layer = load_fixture("overlay");
+    let mut suite = GoldenSuite::new("offset");
+
+    for (x, y) in OFFSETS {
+        let output = apply_offset(&layer, x, y);
+        suite.check(&case_name(x, y), &output, Threshold::IDENTICAL);
+    }
+
+    suite.finish();
+}
@@ -0,0 +1,108 @@
+use super::harness::{gpu, load_fixture, GoldenSuite, Threshold};
+use crate::effects::core::{
+    gpu::resize_gpu::{ResizeAlgorithm, ResizeConfig, ResizeFilter, ResizeGpu},
+    transform::resize_cpu,
+};
+
+const ALL_FILTERS: [ResizeFilter; 7] = [
+    ResizeFilter::Nearest,
+    ResizeFilter::Bilinear,
+    ResizeFilter::Bicubic,
+    ResizeFilter::Lanczos,
+    ResizeFilter::Hamming,
+    ResizeFilter::Mitchell,
+    ResizeFilter::Gaussian,
+];
+
+/// One upscale and one downscale, both with uneven ratios per axis.
+const TARGET_SIZES: [(u32, u32); 2] = [(24, 20), (10, 7)];
+
+/// The kernels go through `sin`/`exp`, whose precision differs between drivers.
+const GPU_THRESHOLD: Threshold = Threshold::within(2, 42.0);
+
+fn all_configs() -> Vec<(String, ResizeConfig)> {
+    let mut configs = vec![(
+        "nearest".to_string(),
+        ResizeConfig {
+            algorithm: ResizeAlgorithm::Nearest,
+            filter: None,
+            super_sampling_factor: None,
+        },
+    )];
+
+    for algorithm in [
+        ResizeAlgorithm::Convolution,
+        ResizeAlgorithm::Interpolation,
+        ResizeAlgorithm::SuperSampling,
+    ] {
+        for filter in ALL_FILTERS {
+            let name = format!("{:?}_{:?}", algorithm, filter).to_lowercase();
+            configs.push((
+                name,
+                ResizeConfig {
+                    algorithm: algorithm.clone(),
+                    filter: Some(filter),
+                    super_sampling_factor: None,
+                },
+            ));
+        }
+    }
+
+    configs.push((
+        "supersampling_x3_lanczos".to_string(),
+        ResizeConfig {
+            algorithm: ResizeAlgorithm::SuperSampling,
+            filter: Some(ResizeFilter::Lanczos),
+            super_sampling_factor: Some(3),
+        },
+    ));
+
+    configs
+}
+
+#[test]
+fn gpu_resize_matches_goldens() {
+    let Some(context) = gpu() else {
+        println!("no GPU adapter, skipping the GPU resize goldens");
+        return;
+    };
+    let manager = &context.manager;
+    let resizer = ResizeGpu::new(manager.device(), manager.queue()).unwrap();
+
+    let source = load_fixture("base");
+    let mut suite = GoldenSuite::new("resize");
+
+    for (name, config) in all_configs() {
+        let config = Some(config);
+        for (width, height) in TARGET_SIZES {
+            let case = format!("{}_{}x{}", name, width, height);
+
+            match resizer.resize_image(
+                manager.device(),
+                manager.queue(),
+                &source,
+                width,
+                height,
+                &config,
+            ) {
+                Ok(output) => suite.check(&case, &output, GPU_THRESHOLD),
+                Err(e) => suite.fail(&case, e),
+            }
+        }
+    }
+
+    suite.finish();
+}
+
+#[test]
+fn cpu_resize_matches_goldens() {
+    let source = load_fixture("base");
+    let mut suite = GoldenSuite::new("resize");
+
+    for (width, height) in TARGET_SIZES {
+        let case = format!("cpu_lanczos3_{}x{}", width, height);
+        suite.check(&case, &resize_cpu(&source, width, height), Threshold::EXACT);
+    }
+
+    suite.finish();
+}
@@ -5,13 +5,7 @@ use dashmap::DashMap;
 use image::{DynamicImage, GenericImageView};
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
-use rayon::prelude::*;
-use std::{
-    collections::{HashMap, HashSet},
-    error::Error,
-    iter::once,
-    sync::Arc,
-};
+use std::{error::Error, iter::once, sync::Arc};
 use wgpu::{BindGroupLayout, ComputePipeline, Device, Queue};
 static PIPELINE_CACHE: Lazy<DashMap<BlendMode, Arc<ComputePipeline>>> =
     Lazy::new(|| DashMap::new());
@@ -323,257 +317,120 @@ impl GpuBlendProcessor {
         Ok(())
     }
 
+    /// Blends `layers` onto `base_texture` in order. Each layer covers only
+    /// its own size at its origin on the base, so a trimmed layer costs its
+    /// visible area rather than the whole canvas.
     pub fn apply_multiple_blends_inplace(
         &self,
         device: &Device,
         base_texture: &mut GpuTexture,
-        layers: &[(&GpuTexture, BlendMode, f32)],
+        layers: &[(&GpuTexture, BlendMode, f32, (u32, u32))],
         queue: &Queue,
     ) -> Result<(), String> {
         if layers.is_empty() {
             return Ok(());
         }
 
-        let texture_size = base_texture.texture().size();
-
-        let temp_a = GpuTexture::new(
-            device,
-            texture_size.width,
-            texture_size.height,
-            wgpu::TextureFormat::Rgba8Unorm,
-        );
-        let temp_b = GpuTexture::new(
-            device,
-            texture_size.width,
-            texture_size.height,
-            wgpu::TextureFormat::Rgba8Unorm,
-        );
-
-        let workgroup_size = 8u32;
-
-        let unique_blend_modes: HashSet<_> = layers.iter().map(|(_, mode, _)| *mode).collect();
-        let pipeline_cache: HashMap<_, _> = unique_blend_modes
-            .into_iter()
-            .map(|mode| {
-                let pipeline = self.get_or_create_pipeline(device, mode)?;
-                Ok((mode, pipeline))
-            })
-            .collect::<Result<_, String>>()?;
-
         let base_size = base_texture.texture().size();
-        let dispatch_x = (base_size.width + workgroup_size - 1) / workgroup_size;
-        let dispatch_y = (base_size.height + workgroup_size - 1) / workgroup_size;
-
-        let ops: Vec<_> = layers
-            .iter()
-            .enumerate()
-            .map(|(i, (layer_texture, blend_mode, opacity))| {
-                let pipeline = pipeline_cache.get(blend_mode).unwrap().clone();
-                let is_last = i == layers.len() - 1;
-
-                let opacity_data = [*opacity as f32];
-                let opacity_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-                    label: Some(&format!("Blend Opacity Uniform Layer {}", i)),
-                    size: 16,
-                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-                    mapped_at_creation: false,
-                });
-                queue.write_buffer(&opacity_buffer, 0, cast_slice(&opacity_data));
-
-                (
-                    pipeline,
-                    *layer_texture,
-                    (dispatch_x, dispatch_y),
-                    is_last,
-                    *blend_mode,
-                    opacity_buffer,
-                )
-            })
-            .collect();
-
-        let all_views: Vec<_> = {
-            let mut views = Vec::with_capacity(ops.len() + 3);
-
-            let layer_views: Vec<_> = ops
-                .par_iter()
-                .map(|op| {
-                    let (_, layer_tex, _, _, _, _) = op;
-                    get_or_create_view(layer_tex.texture())
-                })
-                .collect();
-
-            let temp_a_view = get_or_create_view(temp_a.texture());
-            let temp_b_view = get_or_create_view(temp_b.texture());
-            let base_view = get_or_create_view(base_texture.texture());
-
-            views.push(temp_a_view);
-            views.push(temp_b_view);
-            views.push(base_view);
-            views.extend(layer_views);
-
-            views
-        };
-
-        let temp_a_view = &all_views[0];
-        let temp_b_view = &all_views[1];
-        let base_view = &all_views[2];
-        let all_layer_views = &all_views[3..];
+        let base_view = get_or_create_view(base_texture.texture());
+        let workgroup_size = 16u32;
 
-        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
-        encoder.copy_texture_to_texture(
-            wgpu::ImageCopyTexture {
-                texture: base_texture.texture(),
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            wgpu::ImageCopyTexture {
-                texture: temp_a.texture(),
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            texture_size,
-        );
-
-        let first_layer = ops.get(0).expect("layers non vides");
-        let (pipeline, _layer_tex, dispatch, _is_last, _blend_mode, opacity_buffer) = first_layer;
-        let layer_view = &all_layer_views[0];
-
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Blend BG first"),
-            layout: &self.bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&base_view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::TextureView(layer_view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 2,
-                    resource: wgpu::BindingResource::TextureView(&temp_a_view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 3,
-                    resource: opacity_buffer.as_entire_binding(),
-                },
-            ],
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Blend Regions"),
         });
+        let mut regions = Vec::with_capacity(layers.len());
 
-        {
-            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
-                label: Some("Blend First Pass"),
+        for (i, (layer_texture, blend_mode, opacity, (x, y))) in layers.iter().enumerate() {
+            if *x >= base_size.width || *y >= base_size.height {
+                continue;
+            }
+            let layer_size = layer_texture.texture().size();
+            let width = layer_size.width.min(base_size.width - x);
+            let height = layer_size.height.min(base_size.height - y);
+
+            let pipeline = self.get_or_create_pipeline(device, *blend_mode)?;
+            let region = GpuTexture::new(device, width, height, wgpu::TextureFormat::Rgba8Unorm);
+            let region_view = region
+                .texture()
+                .create_view(&wgpu::TextureViewDescriptor::default());
+            let layer_view = layer_texture
+                .texture()
+                .create_view(&wgpu::TextureViewDescriptor::default());
+
+            let uniform_data = [opacity.to_bits(), 0, *x, *y];
+            let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(&format!("Blend Uniform Layer {}", i)),
+                size: 16,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
             });
-            pass.set_pipeline(pipeline);
-            pass.set_bind_group(0, &bind_group, &[]);
-            pass.dispatch_workgroups(dispatch.0, dispatch.1, 1);
-        }
-
-        let mut use_a_as_src = true;
-        let intermediate_ops: Vec<_> = ops
-            .iter()
-            .skip(1)
-            .take(ops.len().saturating_sub(2))
-            .collect();
-
-        for (i, op) in intermediate_ops.iter().enumerate() {
-            let (pipeline, _layer_tex, dispatch, _is_last, _blend_mode, opacity_buffer) = op;
-
-            let (src_view, dst_view) = if use_a_as_src {
-                (&temp_a_view, &temp_b_view)
-            } else {
-                (&temp_b_view, &temp_a_view)
-            };
-
-            let layer_view = &all_layer_views[i + 1];
+            queue.write_buffer(&uniform_buffer, 0, cast_slice(&uniform_data));
 
             let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("Blend BG mid"),
+                label: Some("Blend Region BG"),
                 layout: &self.bind_group_layout,
                 entries: &[
                     wgpu::BindGroupEntry {
                         binding: 0,
-                        resource: wgpu::BindingResource::TextureView(src_view),
+                        resource: wgpu::BindingResource::TextureView(&base_view),
                     },
                     wgpu::BindGroupEntry {
                         binding: 1,
-                        resource: wgpu::BindingResource::TextureView(layer_view),
+                        resource: wgpu::BindingResource::TextureView(&layer_view),
                     },
                     wgpu::BindGroupEntry {
                         binding: 2,
-                        resource: wgpu::BindingResource::TextureView(dst_view),
+                        resource: wgpu::BindingResource::TextureView(&region_view),
                     },
                     wgpu::BindGroupEntry {
                         binding: 3,
-                        resource: opacity_buffer.as_entire_binding(),
+                        resource: uniform_buffer.as_entire_binding(),
                     },
                 ],
             });
 
             {
                 let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
-                    label: Some("Blend Mid Pass"),
+                    label: Some("Blend Region Pass"),
                 });
-                pass.set_pipeline(pipeline);
+                pass.set_pipeline(&pipeline);
                 pass.set_bind_group(0, &bind_group, &[]);
-                pass.dispatch_workgroups(dispatch.0, dispatch.1, 1);
+                pass.dispatch_workgroups(
+                    (width + workgroup_size - 1) / workgroup_size,
+                    (height + workgroup_size - 1) / workgroup_size,
+                    1,
+                );
             }
 
-            use_a_as_src = !use_a_as_src;
-        }
-
-        if let Some(last_op) = ops.last() {
-            let (pipeline, _layer_tex, dispatch, _is_last, _blend_mode, opacity_buffer) = last_op;
-
-            let src_view = if use_a_as_src {
-                &temp_a_view
-            } else {
-                &temp_b_view
-            };
-
-            let layer_view = all_layer_views.last().expect("Vues non vides");
-
-            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("Blend BG final"),
-                layout: &self.bind_group_layout,
-                entries: &[
-                    wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: wgpu::BindingResource::TextureView(src_view),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 1,
-                        resource: wgpu::BindingResource::TextureView(layer_view),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 2,
-                        resource: wgpu::BindingResource::TextureView(base_view),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 3,
-                        resource: opacity_buffer.as_entire_binding(),
-                    },
-                ],
-            });
-
-            {
-                let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
-                    label: Some("Blend Final Pass"),
-                });
-                pass.set_pipeline(pipeline);
-                pass.set_bind_group(0, &bind_group, &[]);
-                pass.dispatch_workgroups(dispatch.0, dispatch.1, 1);
-            }
+            // The blended region goes back onto the base before the next
+            // layer reads it.
+            encoder.copy_texture_to_texture(
+                wgpu::ImageCopyTexture {
+                    texture: region.texture(),
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::ImageCopyTexture {
+                    texture: base_texture.texture(),
+                    mip_level: 0,
+                    origin: wgpu::Origin3d { x: *x, y: *y, z: 0 },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+            );
+            regions.push(region);
         }
 
-        let cmd = encoder.finish();
-        let _index = queue.submit(once(cmd));
+        queue.submit(once(encoder.finish()));
         device.poll(wgpu::Maintain::Wait);
-        temp_a.destroy();
-        temp_b.destroy();
+        for region in regions {
+            region.destroy();
+        }
         Ok(())
     }
 }
@@ -1,11 +1,29 @@
 use image::{DynamicImage, ImageBuffer, Rgba};
-use std::{cell::RefCell, error::Error, iter::once};
+use std::{
+    cell::RefCell,
+    error::Error,
+    iter::once,
+    sync::atomic::{AtomicU64, Ordering},
+};
 use tokio::sync::oneshot::channel;
 use wgpu::{
     BufferDescriptor, BufferUsages, Device, Extent3d, Queue, Texture, TextureDescriptor,
     TextureFormat, TextureUsages,
 };
 
+static GPU_TEXTURE_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Bytes held by live `GpuTexture`s, the part of VRAM generation accounts for.
+pub fn gpu_texture_bytes() -> u64 {
+    GPU_TEXTURE_BYTES.load(Ordering::Relaxed)
+}
+
+fn texture_bytes(texture: &Texture) -> u64 {
+    let size = texture.size();
+    let block_size = texture.format().block_size(None).unwrap_or(4) as u64;
+    size.width as u64 * size.height as u64 * size.depth_or_array_layers as u64 * block_size
+}
+
 thread_local! {
     static STAGING_BUFFERS: RefCell<Vec<Vec<u8>>> = RefCell::new(Vec::new());
 }
@@ -48,7 +66,9 @@ pub struct GpuTexture {
 }
 
 impl Drop for GpuTexture {
-    fn drop(&mut self) {}
+    fn drop(&mut self) {
+        GPU_TEXTURE_BYTES.fetch_sub(texture_bytes(&self.texture), Ordering::Relaxed);
+    }
 }
 
 impl GpuTexture {
@@ -71,6 +91,7 @@ impl GpuTexture {
                 | TextureUsages::RENDER_ATTACHMENT,
             view_formats: &[],
         });
+        GPU_TEXTURE_BYTES.fetch_add(texture_bytes(&texture), Ordering::Relaxed);
 
         Self { texture, format }
     }
@@ -139,6 +160,7 @@ impl GpuTexture {
         );
 
         return_staging_buffer(aligned_buffer);
+        GPU_TEXTURE_BYTES.fetch_add(texture_bytes(&texture), Ordering::Relaxed);
 
         Ok(Self {
             texture,
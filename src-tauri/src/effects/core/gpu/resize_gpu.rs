@@ -138,40 +138,55 @@ impl ResizeGpu {
             return Ok(Vec::new());
         }
 
-        let algorithm = Self::map_resize_algorithm(resize_config);
-        let filter_type = Self::map_resize_filter(resize_config);
-        let super_sampling_factor = Self::map_super_sampling_factor(resize_config);
-
         let mut results = Vec::with_capacity(frames.len());
 
         for frame in frames {
-            let input_texture = GpuTexture::from_image(device, queue, frame)?;
-            let output_texture = GpuTexture::new(device, width, height, input_texture.format());
-
-            let input_gpu_image = GpuImage::new(
-                input_texture.texture(),
-                input_texture.texture().size().width,
-                input_texture.texture().size().height,
-            );
-            let output_gpu_image = GpuImage::new(output_texture.texture(), width, height);
-
-            self.apply_resize(
-                device,
-                queue,
-                &input_gpu_image,
-                &output_gpu_image,
-                algorithm,
-                filter_type,
-                super_sampling_factor,
-            )?;
-
-            let result_dynamic = output_texture.to_image(device, queue)?;
-            results.push(result_dynamic);
+            results.push(self.resize_image(device, queue, frame, width, height, resize_config)?);
         }
 
         Ok(results)
     }
 
+    pub fn resize_image(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        frame: &DynamicImage,
+        width: u32,
+        height: u32,
+        resize_config: &Option<ResizeConfig>,
+    ) -> Result<DynamicImage, Box<dyn Error>> {
+        if width == 0 || height == 0 {
+            return Err(format!("Invalid dimensions: {}x{}", width, height).into());
+        }
+
+        let algorithm = Self::map_resize_algorithm(resize_config);
+        let filter_type = Self::map_resize_filter(resize_config);
+        let super_sampling_factor = Self::map_super_sampling_factor(resize_config);
+
+        let input_texture = GpuTexture::from_image(device, queue, frame)?;
+        let output_texture = GpuTexture::new(device, width, height, input_texture.format());
+
+        let input_gpu_image = GpuImage::new(
+            input_texture.texture(),
+            input_texture.texture().size().width,
+            input_texture.texture().size().height,
+        );
+        let output_gpu_image = GpuImage::new(output_texture.texture(), width, height);
+
+        self.apply_resize(
+            device,
+            queue,
+            &input_gpu_image,
+            &output_gpu_image,
+            algorithm,
+            filter_type,
+            super_sampling_factor,
+        )?;
+
+        output_texture.to_image(device, queue)
+    }
+
     pub fn new(device: &Device, _queue: &Queue) -> Result<Self, Box<dyn Error>> {
         let shader = load_shader(device, "resize");
 
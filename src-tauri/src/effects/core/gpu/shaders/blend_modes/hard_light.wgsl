@@ -5,6 +5,8 @@
 
 struct OpacityUniform {
     opacity: f32,
+    // Position of the output region on the base texture.
+    offset: vec2<u32>,
 }
 
 fn hard_light_channel(base: f32, blend: f32) -> f32 {
@@ -24,7 +26,7 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
         return;
     }
     
-    let base = textureLoad(base_texture, vec2<u32>(pos), 0);
+    let base = textureLoad(base_texture, vec2<u32>(pos) + opacity_buffer.offset, 0);
     let overlay = textureLoad(overlay_texture, vec2<u32>(pos), 0);
     
     let overlay_alpha = overlay.a * opacity_buffer.opacity;
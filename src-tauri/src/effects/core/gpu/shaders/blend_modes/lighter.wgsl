@@ -5,6 +5,8 @@
 
 struct OpacityUniform {
     opacity: f32,
+    // Position of the output region on the base texture.
+    offset: vec2<u32>,
 }
 
 @compute @workgroup_size(16, 16, 1)
@@ -16,7 +18,7 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
         return;
     }
     
-    let base = textureLoad(base_texture, vec2<u32>(pos), 0);
+    let base = textureLoad(base_texture, vec2<u32>(pos) + opacity_buffer.offset, 0);
     var overlay = textureLoad(overlay_texture, vec2<u32>(pos), 0);
     
     overlay = vec4<f32>(overlay.rgb, overlay.a * opacity_buffer.opacity);
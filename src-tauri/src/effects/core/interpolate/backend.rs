@@ -0,0 +1,84 @@
+use anyhow::Result;
+use image::DynamicImage;
+use once_cell::sync::Lazy;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use crate::effects::core::interpolate::{
+    cpu::CpuInterpolator, interpolation::InterpolationEngine, InterpolationMethod,
+};
+
+static CPU_INTERPOLATOR: Lazy<Arc<CpuInterpolator>> =
+    Lazy::new(|| Arc::new(CpuInterpolator::new()));
+static GPU_UNAVAILABLE: AtomicBool = AtomicBool::new(false);
+
+/// Interpolation engine picked at runtime: the wgpu pipelines when an adapter
+/// exists, otherwise the CPU ports of the same shaders.
+#[derive(Clone)]
+pub enum InterpolationBackend {
+    Gpu(Arc<InterpolationEngine>),
+    Cpu(Arc<CpuInterpolator>),
+}
+
+impl InterpolationBackend {
+    pub async fn get_or_create_global() -> Self {
+        if !GPU_UNAVAILABLE.load(Ordering::Relaxed) {
+            if let Some(engine) = InterpolationEngine::get_or_create_global().await {
+                return Self::Gpu(engine);
+            }
+
+            GPU_UNAVAILABLE.store(true, Ordering::Relaxed);
+            tracing::warn!("⚠️ [INTERPOLATION] No GPU adapter, falling back to the CPU");
+        }
+
+        Self::Cpu(CPU_INTERPOLATOR.clone())
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Gpu(_) => "GPU",
+            Self::Cpu(_) => "CPU",
+        }
+    }
+
+    pub fn prepare(&self, width: u32, height: u32) {
+        if let Self::Gpu(engine) = self {
+            engine.optimize_for_size(width, height);
+            engine.prewarm_pools(&[(width, height)]);
+        }
+    }
+
+    pub fn interpolate_single_pair(
+        &self,
+        frame1: &DynamicImage,
+        frame2: &DynamicImage,
+        alpha: f32,
+        method: InterpolationMethod,
+    ) -> Result<DynamicImage> {
+        match self {
+            Self::Gpu(engine) => engine.interpolate_single_pair(frame1, frame2, alpha, method),
+            Self::Cpu(cpu) => cpu.interpolate_single_pair(frame1, frame2, alpha, method),
+        }
+    }
+
+    pub fn smart_cache_cleanup(&self) {
+        match self {
+            Self::Gpu(engine) => engine.smart_cache_cleanup(),
+            Self::Cpu(cpu) => cpu.clear_cache(),
+        }
+    }
+
+    /// Frees the caches once an animation is written.
+    pub fn release(&self) {
+        match self {
+            Self::Gpu(engine) => {
+                engine.optimize_memory_usage();
+                engine.clear_cache();
+                engine.cleanup();
+            }
+            Self::Cpu(cpu) => cpu.clear_cache(),
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use image::DynamicImage;
+use rayon::prelude::*;
+
+use super::frame::{gaussian_coeff, length, render, sobel_plane, FrameBuffer, SobelLayout};
+
+const PYRAMID_LEVELS: i32 = 5;
+const PYRAMID_SCALE: f32 = 0.5;
+const REFINE_ITERATIONS: u32 = 3;
+const POLY_SIGMA: f32 = 1.5;
+const HARRIS_K: f32 = 0.04;
+const HARRIS_THRESHOLD: f32 = 0.001;
+const MIN_DET: f32 = 1e-6;
+const MAX_CLIPPED_FLOW: f32 = 100.0;
+const MIN_WEIGHT: f32 = 0.1;
+
+struct LevelFlow {
+    scale: f32,
+    width: u32,
+    height: u32,
+    flow: Vec<[f32; 2]>,
+}
+
+/// Flow from `from` to `to` at one level. Like the shader, every level samples
+/// the full-resolution frames and only restricts coordinates to the scaled size.
+fn level_flow(from: &[f32], to: &[f32], stride: u32, bounds: (u32, u32), scale: f32) -> LevelFlow {
+    let grad = sobel_plane(from, stride, bounds, SobelLayout::Transposed);
+    let (width, height) = (bounds.0 as i32, bounds.1 as i32);
+
+    let flow = (0..bounds.0 as usize * bounds.1 as usize)
+        .into_par_iter()
+        .map(|index| {
+            let x = (index % bounds.0 as usize) as i32;
+            let y = (index / bounds.0 as usize) as i32;
+
+            let (mut gx, mut gy, mut gt) = (0.0f32, 0.0f32, 0.0f32);
+            let (mut gxx, mut gyy, mut gxy) = (0.0f32, 0.0f32, 0.0f32);
+            let mut weight_sum = 0.0f32;
+
+            for i in -2..=2 {
+                for j in -2..=2 {
+                    let (sx, sy) = (x + i, y + j);
+                    if !(0..width).contains(&sx) || !(0..height).contains(&sy) {
+                        continue;
+                    }
+
+                    let local = grad[(sy * width + sx) as usize];
+                    let frame_index = sy as usize * stride as usize + sx as usize;
+                    let weight = gaussian_coeff(i, j);
+
+                    gx += local[0] * weight;
+                    gy += local[1] * weight;
+                    gt += (to[frame_index] - from[frame_index]) * weight;
+                    gxx += local[0] * local[0] * weight;
+                    gyy += local[1] * local[1] * weight;
+                    gxy += local[0] * local[1] * weight;
+                    weight_sum += weight;
+                }
+            }
+
+            if weight_sum <= 0.0 {
+                return [0.0; 2];
+            }
+
+            let (hxx, hyy, hxy) = (gxx / weight_sum, gyy / weight_sum, gxy / weight_sum);
+            let trace = hxx + hyy;
+            let harris = hxx * hyy - hxy * hxy - HARRIS_K * trace * trace;
+            if harris <= HARRIS_THRESHOLD {
+                return [0.0; 2];
+            }
+
+            let (gx, gy, gt) = (gx / weight_sum, gy / weight_sum, gt / weight_sum);
+            let (ixx, iyy, ixy) = (gx * gx, gy * gy, gx * gy);
+            let (ixt, iyt) = (gx * gt, gy * gt);
+            let det = (ixx * iyy - ixy * ixy).max(MIN_DET);
+
+            let mut refined = [
+                -(iyy * ixt - ixy * iyt) / det,
+                -(ixx * iyt - ixy * ixt) / det,
+            ];
+            let smooth_factor = (-length(refined) * POLY_SIGMA).exp();
+            for _ in 0..REFINE_ITERATIONS {
+                refined = [refined[0] * smooth_factor, refined[1] * smooth_factor];
+            }
+
+            [refined[0] * scale, refined[1] * scale]
+        })
+        .collect();
+
+    LevelFlow {
+        scale,
+        width: bounds.0,
+        height: bounds.1,
+        flow,
+    }
+}
+
+fn pyramid_flow(from: &[f32], to: &[f32], width: u32, height: u32) -> Vec<LevelFlow> {
+    (0..PYRAMID_LEVELS)
+        .filter_map(|level| {
+            let scale = PYRAMID_SCALE.powi(level);
+            let bounds = (
+                (width as f32 * scale) as u32,
+                (height as f32 * scale) as u32,
+            );
+            (bounds.0 > 0 && bounds.1 > 0).then(|| level_flow(from, to, width, bounds, scale))
+        })
+        .collect()
+}
+
+fn flow_at(levels: &[LevelFlow], x: i32, y: i32) -> [f32; 2] {
+    levels.iter().fold([0.0f32; 2], |flow, level| {
+        let sx = (x as f32 * level.scale) as i32;
+        let sy = (y as f32 * level.scale) as i32;
+        if !(0..level.width as i32).contains(&sx) || !(0..level.height as i32).contains(&sy) {
+            return flow;
+        }
+        let contribution = level.flow[sy as usize * level.width as usize + sx as usize];
+        [flow[0] + contribution[0], flow[1] + contribution[1]]
+    })
+}
+
+pub fn bidirectional_interpolation(
+    frame1: &FrameBuffer,
+    frame2: &FrameBuffer,
+    alpha: f32,
+) -> DynamicImage {
+    let (width, height) = (frame1.width, frame1.height);
+    let gray1 = frame1.gray_plane();
+    let gray2 = frame2.gray_plane();
+
+    let forward = pyramid_flow(&gray1, &gray2, width, height);
+    let backward = pyramid_flow(&gray2, &gray1, width, height);
+
+    render(width, height, |x, y| {
+        let flow_forward = flow_at(&forward, x, y);
+        let flow_backward = flow_at(&backward, x, y);
+
+        let warped1 = frame1.bilinear(
+            x as f32 + flow_forward[0] * alpha,
+            y as f32 + flow_forward[1] * alpha,
+        );
+        let warped2 = frame2.bilinear(
+            x as f32 - flow_backward[0] * (1.0 - alpha),
+            y as f32 - flow_backward[1] * (1.0 - alpha),
+        );
+
+        let confidence1 = (-length(flow_forward).clamp(0.0, MAX_CLIPPED_FLOW) * 0.5).exp();
+        let confidence2 = (-length(flow_backward).clamp(0.0, MAX_CLIPPED_FLOW) * 0.5).exp();
+        let weight1 = ((1.0 - alpha) * confidence1).max(MIN_WEIGHT);
+        let weight2 = (alpha * confidence2).max(MIN_WEIGHT);
+        let total_weight = weight1 + weight2;
+        let (weight1, weight2) = (weight1 / total_weight, weight2 / total_weight);
+
+        [
+            warped1[0] * weight1 + warped2[0] * weight2,
+            warped1[1] * weight1 + warped2[1] * weight2,
+            warped1[2] * weight1 + warped2[2] * weight2,
+            warped1[3] * weight1 + warped2[3] * weight2,
+        ]
+    })
+}
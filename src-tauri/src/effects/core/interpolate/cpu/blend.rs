@@ -0,0 +1,9 @@
+use image::DynamicImage;
+
+use super::frame::{mix, render, FrameBuffer};
+
+pub fn blend_interpolation(frame1: &FrameBuffer, frame2: &FrameBuffer, alpha: f32) -> DynamicImage {
+    render(frame1.width, frame1.height, |x, y| {
+        mix(frame1.pixel(x, y), frame2.pixel(x, y), alpha)
+    })
+}
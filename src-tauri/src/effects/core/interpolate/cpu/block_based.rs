@@ -0,0 +1,101 @@
+use image::DynamicImage;
+
+use super::frame::{length, mix, render, FrameBuffer};
+
+const HALF_BLOCK: i32 = 4;
+const SEARCH_RADIUS: i32 = 4;
+const MAX_MOTION: f32 = 10.0;
+const NO_MATCH: f32 = 1e6;
+
+fn block_similarity(
+    gray1: &[f32],
+    gray2: &[f32],
+    width: i32,
+    height: i32,
+    center: (i32, i32),
+    offset: (i32, i32),
+) -> f32 {
+    let inside = |x: i32, y: i32| (0..width).contains(&x) && (0..height).contains(&y);
+    let mut similarity = 0.0f32;
+    let mut pixel_count = 0.0f32;
+
+    for i in -HALF_BLOCK..=HALF_BLOCK {
+        for j in -HALF_BLOCK..=HALF_BLOCK {
+            let (x1, y1) = (center.0 + i, center.1 + j);
+            let (x2, y2) = (x1 + offset.0, y1 + offset.1);
+
+            if inside(x1, y1) && inside(x2, y2) {
+                let pixel1 = gray1[(y1 * width + x1) as usize];
+                let pixel2 = gray2[(y2 * width + x2) as usize];
+                similarity += (pixel1 - pixel2).abs();
+                pixel_count += 1.0;
+            }
+        }
+    }
+
+    if pixel_count > 0.0 {
+        similarity / pixel_count
+    } else {
+        NO_MATCH
+    }
+}
+
+fn motion_vector(
+    gray1: &[f32],
+    gray2: &[f32],
+    width: i32,
+    height: i32,
+    x: i32,
+    y: i32,
+) -> [f32; 2] {
+    let mut best_offset = [0.0f32; 2];
+    let mut best_similarity = NO_MATCH;
+
+    for i in -SEARCH_RADIUS..=SEARCH_RADIUS {
+        for j in -SEARCH_RADIUS..=SEARCH_RADIUS {
+            let similarity = block_similarity(gray1, gray2, width, height, (x, y), (i, j));
+            if similarity < best_similarity {
+                best_similarity = similarity;
+                best_offset = [i as f32, j as f32];
+            }
+        }
+    }
+
+    let magnitude = length(best_offset);
+    if magnitude > MAX_MOTION {
+        return [
+            best_offset[0] / magnitude * MAX_MOTION,
+            best_offset[1] / magnitude * MAX_MOTION,
+        ];
+    }
+
+    best_offset
+}
+
+pub fn block_based_interpolation(
+    frame1: &FrameBuffer,
+    frame2: &FrameBuffer,
+    alpha: f32,
+) -> DynamicImage {
+    let (width, height) = (frame1.width as i32, frame1.height as i32);
+    let gray1 = frame1.gray_plane();
+    let gray2 = frame2.gray_plane();
+
+    render(frame1.width, frame1.height, |x, y| {
+        let motion = motion_vector(&gray1, &gray2, width, height, x, y);
+
+        let motion_confidence = 1.0 - (length(motion) / MAX_MOTION).clamp(0.0, 1.0);
+        let edge_distance = (x as f32)
+            .min(y as f32)
+            .min((width as f32 - x as f32).min(height as f32 - y as f32));
+        let border_confidence = (edge_distance / 10.0).clamp(0.0, 1.0);
+        let confidence = motion_confidence * border_confidence;
+
+        let wx = x as f32 + motion[0] * alpha;
+        let wy = y as f32 + motion[1] * alpha;
+        let motion_blend = mix(frame1.bilinear(wx, wy), frame2.bilinear(wx, wy), alpha);
+        let simple_blend = mix(frame1.pixel(x, y), frame2.pixel(x, y), alpha);
+
+        mix(simple_blend, motion_blend, confidence)
+    })
+}
@@ -0,0 +1,98 @@
+use image::DynamicImage;
+
+use super::frame::{length, mix, render, sobel_plane, FrameBuffer, SobelLayout};
+
+const MIN_GRADIENT: f32 = 0.001;
+const MAX_INTENSITY_DISPLACEMENT: f32 = 5.0;
+const MAX_CURVATURE: f32 = 3.0;
+const CURVATURE_WEIGHT: f32 = 0.3;
+const MAX_DISPLACEMENT: f32 = 8.0;
+const MAX_GRADIENT: f32 = 2.0;
+
+fn laplacian_plane(gray: &[f32], width: i32, height: i32) -> Vec<f32> {
+    (0..gray.len())
+        .map(|index| {
+            let x = index as i32 % width;
+            let y = index as i32 / width;
+            let center = gray[index];
+
+            [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                .iter()
+                .map(|&(dx, dy)| (x + dx, y + dy))
+                .filter(|&(nx, ny)| (0..width).contains(&nx) && (0..height).contains(&ny))
+                .fold(0.0f32, |laplacian, (nx, ny)| {
+                    laplacian + (gray[(ny * width + nx) as usize] - center)
+                })
+        })
+        .collect()
+}
+
+fn along_gradient(grad: [f32; 2], magnitude: f32, limit: f32) -> [f32; 2] {
+    let length = length(grad);
+    if length > MIN_GRADIENT {
+        let clamped = magnitude.clamp(-limit, limit);
+        [grad[0] / length * clamped, grad[1] / length * clamped]
+    } else {
+        [0.0; 2]
+    }
+}
+
+pub fn displacement_map_interpolation(
+    frame1: &FrameBuffer,
+    frame2: &FrameBuffer,
+    alpha: f32,
+) -> DynamicImage {
+    let (width, height) = (frame1.width, frame1.height);
+    let gray1 = frame1.gray_plane();
+    let gray2 = frame2.gray_plane();
+    let grad1 = sobel_plane(&gray1, width, (width, height), SobelLayout::Standard);
+    let grad2 = sobel_plane(&gray2, width, (width, height), SobelLayout::Standard);
+    let laplacian1 = laplacian_plane(&gray1, width as i32, height as i32);
+    let laplacian2 = laplacian_plane(&gray2, width as i32, height as i32);
+
+    render(width, height, |x, y| {
+        let index = (y as u32 * width + x as u32) as usize;
+        let g1 = [grad1[index][0] / 8.0, grad1[index][1] / 8.0];
+        let g2 = [grad2[index][0] / 8.0, grad2[index][1] / 8.0];
+
+        let grad_avg = [(g1[0] + g2[0]) * 0.5, (g1[1] + g2[1]) * 0.5];
+        let intensity = along_gradient(
+            grad_avg,
+            (gray2[index] - gray1[index]) * 2.0,
+            MAX_INTENSITY_DISPLACEMENT,
+        );
+        let curvature = along_gradient(
+            g1,
+            (laplacian2[index] - laplacian1[index]) * 0.5,
+            MAX_CURVATURE,
+        );
+
+        let mut displacement = [
+            intensity[0] + curvature[0] * CURVATURE_WEIGHT,
+            intensity[1] + curvature[1] * CURVATURE_WEIGHT,
+        ];
+        let magnitude = length(displacement);
+        if magnitude > MAX_DISPLACEMENT {
+            displacement = [
+                displacement[0] / magnitude * MAX_DISPLACEMENT,
+                displacement[1] / magnitude * MAX_DISPLACEMENT,
+            ];
+        }
+
+        let displacement_confidence =
+            1.0 - (length(displacement) / MAX_DISPLACEMENT).clamp(0.0, 1.0);
+        let edge_distance = (x as f32)
+            .min(y as f32)
+            .min((width as f32 - x as f32).min(height as f32 - y as f32));
+        let border_confidence = (edge_distance / 10.0).clamp(0.0, 1.0);
+        let gradient_confidence = (length(g1) / MAX_GRADIENT).clamp(0.0, 1.0);
+        let confidence = displacement_confidence * border_confidence * gradient_confidence;
+
+        let wx = x as f32 + displacement[0] * alpha;
+        let wy = y as f32 + displacement[1] * alpha;
+        let displacement_blend = mix(frame1.bilinear(wx, wy), frame2.bilinear(wx, wy), alpha);
+        let simple_blend = mix(frame1.pixel(x, y), frame2.pixel(x, y), alpha);
+
+        mix(simple_blend, displacement_blend, confidence)
+    })
+}
@@ -0,0 +1,176 @@
+use image::DynamicImage;
+
+use super::frame::{length, mix, render, rgba_to_gray, smoothstep, step, FrameBuffer};
+
+const TRANSITION_WIDTH: f32 = 0.15;
+const MORPHING_STRENGTH: f32 = 0.3;
+
+#[inline(always)]
+fn fract(v: f32) -> f32 {
+    v - v.floor()
+}
+
+/// Integer hash of a lattice point, bit-for-bit the same as the shader's.
+fn hash_noise(x: f32, y: f32) -> f32 {
+    let mut h =
+        (x as i32 as u32).wrapping_mul(0x8da6_b343) ^ (y as i32 as u32).wrapping_mul(0xd816_3841);
+    h = (h ^ (h >> 16)).wrapping_mul(0x7feb_352d);
+    h = (h ^ (h >> 15)).wrapping_mul(0x846c_a68b);
+    h ^= h >> 16;
+    (h >> 8) as f32 / 16_777_216.0
+}
+
+fn perlin_noise(x: f32, y: f32) -> f32 {
+    let (ix, iy) = (x.floor(), y.floor());
+    let (fx, fy) = (fract(x), fract(y));
+    let ux = fx * fx * (3.0 - 2.0 * fx);
+    let uy = fy * fy * (3.0 - 2.0 * fy);
+
+    let a = hash_noise(ix, iy);
+    let b = hash_noise(ix + 1.0, iy);
+    let c = hash_noise(ix, iy + 1.0);
+    let d = hash_noise(ix + 1.0, iy + 1.0);
+
+    let lerp = |from: f32, to: f32, t: f32| from * (1.0 - t) + to * t;
+    lerp(lerp(a, b, ux), lerp(c, d, ux), uy)
+}
+
+fn fractal_noise(x: f32, y: f32, octaves: u32) -> f32 {
+    let mut noise = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    let mut max_value = 0.0;
+
+    for _ in 0..octaves {
+        noise += perlin_noise(x * frequency, y * frequency) * amplitude;
+        max_value += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+
+    noise / max_value
+}
+
+struct MaskContext<'a> {
+    frame1: &'a FrameBuffer,
+    frame2: &'a FrameBuffer,
+    width: f32,
+    height: f32,
+    alpha: f32,
+}
+
+impl MaskContext<'_> {
+    fn normalized_noise(&self, x: f32, y: f32, scale: f32, octaves: u32) -> f32 {
+        fractal_noise(x / self.width * scale, y / self.height * scale, octaves)
+    }
+
+    fn particle_diffusion(&self, x: f32, y: f32) -> f32 {
+        let base_noise = self.normalized_noise(x, y, 8.0, 4);
+        let detail_noise = self.normalized_noise(x, y, 16.0, 2);
+        let density = base_noise * 0.7 + detail_noise * 0.3;
+        let threshold = 1.0 - self.alpha * 2.0;
+
+        step(threshold, density) * (density - threshold) / (1.0 - threshold)
+    }
+
+    fn multi_threshold(&self, x: f32, y: f32) -> f32 {
+        let mask1 = step(self.alpha * 0.3, self.normalized_noise(x, y, 4.0, 3));
+        let mask2 = step(self.alpha * 0.6, self.normalized_noise(x, y, 8.0, 2));
+        let mask3 = step(self.alpha * 0.9, self.normalized_noise(x, y, 16.0, 1));
+
+        (mask1 + mask2 + mask3) / 3.0
+    }
+
+    fn luminance_based(&self, x: f32, y: f32) -> f32 {
+        let (px, py) = (x as i32, y as i32);
+        let luminance1 = rgba_to_gray(self.frame1.pixel(px, py));
+        let luminance2 = rgba_to_gray(self.frame2.pixel(px, py));
+        let luminance_diff = (luminance2 - luminance1).abs();
+        let noise = self.normalized_noise(x, y, 6.0, 2);
+
+        let luminance_mask = step(self.alpha * (1.0 + luminance_diff * 2.0), luminance_diff);
+        let noise_mask = step(self.alpha * 0.5, noise);
+
+        (luminance_mask + noise_mask) * 0.5
+    }
+
+    fn morphing(&self, x: f32, y: f32) -> f32 {
+        let morphing_noise = self.normalized_noise(x, y, 6.0, 3);
+        let edge_noise = self.normalized_noise(x, y, 12.0, 2);
+        let morphing_factor = self.alpha * 2.0;
+        let edge_factor = self.alpha * 1.5;
+
+        let morphing_mask =
+            smoothstep(morphing_factor - 0.2, morphing_factor + 0.2, morphing_noise);
+        let edge_mask = smoothstep(edge_factor - 0.1, edge_factor + 0.1, edge_noise);
+
+        morphing_mask * 0.7 + edge_mask * 0.3
+    }
+
+    fn dispersion(&self, x: f32, y: f32) -> f32 {
+        let offset = [x - self.width * 0.5, y - self.height * 0.5];
+        let distance = length(offset);
+        // normalize() of a zero vector is undefined in WGSL; keep the center finite.
+        let direction = if distance > 0.0 {
+            [offset[0] / distance, offset[1] / distance]
+        } else {
+            [0.0; 2]
+        };
+
+        let dispersion_noise = fractal_noise(
+            direction[0] * distance * 0.01,
+            direction[1] * distance * 0.01,
+            2,
+        );
+        let radial_noise = fractal_noise(distance * 0.02, 0.0, 3);
+        let dispersion_factor = self.alpha * 1.5;
+        let radial_factor = self.alpha * 0.8;
+
+        let dispersion_mask = smoothstep(
+            dispersion_factor - 0.3,
+            dispersion_factor + 0.3,
+            dispersion_noise,
+        );
+        let radial_mask = smoothstep(radial_factor - 0.2, radial_factor + 0.2, radial_noise);
+
+        (dispersion_mask + radial_mask) * 0.5
+    }
+
+    fn advanced(&self, x: f32, y: f32) -> f32 {
+        self.particle_diffusion(x, y) * 0.25
+            + self.multi_threshold(x, y) * 0.2
+            + self.luminance_based(x, y) * 0.2
+            + self.morphing(x, y) * 0.2
+            + self.dispersion(x, y) * 0.15
+    }
+}
+
+pub fn dissolve_interpolation(
+    frame1: &FrameBuffer,
+    frame2: &FrameBuffer,
+    alpha: f32,
+) -> DynamicImage {
+    let masks = MaskContext {
+        frame1,
+        frame2,
+        width: frame1.width as f32,
+        height: frame1.height as f32,
+        alpha,
+    };
+
+    render(frame1.width, frame1.height, |x, y| {
+        let (fx, fy) = (x as f32, y as f32);
+        let mask = masks.advanced(fx, fy);
+        let transition = smoothstep(alpha - TRANSITION_WIDTH, alpha + TRANSITION_WIDTH, mask);
+
+        let warp = (mask - 0.5) * 2.0;
+        let morphing_blend = mix(
+            frame1.bilinear(fx + warp, fy + warp),
+            frame2.bilinear(fx + warp, fy + warp),
+            transition,
+        );
+        let simple_blend = mix(frame1.pixel(x, y), frame2.pixel(x, y), transition);
+
+        mix(simple_blend, morphing_blend, mask * MORPHING_STRENGTH)
+    })
+}
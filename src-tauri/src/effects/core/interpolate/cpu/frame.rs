@@ -0,0 +1,232 @@
+use image::{DynamicImage, RgbaImage};
+use rayon::prelude::*;
+
+const GAUSSIAN_5X5: [[f32; 5]; 5] = [
+    [0.003765, 0.015019, 0.023792, 0.015019, 0.003765],
+    [0.015019, 0.059912, 0.094907, 0.059912, 0.015019],
+    [0.023792, 0.094907, 0.150342, 0.094907, 0.023792],
+    [0.015019, 0.059912, 0.094907, 0.059912, 0.015019],
+    [0.003765, 0.015019, 0.023792, 0.015019, 0.003765],
+];
+
+pub type Pixel = [f32; 4];
+
+/// Which Sobel orientation a shader uses. Some of the WGSL kernels index their
+/// coefficient tables with the row first, which swaps the x and y responses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SobelLayout {
+    Standard,
+    Transposed,
+}
+
+/// Normalized RGBA frame, laid out the way the shaders see an `rgba8unorm` texture.
+#[derive(Clone, Debug)]
+pub struct FrameBuffer {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<Pixel>,
+}
+
+impl FrameBuffer {
+    pub fn from_image(image: &DynamicImage) -> Self {
+        let rgba = image.to_rgba8();
+        let (width, height) = rgba.dimensions();
+        let pixels = rgba
+            .pixels()
+            .map(|p| {
+                [
+                    p[0] as f32 / 255.0,
+                    p[1] as f32 / 255.0,
+                    p[2] as f32 / 255.0,
+                    p[3] as f32 / 255.0,
+                ]
+            })
+            .collect();
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    #[inline(always)]
+    pub fn pixel(&self, x: i32, y: i32) -> Pixel {
+        self.pixels[y as usize * self.width as usize + x as usize]
+    }
+
+    pub fn gray_plane(&self) -> Vec<f32> {
+        self.pixels.par_iter().map(|&p| rgba_to_gray(p)).collect()
+    }
+
+    #[inline(always)]
+    fn clamped_pixel(&self, x: i32, y: i32) -> Pixel {
+        self.pixel(
+            x.clamp(0, self.width as i32 - 1),
+            y.clamp(0, self.height as i32 - 1),
+        )
+    }
+
+    /// Bilinear sample with clamp-to-edge addressing.
+    #[inline(always)]
+    pub fn bilinear(&self, x: f32, y: f32) -> Pixel {
+        let x0 = x.floor() as i32;
+        let y0 = y.floor() as i32;
+        let fx = x - x.floor();
+        let fy = y - y.floor();
+
+        let p00 = self.clamped_pixel(x0, y0);
+        let p01 = self.clamped_pixel(x0, y0 + 1);
+        let p10 = self.clamped_pixel(x0 + 1, y0);
+        let p11 = self.clamped_pixel(x0 + 1, y0 + 1);
+
+        mix(mix(p00, p01, fy), mix(p10, p11, fy), fx)
+    }
+
+    /// Half-resolution level of a gaussian pyramid, quantized like the
+    /// `rgba8unorm` textures the GPU pyramid is stored in.
+    pub fn reduce(&self) -> Self {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+
+        let pixels = (0..height * width)
+            .into_par_iter()
+            .map(|index| {
+                let cx = (index % width) as i32 * 2;
+                let cy = (index / width) as i32 * 2;
+
+                let mut sum = [0.0f32; 4];
+                let mut weight_sum = 0.0;
+                for i in -2..=2i32 {
+                    for j in -2..=2i32 {
+                        let weight = GAUSSIAN_5X5[(i + 2) as usize][(j + 2) as usize];
+                        let sample = self.clamped_pixel(cx + i, cy + j);
+                        for (total, value) in sum.iter_mut().zip(sample) {
+                            *total += value * weight;
+                        }
+                        weight_sum += weight;
+                    }
+                }
+
+                sum.map(|v| quantize(v / weight_sum) as f32 / 255.0)
+            })
+            .collect();
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+#[inline(always)]
+pub fn rgba_to_gray(p: Pixel) -> f32 {
+    0.2126 * p[0] + 0.7152 * p[1] + 0.0722 * p[2]
+}
+
+#[inline(always)]
+pub fn mix(a: Pixel, b: Pixel, t: f32) -> Pixel {
+    [
+        a[0] * (1.0 - t) + b[0] * t,
+        a[1] * (1.0 - t) + b[1] * t,
+        a[2] * (1.0 - t) + b[2] * t,
+        a[3] * (1.0 - t) + b[3] * t,
+    ]
+}
+
+#[inline(always)]
+pub fn gaussian_coeff(i: i32, j: i32) -> f32 {
+    GAUSSIAN_5X5[(i + 2) as usize][(j + 2) as usize]
+}
+
+#[inline(always)]
+pub fn length(v: [f32; 2]) -> f32 {
+    (v[0] * v[0] + v[1] * v[1]).sqrt()
+}
+
+#[inline(always)]
+pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[inline(always)]
+pub fn step(edge: f32, x: f32) -> f32 {
+    if x >= edge {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+#[inline(always)]
+fn quantize(v: f32) -> u8 {
+    (v.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[inline(always)]
+fn sobel_coeffs(dx: i32, dy: i32, layout: SobelLayout) -> (f32, f32) {
+    let x_weight = if dy == 0 { 2.0 } else { 1.0 };
+    let y_weight = if dx == 0 { 2.0 } else { 1.0 };
+    match layout {
+        SobelLayout::Standard => (dx as f32 * x_weight, dy as f32 * y_weight),
+        SobelLayout::Transposed => (dy as f32 * y_weight, dx as f32 * x_weight),
+    }
+}
+
+/// Unnormalized 3x3 Sobel response over the top-left `bounds` region of a
+/// gray plane with row length `stride`. Neighbours outside `bounds` are skipped,
+/// as the shaders do.
+pub fn sobel_plane(
+    gray: &[f32],
+    stride: u32,
+    bounds: (u32, u32),
+    layout: SobelLayout,
+) -> Vec<[f32; 2]> {
+    let (width, height) = (bounds.0 as i32, bounds.1 as i32);
+
+    (0..bounds.0 as usize * bounds.1 as usize)
+        .into_par_iter()
+        .map(|index| {
+            let x = (index % bounds.0 as usize) as i32;
+            let y = (index / bounds.0 as usize) as i32;
+
+            let mut grad = [0.0f32; 2];
+            for i in -1..=1 {
+                for j in -1..=1 {
+                    let (sx, sy) = (x + i, y + j);
+                    if (0..width).contains(&sx) && (0..height).contains(&sy) {
+                        let value = gray[sy as usize * stride as usize + sx as usize];
+                        let (cx, cy) = sobel_coeffs(i, j, layout);
+                        grad[0] += value * cx;
+                        grad[1] += value * cy;
+                    }
+                }
+            }
+            grad
+        })
+        .collect()
+}
+
+/// Runs `shade` for every pixel and stores the clamped result as RGBA8.
+pub fn render<F>(width: u32, height: u32, shade: F) -> DynamicImage
+where
+    F: Fn(i32, i32) -> Pixel + Sync,
+{
+    let mut output = RgbaImage::new(width, height);
+
+    output
+        .par_chunks_mut(width as usize * 4)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for (x, out) in row.chunks_exact_mut(4).enumerate() {
+                let pixel = shade(x as i32, y as i32);
+                for (channel, value) in out.iter_mut().zip(pixel) {
+                    *channel = quantize(value);
+                }
+            }
+        });
+
+    DynamicImage::ImageRgba8(output)
+}
@@ -0,0 +1,150 @@
+use image::DynamicImage;
+
+use super::frame::{length, mix, render, sobel_plane, FrameBuffer, SobelLayout};
+
+const HARRIS_WINDOW: i32 = 2;
+const HARRIS_K: f32 = 0.04;
+const HARRIS_THRESHOLD: f32 = 0.01;
+const FLOW_WINDOW: i32 = 3;
+const MIN_EIGENVALUE: f32 = 0.001;
+const MAX_FLOW: f32 = 10.0;
+
+struct Planes<'a> {
+    width: i32,
+    height: i32,
+    gray1: &'a [f32],
+    gray2: &'a [f32],
+    grad1: &'a [[f32; 2]],
+    grad2: &'a [[f32; 2]],
+}
+
+impl Planes<'_> {
+    #[inline(always)]
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if (0..self.width).contains(&x) && (0..self.height).contains(&y) {
+            Some(y as usize * self.width as usize + x as usize)
+        } else {
+            None
+        }
+    }
+
+    fn harris_response(&self, x: i32, y: i32) -> f32 {
+        let (mut ixx, mut iyy, mut ixy, mut weight_sum) = (0.0f32, 0.0f32, 0.0f32, 0.0f32);
+
+        for i in -HARRIS_WINDOW..=HARRIS_WINDOW {
+            for j in -HARRIS_WINDOW..=HARRIS_WINDOW {
+                if let Some(index) = self.index(x + i, y + j) {
+                    let grad = self.grad1[index];
+                    let weight = (-((i * i + j * j) as f32) / 2.0).exp();
+                    ixx += grad[0] * grad[0] * weight;
+                    iyy += grad[1] * grad[1] * weight;
+                    ixy += grad[0] * grad[1] * weight;
+                    weight_sum += weight;
+                }
+            }
+        }
+
+        if weight_sum <= 0.0 {
+            return 0.0;
+        }
+
+        let (ixx, iyy, ixy) = (ixx / weight_sum, iyy / weight_sum, ixy / weight_sum);
+        let trace = ixx + iyy;
+        ixx * iyy - ixy * ixy - HARRIS_K * trace * trace
+    }
+
+    fn flow(&self, x: i32, y: i32) -> [f32; 2] {
+        let (mut a00, mut a01, mut a11) = (0.0f32, 0.0f32, 0.0f32);
+        let mut b = [0.0f32; 2];
+        let mut weight_sum = 0.0f32;
+
+        for i in -FLOW_WINDOW..=FLOW_WINDOW {
+            for j in -FLOW_WINDOW..=FLOW_WINDOW {
+                if let Some(index) = self.index(x + i, y + j) {
+                    let ix = (self.grad1[index][0] + self.grad2[index][0]) * 0.5;
+                    let iy = (self.grad1[index][1] + self.grad2[index][1]) * 0.5;
+                    let it = self.gray2[index] - self.gray1[index];
+                    let weight = (-((i * i + j * j) as f32) / 4.0).exp();
+
+                    a00 += ix * ix * weight;
+                    a01 += ix * iy * weight;
+                    a11 += iy * iy * weight;
+                    b[0] += -ix * it * weight;
+                    b[1] += -iy * it * weight;
+                    weight_sum += weight;
+                }
+            }
+        }
+
+        if weight_sum <= 0.0 {
+            return [0.0; 2];
+        }
+
+        let (a00, a01, a11) = (a00 / weight_sum, a01 / weight_sum, a11 / weight_sum);
+        let b = [b[0] / weight_sum, b[1] / weight_sum];
+        let det = a00 * a11 - a01 * a01;
+
+        if det.abs() > MIN_EIGENVALUE {
+            let inv_det = 1.0 / det;
+            let flow = [
+                (a11 * b[0] - a01 * b[1]) * inv_det,
+                (-a01 * b[0] + a00 * b[1]) * inv_det,
+            ];
+            if length(flow) < MAX_FLOW {
+                return flow;
+            }
+        }
+
+        [0.0; 2]
+    }
+}
+
+pub fn lucas_kanade_interpolation(
+    frame1: &FrameBuffer,
+    frame2: &FrameBuffer,
+    alpha: f32,
+) -> DynamicImage {
+    let (width, height) = (frame1.width, frame1.height);
+    let gray1 = frame1.gray_plane();
+    let gray2 = frame2.gray_plane();
+    let normalize = |grad: Vec<[f32; 2]>| -> Vec<[f32; 2]> {
+        grad.into_iter().map(|g| [g[0] / 8.0, g[1] / 8.0]).collect()
+    };
+    let grad1 = normalize(sobel_plane(
+        &gray1,
+        width,
+        (width, height),
+        SobelLayout::Standard,
+    ));
+    let grad2 = normalize(sobel_plane(
+        &gray2,
+        width,
+        (width, height),
+        SobelLayout::Standard,
+    ));
+
+    let planes = Planes {
+        width: width as i32,
+        height: height as i32,
+        gray1: &gray1,
+        gray2: &gray2,
+        grad1: &grad1,
+        grad2: &grad2,
+    };
+
+    render(width, height, |x, y| {
+        let harris = planes.harris_response(x, y);
+        let flow = planes.flow(x, y);
+
+        let feature_confidence = (harris / HARRIS_THRESHOLD).clamp(0.0, 1.0);
+        let flow_confidence = 1.0 - (length(flow) / MAX_FLOW).clamp(0.0, 1.0);
+        let confidence = feature_confidence * flow_confidence;
+
+        let wx = x as f32 + flow[0] * alpha;
+        let wy = y as f32 + flow[1] * alpha;
+        let motion_blend = mix(frame1.bilinear(wx, wy), frame2.bilinear(wx, wy), alpha);
+        let simple_blend = mix(frame1.pixel(x, y), frame2.pixel(x, y), alpha);
+
+        mix(simple_blend, motion_blend, confidence)
+    })
+}
@@ -0,0 +1,79 @@
+pub mod bidirectional;
+pub mod blend;
+pub mod block_based;
+pub mod displacement_map;
+pub mod dissolve;
+pub mod frame;
+pub mod lucas_kanade;
+pub mod motion_flow;
+
+use anyhow::Result;
+use image::{DynamicImage, GenericImageView};
+use parking_lot::RwLock;
+
+use crate::{
+    effects::core::interpolate::methods::phase_based::{phase_based_interpolation, FftCache},
+    types::InterpolationMethod,
+};
+use frame::FrameBuffer;
+
+/// CPU counterpart of `InterpolationEngine`. Every method mirrors its WGSL
+/// shader so both backends produce the same frames within rounding.
+pub struct CpuInterpolator {
+    fft_cache: RwLock<FftCache>,
+}
+
+impl Default for CpuInterpolator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CpuInterpolator {
+    pub fn new() -> Self {
+        Self {
+            fft_cache: RwLock::new(FftCache::new(100)),
+        }
+    }
+
+    pub fn interpolate_single_pair(
+        &self,
+        frame1: &DynamicImage,
+        frame2: &DynamicImage,
+        alpha: f32,
+        method: InterpolationMethod,
+    ) -> Result<DynamicImage> {
+        if frame1.dimensions() != frame2.dimensions() {
+            return Err(anyhow::anyhow!(
+                "Cannot interpolate frames of different sizes: {:?} and {:?}",
+                frame1.dimensions(),
+                frame2.dimensions()
+            ));
+        }
+
+        let interpolate: fn(&FrameBuffer, &FrameBuffer, f32) -> DynamicImage = match method {
+            InterpolationMethod::PhaseBased => {
+                return phase_based_interpolation(frame1, frame2, alpha, &self.fft_cache);
+            }
+            InterpolationMethod::LucasKanade => lucas_kanade::lucas_kanade_interpolation,
+            InterpolationMethod::Blend => blend::blend_interpolation,
+            InterpolationMethod::MotionFlow => motion_flow::motion_flow_interpolation,
+            InterpolationMethod::Bidirectional => bidirectional::bidirectional_interpolation,
+            InterpolationMethod::Dissolve => dissolve::dissolve_interpolation,
+            InterpolationMethod::BlockBased => block_based::block_based_interpolation,
+            InterpolationMethod::DisplacementMap => {
+                displacement_map::displacement_map_interpolation
+            }
+        };
+
+        Ok(interpolate(
+            &FrameBuffer::from_image(frame1),
+            &FrameBuffer::from_image(frame2),
+            alpha,
+        ))
+    }
+
+    pub fn clear_cache(&self) {
+        self.fft_cache.write().clear();
+    }
+}
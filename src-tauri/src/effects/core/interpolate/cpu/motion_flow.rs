@@ -0,0 +1,144 @@
+use image::DynamicImage;
+use rayon::prelude::*;
+
+use super::frame::{length, mix, render, sobel_plane, FrameBuffer, SobelLayout};
+
+const PYRAMID_LEVELS: i32 = 3;
+const REFINE_ITERATIONS: u32 = 3;
+const POLY_SIGMA: f32 = 1.2;
+const DET_THRESHOLD: f32 = 1e-6;
+const MAX_FLOW: f32 = 10.0;
+const MAX_GRADIENT: f32 = 2.0;
+
+/// Flow contributed by one pyramid level, on that level's grid.
+struct LevelFlow {
+    scale: f32,
+    width: u32,
+    height: u32,
+    flow: Vec<Option<[f32; 2]>>,
+}
+
+impl LevelFlow {
+    fn at(&self, x: i32, y: i32) -> Option<[f32; 2]> {
+        let sx = (x as f32 * self.scale) as i32;
+        let sy = (y as f32 * self.scale) as i32;
+        if !(0..self.width as i32).contains(&sx) || !(0..self.height as i32).contains(&sy) {
+            return None;
+        }
+        self.flow[sy as usize * self.width as usize + sx as usize]
+    }
+}
+
+fn level_flow(
+    level1: &FrameBuffer,
+    level2: &FrameBuffer,
+    bounds: (u32, u32),
+    scale: f32,
+) -> LevelFlow {
+    let gray1 = level1.gray_plane();
+    let gray2 = level2.gray_plane();
+    let grad1 = sobel_plane(&gray1, level1.width, bounds, SobelLayout::Transposed);
+    let grad2 = sobel_plane(&gray2, level2.width, bounds, SobelLayout::Transposed);
+
+    let flow = (0..bounds.0 as usize * bounds.1 as usize)
+        .into_par_iter()
+        .map(|index| {
+            let x = index % bounds.0 as usize;
+            let y = index / bounds.0 as usize;
+            let plane_index = y * level1.width as usize + x;
+
+            let ix = (grad1[index][0] / 8.0 + grad2[index][0] / 8.0) * 0.5;
+            let iy = (grad1[index][1] / 8.0 + grad2[index][1] / 8.0) * 0.5;
+            let it = gray2[plane_index] - gray1[plane_index];
+
+            let (ixx, iyy, ixy) = (ix * ix, iy * iy, ix * iy);
+            let (ixt, iyt) = (ix * it, iy * it);
+            let det = ixx * iyy - ixy * ixy;
+            if det.abs() <= DET_THRESHOLD {
+                return None;
+            }
+
+            let inv_det = 1.0 / det;
+            let mut refined = [
+                -(iyy * ixt - ixy * iyt) * inv_det,
+                -(ixx * iyt - ixy * ixt) * inv_det,
+            ];
+            let magnitude = length(refined);
+            if magnitude >= MAX_FLOW {
+                return None;
+            }
+
+            for _ in 0..REFINE_ITERATIONS {
+                let smooth_weight = (-magnitude * POLY_SIGMA).exp();
+                refined = [refined[0] * smooth_weight, refined[1] * smooth_weight];
+            }
+
+            Some([refined[0] * scale, refined[1] * scale])
+        })
+        .collect();
+
+    LevelFlow {
+        scale,
+        width: bounds.0,
+        height: bounds.1,
+        flow,
+    }
+}
+
+pub fn motion_flow_interpolation(
+    frame1: &FrameBuffer,
+    frame2: &FrameBuffer,
+    alpha: f32,
+) -> DynamicImage {
+    let (width, height) = (frame1.width, frame1.height);
+
+    let mut pyramid1 = vec![frame1.clone()];
+    let mut pyramid2 = vec![frame2.clone()];
+    for level in 1..PYRAMID_LEVELS as usize {
+        pyramid1.push(pyramid1[level - 1].reduce());
+        pyramid2.push(pyramid2[level - 1].reduce());
+    }
+
+    let levels: Vec<LevelFlow> = (0..PYRAMID_LEVELS)
+        .filter_map(|level| {
+            let scale = 0.5f32.powi(level);
+            let bounds = (
+                (width as f32 * scale) as u32,
+                (height as f32 * scale) as u32,
+            );
+            (bounds.0 > 0 && bounds.1 > 0).then(|| {
+                level_flow(
+                    &pyramid1[level as usize],
+                    &pyramid2[level as usize],
+                    bounds,
+                    scale,
+                )
+            })
+        })
+        .collect();
+
+    let gray1 = frame1.gray_plane();
+    let grad1 = sobel_plane(&gray1, width, (width, height), SobelLayout::Transposed);
+
+    render(width, height, |x, y| {
+        let mut flow = [0.0f32; 2];
+        for level in &levels {
+            if let Some(contribution) = level.at(x, y) {
+                flow = [flow[0] + contribution[0], flow[1] + contribution[1]];
+            }
+        }
+
+        let grad = grad1[(y as u32 * width + x as u32) as usize];
+        let grad_magnitude = length([grad[0] / 8.0, grad[1] / 8.0]);
+        let flow_confidence = 1.0 - (length(flow) / MAX_FLOW).clamp(0.0, 1.0);
+        let grad_confidence = (grad_magnitude / MAX_GRADIENT).clamp(0.0, 1.0);
+        let confidence = (flow_confidence * grad_confidence).clamp(0.0, 1.0);
+
+        let warped1 = frame1.bilinear(x as f32 + flow[0] * alpha, y as f32 + flow[1] * alpha);
+        let pixel2 = frame2.pixel(x, y);
+        let simple_blend = mix(frame1.pixel(x, y), pixel2, alpha);
+        let motion_blend = mix(warped1, pixel2, alpha);
+
+        mix(simple_blend, motion_blend, confidence)
+    })
+}
@@ -527,7 +527,7 @@ impl InterpolationEngine {
         Ok(interpolated_frames)
     }
 
-    fn interpolate_single_pair(
+    pub fn interpolate_single_pair(
         &self,
         frame1: &DynamicImage,
         frame2: &DynamicImage,
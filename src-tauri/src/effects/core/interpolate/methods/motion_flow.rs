@@ -96,6 +96,20 @@ impl InterpolationEngine {
             let mut texture_pool = self.texture_pool.lock();
             texture_pool.acquire(self.engine.device(), texture.width(), texture.height())
         };
+
+        let mut encoder =
+            self.engine
+                .device()
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Gaussian Pyramid Base Copy"),
+                });
+        encoder.copy_texture_to_texture(
+            texture.as_image_copy(),
+            first_texture.as_image_copy(),
+            texture.size(),
+        );
+        self.engine.queue().submit(iter::once(encoder.finish()));
+
         pyramid.push(first_texture);
 
         for level in 1..levels {
@@ -1,8 +1,12 @@
+pub mod backend;
+pub mod cpu;
 pub mod interpolation;
 pub mod methods;
 pub mod pipelines;
 pub mod utils;
+pub mod verify;
 
+pub use backend::InterpolationBackend;
 pub use interpolation::*;
 
 use crate::types::InterpolationMethod;
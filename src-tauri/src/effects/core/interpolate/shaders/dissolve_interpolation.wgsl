@@ -13,8 +13,14 @@ fn rgba_to_luminance(rgba: vec4<f32>) -> f32 {
     return 0.2126 * rgba.r + 0.7152 * rgba.g + 0.0722 * rgba.b;
 }
 
+// Integer hash of a lattice point. sin() loses precision for large arguments
+// and differs between GPUs, this one matches the CPU port exactly.
 fn hash_noise(pos: vec2<f32>) -> f32 {
-    return fract(sin(dot(pos, vec2<f32>(12.9898, 78.233))) * 43758.5453);
+    var h = bitcast<u32>(i32(pos.x)) * 0x8da6b343u ^ bitcast<u32>(i32(pos.y)) * 0xd8163841u;
+    h = (h ^ (h >> 16u)) * 0x7feb352du;
+    h = (h ^ (h >> 15u)) * 0x846ca68bu;
+    h = h ^ (h >> 16u);
+    return f32(h >> 8u) / 16777216.0;
 }
 
 fn perlin_noise(pos: vec2<f32>) -> f32 {
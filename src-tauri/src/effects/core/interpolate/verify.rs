@@ -0,0 +1,195 @@
+use anyhow::Result;
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+use std::fmt;
+
+use crate::effects::core::interpolate::{
+    cpu::CpuInterpolator, interpolation::InterpolationEngine, InterpolationMethod,
+};
+
+pub const ALL_INTERPOLATION_METHODS: [InterpolationMethod; 8] = [
+    InterpolationMethod::Blend,
+    InterpolationMethod::LucasKanade,
+    InterpolationMethod::MotionFlow,
+    InterpolationMethod::PhaseBased,
+    InterpolationMethod::Bidirectional,
+    InterpolationMethod::Dissolve,
+    InterpolationMethod::BlockBased,
+    InterpolationMethod::DisplacementMap,
+];
+
+const CROSS_CHECK_SIZE: u32 = 64;
+const CROSS_CHECK_ALPHA: f32 = 0.4;
+
+/// How far the CPU output may drift from the GPU output. Flow estimators sit on
+/// thresholds (determinants, harris responses) where float rounding can flip a
+/// few pixels. Dissolve hashes integer lattice points the same way on both
+/// sides, only its smoothstep edges and bilinear warp can round differently.
+#[derive(Debug, Clone, Copy)]
+pub struct Tolerance {
+    pub max_delta: u8,
+    pub max_outlier_ratio: f64,
+}
+
+pub fn tolerance_for(method: InterpolationMethod) -> Tolerance {
+    match method {
+        InterpolationMethod::Blend | InterpolationMethod::PhaseBased => Tolerance {
+            max_delta: 1,
+            max_outlier_ratio: 0.0,
+        },
+        InterpolationMethod::BlockBased => Tolerance {
+            max_delta: 1,
+            max_outlier_ratio: 0.001,
+        },
+        InterpolationMethod::LucasKanade
+        | InterpolationMethod::MotionFlow
+        | InterpolationMethod::Bidirectional
+        | InterpolationMethod::DisplacementMap => Tolerance {
+            max_delta: 2,
+            max_outlier_ratio: 0.01,
+        },
+        InterpolationMethod::Dissolve => Tolerance {
+            max_delta: 2,
+            max_outlier_ratio: 0.005,
+        },
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BackendComparison {
+    pub method: InterpolationMethod,
+    pub max_delta: u8,
+    pub mean_delta: f64,
+    pub outlier_ratio: f64,
+    pub within_tolerance: bool,
+}
+
+impl fmt::Display for BackendComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: max delta {}, mean delta {:.3}, {:.2}% outside tolerance",
+            self.method,
+            self.max_delta,
+            self.mean_delta,
+            self.outlier_ratio * 100.0
+        )
+    }
+}
+
+pub fn compare_images(
+    method: InterpolationMethod,
+    expected: &DynamicImage,
+    actual: &DynamicImage,
+) -> Result<BackendComparison> {
+    if expected.dimensions() != actual.dimensions() {
+        return Err(anyhow::anyhow!(
+            "{} output sizes differ: {:?} and {:?}",
+            method,
+            expected.dimensions(),
+            actual.dimensions()
+        ));
+    }
+
+    let tolerance = tolerance_for(method);
+    let expected = expected.to_rgba8();
+    let actual = actual.to_rgba8();
+
+    let mut max_delta = 0u8;
+    let mut total_delta = 0u64;
+    let mut outliers = 0u64;
+    for (a, b) in expected.as_raw().iter().zip(actual.as_raw()) {
+        let delta = a.abs_diff(*b);
+        max_delta = max_delta.max(delta);
+        total_delta += delta as u64;
+        if delta > tolerance.max_delta {
+            outliers += 1;
+        }
+    }
+
+    let channel_count = expected.as_raw().len().max(1) as f64;
+    let outlier_ratio = outliers as f64 / channel_count;
+
+    Ok(BackendComparison {
+        method,
+        max_delta,
+        mean_delta: total_delta as f64 / channel_count,
+        outlier_ratio,
+        within_tolerance: outlier_ratio <= tolerance.max_outlier_ratio,
+    })
+}
+
+pub fn compare_backends(
+    engine: &InterpolationEngine,
+    cpu: &CpuInterpolator,
+    frame1: &DynamicImage,
+    frame2: &DynamicImage,
+    alpha: f32,
+    method: InterpolationMethod,
+) -> Result<BackendComparison> {
+    let gpu_output = engine.interpolate_single_pair(frame1, frame2, alpha, method)?;
+    let cpu_output = cpu.interpolate_single_pair(frame1, frame2, alpha, method)?;
+    compare_images(method, &gpu_output, &cpu_output)
+}
+
+/// Textured frame with a square that moves between the two frames, so every
+/// method has edges, corners and motion to work with.
+pub fn synthetic_frame_pair(width: u32, height: u32) -> (DynamicImage, DynamicImage) {
+    let frame = |shift_x: u32, shift_y: u32| {
+        RgbaImage::from_fn(width, height, |x, y| {
+            let (fx, fy) = (x as f32 / width as f32, y as f32 / height as f32);
+            let inside = x >= width / 4 + shift_x
+                && x < width / 2 + shift_x
+                && y >= height / 4 + shift_y
+                && y < height / 2 + shift_y;
+
+            if inside {
+                Rgba([230, 200, 40, 255])
+            } else {
+                let wave = ((fx * 9.0).sin() * (fy * 7.0).cos() * 0.5 + 0.5) * 120.0;
+                Rgba([(fx * 255.0) as u8, wave as u8, (fy * 255.0) as u8, 255])
+            }
+        })
+    };
+
+    (
+        DynamicImage::ImageRgba8(frame(0, 0)),
+        DynamicImage::ImageRgba8(frame(2, 1)),
+    )
+}
+
+pub fn cross_check_all(
+    engine: &InterpolationEngine,
+    cpu: &CpuInterpolator,
+) -> Result<Vec<BackendComparison>> {
+    let (frame1, frame2) = synthetic_frame_pair(CROSS_CHECK_SIZE, CROSS_CHECK_SIZE);
+
+    ALL_INTERPOLATION_METHODS
+        .iter()
+        .map(|&method| compare_backends(engine, cpu, &frame1, &frame2, CROSS_CHECK_ALPHA, method))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpu_ports_match_gpu_pipelines() {
+        let Some(engine) = pollster::block_on(InterpolationEngine::get_or_create_global()) else {
+            println!("no GPU adapter, skipping the CPU/GPU cross-check");
+            return;
+        };
+
+        let comparisons = cross_check_all(&engine, &CpuInterpolator::new()).unwrap();
+        let failures: Vec<String> = comparisons
+            .iter()
+            .filter(|comparison| !comparison.within_tolerance)
+            .map(|comparison| comparison.to_string())
+            .collect();
+        assert!(
+            failures.is_empty(),
+            "CPU/GPU mismatch:\n{}",
+            failures.join("\n")
+        );
+    }
+}
@@ -1,4 +1,6 @@
 pub mod cpu;
+#[cfg(test)]
+mod golden;
 pub mod gpu;
 pub mod interpolate;
 pub mod transform;
@@ -1,4 +1,6 @@
 pub mod offset;
+pub mod resize;
+pub mod trim;
 
 pub use offset::apply_offset;
-
+pub use resize::resize_cpu;
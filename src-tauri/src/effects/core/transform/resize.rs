@@ -0,0 +1,6 @@
+use image::{imageops::FilterType, DynamicImage};
+
+/// Resize on the CPU, used where the GPU resize is unavailable or failed
+pub fn resize_cpu(image: &DynamicImage, width: u32, height: u32) -> DynamicImage {
+    image.resize_exact(width, height, FilterType::Lanczos3)
+}
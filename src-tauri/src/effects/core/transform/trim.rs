@@ -0,0 +1,109 @@
+use std::borrow::Cow;
+
+use image::{imageops, RgbaImage};
+
+/// Rectangle of an image that holds its visible pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrimRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl TrimRegion {
+    pub fn full(width: u32, height: u32) -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        Self {
+            x,
+            y,
+            width: right - x,
+            height: bottom - y,
+        }
+    }
+}
+
+/// Bounding box of the non-transparent pixels, `None` when there are none.
+pub fn alpha_bounds(image: &RgbaImage) -> Option<TrimRegion> {
+    let (width, height) = image.dimensions();
+    let row_is_empty = |y: u32| (0..width).all(|x| image.get_pixel(x, y)[3] == 0);
+
+    let top = (0..height).find(|&y| !row_is_empty(y))?;
+    let bottom = (top..height).rev().find(|&y| !row_is_empty(y))?;
+
+    let column_is_empty = |x: u32| (top..=bottom).all(|y| image.get_pixel(x, y)[3] == 0);
+    let left = (0..width).find(|&x| !column_is_empty(x))?;
+    let right = (left..width).rev().find(|&x| !column_is_empty(x))?;
+
+    Some(TrimRegion {
+        x: left,
+        y: top,
+        width: right - left + 1,
+        height: bottom - top + 1,
+    })
+}
+
+/// Visible part of a trait and where it sits on its canvas.
+pub struct TrimmedImage {
+    pub image: RgbaImage,
+    pub x: u32,
+    pub y: u32,
+    pub canvas_width: u32,
+    pub canvas_height: u32,
+}
+
+impl TrimmedImage {
+    /// `None` when the image is fully transparent.
+    pub fn new(image: &RgbaImage) -> Option<Self> {
+        let region = alpha_bounds(image)?;
+        let (canvas_width, canvas_height) = image.dimensions();
+
+        Some(Self {
+            image: imageops::crop_imm(image, region.x, region.y, region.width, region.height)
+                .to_image(),
+            x: region.x,
+            y: region.y,
+            canvas_width,
+            canvas_height,
+        })
+    }
+
+    /// Shifts the image like `apply_offset` and returns the part that stays on
+    /// the canvas with its position, `None` when it moves out entirely.
+    pub fn placed(&self, offset_x: i32, offset_y: i32) -> Option<(Cow<'_, RgbaImage>, (u32, u32))> {
+        let left = self.x as i64 + offset_x as i64;
+        let top = self.y as i64 + offset_y as i64;
+        let right = (left + self.image.width() as i64).min(self.canvas_width as i64);
+        let bottom = (top + self.image.height() as i64).min(self.canvas_height as i64);
+        let (x, y) = (left.max(0), top.max(0));
+        if x >= right || y >= bottom {
+            return None;
+        }
+
+        let origin = (x as u32, y as u32);
+        let (crop_x, crop_y) = ((x - left) as u32, (y - top) as u32);
+        let (width, height) = ((right - x) as u32, (bottom - y) as u32);
+        if (crop_x, crop_y) == (0, 0) && (width, height) == self.image.dimensions() {
+            return Some((Cow::Borrowed(&self.image), origin));
+        }
+
+        let cropped = imageops::crop_imm(&self.image, crop_x, crop_y, width, height).to_image();
+        Some((Cow::Owned(cropped), origin))
+    }
+
+    pub fn byte_size(&self) -> usize {
+        self.image.as_raw().len()
+    }
+}
@@ -1,7 +1,9 @@
 pub mod encode;
 pub mod get_ffmpeg;
+pub mod stream;
 pub mod wrapper;
 
 pub use encode::*;
 pub use get_ffmpeg::*;
+pub use stream::*;
 pub use wrapper::*;
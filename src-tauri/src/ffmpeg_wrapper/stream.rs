@@ -0,0 +1,154 @@
+use std::{
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, Command, Stdio},
+    thread::JoinHandle,
+};
+
+use anyhow::Result;
+use image::RgbaImage;
+
+pub struct RawVideoEncoder {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stderr_reader: Option<JoinHandle<String>>,
+    width: u32,
+    height: u32,
+    frames_written: u32,
+    label: &'static str,
+}
+
+impl RawVideoEncoder {
+    pub fn spawn(
+        ffmpeg_path: &PathBuf,
+        output_path: &Path,
+        width: u32,
+        height: u32,
+        fps: f32,
+        output_args: &[String],
+        label: &'static str,
+    ) -> Result<Self> {
+        if width == 0 || height == 0 {
+            return Err(anyhow::anyhow!(
+                "Invalid stream dimensions: {}x{}",
+                width,
+                height
+            ));
+        }
+
+        let size_str = format!("{}x{}", width, height);
+        let fps_str = fps.to_string();
+
+        let mut cmd = Command::new(ffmpeg_path);
+        cmd.args([
+            "-y",
+            "-loglevel",
+            "error",
+            "-f",
+            "rawvideo",
+            "-pix_fmt",
+            "rgba",
+            "-s",
+            &size_str,
+            "-framerate",
+            &fps_str,
+            "-i",
+            "pipe:0",
+        ]);
+        cmd.args(output_args);
+        cmd.arg(output_path);
+
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            cmd.creation_flags(0x08000000);
+        }
+
+        cmd.stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+
+        let mut child = cmd.spawn()?;
+        let stdin = child.stdin.take();
+
+        let stderr_reader = child.stderr.take().map(|mut stderr| {
+            std::thread::spawn(move || {
+                let mut output = String::new();
+                let _ = stderr.read_to_string(&mut output);
+                output
+            })
+        });
+
+        Ok(Self {
+            child,
+            stdin,
+            stderr_reader,
+            width,
+            height,
+            frames_written: 0,
+            label,
+        })
+    }
+
+    pub fn write_frame(&mut self, frame: &RgbaImage) -> Result<()> {
+        let (w, h) = frame.dimensions();
+        if w != self.width || h != self.height {
+            return Err(anyhow::anyhow!(
+                "[{}] Frame {} has dimensions {}x{}, expected {}x{}",
+                self.label,
+                self.frames_written,
+                w,
+                h,
+                self.width,
+                self.height
+            ));
+        }
+
+        let stdin = self
+            .stdin
+            .as_mut()
+            .ok_or_else(|| anyhow::anyhow!("[{}] FFmpeg stdin already closed", self.label))?;
+
+        stdin.write_all(frame.as_raw())?;
+        self.frames_written += 1;
+        Ok(())
+    }
+
+    pub fn frames_written(&self) -> u32 {
+        self.frames_written
+    }
+
+    pub fn finish(mut self) -> Result<()> {
+        drop(self.stdin.take());
+        let status = self.child.wait()?;
+
+        let stderr = self
+            .stderr_reader
+            .take()
+            .and_then(|handle| handle.join().ok())
+            .unwrap_or_default();
+
+        if self.frames_written == 0 {
+            return Err(anyhow::anyhow!("No frames provided for encoding"));
+        }
+
+        if !status.success() {
+            return Err(anyhow::anyhow!(
+                "FFmpeg {} encoding failed: {}",
+                self.label,
+                stderr.trim()
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for RawVideoEncoder {
+    fn drop(&mut self) {
+        if self.stdin.take().is_some() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
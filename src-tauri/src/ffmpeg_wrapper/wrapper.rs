@@ -6,7 +6,8 @@ use std::{
 use anyhow::Result;
 
 use super::{
-    encode_gif_direct, encode_mp4_direct, encode_webm_direct, encode_webp_direct, get_ffmpeg_path,
+    get_ffmpeg_path, gif_output_args, mp4_output_args, webm_output_args, webp_output_args,
+    RawVideoEncoder,
 };
 
 pub struct FFmpegWrapper {
@@ -103,6 +104,10 @@ impl FFmpegWrapper {
     }
 
     fn get_video_fps(&self, video_path: &Path) -> Result<f32> {
+        self.probe_video_timing(video_path).map(|(fps, _)| fps)
+    }
+
+    pub fn probe_video_timing(&self, video_path: &Path) -> Result<(f32, u32)> {
         let mut cmd = Command::new(&self.ffmpeg_path);
         cmd.args(&["-i", video_path.to_str().unwrap(), "-f", "null", "-"]);
 
@@ -120,61 +125,67 @@ impl FFmpegWrapper {
         }
 
         let stderr = String::from_utf8_lossy(&output.stderr);
-        for line in stderr.lines() {
-            if line.contains("Stream") && line.contains("Video") {
-                if let Some(fps_start) = line.find("fps=") {
-                    let fps_part = &line[fps_start + 4..];
-                    if let Some(fps_end) = fps_part.find(' ') {
-                        if let Ok(fps) = fps_part[..fps_end].parse::<f32>() {
-                            return Ok(fps);
-                        }
-                    }
-                }
-            }
-        }
 
-        Ok(25.0)
+        let fps = stderr
+            .lines()
+            .filter(|line| line.contains("Stream") && line.contains("Video"))
+            .find_map(|line| {
+                let fps_end = line.find(" fps")?;
+                line[..fps_end]
+                    .rsplit([' ', ','])
+                    .next()?
+                    .parse::<f32>()
+                    .ok()
+            })
+            .filter(|fps| *fps > 0.0)
+            .unwrap_or(25.0);
+
+        let frame_count = stderr
+            .rfind("frame=")
+            .and_then(|start| {
+                stderr[start + 6..]
+                    .trim_start()
+                    .split(|c: char| !c.is_ascii_digit())
+                    .next()?
+                    .parse::<u32>()
+                    .ok()
+            })
+            .unwrap_or(0);
+
+        Ok((fps, frame_count))
     }
 
-    pub fn encode_animation_direct(
+    pub fn open_animation_stream(
         &self,
-        frames: &[image::DynamicImage],
         output_path: &Path,
         format: &str,
+        width: u32,
+        height: u32,
         fps: f32,
         quality: Option<u8>,
         optimize: bool,
         lossless: Option<bool>,
         method: Option<u8>,
-    ) -> Result<()> {
-        match format {
-            "mp4" => encode_mp4_direct(
-                &self.ffmpeg_path,
-                frames,
-                output_path,
-                fps,
-                quality,
-                optimize,
+    ) -> Result<RawVideoEncoder> {
+        let (output_args, label) = match format {
+            "mp4" => (mp4_output_args(quality, optimize), "MP4"),
+            "webm" => (webm_output_args(quality, optimize), "WEBM"),
+            "webp" => (
+                webp_output_args(quality, lossless.unwrap_or(true), method.unwrap_or(10)),
+                "WEBP",
             ),
-            "webm" => encode_webm_direct(
-                &self.ffmpeg_path,
-                frames,
-                output_path,
-                fps,
-                quality,
-                optimize,
-            ),
-            "webp" => encode_webp_direct(
-                &self.ffmpeg_path,
-                frames,
-                output_path,
-                fps,
-                quality,
-                lossless.unwrap_or(true),
-                method.unwrap_or(10),
-            ),
-            "gif" => encode_gif_direct(&self.ffmpeg_path, frames, output_path, fps),
-            _ => Err(anyhow::anyhow!("Unsupported format: {}", format)),
-        }
+            "gif" => (gif_output_args(fps), "GIF"),
+            _ => return Err(anyhow::anyhow!("Unsupported format: {}", format)),
+        };
+
+        RawVideoEncoder::spawn(
+            &self.ffmpeg_path,
+            output_path,
+            width,
+            height,
+            fps,
+            &output_args,
+            label,
+        )
     }
 }
@@ -1,6 +1,6 @@
-use std::{fs, path::PathBuf};
-use tauri::Manager;
+use std::path::{Path, PathBuf};
 
+#[derive(Clone)]
 pub struct StorageFiles {
     pub project_setup: PathBuf,
     pub ordered_layers: PathBuf,
@@ -12,21 +12,26 @@ pub struct StorageFiles {
     pub other_parameters: PathBuf,
     pub preferences: PathBuf,
     pub global_rarity: PathBuf,
+    pub rename_journal: PathBuf,
 }
 
-impl StorageFiles {
-    pub fn new(app_handle: &tauri::AppHandle) -> Result<Self, String> {
-        let app_data_dir = app_handle
-            .path()
-            .app_data_dir()
-            .map_err(|e| format!("Failed to get app data directory: {}", e))?;
-
-        let config_dir = app_data_dir.join("config");
-
-        fs::create_dir_all(&config_dir)
-            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+/// Per-project files, copied from the old shared `config` directory when the
+/// first project is created.
+pub const LEGACY_CONFIG_FILES: [&str; 9] = [
+    "project_setup.json",
+    "ordered_layers.json",
+    "rarity_config.json",
+    "incompatibility.json",
+    "forced_combination.json",
+    "filter_config.json",
+    "image_setup.json",
+    "other_parameters.json",
+    "global_rarity.json",
+];
 
-        let storage_files = StorageFiles {
+impl StorageFiles {
+    pub fn in_dir(config_dir: &Path, preferences: &Path) -> Self {
+        StorageFiles {
             project_setup: config_dir.join("project_setup.json"),
             ordered_layers: config_dir.join("ordered_layers.json"),
             rarity_config: config_dir.join("rarity_config.json"),
@@ -35,11 +40,10 @@ impl StorageFiles {
             filter_config: config_dir.join("filter_config.json"),
             image_setup: config_dir.join("image_setup.json"),
             other_parameters: config_dir.join("other_parameters.json"),
-            preferences: config_dir.join("preferences.json"),
+            preferences: preferences.to_path_buf(),
             global_rarity: config_dir.join("global_rarity.json"),
-        };
-
-        Ok(storage_files)
+            rename_journal: config_dir.join("rename_journal.json"),
+        }
     }
 
     pub fn get_config_dir(&self) -> PathBuf {
@@ -47,21 +51,6 @@ impl StorageFiles {
         dir
     }
 
-    pub fn check_files_exist(&self) -> bool {
-        let exists = self.project_setup.exists()
-            && self.ordered_layers.exists()
-            && self.rarity_config.exists()
-            && self.incompatibility.exists()
-            && self.forced_combination.exists()
-            && self.filter_config.exists()
-            && self.image_setup.exists()
-            && self.other_parameters.exists()
-            && self.preferences.exists()
-            && self.global_rarity.exists();
-
-        exists
-    }
-
     pub fn get_path_for_filename(&self, filename: &str) -> Option<&PathBuf> {
         match filename {
             "project_setup.json" => Some(&self.project_setup),
@@ -74,6 +63,7 @@ impl StorageFiles {
             "other_parameters.json" => Some(&self.other_parameters),
             "preferences.json" => Some(&self.preferences),
             "global_rarity.json" => Some(&self.global_rarity),
+            "rename_journal.json" => Some(&self.rename_journal),
             _ => None,
         }
     }
@@ -92,6 +82,7 @@ impl Default for StorageFiles {
             other_parameters: "other_parameters.json".to_string().into(),
             preferences: "preferences.json".to_string().into(),
             global_rarity: "global_rarity.json".to_string().into(),
+            rename_journal: "rename_journal.json".to_string().into(),
         }
     }
 }
@@ -8,7 +8,7 @@ use walkdir::WalkDir;
 
 use crate::{
     filesystem::{
-        constants::StorageFiles,
+        projects::ProjectRegistry,
         storage::{load_storage, save_storage},
         utils::normalize_path,
     },
@@ -211,8 +211,10 @@ pub async fn is_folder_modified(
 #[tauri::command]
 pub async fn get_previous_hash<'r>(
     folder_path: String,
-    storage_files: State<'r, StorageFiles>,
+    registry: State<'r, ProjectRegistry>,
 ) -> Result<Option<String>, String> {
+    let storage_files = registry.storage_files();
+
     tracing::debug!(
         "[FolderHash] Getting previous hash for folder: {}",
         folder_path
@@ -237,8 +239,10 @@ pub async fn get_previous_hash<'r>(
 pub async fn save_folder_hash<'r>(
     folder_path: String,
     hash: String,
-    storage_files: State<'r, StorageFiles>,
+    registry: State<'r, ProjectRegistry>,
 ) -> Result<serde_json::Value, String> {
+    let storage_files = registry.storage_files();
+
     tracing::info!(
         "[FolderHash] Saving hash for folder: {} -> {}",
         folder_path,
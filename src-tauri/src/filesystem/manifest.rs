@@ -0,0 +1,272 @@
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::{self, Read},
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::UNIX_EPOCH,
+};
+use tracing;
+use walkdir::WalkDir;
+
+use crate::filesystem::utils::normalize_path;
+
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestEntry {
+    pub size: u64,
+    pub modified_ms: u64,
+    pub hash: String,
+    /// Probed frame timing, kept so unchanged animations are not decoded again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frame_durations_ms: Option<Vec<u32>>,
+}
+
+/// Every file under a layer folder, keyed by its `/`-separated relative path.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderManifest {
+    pub files: BTreeMap<String, ManifestEntry>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ManifestDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl ManifestDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Paths whose current content has to be processed again.
+    pub fn modified(&self) -> BTreeSet<&str> {
+        self.added
+            .iter()
+            .chain(&self.changed)
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+impl FolderManifest {
+    /// Walks `root` and hashes its files. Entries whose size and mtime match
+    /// `previous` keep their hash without the file being read again.
+    pub fn scan(root: &Path, previous: Option<&FolderManifest>) -> Self {
+        let mut candidates = Vec::new();
+
+        for entry in WalkDir::new(root).follow_links(false) {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    tracing::warn!("[Manifest] Failed to access entry: {}", e);
+                    continue;
+                }
+            };
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+            let key = normalize_path(&relative.to_string_lossy());
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    tracing::warn!("[Manifest] Failed to read metadata of {}: {}", key, e);
+                    continue;
+                }
+            };
+            let modified_ms = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |duration| duration.as_millis() as u64);
+
+            candidates.push((key, entry.into_path(), metadata.len(), modified_ms));
+        }
+
+        let hashed = AtomicUsize::new(0);
+        let files: BTreeMap<String, ManifestEntry> = candidates
+            .into_par_iter()
+            .filter_map(|(key, path, size, modified_ms)| {
+                let cached = previous
+                    .and_then(|manifest| manifest.files.get(&key))
+                    .filter(|entry| entry.size == size && entry.modified_ms == modified_ms);
+
+                if let Some(entry) = cached {
+                    return Some((key, entry.clone()));
+                }
+
+                hashed.fetch_add(1, Ordering::Relaxed);
+                match hash_file(&path) {
+                    Ok(hash) => {
+                        // A touched file with the same content keeps its probed timing.
+                        let frame_durations_ms = previous
+                            .and_then(|manifest| manifest.files.get(&key))
+                            .filter(|old| old.hash == hash)
+                            .and_then(|old| old.frame_durations_ms.clone());
+
+                        Some((
+                            key,
+                            ManifestEntry {
+                                size,
+                                modified_ms,
+                                hash,
+                                frame_durations_ms,
+                            },
+                        ))
+                    }
+                    Err(e) => {
+                        tracing::warn!("[Manifest] Failed to hash {}: {}", path.display(), e);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        tracing::info!(
+            "[Manifest] Scanned {} files in {} ({} hashed)",
+            files.len(),
+            root.display(),
+            hashed.into_inner()
+        );
+
+        Self { files }
+    }
+
+    /// Files added, removed or whose content hash differs since `previous`.
+    /// A touched file with identical content is not reported.
+    pub fn diff(&self, previous: &FolderManifest) -> ManifestDiff {
+        let mut diff = ManifestDiff::default();
+
+        for (key, entry) in &self.files {
+            match previous.files.get(key) {
+                None => diff.added.push(key.clone()),
+                Some(old) if old.hash != entry.hash => diff.changed.push(key.clone()),
+                Some(_) => {}
+            }
+        }
+
+        diff.removed = previous
+            .files
+            .keys()
+            .filter(|key| !self.files.contains_key(*key))
+            .cloned()
+            .collect();
+
+        diff
+    }
+
+    pub fn frame_durations(&self, key: &str) -> Option<&[u32]> {
+        self.files.get(key)?.frame_durations_ms.as_deref()
+    }
+
+    pub fn set_frame_durations(&mut self, key: &str, durations: Vec<u32>) {
+        if let Some(entry) = self.files.get_mut(key) {
+            entry.frame_durations_ms = Some(durations);
+        }
+    }
+
+    pub fn load(dir: &Path) -> Option<Self> {
+        let content = fs::read_to_string(dir.join(MANIFEST_FILE_NAME)).ok()?;
+        serde_json::from_str(&content)
+            .map_err(|e| tracing::warn!("[Manifest] Ignoring invalid manifest: {}", e))
+            .ok()
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<(), String> {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create manifest directory: {}", e))?;
+
+        let json = serde_json::to_string(self)
+            .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+
+        let path = dir.join(MANIFEST_FILE_NAME);
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, json).map_err(|e| format!("Failed to write manifest: {}", e))?;
+        fs::rename(&temp_path, &path).map_err(|e| format!("Failed to replace manifest: {}", e))
+    }
+}
+
+pub fn hash_file(path: &Path) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 8192];
+
+    loop {
+        let bytes_read = file.read(&mut buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..bytes_read]);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(hash: &str) -> ManifestEntry {
+        ManifestEntry {
+            size: 1,
+            modified_ms: 0,
+            hash: hash.to_string(),
+            frame_durations_ms: None,
+        }
+    }
+
+    fn manifest(files: &[(&str, &str)]) -> FolderManifest {
+        FolderManifest {
+            files: files
+                .iter()
+                .map(|(key, hash)| (key.to_string(), entry(hash)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed_files() {
+        let previous = manifest(&[("a/1.png", "x"), ("a/2.png", "y"), ("b/1.gif", "z")]);
+        let current = manifest(&[("a/1.png", "x"), ("a/2.png", "y2"), ("c/1.png", "w")]);
+
+        let diff = current.diff(&previous);
+
+        assert_eq!(diff.added, vec!["c/1.png"]);
+        assert_eq!(diff.changed, vec!["a/2.png"]);
+        assert_eq!(diff.removed, vec!["b/1.gif"]);
+        assert_eq!(
+            diff.modified().into_iter().collect::<Vec<_>>(),
+            vec!["a/2.png", "c/1.png"]
+        );
+    }
+
+    #[test]
+    fn touched_file_with_same_content_is_not_a_change() {
+        let previous = manifest(&[("a/1.png", "x")]);
+        let mut current = previous.clone();
+        current.files.get_mut("a/1.png").unwrap().modified_ms = 42;
+
+        assert!(current.diff(&previous).is_empty());
+    }
+
+    #[test]
+    fn file_left_out_after_a_failed_extraction_is_added_again() {
+        let current = manifest(&[("a/1.gif", "x"), ("a/2.gif", "y")]);
+        let mut saved = current.clone();
+        saved.files.remove("a/2.gif");
+
+        let diff = current.diff(&saved);
+
+        assert_eq!(diff.added, vec!["a/2.gif"]);
+        assert!(diff.changed.is_empty() && diff.removed.is_empty());
+    }
+}
@@ -1,9 +1,13 @@
 pub mod constants;
 pub mod default_json;
 pub mod folderhash;
+pub mod manifest;
 pub mod persist;
+pub mod projects;
 pub mod rarity;
 pub mod rename;
+pub mod rename_transaction;
 pub mod storage;
 pub mod temp_dir;
+pub mod trait_files;
 pub mod utils;
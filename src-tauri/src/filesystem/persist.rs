@@ -2,6 +2,7 @@ use crate::{
     filesystem::{
         constants::StorageFiles,
         default_json::get_default_json_content,
+        projects::ProjectRegistry,
         storage::{load_storage, save_storage},
     },
     types::{
@@ -18,8 +19,9 @@ use tracing;
 
 #[tauri::command]
 pub async fn load_preferences(
-    storage_files: State<'_, StorageFiles>,
+    registry: State<'_, ProjectRegistry>,
 ) -> Result<Option<Preferences>, String> {
+    let storage_files = registry.storage_files();
     load_storage(&storage_files.preferences)
         .await
         .map_err(|e| e.to_string())
@@ -29,8 +31,9 @@ pub async fn load_preferences(
 pub async fn save_preferences(
     preferences: Preferences,
     app_handle: tauri::AppHandle,
-    storage_files: State<'_, StorageFiles>,
+    registry: State<'_, ProjectRegistry>,
 ) -> Result<(), String> {
+    let storage_files = registry.storage_files();
     save_storage(&storage_files.preferences, &preferences)
         .await
         .map_err(|e| e.to_string())?;
@@ -61,8 +64,9 @@ pub async fn save_preferences(
 #[tauri::command]
 pub async fn save_projectsetup_state(
     state: ProjectSetupState,
-    storage_files: State<'_, StorageFiles>,
+    registry: State<'_, ProjectRegistry>,
 ) -> Result<serde_json::Value, String> {
+    let storage_files = registry.storage_files();
     save_storage(&storage_files.project_setup, &state)
         .await
         .map(|_| json!({ "success": true }))
@@ -71,8 +75,9 @@ pub async fn save_projectsetup_state(
 
 #[tauri::command]
 pub async fn load_projectsetup_state(
-    storage_files: State<'_, StorageFiles>,
+    registry: State<'_, ProjectRegistry>,
 ) -> Result<Option<ProjectSetupState>, String> {
+    let storage_files = registry.storage_files();
     load_storage(&storage_files.project_setup)
         .await
         .map_err(|e| e.to_string())
@@ -80,8 +85,12 @@ pub async fn load_projectsetup_state(
 
 #[tauri::command]
 pub async fn load_layer_order_state(
-    storage_files: State<'_, StorageFiles>,
+    registry: State<'_, ProjectRegistry>,
 ) -> Result<SetsStorage, String> {
+    read_layer_order_state(&registry.storage_files()).await
+}
+
+pub async fn read_layer_order_state(storage_files: &StorageFiles) -> Result<SetsStorage, String> {
     tracing::debug!("[LayerOrder] Loading layer order state");
 
     match load_storage::<SetsStorage>(&storage_files.ordered_layers).await {
@@ -133,8 +142,10 @@ pub async fn load_layer_order_state(
 #[tauri::command]
 pub async fn save_layer_order_state(
     state: SetsStorage,
-    storage_files: State<'_, StorageFiles>,
+    registry: State<'_, ProjectRegistry>,
 ) -> Result<serde_json::Value, String> {
+    let storage_files = registry.storage_files();
+
     tracing::info!("[LayerOrder] Saving layer order state");
 
     match save_storage(&storage_files.ordered_layers, &state).await {
@@ -151,7 +162,13 @@ pub async fn save_layer_order_state(
 
 #[tauri::command]
 pub async fn load_incompatibility_state(
-    storage_files: State<'_, StorageFiles>,
+    registry: State<'_, ProjectRegistry>,
+) -> Result<IncompatibilitiesBySets, String> {
+    read_incompatibility_state(&registry.storage_files()).await
+}
+
+pub async fn read_incompatibility_state(
+    storage_files: &StorageFiles,
 ) -> Result<IncompatibilitiesBySets, String> {
     tracing::debug!("[Incompatibility] Loading incompatibility state");
 
@@ -177,8 +194,10 @@ pub async fn load_incompatibility_state(
 #[tauri::command]
 pub async fn save_incompatibility_state(
     state: IncompatibilitiesBySets,
-    storage_files: State<'_, StorageFiles>,
+    registry: State<'_, ProjectRegistry>,
 ) -> Result<serde_json::Value, String> {
+    let storage_files = registry.storage_files();
+
     tracing::info!("[Incompatibility] Saving incompatibility state");
 
     match save_storage(&storage_files.incompatibility, &state).await {
@@ -198,7 +217,13 @@ pub async fn save_incompatibility_state(
 
 #[tauri::command]
 pub async fn load_forced_combination_state(
-    storage_files: State<'_, StorageFiles>,
+    registry: State<'_, ProjectRegistry>,
+) -> Result<ForcedCombinationsBySets, String> {
+    read_forced_combination_state(&registry.storage_files()).await
+}
+
+pub async fn read_forced_combination_state(
+    storage_files: &StorageFiles,
 ) -> Result<ForcedCombinationsBySets, String> {
     tracing::debug!("[ForcedCombination] Loading forced combination state");
 
@@ -226,8 +251,10 @@ pub async fn load_forced_combination_state(
 #[tauri::command]
 pub async fn save_forced_combination_state(
     state: ForcedCombinationsBySets,
-    storage_files: State<'_, StorageFiles>,
+    registry: State<'_, ProjectRegistry>,
 ) -> Result<serde_json::Value, String> {
+    let storage_files = registry.storage_files();
+
     tracing::info!("[ForcedCombination] Saving forced combination state");
 
     match save_storage(&storage_files.forced_combination, &state).await {
@@ -247,11 +274,13 @@ pub async fn save_forced_combination_state(
 
 #[tauri::command]
 pub async fn load_image_setup_state(
-    storage_files: State<'_, StorageFiles>,
+    registry: State<'_, ProjectRegistry>,
 ) -> Result<ImageSetupState, String> {
+    let storage_files = registry.storage_files();
+
     tracing::debug!("[ImageSetup] Loading image setup state");
 
-    let project_state = match load_projectsetup_state(storage_files.clone()).await {
+    let project_state = match load_projectsetup_state(registry.clone()).await {
         Ok(state) => {
             tracing::debug!("[ImageSetup] Successfully loaded project setup state");
             state
@@ -300,6 +329,7 @@ pub async fn load_image_setup_state(
                 solana_config: config.solana_config.or(default_state.solana_config),
                 animation_quality: config.animation_quality.or(default_state.animation_quality),
                 resize_config: config.resize_config.or(default_state.resize_config),
+                packaging: config.packaging,
             };
 
             tracing::debug!("[ImageSetup] Successfully merged image setup config with defaults");
@@ -322,8 +352,10 @@ pub async fn load_image_setup_state(
 #[tauri::command]
 pub async fn save_image_setup_state(
     state: ImageSetupState,
-    storage_files: State<'_, StorageFiles>,
+    registry: State<'_, ProjectRegistry>,
 ) -> Result<serde_json::Value, String> {
+    let storage_files = registry.storage_files();
+
     tracing::info!("[ImageSetup] Saving image setup state");
 
     match save_storage(&storage_files.image_setup, &state).await {
@@ -340,8 +372,12 @@ pub async fn save_image_setup_state(
 
 #[tauri::command]
 pub async fn load_rarity_config(
-    storage_files: State<'_, StorageFiles>,
+    registry: State<'_, ProjectRegistry>,
 ) -> Result<RarityConfig, String> {
+    read_rarity_config(&registry.storage_files()).await
+}
+
+pub async fn read_rarity_config(storage_files: &StorageFiles) -> Result<RarityConfig, String> {
     tracing::debug!("[Rarity] Loading rarity config");
 
     let config: Option<RarityConfigStorage> = match load_storage(&storage_files.rarity_config).await
@@ -378,8 +414,10 @@ pub async fn load_rarity_config(
 #[tauri::command]
 pub async fn save_rarity_config(
     config: RarityConfig,
-    storage_files: State<'_, StorageFiles>,
+    registry: State<'_, ProjectRegistry>,
 ) -> Result<serde_json::Value, String> {
+    let storage_files = registry.storage_files();
+
     tracing::info!("[Rarity] Saving rarity config");
 
     let rarity_config = RarityConfigStorage {
@@ -401,8 +439,10 @@ pub async fn save_rarity_config(
 #[tauri::command]
 pub async fn save_global_rarity(
     data: serde_json::Value,
-    storage_files: State<'_, StorageFiles>,
+    registry: State<'_, ProjectRegistry>,
 ) -> Result<(), String> {
+    let storage_files = registry.storage_files();
+
     tracing::info!("[GlobalRarity] Saving global rarity data");
 
     let result = save_storage(&storage_files.global_rarity, &data)
@@ -421,8 +461,10 @@ pub async fn save_global_rarity(
 }
 #[tauri::command]
 pub async fn load_global_rarity(
-    storage_files: State<'_, StorageFiles>,
+    registry: State<'_, ProjectRegistry>,
 ) -> Result<Option<serde_json::Value>, String> {
+    let storage_files = registry.storage_files();
+
     tracing::debug!("[GlobalRarity] Loading global rarity data");
 
     if !storage_files.global_rarity.exists() {
@@ -505,7 +547,7 @@ where
 {
     tracing::info!("[StorageCommand] Saving data to file: {}", filename);
 
-    let storage_files = app_handle.state::<StorageFiles>();
+    let storage_files = app_handle.state::<ProjectRegistry>().storage_files();
     let path = match storage_files.get_path_for_filename(filename) {
         Some(path) => {
             tracing::debug!(
@@ -544,7 +586,7 @@ pub async fn load_storage_command(
 ) -> Result<Option<String>, String> {
     tracing::info!("[StorageCommand] Loading data from file: {}", filename);
 
-    let storage_files = app_handle.state::<StorageFiles>();
+    let storage_files = app_handle.state::<ProjectRegistry>().storage_files();
     let path = match storage_files.get_path_for_filename(filename) {
         Some(path) => {
             tracing::debug!(
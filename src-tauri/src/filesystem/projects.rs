@@ -0,0 +1,399 @@
+use crate::{
+    filesystem::{
+        constants::{StorageFiles, LEGACY_CONFIG_FILES},
+        storage::load_storage,
+    },
+    layerpreview::{
+        animations::utils::reset_animation_state,
+        validation::watcher::{start_layer_watcher, stop_layer_watcher},
+    },
+    types::{ProjectInfo, ProjectSetupState},
+};
+use chrono::Utc;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tauri::{Emitter, Manager, State};
+use tracing;
+use walkdir::WalkDir;
+
+const REGISTRY_FILE_NAME: &str = "registry.json";
+const DEFAULT_PROJECT_NAME: &str = "Untitled project";
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+struct RegistryState {
+    projects: Vec<ProjectInfo>,
+    active_project_id: Option<String>,
+}
+
+/// Config and cache directories of one project, resolved once so work that
+/// outlives a project switch keeps using the project it started from.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectDirs {
+    pub config: PathBuf,
+    pub cache: PathBuf,
+}
+
+/// Every project owns `projects/<id>/config` for its JSON state and
+/// `projects/<id>/cache` for derived data such as extracted frames.
+/// Preferences stay global and live in `config/preferences.json`.
+pub struct ProjectRegistry {
+    projects_dir: PathBuf,
+    preferences: PathBuf,
+    state: RwLock<RegistryState>,
+}
+
+impl ProjectRegistry {
+    pub fn load(app_handle: &tauri::AppHandle) -> Result<Self, String> {
+        let app_data_dir = app_handle
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+
+        let legacy_config_dir = app_data_dir.join("config");
+        let projects_dir = app_data_dir.join("projects");
+
+        fs::create_dir_all(&legacy_config_dir)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        fs::create_dir_all(&projects_dir)
+            .map_err(|e| format!("Failed to create projects directory: {}", e))?;
+
+        let state = match fs::read_to_string(projects_dir.join(REGISTRY_FILE_NAME)) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse project registry: {}", e))?,
+            Err(_) => RegistryState::default(),
+        };
+
+        let registry = Self {
+            projects_dir,
+            preferences: legacy_config_dir.join("preferences.json"),
+            state: RwLock::new(state),
+        };
+
+        if registry.state.read().projects.is_empty() {
+            let project = registry.create(DEFAULT_PROJECT_NAME)?;
+            migrate_legacy_config(&legacy_config_dir, &registry.config_dir(&project.id));
+        }
+
+        {
+            let mut state = registry.state.write();
+            let active_exists = state
+                .active_project_id
+                .as_ref()
+                .is_some_and(|id| state.projects.iter().any(|p| &p.id == id));
+            if !active_exists {
+                state.active_project_id = state.projects.first().map(|p| p.id.clone());
+            }
+        }
+        registry.save()?;
+
+        {
+            let state = registry.state.read();
+            tracing::info!(
+                "[Projects] Loaded {} projects, active: {:?}",
+                state.projects.len(),
+                state.active_project_id
+            );
+        }
+
+        Ok(registry)
+    }
+
+    /// Storage paths of the active project.
+    pub fn storage_files(&self) -> StorageFiles {
+        StorageFiles::in_dir(&self.config_dir(&self.active_id()), &self.preferences)
+    }
+
+    /// Cache directory of the active project.
+    pub fn cache_dir(&self) -> PathBuf {
+        self.active_dirs().cache
+    }
+
+    pub fn active_dirs(&self) -> ProjectDirs {
+        self.project_dirs(&self.active_id())
+    }
+
+    pub fn project_dirs(&self, id: &str) -> ProjectDirs {
+        ProjectDirs {
+            config: self.config_dir(id),
+            cache: self.project_dir(id).join("cache"),
+        }
+    }
+
+    /// Storage paths inside the config directory of `dirs`.
+    pub fn storage_files_in(&self, dirs: &ProjectDirs) -> StorageFiles {
+        StorageFiles::in_dir(&dirs.config, &self.preferences)
+    }
+
+    pub fn active_project(&self) -> Option<ProjectInfo> {
+        let state = self.state.read();
+        let id = state.active_project_id.as_ref()?;
+        state.projects.iter().find(|p| &p.id == id).cloned()
+    }
+
+    fn active_id(&self) -> String {
+        self.state
+            .read()
+            .active_project_id
+            .clone()
+            .unwrap_or_default()
+    }
+
+    fn project_dir(&self, id: &str) -> PathBuf {
+        self.projects_dir.join(id)
+    }
+
+    fn config_dir(&self, id: &str) -> PathBuf {
+        self.project_dir(id).join("config")
+    }
+
+    fn create(&self, name: &str) -> Result<ProjectInfo, String> {
+        let now = Utc::now().to_rfc3339();
+        let project = ProjectInfo {
+            id: format!("{:016x}", rand::random::<u64>()),
+            name: name.trim().to_string(),
+            created_at: now.clone(),
+            last_opened_at: now,
+        };
+
+        fs::create_dir_all(self.config_dir(&project.id))
+            .map_err(|e| format!("Failed to create project config directory: {}", e))?;
+        fs::create_dir_all(self.project_dir(&project.id).join("cache"))
+            .map_err(|e| format!("Failed to create project cache directory: {}", e))?;
+
+        let mut state = self.state.write();
+        state.projects.push(project.clone());
+        if state.active_project_id.is_none() {
+            state.active_project_id = Some(project.id.clone());
+        }
+
+        Ok(project)
+    }
+
+    fn find(&self, id: &str) -> Result<ProjectInfo, String> {
+        self.state
+            .read()
+            .projects
+            .iter()
+            .find(|p| p.id == id)
+            .cloned()
+            .ok_or_else(|| format!("Unknown project: {}", id))
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(&*self.state.read())
+            .map_err(|e| format!("Failed to serialize project registry: {}", e))?;
+
+        let path = self.projects_dir.join(REGISTRY_FILE_NAME);
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, json)
+            .map_err(|e| format!("Failed to write project registry: {}", e))?;
+        fs::rename(&temp_path, &path)
+            .map_err(|e| format!("Failed to replace project registry: {}", e))
+    }
+}
+
+/// Moves the single pre-registry config into the first project.
+fn migrate_legacy_config(legacy_dir: &Path, config_dir: &Path) {
+    for filename in LEGACY_CONFIG_FILES {
+        let source = legacy_dir.join(filename);
+        if !source.exists() {
+            continue;
+        }
+
+        match fs::copy(&source, config_dir.join(filename)) {
+            Ok(_) => tracing::info!("[Projects] Migrated {} into the default project", filename),
+            Err(e) => tracing::warn!("[Projects] Failed to migrate {}: {}", filename, e),
+        }
+    }
+}
+
+fn copy_dir(source: &Path, target: &Path) -> Result<(), String> {
+    for entry in WalkDir::new(source).follow_links(false) {
+        let entry = entry.map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+        let relative = entry.path().strip_prefix(source).unwrap_or(entry.path());
+        let destination = target.join(relative);
+
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&destination)
+                .map_err(|e| format!("Failed to create {}: {}", destination.display(), e))?;
+        } else if entry.file_type().is_file() {
+            fs::copy(entry.path(), &destination)
+                .map_err(|e| format!("Failed to copy {}: {}", entry.path().display(), e))?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn activate(
+    registry: &ProjectRegistry,
+    project: &ProjectInfo,
+    app_handle: &tauri::AppHandle,
+) -> Result<(), String> {
+    {
+        let mut state = registry.state.write();
+        state.active_project_id = Some(project.id.clone());
+        if let Some(entry) = state.projects.iter_mut().find(|p| p.id == project.id) {
+            entry.last_opened_at = Utc::now().to_rfc3339();
+        }
+    }
+    registry.save()?;
+
+    reset_animation_state().await;
+    watch_project_folder(registry, app_handle).await;
+
+    app_handle
+        .emit("project-changed", project)
+        .map_err(|e| e.to_string())?;
+
+    tracing::info!(
+        "[Projects] Active project: {} ({})",
+        project.name,
+        project.id
+    );
+    Ok(())
+}
+
+/// Points the layer watcher at the layer folder of the active project.
+async fn watch_project_folder(registry: &ProjectRegistry, app_handle: &tauri::AppHandle) {
+    stop_layer_watcher();
+
+    let setup: Option<ProjectSetupState> = load_storage(&registry.storage_files().project_setup)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!("[Projects] Failed to load project setup: {}", e);
+            None
+        });
+    let folder = setup
+        .and_then(|setup| setup.selected_folder)
+        .map(PathBuf::from)
+        .filter(|folder| folder.is_dir());
+    if let Some(folder) = folder {
+        start_layer_watcher(app_handle.clone(), folder);
+    }
+}
+
+#[tauri::command]
+pub async fn list_projects(
+    registry: State<'_, ProjectRegistry>,
+) -> Result<Vec<ProjectInfo>, String> {
+    Ok(registry.state.read().projects.clone())
+}
+
+#[tauri::command]
+pub async fn get_active_project(
+    registry: State<'_, ProjectRegistry>,
+) -> Result<Option<ProjectInfo>, String> {
+    Ok(registry.active_project())
+}
+
+#[tauri::command]
+pub async fn create_project(
+    name: String,
+    app_handle: tauri::AppHandle,
+    registry: State<'_, ProjectRegistry>,
+) -> Result<ProjectInfo, String> {
+    if name.trim().is_empty() {
+        return Err("Project name cannot be empty".to_string());
+    }
+
+    tracing::info!("[Projects] Creating project: {}", name);
+    let project = registry.create(&name)?;
+    activate(&registry, &project, &app_handle).await?;
+
+    Ok(project)
+}
+
+#[tauri::command]
+pub async fn open_project(
+    id: String,
+    app_handle: tauri::AppHandle,
+    registry: State<'_, ProjectRegistry>,
+) -> Result<ProjectInfo, String> {
+    let project = registry.find(&id)?;
+    activate(&registry, &project, &app_handle).await?;
+
+    Ok(project)
+}
+
+/// Copies the project's configuration only. The cache is rebuilt on first use.
+#[tauri::command]
+pub async fn duplicate_project(
+    id: String,
+    name: Option<String>,
+    app_handle: tauri::AppHandle,
+    registry: State<'_, ProjectRegistry>,
+) -> Result<ProjectInfo, String> {
+    let source = registry.find(&id)?;
+    let name = name
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| format!("{} copy", source.name));
+
+    tracing::info!("[Projects] Duplicating {} as {}", source.name, name);
+    let project = registry.create(&name)?;
+
+    if let Err(e) = copy_dir(
+        &registry.config_dir(&source.id),
+        &registry.config_dir(&project.id),
+    ) {
+        registry
+            .state
+            .write()
+            .projects
+            .retain(|p| p.id != project.id);
+        let _ = fs::remove_dir_all(registry.project_dir(&project.id));
+        return Err(e);
+    }
+
+    activate(&registry, &project, &app_handle).await?;
+
+    Ok(project)
+}
+
+/// Deletes a project and its files. Deleting the active project opens the
+/// next one, or a fresh project when it was the last.
+#[tauri::command]
+pub async fn delete_project(
+    id: String,
+    app_handle: tauri::AppHandle,
+    registry: State<'_, ProjectRegistry>,
+) -> Result<Vec<ProjectInfo>, String> {
+    let project = registry.find(&id)?;
+    let was_active = registry.state.read().active_project_id.as_deref() == Some(id.as_str());
+
+    tracing::info!("[Projects] Deleting project: {} ({})", project.name, id);
+    if was_active {
+        stop_layer_watcher();
+    }
+
+    {
+        let mut state = registry.state.write();
+        state.projects.retain(|p| p.id != id);
+        if was_active {
+            state.active_project_id = None;
+        }
+    }
+
+    if let Err(e) = fs::remove_dir_all(registry.project_dir(&id)) {
+        tracing::warn!("[Projects] Failed to remove files of {}: {}", id, e);
+    }
+
+    if was_active {
+        let next = registry.state.read().projects.first().cloned();
+        let next = match next {
+            Some(next) => next,
+            None => registry.create(DEFAULT_PROJECT_NAME)?,
+        };
+        activate(&registry, &next, &app_handle).await?;
+    } else {
+        registry.save()?;
+    }
+
+    Ok(registry.state.read().projects.clone())
+}
@@ -1,5 +1,5 @@
 use crate::{
-    filesystem::{constants::StorageFiles, storage::save_storage},
+    filesystem::{projects::ProjectRegistry, storage::save_storage},
     types::{GlobalRarityInput, RarityConfig},
 };
 use serde_json::json;
@@ -83,8 +83,10 @@ pub async fn validate_rarity_config(config: RarityConfig) -> Result<serde_json::
 #[tauri::command]
 pub async fn update_global_rarity_from_config(
     input: GlobalRarityInput,
-    storage_files: State<'_, StorageFiles>,
+    registry: State<'_, ProjectRegistry>,
 ) -> Result<(), String> {
+    let storage_files = registry.storage_files();
+
     tracing::info!(
         "[Rarity] Starting global rarity update from config with {} layers",
         input.rarity_config.layers.len()
@@ -187,7 +189,7 @@ pub async fn get_rarity_data(
     layer_name: String,
     trait_name: String,
     set_id: String,
-    storage_files: State<'_, StorageFiles>,
+    registry: State<'_, ProjectRegistry>,
 ) -> Result<serde_json::Value, String> {
     tracing::debug!(
         "[Rarity] Getting rarity data for layer: {}, trait: {}, set: {}",
@@ -196,7 +198,7 @@ pub async fn get_rarity_data(
         set_id
     );
 
-    let rarity_config = match load_rarity_config(storage_files.clone()).await {
+    let rarity_config = match load_rarity_config(registry.clone()).await {
         Ok(config) => {
             tracing::debug!("[Rarity] Successfully loaded rarity config");
             config
@@ -207,7 +209,7 @@ pub async fn get_rarity_data(
         }
     };
 
-    let global_rarity_data = match load_global_rarity(storage_files).await {
+    let global_rarity_data = match load_global_rarity(registry).await {
         Ok(data) => {
             tracing::debug!("[Rarity] Successfully loaded global rarity data");
             data
@@ -1,138 +1,204 @@
+use chrono::Utc;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::json;
-use std::{collections::HashMap, fs, path::Path};
-use tauri::{Manager, State};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+use tauri::State;
 use tracing;
 
 use crate::{
     filesystem::{
         constants::StorageFiles,
+        projects::ProjectRegistry,
+        rename_transaction::{read_json, FileSnapshot, RenameTransaction},
         storage::{load_storage, save_storage},
+        trait_files::{scan_trait_files, TraitFile},
         utils::normalize_path,
     },
-    types::{RarityConfig, RarityConfigStorage, SetsStorage},
+    types::{
+        ForcedCombinationsBySets, IncompatibilitiesBySets, RarityConfig, RarityConfigStorage,
+        SetsStorage,
+    },
 };
 
-#[tauri::command]
-pub async fn rename_item(
-    app_handle: tauri::AppHandle,
-    storage_files: State<'_, StorageFiles>,
-    base_path: String,
-    old_name: String,
-    new_name: String,
-) -> Result<serde_json::Value, String> {
-    let old_path = Path::new(&base_path).join(&old_name);
-    let new_path = Path::new(&base_path).join(&new_name);
-
-    let is_trait_rename = old_name.contains('.') && new_name.contains('.');
-
-    let (layer_name, old_trait_name, new_trait_name) = if is_trait_rename {
-        let old_path_parts: Vec<&str> = old_name.split('/').collect();
-        let new_path_parts: Vec<&str> = new_name.split('/').collect();
-
-        if old_path_parts.len() == 2 && new_path_parts.len() == 2 {
-            let layer_name = old_path_parts[0].to_string();
-            let old_trait_name = normalize_path(
-                &Path::new(old_path_parts[1])
-                    .file_stem()
-                    .ok_or("Could not extract trait name from old name")?
-                    .to_string_lossy(),
-            );
-            let new_trait_name = normalize_path(
-                &Path::new(new_path_parts[1])
-                    .file_stem()
-                    .ok_or("Could not extract trait name from new name")?
-                    .to_string_lossy(),
-            );
+/// Number of renames kept in the undo journal.
+const RENAME_JOURNAL_LIMIT: usize = 20;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameJournalEntry {
+    pub base_path: String,
+    pub old_name: String,
+    pub new_name: String,
+    pub renamed_at: String,
+    /// The configs as they were before the rename, put back by undo.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub previous_files: Vec<FileSnapshot>,
+}
 
-            (layer_name, old_trait_name, new_trait_name)
-        } else {
-            let layer_path = Path::new(&base_path);
-            let layer_name = normalize_path(
-                &layer_path
-                    .file_name()
-                    .ok_or("Could not extract layer name from base path")?
-                    .to_string_lossy(),
-            );
+/// Last renames of the project, oldest first.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameJournal {
+    pub entries: Vec<RenameJournalEntry>,
+}
 
-            let old_trait_name = normalize_path(
-                &Path::new(&old_name)
-                    .file_stem()
-                    .ok_or("Could not extract trait name from old name")?
-                    .to_string_lossy(),
-            );
+/// Layer -> trait -> layer -> traits, the shape of the incompatibility and
+/// forced combination rules of a set.
+pub type RuleMap = HashMap<String, HashMap<String, HashMap<String, Vec<String>>>>;
 
-            let new_trait_name = normalize_path(
-                &Path::new(&new_name)
-                    .file_stem()
-                    .ok_or("Could not extract trait name from new name")?
-                    .to_string_lossy(),
-            );
+enum RenameTarget {
+    Layer {
+        old: String,
+        new: String,
+    },
+    Trait {
+        layer: String,
+        old: TraitFile,
+        new: TraitFile,
+    },
+    /// A trait group folder, e.g. `Rare` -> `Legendary`.
+    Group {
+        layer: String,
+        old: String,
+        new: String,
+    },
+    /// Anything that is not referenced by the project configs.
+    Other,
+}
+
+struct RenamePlan {
+    old_path: PathBuf,
+    new_path: PathBuf,
+    project_id: String,
+    target: RenameTarget,
+}
 
-            (layer_name, old_trait_name, new_trait_name)
+impl RenamePlan {
+    /// `old_name` and `new_name` are either a layer folder, a path inside a
+    /// layer such as `Hats/Rare/Crown.png`, or a file of the layer folder
+    /// `base_path` points to.
+    fn new(base_path: &str, old_name: &str, new_name: &str) -> Result<Self, String> {
+        let base = Path::new(base_path);
+        let old_name = normalize_path(old_name).trim_matches('/').to_string();
+        let new_name = normalize_path(new_name).trim_matches('/').to_string();
+        let old_path = base.join(&old_name);
+        let new_path = base.join(&new_name);
+
+        if old_name.is_empty() || new_name.is_empty() {
+            return Err("Names cannot be empty".to_string());
+        }
+        if !old_path.exists() {
+            return Err(format!("'{}' does not exist", old_name));
         }
-    } else {
-        (String::new(), String::new(), String::new())
-    };
 
-    match fs::rename(&old_path, &new_path) {
-        Ok(_) => {
-            if !is_trait_rename {
-                if let Err(e) =
-                    rename_animation_directories(&app_handle, &base_path, &old_name, &new_name)
-                        .await
-                {
-                    tracing::warn!("Warning: Failed to rename animation directories: {}", e);
+        let (project_path, layer, old_relative, new_relative) =
+            match (old_name.split_once('/'), new_name.split_once('/')) {
+                (Some((layer, old_relative)), Some((new_layer, new_relative))) => {
+                    if layer != new_layer {
+                        return Err("Moving items between layers is not supported".to_string());
+                    }
+                    (
+                        base,
+                        layer.to_string(),
+                        old_relative.to_string(),
+                        new_relative.to_string(),
+                    )
                 }
-            } else {
-                if let Err(e) = update_rarity_config_for_trait_rename(
-                    &storage_files,
-                    &layer_name,
-                    &old_trait_name,
-                    &new_trait_name,
-                )
-                .await
-                {
-                    tracing::warn!("Warning: Failed to update rarity config: {}", e);
-                }
-
-                if let Err(e) = rename_trait_animation_directories(
-                    &app_handle,
-                    &base_path,
-                    &layer_name,
-                    &old_trait_name,
-                    &new_trait_name,
-                )
-                .await
-                {
-                    tracing::warn!(
-                        "Warning: Failed to rename trait animation directories: {}",
-                        e
-                    );
+                (None, None) if old_path.is_dir() => {
+                    return Ok(Self {
+                        project_id: folder_name(base),
+                        target: RenameTarget::Layer {
+                            old: old_name,
+                            new: new_name,
+                        },
+                        old_path,
+                        new_path,
+                    });
                 }
+                (None, None) => (
+                    base.parent().unwrap_or(base),
+                    folder_name(base),
+                    old_name,
+                    new_name,
+                ),
+                _ => return Err("Moving items between layers is not supported".to_string()),
+            };
+
+        let target = if old_path.is_dir() {
+            RenameTarget::Group {
+                layer,
+                old: old_relative,
+                new: new_relative,
+            }
+        } else {
+            match (
+                TraitFile::parse(&old_relative),
+                TraitFile::parse(&new_relative),
+            ) {
+                (Some(old), Some(new)) => RenameTarget::Trait { layer, old, new },
+                _ => RenameTarget::Other,
             }
+        };
 
-            if !is_trait_rename {
-                if let Err(e) =
-                    update_rarity_config_for_layer_rename(&storage_files, &old_name, &new_name)
-                        .await
-                {
-                    tracing::warn!(
-                        "Warning: Failed to update rarity config for layer rename: {}",
-                        e
-                    );
-                }
+        Ok(Self {
+            old_path,
+            new_path,
+            project_id: folder_name(project_path),
+            target,
+        })
+    }
+}
 
-                if let Err(e) =
-                    update_ordered_layers_for_layer_rename(&storage_files, &old_name, &new_name)
-                        .await
-                {
-                    tracing::warn!(
-                        "Warning: Failed to update ordered layers for layer rename: {}",
-                        e
-                    );
-                }
-            }
+/// Maps the trait names of one layer to their new names.
+pub struct TraitRenaming<'a> {
+    pub layer: &'a str,
+    pub old: &'a str,
+    pub new: &'a str,
+    /// Renames everything below the `old` group instead of a single trait.
+    pub group: bool,
+}
+
+impl TraitRenaming<'_> {
+    pub fn apply(&self, name: &str) -> Option<String> {
+        if name == self.old {
+            return Some(self.new.to_string());
+        }
+        let rest = name.strip_prefix(self.old)?.strip_prefix('/')?;
+        self.group.then(|| format!("{}/{}", self.new, rest))
+    }
+}
+
+/// Renames a layer, trait or trait group on disk and updates every file that
+/// refers to it: the rarity config, ordered layers, the rule files and the
+/// animated frame caches. Either everything is renamed or nothing is.
+#[tauri::command]
+pub async fn rename_item(
+    registry: State<'_, ProjectRegistry>,
+    base_path: String,
+    old_name: String,
+    new_name: String,
+) -> Result<serde_json::Value, String> {
+    let storage_files = registry.storage_files();
+    let animated_dir = registry.cache_dir().join("animated");
+
+    let result = RenamePlan::new(&base_path, &old_name, &new_name)
+        .and_then(|plan| apply_rename(&plan, &storage_files, &animated_dir, Vec::new()));
 
+    match result {
+        Ok(previous_files) => {
+            let entry = RenameJournalEntry {
+                base_path,
+                old_name,
+                new_name,
+                renamed_at: Utc::now().to_rfc3339(),
+                previous_files,
+            };
+            if let Err(e) = record_rename(&storage_files, entry).await {
+                tracing::warn!("[Rename] Failed to update the rename journal: {}", e);
+            }
             Ok(json!({
                 "success": true
             }))
@@ -141,504 +207,607 @@ pub async fn rename_item(
             tracing::error!("Error renaming item: {}", error);
             Ok(json!({
                 "success": false,
-                "error": error.to_string()
+                "error": error
             }))
         }
     }
 }
 
-async fn rename_animation_directories(
-    app_handle: &tauri::AppHandle,
-    base_path: &str,
-    old_layer_name: &str,
-    new_layer_name: &str,
-) -> Result<(), String> {
-    tracing::debug!(
-        "[Rename] Renaming animation directories for layer: {} -> {}",
-        old_layer_name,
-        new_layer_name
-    );
-
-    let project_id = normalize_path(
-        &Path::new(base_path)
-            .file_name()
-            .ok_or("Could not extract project ID from base path")?
-            .to_string_lossy(),
-    );
-    tracing::debug!("[Rename] Project ID extracted: {}", project_id);
+/// Renames kept in the undo journal, newest first.
+#[tauri::command]
+pub async fn get_rename_journal(
+    registry: State<'_, ProjectRegistry>,
+) -> Result<Vec<RenameJournalEntry>, String> {
+    let journal: RenameJournal = load_storage(&registry.storage_files().rename_journal)
+        .await?
+        .unwrap_or_default();
+
+    Ok(journal.entries.into_iter().rev().collect())
+}
 
-    let app_data_dir = app_handle.path().app_data_dir().map_err(|e| {
-        tracing::error!("[Rename] Failed to get app data directory: {}", e);
-        format!("Failed to get app data directory: {}", e)
-    })?;
+/// Reverts the last `count` renames, newest first, and returns them. Stops at
+/// the first rename that cannot be reverted.
+#[tauri::command]
+pub async fn undo_rename(
+    registry: State<'_, ProjectRegistry>,
+    count: Option<usize>,
+) -> Result<Vec<RenameJournalEntry>, String> {
+    let storage_files = registry.storage_files();
+    let animated_dir = registry.cache_dir().join("animated");
+    let mut journal: RenameJournal = load_storage(&storage_files.rename_journal)
+        .await?
+        .unwrap_or_default();
+
+    let mut reverted = Vec::new();
+    let mut result = Ok(Vec::new());
+    for _ in 0..count.unwrap_or(1) {
+        let Some(entry) = journal.entries.last() else {
+            break;
+        };
 
-    let animated_dir = app_data_dir.join("animated").join(&project_id);
+        result =
+            RenamePlan::new(&entry.base_path, &entry.new_name, &entry.old_name).and_then(|plan| {
+                apply_rename(
+                    &plan,
+                    &storage_files,
+                    &animated_dir,
+                    entry.previous_files.clone(),
+                )
+            });
+        if result.is_err() {
+            break;
+        }
 
-    if !animated_dir.exists() {
-        tracing::debug!("[Rename] Animated directory does not exist, skipping");
-        return Ok(());
+        tracing::info!(
+            "[Rename] Reverted rename '{}' -> '{}'",
+            entry.old_name,
+            entry.new_name
+        );
+        reverted.extend(journal.entries.pop());
     }
 
-    tracing::debug!("[Rename] Processing frames directory");
-    let old_frames_dir = animated_dir.join("frames").join(old_layer_name);
-    let new_frames_dir = animated_dir.join("frames").join(new_layer_name);
-
-    if old_frames_dir.exists() {
-        if new_frames_dir.exists() {
-            tracing::debug!(
-                "[Rename] Removing existing frames directory: {:?}",
-                new_frames_dir
-            );
-            fs::remove_dir_all(&new_frames_dir).map_err(|e| {
-                tracing::error!("[Rename] Failed to remove existing frames directory: {}", e);
-                format!("Failed to remove existing frames directory: {}", e)
-            })?;
-        }
+    save_storage(&storage_files.rename_journal, &journal).await?;
+    result.map(|_| reverted)
+}
 
-        if let Some(parent) = new_frames_dir.parent() {
-            tracing::debug!("[Rename] Creating frames parent directory: {:?}", parent);
-            fs::create_dir_all(parent).map_err(|e| {
-                tracing::error!("[Rename] Failed to create frames parent directory: {}", e);
-                format!("Failed to create frames parent directory: {}", e)
-            })?;
-        }
+async fn record_rename(
+    storage_files: &StorageFiles,
+    entry: RenameJournalEntry,
+) -> Result<(), String> {
+    let mut journal: RenameJournal = load_storage(&storage_files.rename_journal)
+        .await?
+        .unwrap_or_default();
 
-        tracing::debug!(
-            "[Rename] Renaming frames directory: {:?} -> {:?}",
-            old_frames_dir,
-            new_frames_dir
-        );
-        fs::rename(&old_frames_dir, &new_frames_dir).map_err(|e| {
-            tracing::error!("[Rename] Failed to rename frames directory: {}", e);
-            format!("Failed to rename frames directory: {}", e)
-        })?;
-        tracing::info!("[Rename] Successfully renamed frames directory");
-    } else {
-        tracing::debug!("[Rename] Old frames directory does not exist, skipping");
-    }
+    journal.entries.push(entry);
+    let excess = journal.entries.len().saturating_sub(RENAME_JOURNAL_LIMIT);
+    journal.entries.drain(..excess);
 
-    tracing::debug!("[Rename] Processing spritesheets directory");
-    let old_spritesheets_dir = animated_dir.join("spritesheets").join(old_layer_name);
-    let new_spritesheets_dir = animated_dir.join("spritesheets").join(new_layer_name);
+    save_storage(&storage_files.rename_journal, &journal).await
+}
 
-    if old_spritesheets_dir.exists() {
-        if new_spritesheets_dir.exists() {
-            tracing::debug!(
-                "[Rename] Removing existing spritesheets directory: {:?}",
-                new_spritesheets_dir
+/// Applies `plan` and returns the configs it wrote as they were before. Undo
+/// passes the `snapshots` of the rename it reverts, so the configs come back
+/// exactly instead of being renamed back.
+fn apply_rename(
+    plan: &RenamePlan,
+    storage_files: &StorageFiles,
+    animated_dir: &Path,
+    snapshots: Vec<FileSnapshot>,
+) -> Result<Vec<FileSnapshot>, String> {
+    let mut transaction = RenameTransaction::restoring(snapshots);
+
+    let result = rename_in_transaction(&mut transaction, plan, storage_files, animated_dir)
+        .and_then(|_| transaction.restore_remaining());
+    match result {
+        Ok(()) => {
+            let snapshots = transaction.snapshots();
+            transaction.commit();
+            tracing::info!(
+                "[Rename] Renamed {:?} -> {:?}",
+                plan.old_path,
+                plan.new_path
             );
-            fs::remove_dir_all(&new_spritesheets_dir).map_err(|e| {
-                tracing::error!(
-                    "[Rename] Failed to remove existing spritesheets directory: {}",
-                    e
-                );
-                format!("Failed to remove existing spritesheets directory: {}", e)
-            })?;
+            Ok(snapshots)
         }
-
-        if let Some(parent) = new_spritesheets_dir.parent() {
-            tracing::debug!(
-                "[Rename] Creating spritesheets parent directory: {:?}",
-                parent
-            );
-            fs::create_dir_all(parent).map_err(|e| {
-                tracing::error!(
-                    "[Rename] Failed to create spritesheets parent directory: {}",
-                    e
-                );
-                format!("Failed to create spritesheets parent directory: {}", e)
-            })?;
+        Err(e) => {
+            tracing::error!("[Rename] {} - rolling back", e);
+            transaction.rollback();
+            Err(e)
         }
-
-        tracing::debug!(
-            "[Rename] Renaming spritesheets directory: {:?} -> {:?}",
-            old_spritesheets_dir,
-            new_spritesheets_dir
-        );
-        fs::rename(&old_spritesheets_dir, &new_spritesheets_dir).map_err(|e| {
-            tracing::error!("[Rename] Failed to rename spritesheets directory: {}", e);
-            format!("Failed to rename spritesheets directory: {}", e)
-        })?;
-        tracing::info!("[Rename] Successfully renamed spritesheets directory");
-    } else {
-        tracing::debug!("[Rename] Old spritesheets directory does not exist, skipping");
     }
-
-    tracing::info!(
-        "[Rename] Successfully completed animation directories rename for layer: {} -> {}",
-        old_layer_name,
-        new_layer_name
-    );
-    Ok(())
 }
 
-async fn update_rarity_config_for_trait_rename(
+fn rename_in_transaction(
+    transaction: &mut RenameTransaction,
+    plan: &RenamePlan,
     storage_files: &StorageFiles,
-    layer_name: &str,
-    old_trait_name: &str,
-    new_trait_name: &str,
+    animated_dir: &Path,
 ) -> Result<(), String> {
-    tracing::debug!(
-        "[Rename] Updating rarity config for trait rename: {} -> {} in layer {}",
-        old_trait_name,
-        new_trait_name,
-        layer_name
-    );
-
-    let current_config: Option<RarityConfigStorage> =
-        match load_storage(&storage_files.rarity_config).await {
-            Ok(Some(config)) => {
-                tracing::debug!("[Rename] Successfully loaded existing rarity config");
-                Some(config)
-            }
-            Ok(_none) => {
-                tracing::warn!("[Rename] No rarity config found, creating default");
-                Some(RarityConfigStorage {
-                    rarity_config_storage: RarityConfig::default(),
-                })
-            }
-            Err(e) => {
-                tracing::error!("[Rename] Failed to load rarity config: {}", e);
-                return Err(format!("Failed to load rarity config: {}", e));
+    transaction.move_path(&plan.old_path, &plan.new_path, false)?;
+    let animated_dir = animated_dir.join(&plan.project_id);
+
+    match &plan.target {
+        RenameTarget::Layer { old, new } => {
+            update_json(
+                transaction,
+                &storage_files.rarity_config,
+                |config: &mut RarityConfigStorage| {
+                    rename_layer_in_rarity(&mut config.rarity_config_storage, old, new)
+                },
+            )?;
+            update_json(
+                transaction,
+                &storage_files.ordered_layers,
+                |storage: &mut SetsStorage| rename_layer_in_sets(storage, old, new),
+            )?;
+            update_json(
+                transaction,
+                &storage_files.incompatibility,
+                |rules: &mut IncompatibilitiesBySets| {
+                    rules.sets.values_mut().fold(false, |changed, set| {
+                        rename_layer_in_rules(&mut set.incompatibilities, old, new) || changed
+                    })
+                },
+            )?;
+            update_json(
+                transaction,
+                &storage_files.forced_combination,
+                |rules: &mut ForcedCombinationsBySets| {
+                    rules.sets.values_mut().fold(false, |changed, set| {
+                        rename_layer_in_rules(&mut set.forced_combinations, old, new) || changed
+                    })
+                },
+            )?;
+            move_animation_dirs(transaction, &animated_dir, old, new)?;
+        }
+        RenameTarget::Trait { layer, old, new } => {
+            let layer_path = plan
+                .new_path
+                .ancestors()
+                .nth(new.relative_path.split('/').count());
+            if let Some(layer_path) = layer_path {
+                update_trait_references(transaction, storage_files, layer_path, layer, old, new)?;
             }
-        };
 
-    if let Some(mut config) = current_config {
-        if let Some(layer_config) = config.rarity_config_storage.layers.get_mut(layer_name) {
-            if let Some(trait_config) = layer_config.traits.remove(old_trait_name) {
-                layer_config
-                    .traits
-                    .insert(new_trait_name.to_string(), trait_config);
-                tracing::info!(
-                    "[Rename] Updated trait name from '{}' to '{}' in layer '{}'",
-                    old_trait_name,
-                    new_trait_name,
-                    layer_name
-                );
-            } else {
-                tracing::warn!(
-                    "[Rename] Trait '{}' not found in layer '{}'",
-                    old_trait_name,
-                    layer_name
-                );
-                return Ok(());
-            }
-        } else {
-            tracing::warn!("[Rename] Layer '{}' not found in rarity config", layer_name);
-            return Ok(());
+            move_animation_dirs(
+                transaction,
+                &animated_dir,
+                &format!("{}/{}", layer, old.key),
+                &format!("{}/{}", layer, new.key),
+            )?;
         }
-
-        match save_storage(&storage_files.rarity_config, &config).await {
-            Ok(_) => {
-                tracing::info!(
-                    "[Rename] Successfully updated rarity_config.json with trait rename"
-                );
-            }
-            Err(e) => {
-                tracing::error!("[Rename] Failed to save updated rarity config: {}", e);
-                return Err(format!("Failed to save updated rarity config: {}", e));
-            }
+        RenameTarget::Group { layer, old, new } => {
+            rename_traits(
+                transaction,
+                storage_files,
+                &TraitRenaming {
+                    layer,
+                    old,
+                    new,
+                    group: true,
+                },
+            )?;
+            move_animation_dirs(
+                transaction,
+                &animated_dir,
+                &format!("{}/{}", layer, old),
+                &format!("{}/{}", layer, new),
+            )?;
         }
+        RenameTarget::Other => {}
     }
 
     Ok(())
 }
 
-async fn update_rarity_config_for_layer_rename(
+/// Points the configs at a renamed trait file of `layer_path`. The rarity and
+/// rules only follow when no other variant or part keeps the old trait name.
+pub fn update_trait_references(
+    transaction: &mut RenameTransaction,
     storage_files: &StorageFiles,
-    old_name: &str,
-    new_name: &str,
+    layer_path: &Path,
+    layer: &str,
+    old: &TraitFile,
+    new: &TraitFile,
 ) -> Result<(), String> {
-    tracing::debug!(
-        "[Rename] Updating rarity config for layer rename: {} -> {}",
-        old_name,
-        new_name
-    );
-
-    let current_config: Option<RarityConfigStorage> =
-        match load_storage(&storage_files.rarity_config).await {
-            Ok(Some(config)) => {
-                tracing::debug!("[Rename] Successfully loaded existing rarity config");
-                Some(config)
-            }
-            Ok(_none) => {
-                tracing::warn!("[Rename] No rarity config found, creating default");
-                Some(RarityConfigStorage {
-                    rarity_config_storage: RarityConfig::default(),
-                })
-            }
-            Err(e) => {
-                tracing::error!("[Rename] Failed to load rarity config: {}", e);
-                return Err(format!("Failed to load rarity config: {}", e));
-            }
-        };
-
-    if let Some(mut config) = current_config {
-        if let Some(layer_config) = config.rarity_config_storage.layers.remove(old_name) {
-            config
-                .rarity_config_storage
-                .layers
-                .insert(new_name.to_string(), layer_config);
-            tracing::info!(
-                "[Rename] Updated layer name from '{}' to '{}' in rarity config",
-                old_name,
-                new_name
-            );
-        } else {
-            tracing::warn!("[Rename] Layer '{}' not found in rarity config", old_name);
+    if old.trait_name != new.trait_name {
+        let still_used = scan_trait_files(layer_path)
+            .iter()
+            .any(|file| file.trait_name == old.trait_name);
+        if still_used {
             return Ok(());
         }
 
-        match save_storage(&storage_files.rarity_config, &config).await {
-            Ok(_) => {
-                tracing::info!(
-                    "[Rename] Successfully updated rarity_config.json with layer rename"
-                );
-            }
-            Err(e) => {
-                tracing::error!("[Rename] Failed to save updated rarity config: {}", e);
-                return Err(format!("Failed to save updated rarity config: {}", e));
-            }
-        }
+        return rename_traits(
+            transaction,
+            storage_files,
+            &TraitRenaming {
+                layer,
+                old: &old.trait_name,
+                new: &new.trait_name,
+                group: false,
+            },
+        );
     }
 
-    Ok(())
+    let (Some(old_variant), Some(new_variant), None) = (&old.variant, &new.variant, &old.part)
+    else {
+        return Ok(());
+    };
+    update_json(
+        transaction,
+        &storage_files.rarity_config,
+        |config: &mut RarityConfigStorage| {
+            config
+                .rarity_config_storage
+                .layers
+                .get_mut(layer)
+                .and_then(|layer_config| layer_config.traits.get_mut(&old.trait_name))
+                .and_then(|trait_config| {
+                    let weight = trait_config.variants.remove(old_variant)?;
+                    trait_config.variants.insert(new_variant.clone(), weight);
+                    Some(())
+                })
+                .is_some()
+        },
+    )
 }
 
-async fn update_ordered_layers_for_layer_rename(
+pub fn rename_traits(
+    transaction: &mut RenameTransaction,
     storage_files: &StorageFiles,
-    old_name: &str,
-    new_name: &str,
+    renaming: &TraitRenaming,
 ) -> Result<(), String> {
-    tracing::debug!(
-        "[Rename] Updating ordered layers for layer rename: {} -> {}",
-        old_name,
-        new_name
-    );
+    update_json(
+        transaction,
+        &storage_files.rarity_config,
+        |config: &mut RarityConfigStorage| {
+            rename_traits_in_rarity(&mut config.rarity_config_storage, renaming)
+        },
+    )?;
+    update_json(
+        transaction,
+        &storage_files.incompatibility,
+        |rules: &mut IncompatibilitiesBySets| {
+            rules.sets.values_mut().fold(false, |changed, set| {
+                rename_traits_in_rules(&mut set.incompatibilities, renaming) || changed
+            })
+        },
+    )?;
+    update_json(
+        transaction,
+        &storage_files.forced_combination,
+        |rules: &mut ForcedCombinationsBySets| {
+            rules.sets.values_mut().fold(false, |changed, set| {
+                rename_traits_in_rules(&mut set.forced_combinations, renaming) || changed
+            })
+        },
+    )
+}
 
-    let current_config: Option<SetsStorage> =
-        match load_storage(&storage_files.ordered_layers).await {
-            Ok(Some(config)) => {
-                tracing::debug!("[Rename] Successfully loaded existing ordered layers config");
-                Some(config)
-            }
-            Ok(_none) => {
-                tracing::warn!("[Rename] No ordered layers config found, creating default");
-                Some(SetsStorage {
-                    sets: HashMap::new(),
-                    active_set_id: "set1".to_string(),
-                    set_orders: vec![],
-                })
-            }
-            Err(e) => {
-                tracing::error!("[Rename] Failed to load ordered layers config: {}", e);
-                return Err(format!("Failed to load ordered layers config: {}", e));
-            }
-        };
+/// Applies `update` to a JSON config and writes it back when it reports a
+/// change. Missing files hold no references and are left alone.
+pub fn update_json<T, F>(
+    transaction: &mut RenameTransaction,
+    path: &Path,
+    update: F,
+) -> Result<(), String>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce(&mut T) -> bool,
+{
+    let Some(mut data) = read_json::<T>(path)? else {
+        return Ok(());
+    };
+    if update(&mut data) {
+        transaction.write_json(path, &data)?;
+    }
+    Ok(())
+}
 
-    if let Some(mut config) = current_config {
-        let mut updated = false;
-        for set_info in config.sets.values_mut() {
-            if let Some(index) = set_info.layers.iter().position(|layer| layer == old_name) {
-                set_info.layers[index] = new_name.to_string();
-                updated = true;
-                tracing::debug!(
-                    "[Rename] Updated layer '{}' to '{}' in set",
-                    old_name,
-                    new_name
-                );
-            }
+/// Moves the extracted frames and spritesheets of a layer, trait or group.
+fn move_animation_dirs(
+    transaction: &mut RenameTransaction,
+    animated_dir: &Path,
+    old_relative: &str,
+    new_relative: &str,
+) -> Result<(), String> {
+    for cache in ["frames", "spritesheets"] {
+        let old_dir = animated_dir.join(cache).join(old_relative);
+        if old_dir.exists() {
+            let new_dir = animated_dir.join(cache).join(new_relative);
+            transaction.move_path(&old_dir, &new_dir, true)?;
         }
+    }
+    Ok(())
+}
 
-        if updated {
-            tracing::info!(
-                "[Rename] Updated layer name from '{}' to '{}' in ordered layers config",
-                old_name,
-                new_name
-            );
-        } else {
-            tracing::warn!(
-                "[Rename] Layer '{}' not found in ordered layers config",
-                old_name
-            );
-            return Ok(());
-        }
+fn rename_layer_in_rarity(config: &mut RarityConfig, old: &str, new: &str) -> bool {
+    let Some(layer_config) = config.layers.remove(old) else {
+        tracing::warn!("[Rename] Layer '{}' not found in rarity config", old);
+        return false;
+    };
+    config.layers.insert(new.to_string(), layer_config);
 
-        match save_storage(&storage_files.ordered_layers, &config).await {
-            Ok(_) => {
-                tracing::info!(
-                    "[Rename] Successfully updated ordered_layers.json with layer rename"
-                );
-            }
-            Err(e) => {
-                tracing::error!(
-                    "[Rename] Failed to save updated ordered layers config: {}",
-                    e
-                );
-                return Err(format!(
-                    "Failed to save updated ordered layers config: {}",
-                    e
-                ));
+    for layer_config in config.layers.values_mut() {
+        for part in layer_config.parts.values_mut() {
+            for anchor in [&mut part.below_layer, &mut part.above_layer] {
+                if anchor.as_deref() == Some(old) {
+                    *anchor = Some(new.to_string());
+                }
             }
         }
     }
-
-    Ok(())
+    true
 }
 
-async fn rename_trait_animation_directories(
-    app_handle: &tauri::AppHandle,
-    base_path: &str,
-    layer_name: &str,
-    old_trait_name: &str,
-    new_trait_name: &str,
-) -> Result<(), String> {
-    tracing::debug!(
-        "[Rename] Renaming trait animation directories: {} -> {} in layer {}",
-        old_trait_name,
-        new_trait_name,
-        layer_name
-    );
+fn rename_traits_in_rarity(config: &mut RarityConfig, renaming: &TraitRenaming) -> bool {
+    let Some(layer_config) = config.layers.get_mut(renaming.layer) else {
+        return false;
+    };
 
-    let project_id = normalize_path(
-        &Path::new(base_path)
-            .file_name()
-            .ok_or("Could not extract project ID from base path")?
-            .to_string_lossy(),
+    // The moved entry replaces any stale entry of the new name.
+    let traits = rename_keys(
+        &mut layer_config.traits,
+        |name| renaming.apply(name),
+        |existing, moved| *existing = moved,
     );
-    tracing::debug!("[Rename] Project ID extracted: {}", project_id);
+    let groups = renaming.group
+        && rename_keys(
+            &mut layer_config.groups,
+            |name| renaming.apply(name),
+            |existing, moved| *existing = moved,
+        );
+    traits || groups
+}
 
-    let app_data_dir = app_handle.path().app_data_dir().map_err(|e| {
-        tracing::error!("[Rename] Failed to get app data directory: {}", e);
-        format!("Failed to get app data directory: {}", e)
-    })?;
+fn rename_layer_in_sets(storage: &mut SetsStorage, old: &str, new: &str) -> bool {
+    let mut updated = false;
+    for set_info in storage.sets.values_mut() {
+        for layer in set_info.layers.iter_mut().filter(|layer| *layer == old) {
+            *layer = new.to_string();
+            updated = true;
+        }
+    }
+    updated
+}
 
-    let animated_dir = app_data_dir.join("animated").join(&project_id);
+fn rename_layer_in_rules(rules: &mut RuleMap, old: &str, new: &str) -> bool {
+    let rename = |name: &str| (name == old).then(|| new.to_string());
 
-    if !animated_dir.exists() {
-        tracing::debug!("[Rename] Animated directory does not exist, skipping");
-        return Ok(());
+    let mut updated = rename_keys(rules, rename, merge_trait_rules);
+    for targets in rules
+        .values_mut()
+        .flat_map(|trait_rules| trait_rules.values_mut())
+    {
+        updated |= rename_keys(targets, rename, extend_unique);
     }
+    updated
+}
 
-    tracing::info!("[Rename] Animated directory exists, proceeding with rename");
-
-    tracing::debug!("[Rename] Processing frames directory for trait");
-    let old_frames_dir = animated_dir
-        .join("frames")
-        .join(layer_name)
-        .join(old_trait_name);
-    let new_frames_dir = animated_dir
-        .join("frames")
-        .join(layer_name)
-        .join(new_trait_name);
-
-    if old_frames_dir.exists() {
-        if new_frames_dir.exists() {
-            tracing::debug!(
-                "[Rename] Removing existing trait frames directory: {:?}",
-                new_frames_dir
-            );
-            fs::remove_dir_all(&new_frames_dir).map_err(|e| {
-                tracing::error!(
-                    "[Rename] Failed to remove existing trait frames directory: {}",
-                    e
-                );
-                format!("Failed to remove existing trait frames directory: {}", e)
-            })?;
+fn rename_traits_in_rules(rules: &mut RuleMap, renaming: &TraitRenaming) -> bool {
+    let mut updated = rules.get_mut(renaming.layer).is_some_and(|trait_rules| {
+        rename_keys(trait_rules, |name| renaming.apply(name), merge_targets)
+    });
+
+    for targets in rules
+        .values_mut()
+        .flat_map(|trait_rules| trait_rules.values_mut())
+    {
+        let Some(traits) = targets.get_mut(renaming.layer) else {
+            continue;
+        };
+        let mut renamed = false;
+        for name in traits.iter_mut() {
+            if let Some(new_name) = renaming.apply(name) {
+                *name = new_name;
+                renamed = true;
+            }
         }
+        if renamed {
+            let mut seen = HashSet::new();
+            traits.retain(|name| seen.insert(name.clone()));
+            updated = true;
+        }
+    }
+    updated
+}
 
-        if let Some(parent) = new_frames_dir.parent() {
-            tracing::info!("[Rename] Frames - creating parent directory: {:?}", parent);
-            fs::create_dir_all(parent).map_err(|e| {
-                tracing::error!(
-                    "[Rename] Failed to create trait frames parent directory: {}",
-                    e
-                );
-                format!("Failed to create trait frames parent directory: {}", e)
-            })?;
+/// Renames the keys `rename` maps to a new name, merging into any entry that
+/// already uses it. Returns whether a key changed.
+fn rename_keys<V>(
+    map: &mut HashMap<String, V>,
+    rename: impl Fn(&str) -> Option<String>,
+    merge: impl Fn(&mut V, V),
+) -> bool {
+    let renamed: Vec<(String, String)> = map
+        .keys()
+        .filter_map(|key| rename(key).map(|new_key| (key.clone(), new_key)))
+        .collect();
+
+    for (old_key, new_key) in &renamed {
+        if let Some(value) = map.remove(old_key) {
+            match map.get_mut(new_key) {
+                Some(existing) => merge(existing, value),
+                None => {
+                    map.insert(new_key.clone(), value);
+                }
+            }
         }
+    }
+    !renamed.is_empty()
+}
 
-        tracing::debug!(
-            "[Rename] Renaming trait frames directory: {:?} -> {:?}",
-            old_frames_dir,
-            new_frames_dir
-        );
-        fs::rename(&old_frames_dir, &new_frames_dir).map_err(|e| {
-            tracing::error!("[Rename] Failed to rename trait frames directory: {}", e);
-            format!("Failed to rename trait frames directory: {}", e)
-        })?;
-        tracing::info!("[Rename] Successfully renamed trait frames directory");
-    } else {
-        tracing::info!("[Rename] Frames - old directory does not exist, skipping");
+fn merge_trait_rules(
+    existing: &mut HashMap<String, HashMap<String, Vec<String>>>,
+    moved: HashMap<String, HashMap<String, Vec<String>>>,
+) {
+    for (trait_name, targets) in moved {
+        merge_targets(existing.entry(trait_name).or_default(), targets);
     }
+}
 
-    tracing::debug!("[Rename] Processing spritesheets directory for trait");
-    let old_spritesheets_dir = animated_dir
-        .join("spritesheets")
-        .join(layer_name)
-        .join(old_trait_name);
-    let new_spritesheets_dir = animated_dir
-        .join("spritesheets")
-        .join(layer_name)
-        .join(new_trait_name);
-
-    if old_spritesheets_dir.exists() {
-        if new_spritesheets_dir.exists() {
-            tracing::debug!(
-                "[Rename] Removing existing trait spritesheets directory: {:?}",
-                new_spritesheets_dir
-            );
-            fs::remove_dir_all(&new_spritesheets_dir).map_err(|e| {
-                tracing::error!(
-                    "[Rename] Failed to remove existing trait spritesheets directory: {}",
-                    e
-                );
-                format!(
-                    "Failed to remove existing trait spritesheets directory: {}",
-                    e
-                )
-            })?;
+fn merge_targets(existing: &mut HashMap<String, Vec<String>>, moved: HashMap<String, Vec<String>>) {
+    for (layer, traits) in moved {
+        extend_unique(existing.entry(layer).or_default(), traits);
+    }
+}
+
+fn extend_unique(existing: &mut Vec<String>, moved: Vec<String>) {
+    for name in moved {
+        if !existing.contains(&name) {
+            existing.push(name);
         }
+    }
+}
 
-        if let Some(parent) = new_spritesheets_dir.parent() {
-            tracing::debug!(
-                "[Rename] Creating trait spritesheets parent directory: {:?}",
-                parent
-            );
-            fs::create_dir_all(parent).map_err(|e| {
-                tracing::error!(
-                    "[Rename] Failed to create trait spritesheets parent directory: {}",
-                    e
-                );
-                format!(
-                    "Failed to create trait spritesheets parent directory: {}",
-                    e
-                )
+fn folder_name(path: &Path) -> String {
+    normalize_path(&path.file_name().unwrap_or_default().to_string_lossy())
+}
+
+/// Sets the name written in metadata for a layer (`trait_name` is `None`) or
+/// a trait. Only the rarity config changes; files keep their names. An empty
+/// `display_name` falls back to the file name again.
+#[tauri::command]
+pub async fn set_display_name(
+    registry: State<'_, ProjectRegistry>,
+    layer_name: String,
+    trait_name: Option<String>,
+    display_name: String,
+    locale: Option<String>,
+) -> Result<(), String> {
+    let storage_files = registry.storage_files();
+    let mut config: RarityConfigStorage = load_storage(&storage_files.rarity_config)
+        .await?
+        .ok_or("No rarity config found")?;
+
+    let layer_config = config
+        .rarity_config_storage
+        .layers
+        .get_mut(&layer_name)
+        .ok_or_else(|| format!("Layer '{}' not found in rarity config", layer_name))?;
+
+    let display_name = display_name.trim();
+    let display_name = (!display_name.is_empty()).then(|| display_name.to_string());
+
+    let (name, localized_names) = match &trait_name {
+        Some(trait_name) => {
+            let trait_config = layer_config.traits.get_mut(trait_name).ok_or_else(|| {
+                format!("Trait '{}' not found in layer '{}'", trait_name, layer_name)
             })?;
+            (
+                &mut trait_config.display_name,
+                &mut trait_config.localized_names,
+            )
         }
+        None => (
+            &mut layer_config.trait_type,
+            &mut layer_config.localized_trait_types,
+        ),
+    };
 
-        tracing::debug!(
-            "[Rename] Renaming trait spritesheets directory: {:?} -> {:?}",
-            old_spritesheets_dir,
-            new_spritesheets_dir
-        );
-        fs::rename(&old_spritesheets_dir, &new_spritesheets_dir).map_err(|e| {
-            tracing::error!(
-                "[Rename] Failed to rename trait spritesheets directory: {}",
-                e
-            );
-            format!("Failed to rename trait spritesheets directory: {}", e)
-        })?;
-        tracing::info!("[Rename] Successfully renamed trait spritesheets directory");
-    } else {
-        tracing::info!("[Rename] Spritesheets - old directory does not exist, skipping");
+    match (locale, display_name) {
+        (Some(locale), Some(display_name)) => {
+            localized_names.insert(locale, display_name);
+        }
+        (Some(locale), None) => {
+            localized_names.remove(&locale);
+        }
+        (None, display_name) => *name = display_name,
     }
 
+    save_storage(&storage_files.rarity_config, &config).await?;
+
     tracing::info!(
-        "[Rename] Successfully completed trait animation directories rename: {} -> {} in layer {}",
-        old_trait_name,
-        new_trait_name,
+        "[Rename] Updated display name of '{}' in layer '{}'",
+        trait_name.as_deref().unwrap_or(&layer_name),
         layer_name
     );
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(value: serde_json::Value) -> RuleMap {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn rename_keys_merges_into_existing_entries() {
+        let mut map = HashMap::from([
+            ("Old".to_string(), vec!["a".to_string(), "b".to_string()]),
+            ("New".to_string(), vec!["b".to_string(), "c".to_string()]),
+            ("Other".to_string(), vec!["d".to_string()]),
+        ]);
+
+        let renamed = rename_keys(
+            &mut map,
+            |name| (name == "Old").then(|| "New".to_string()),
+            extend_unique,
+        );
+        assert!(renamed);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["New"], ["b", "c", "a"]);
+        assert_eq!(map["Other"], ["d"]);
+
+        assert!(!rename_keys(&mut map, |_| None, extend_unique));
+    }
+
+    #[test]
+    fn group_renames_follow_rules_and_drop_duplicates() {
+        let mut rules = rules(serde_json::json!({
+            "Hats": {
+                "Rare/Crown": { "Eyes": ["Blue"] },
+                "Legendary/Crown": { "Eyes": ["Red"] },
+                "Rarest": { "Eyes": ["Green"] }
+            },
+            "Eyes": {
+                "Blue": { "Hats": ["Rare/Crown", "Legendary/Crown", "Rare"] }
+            }
+        }));
+
+        let renamed = rename_traits_in_rules(
+            &mut rules,
+            &TraitRenaming {
+                layer: "Hats",
+                old: "Rare",
+                new: "Legendary",
+                group: true,
+            },
+        );
+        assert!(renamed);
+
+        let hats = &rules["Hats"];
+        assert_eq!(hats.len(), 2);
+        let mut eyes = hats["Legendary/Crown"]["Eyes"].clone();
+        eyes.sort();
+        assert_eq!(eyes, ["Blue", "Red"]);
+        assert_eq!(hats["Rarest"]["Eyes"], ["Green"]);
+        assert_eq!(
+            rules["Eyes"]["Blue"]["Hats"],
+            ["Legendary/Crown", "Legendary"]
+        );
+    }
+
+    #[test]
+    fn trait_renames_leave_other_layers_alone() {
+        let mut rules = rules(serde_json::json!({
+            "Hats": { "Crown": { "Eyes": ["Crown"] } },
+            "Eyes": { "Crown": { "Hats": ["Crown"] } }
+        }));
+
+        rename_traits_in_rules(
+            &mut rules,
+            &TraitRenaming {
+                layer: "Hats",
+                old: "Crown",
+                new: "Tiara",
+                group: false,
+            },
+        );
+        assert_eq!(rules["Hats"]["Tiara"]["Eyes"], ["Crown"]);
+        assert_eq!(rules["Eyes"]["Crown"]["Hats"], ["Tiara"]);
+    }
+}
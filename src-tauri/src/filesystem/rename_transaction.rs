@@ -0,0 +1,350 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+use tracing;
+
+const ASIDE_SUFFIX: &str = ".rename-old";
+
+enum Step {
+    Moved {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    /// An existing target moved out of the way, deleted on commit.
+    SetAside {
+        original: PathBuf,
+        aside: PathBuf,
+    },
+    Written {
+        path: PathBuf,
+        previous: Option<Vec<u8>>,
+    },
+}
+
+/// A file as it was before a rename first wrote it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSnapshot {
+    pub path: String,
+    pub previous: Option<String>,
+    /// Hash of what the rename left in the file. The snapshot is only put
+    /// back while the file still holds exactly that.
+    pub written_hash: String,
+}
+
+/// Records every change of a rename as it is made, so a failure halfway can
+/// put the project back exactly as it was.
+#[derive(Default)]
+pub struct RenameTransaction {
+    steps: Vec<Step>,
+    restore: HashMap<PathBuf, FileSnapshot>,
+}
+
+impl RenameTransaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A transaction undoing a rename: the files it wrote are put back from
+    /// `snapshots` instead of being rewritten.
+    pub fn restoring(snapshots: Vec<FileSnapshot>) -> Self {
+        Self {
+            steps: Vec::new(),
+            restore: snapshots
+                .into_iter()
+                .map(|snapshot| (PathBuf::from(&snapshot.path), snapshot))
+                .collect(),
+        }
+    }
+
+    /// Moves `from` to `to`, creating the parent folders of `to`. With
+    /// `replace`, an existing `to` is set aside and dropped on commit.
+    pub fn move_path(&mut self, from: &Path, to: &Path, replace: bool) -> Result<(), String> {
+        // On case-insensitive filesystems `to` already "exists" when only the
+        // case changes.
+        let case_only =
+            from.to_string_lossy().to_lowercase() == to.to_string_lossy().to_lowercase();
+        if to.exists() && !case_only {
+            if !replace {
+                return Err(format!("'{}' already exists", to.display()));
+            }
+            let aside = aside_path(to);
+            if aside.exists() {
+                remove_path(&aside)?;
+            }
+            fs::rename(to, &aside)
+                .map_err(|e| format!("Failed to move aside {}: {}", to.display(), e))?;
+            self.steps.push(Step::SetAside {
+                original: to.to_path_buf(),
+                aside,
+            });
+        }
+
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        fs::rename(from, to).map_err(|e| {
+            format!(
+                "Failed to rename {} to {}: {}",
+                from.display(),
+                to.display(),
+                e
+            )
+        })?;
+        self.steps.push(Step::Moved {
+            from: from.to_path_buf(),
+            to: to.to_path_buf(),
+        });
+        Ok(())
+    }
+
+    /// Writes `data` to `path`, or puts back the snapshot of `path` when one
+    /// can be restored.
+    pub fn write_json<T: Serialize>(&mut self, path: &Path, data: &T) -> Result<(), String> {
+        if self.restore_snapshot(path)? {
+            return Ok(());
+        }
+        let content = serde_json::to_string_pretty(data)
+            .map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+        self.write_file(path, Some(content.into_bytes()))
+    }
+
+    /// Puts back the snapshots no write of the transaction reached.
+    pub fn restore_remaining(&mut self) -> Result<(), String> {
+        let paths: Vec<PathBuf> = self.restore.keys().cloned().collect();
+        for path in paths {
+            self.restore_snapshot(&path)?;
+        }
+        Ok(())
+    }
+
+    /// The files written so far, as they were before their first write.
+    pub fn snapshots(&self) -> Vec<FileSnapshot> {
+        let mut seen = HashSet::new();
+        self.steps
+            .iter()
+            .filter_map(|step| match step {
+                Step::Written { path, previous } if seen.insert(path) => Some(FileSnapshot {
+                    path: path.to_string_lossy().to_string(),
+                    previous: previous
+                        .as_ref()
+                        .map(|content| String::from_utf8_lossy(content).into_owned()),
+                    written_hash: content_hash(&fs::read(path).ok()?),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn restore_snapshot(&mut self, path: &Path) -> Result<bool, String> {
+        let Some(snapshot) = self.restore.remove(path) else {
+            return Ok(false);
+        };
+        let current = fs::read(path).ok();
+        if current.as_deref().map(content_hash) != Some(snapshot.written_hash) {
+            tracing::warn!(
+                "[Rename] {} changed since the rename, updating it instead",
+                path.display()
+            );
+            return Ok(false);
+        }
+
+        self.write_file(path, snapshot.previous.map(String::into_bytes))?;
+        Ok(true)
+    }
+
+    /// Writes `content` to `path`, or removes it for `None`.
+    fn write_file(&mut self, path: &Path, content: Option<Vec<u8>>) -> Result<(), String> {
+        let previous = fs::read(path).ok();
+
+        match content {
+            Some(content) => fs::write(path, content)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?,
+            None => fs::remove_file(path)
+                .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?,
+        }
+        self.steps.push(Step::Written {
+            path: path.to_path_buf(),
+            previous,
+        });
+        Ok(())
+    }
+
+    /// Keeps the changes and deletes what was set aside.
+    pub fn commit(self) {
+        for step in self.steps {
+            if let Step::SetAside { aside, .. } = step {
+                if let Err(e) = remove_path(&aside) {
+                    tracing::warn!("[Rename] {}", e);
+                }
+            }
+        }
+    }
+
+    /// Undoes the recorded changes, newest first. Every step is attempted even
+    /// if an earlier one fails.
+    pub fn rollback(self) {
+        for step in self.steps.into_iter().rev() {
+            let result = match step {
+                Step::Moved { from, to } => fs::rename(&to, &from)
+                    .map_err(|e| format!("Failed to move back {}: {}", to.display(), e)),
+                Step::SetAside { original, aside } => fs::rename(&aside, &original)
+                    .map_err(|e| format!("Failed to restore {}: {}", original.display(), e)),
+                Step::Written {
+                    path,
+                    previous: Some(content),
+                } => fs::write(&path, content)
+                    .map_err(|e| format!("Failed to restore {}: {}", path.display(), e)),
+                Step::Written {
+                    path,
+                    previous: None,
+                } if path.exists() => fs::remove_file(&path)
+                    .map_err(|e| format!("Failed to remove {}: {}", path.display(), e)),
+                Step::Written { .. } => Ok(()),
+            };
+
+            if let Err(e) = result {
+                tracing::error!("[Rename] Rollback step failed: {}", e);
+            }
+        }
+    }
+}
+
+/// Reads a JSON file, `None` when it does not exist or is empty.
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    if content.trim().is_empty() {
+        return Ok(None);
+    }
+
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+fn content_hash(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+fn aside_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(ASIDE_SUFFIX);
+    path.with_file_name(name)
+}
+
+fn remove_path(path: &Path) -> Result<(), String> {
+    let result = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+    result.map_err(|e| format!("Failed to remove {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rename-transaction-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn rollback_restores_moves_replaced_targets_and_writes() {
+        let dir = scratch_dir("rollback");
+        let (from, to, config) = (dir.join("a"), dir.join("b"), dir.join("config.json"));
+        fs::write(&from, "a").unwrap();
+        fs::write(&to, "b").unwrap();
+        fs::write(&config, "{}").unwrap();
+
+        let mut transaction = RenameTransaction::new();
+        transaction.move_path(&from, &to, true).unwrap();
+        transaction.write_json(&config, &json!({ "x": 1 })).unwrap();
+        transaction
+            .write_json(&dir.join("new.json"), &json!([]))
+            .unwrap();
+        transaction.rollback();
+
+        assert_eq!(fs::read_to_string(&from).unwrap(), "a");
+        assert_eq!(fs::read_to_string(&to).unwrap(), "b");
+        assert_eq!(fs::read_to_string(&config).unwrap(), "{}");
+        assert!(!dir.join("new.json").exists());
+        assert!(!aside_path(&to).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn move_without_replace_keeps_existing_target() {
+        let dir = scratch_dir("no-replace");
+        fs::write(dir.join("a"), "a").unwrap();
+        fs::write(dir.join("b"), "b").unwrap();
+
+        let mut transaction = RenameTransaction::new();
+        assert!(transaction
+            .move_path(&dir.join("a"), &dir.join("b"), false)
+            .is_err());
+        assert_eq!(fs::read_to_string(dir.join("b")).unwrap(), "b");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn snapshots_restore_the_original_bytes() {
+        let dir = scratch_dir("snapshots");
+        let config = dir.join("config.json");
+        let original = "{\"keep\":  \"formatting\"}";
+        fs::write(&config, original).unwrap();
+
+        let mut rename = RenameTransaction::new();
+        rename
+            .write_json(&config, &json!({ "renamed": 1 }))
+            .unwrap();
+        rename
+            .write_json(&config, &json!({ "renamed": 2 }))
+            .unwrap();
+        let snapshots = rename.snapshots();
+        rename.commit();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].previous.as_deref(), Some(original));
+
+        let mut undo = RenameTransaction::restoring(snapshots);
+        undo.restore_remaining().unwrap();
+        undo.commit();
+        assert_eq!(fs::read_to_string(&config).unwrap(), original);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn snapshots_are_not_restored_over_later_edits() {
+        let dir = scratch_dir("edited");
+        let config = dir.join("config.json");
+        fs::write(&config, "{}").unwrap();
+
+        let mut rename = RenameTransaction::new();
+        rename
+            .write_json(&config, &json!({ "renamed": 1 }))
+            .unwrap();
+        let snapshots = rename.snapshots();
+        rename.commit();
+        fs::write(&config, "{\"edited\":true}").unwrap();
+
+        let mut undo = RenameTransaction::restoring(snapshots);
+        undo.write_json(&config, &json!({ "reverted": 1 })).unwrap();
+        undo.commit();
+        let reverted: serde_json::Value = read_json(&config).unwrap().unwrap();
+        assert_eq!(reverted, json!({ "reverted": 1 }));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
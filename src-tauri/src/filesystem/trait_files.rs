@@ -0,0 +1,116 @@
+use std::path::Path;
+use walkdir::WalkDir;
+
+use crate::filesystem::utils::normalize_path;
+
+pub const TRAIT_EXTENSIONS: [&str; 8] = ["png", "webp", "gif", "mp4", "webm", "mov", "avi", "mkv"];
+
+/// Separates a trait from its variant in a file name, as in `Shirt#Red.png`.
+pub const VARIANT_SEPARATOR: char = '#';
+
+/// Separates a trait from one of its linked parts, as in `Long__back.png`.
+/// Parts are drawn with the trait but at their own position in the stack.
+pub const PART_SEPARATOR: &str = "__";
+
+/// One image of a layer. Subfolders of the layer are trait groups, so
+/// `Hats/Rare/Crown#Gold.png` belongs to the `Rare` group of the `Hats` layer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraitFile {
+    /// Path relative to the layer folder, e.g. `Rare/Crown#Gold.png`.
+    pub relative_path: String,
+    /// Relative path without extension, e.g. `Rare/Crown#Gold`. Identifies the
+    /// file that is rendered and the folder of its spritesheets.
+    pub key: String,
+    /// Rarity entry shared by every variant, e.g. `Rare/Crown`.
+    pub trait_name: String,
+    pub variant: Option<String>,
+    pub part: Option<String>,
+}
+
+impl TraitFile {
+    pub fn parse(relative_path: &str) -> Option<Self> {
+        let relative_path = normalize_path(relative_path);
+        let (key, extension) = relative_path.rsplit_once('.')?;
+
+        if extension.contains('/')
+            || !TRAIT_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+            || relative_path
+                .split('/')
+                .any(|part| part.is_empty() || part.starts_with('.'))
+        {
+            return None;
+        }
+
+        let (main_key, part) = split_part(key);
+        let (trait_name, variant) = split_variant(main_key);
+        if trait_name.is_empty() || trait_name.ends_with('/') {
+            return None;
+        }
+
+        Some(Self {
+            trait_name: trait_name.to_string(),
+            variant: variant.map(str::to_string),
+            part: part.map(str::to_string),
+            key: key.to_string(),
+            relative_path,
+        })
+    }
+}
+
+/// Splits a file key into its trait name and variant. Only the file name part
+/// can carry a variant.
+pub fn split_variant(key: &str) -> (&str, Option<&str>) {
+    match key.rsplit_once(VARIANT_SEPARATOR) {
+        Some((name, variant)) if !variant.is_empty() && !variant.contains('/') => {
+            (name, Some(variant))
+        }
+        _ => (key, None),
+    }
+}
+
+/// Splits a file key into the key of the main file and the part name.
+pub fn split_part(key: &str) -> (&str, Option<&str>) {
+    match key.rsplit_once(PART_SEPARATOR) {
+        Some((main_key, part))
+            if !part.is_empty()
+                && !part.contains('/')
+                && !main_key.is_empty()
+                && !main_key.ends_with('/') =>
+        {
+            (main_key, Some(part))
+        }
+        _ => (key, None),
+    }
+}
+
+pub fn part_file_key(main_key: &str, part: &str) -> String {
+    format!("{}{}{}", main_key, PART_SEPARATOR, part)
+}
+
+pub fn trait_group(trait_name: &str) -> Option<&str> {
+    trait_name.rsplit_once('/').map(|(group, _)| group)
+}
+
+/// Trait name without its groups, as shown in metadata.
+pub fn trait_label(trait_name: &str) -> &str {
+    trait_name.rsplit('/').next().unwrap_or(trait_name)
+}
+
+/// Every trait image under `layer_path`, subfolders included, sorted by path.
+/// Hidden files and folders are skipped.
+pub fn scan_trait_files(layer_path: &Path) -> Vec<TraitFile> {
+    let mut files: Vec<TraitFile> = WalkDir::new(layer_path)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let relative = entry.path().strip_prefix(layer_path).ok()?;
+            TraitFile::parse(&relative.to_string_lossy())
+        })
+        .collect();
+
+    files.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+    files
+}
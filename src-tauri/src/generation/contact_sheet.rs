@@ -0,0 +1,553 @@
+use anyhow::{Context, Result};
+use image::{imageops, DynamicImage, Rgba, RgbaImage};
+use once_cell::sync::Lazy;
+use rayon::prelude::*;
+use rusttype::{point, Font, Scale};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tauri::State;
+use wgpu::{Device, Queue};
+
+use crate::{
+    effects::core::{
+        gpu::{
+            resize_gpu::ResizeGpu,
+            shaders::{get_global_device, get_global_queue},
+        },
+        transform::resize_cpu,
+    },
+    filesystem::{persist::load_projectsetup_state, projects::ProjectRegistry},
+    generation::generate::generate_single::static_single::{
+        get_or_init_shared_gpu_pipeline, get_or_init_shared_resize_gpu,
+    },
+    types::SpritesheetLayout,
+};
+
+static CAPTION_FONT: Lazy<Option<Font<'static>>> = Lazy::new(|| {
+    Font::try_from_bytes(include_bytes!(
+        "../../../public/fonts/Inter/Inter-VariableFont_opsz,wght.ttf"
+    ))
+});
+
+const BACKGROUND: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const TEXT_COLOR: Rgba<u8> = Rgba([40, 40, 40, 255]);
+const CELL_GAP: u32 = 16;
+const CAPTION_PADDING: u32 = 6;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(tag = "by", rename_all = "camelCase")]
+pub enum ContactSheetSort {
+    #[default]
+    Edition,
+    Rarity,
+    Trait {
+        #[serde(rename = "traitType")]
+        trait_type: String,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ContactSheetOptions {
+    pub rows: u32,
+    pub cols: u32,
+    pub thumbnail_size: u32,
+    pub margin: u32,
+    pub font_size: f32,
+    pub show_edition: bool,
+    pub show_traits: bool,
+    pub sort: ContactSheetSort,
+    /// Defaults to `contact_sheets` in the export folder.
+    pub output_folder: Option<String>,
+}
+
+impl Default for ContactSheetOptions {
+    fn default() -> Self {
+        Self {
+            rows: 5,
+            cols: 5,
+            thumbnail_size: 256,
+            margin: 48,
+            font_size: 14.0,
+            show_edition: true,
+            show_traits: false,
+            sort: ContactSheetSort::Edition,
+            output_folder: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactSheetResult {
+    pub pages: Vec<String>,
+    pub items: usize,
+    pub skipped: Vec<String>,
+}
+
+struct SheetItem {
+    edition: u32,
+    image_path: PathBuf,
+    attributes: Vec<(String, String)>,
+    rarity_rank: u32,
+}
+
+enum ThumbnailResizer {
+    Gpu {
+        resize_gpu: Arc<ResizeGpu>,
+        device: Arc<Device>,
+        queue: Arc<Queue>,
+    },
+    Cpu,
+}
+
+impl ThumbnailResizer {
+    async fn new() -> Self {
+        let resize_gpu = match get_or_init_shared_gpu_pipeline().await {
+            Ok(_) => get_or_init_shared_resize_gpu().await.ok(),
+            Err(_) => None,
+        };
+
+        match (resize_gpu, get_global_device(), get_global_queue()) {
+            (Some(resize_gpu), Some(device), Some(queue)) => Self::Gpu {
+                resize_gpu,
+                device,
+                queue,
+            },
+            _ => {
+                tracing::warn!("⚠️ [CONTACT SHEET] GPU unavailable, resizing thumbnails on CPU");
+                Self::Cpu
+            }
+        }
+    }
+
+    fn resize(&self, image: &DynamicImage, width: u32, height: u32) -> RgbaImage {
+        if let Self::Gpu {
+            resize_gpu,
+            device,
+            queue,
+        } = self
+        {
+            match resize_gpu.resize_image(device, queue, image, width, height, &None) {
+                Ok(resized) => return resized.into_rgba8(),
+                Err(e) => tracing::warn!("⚠️ [CONTACT SHEET] GPU resize failed: {}", e),
+            }
+        }
+        resize_cpu(image, width, height).into_rgba8()
+    }
+}
+
+fn edition_from_stem(stem: &str) -> Option<u32> {
+    stem.rsplit('_').next()?.parse().ok()
+}
+
+fn read_attributes(metadata_path: &Path) -> Vec<(String, String)> {
+    let Ok(content) = fs::read_to_string(metadata_path) else {
+        return Vec::new();
+    };
+    let Ok(metadata) = serde_json::from_str::<serde_json::Value>(&content) else {
+        return Vec::new();
+    };
+
+    metadata
+        .get("attributes")
+        .and_then(|a| a.as_array())
+        .map(|attributes| {
+            attributes
+                .iter()
+                .filter_map(|attribute| {
+                    let trait_type = attribute.get("trait_type")?.as_str()?;
+                    let value = match attribute.get("value")? {
+                        serde_json::Value::String(value) => value.clone(),
+                        value => value.to_string(),
+                    };
+                    Some((trait_type.to_string(), value))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn collect_items(collection_path: &Path) -> Result<Vec<SheetItem>> {
+    let images_path = collection_path.join("images");
+    let metadata_path = collection_path.join("metadata");
+
+    let mut items: Vec<SheetItem> = fs::read_dir(&images_path)
+        .with_context(|| format!("Failed to read {}", images_path.display()))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter_map(|path| {
+            let stem = path.file_stem()?.to_str()?.to_string();
+            let edition = edition_from_stem(&stem)?;
+            let attributes = read_attributes(&metadata_path.join(format!("{}.json", stem)));
+            Some(SheetItem {
+                edition,
+                image_path: path,
+                attributes,
+                rarity_rank: 0,
+            })
+        })
+        .collect();
+
+    assign_rarity_ranks(&mut items);
+    Ok(items)
+}
+
+/// Ranks items by the sum of `1 / trait frequency` over their attributes,
+/// rank 1 being the rarest.
+fn assign_rarity_ranks(items: &mut [SheetItem]) {
+    let mut counts: HashMap<(&str, &str), u32> = HashMap::new();
+    for item in items.iter() {
+        for (trait_type, value) in &item.attributes {
+            *counts
+                .entry((trait_type.as_str(), value.as_str()))
+                .or_insert(0) += 1;
+        }
+    }
+
+    let total = items.len() as f64;
+    let mut scores: Vec<(usize, f64, u32)> = items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let score = item
+                .attributes
+                .iter()
+                .map(|(trait_type, value)| {
+                    total / counts[&(trait_type.as_str(), value.as_str())] as f64
+                })
+                .sum();
+            (index, score, item.edition)
+        })
+        .collect();
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.2.cmp(&b.2)));
+
+    let ranks: Vec<(usize, u32)> = scores
+        .iter()
+        .enumerate()
+        .map(|(rank, (index, _, _))| (*index, rank as u32 + 1))
+        .collect();
+    for (index, rank) in ranks {
+        items[index].rarity_rank = rank;
+    }
+}
+
+fn sort_items(items: &mut [SheetItem], sort: &ContactSheetSort) {
+    match sort {
+        ContactSheetSort::Edition => items.sort_by_key(|item| item.edition),
+        ContactSheetSort::Rarity => items.sort_by_key(|item| item.rarity_rank),
+        ContactSheetSort::Trait { trait_type } => items.sort_by(|a, b| {
+            let value = |item: &SheetItem| {
+                item.attributes
+                    .iter()
+                    .find(|(t, _)| t == trait_type)
+                    .map(|(_, value)| value.clone())
+            };
+            // Items without the trait go last.
+            match (value(a), value(b)) {
+                (Some(a_value), Some(b_value)) => a_value.cmp(&b_value),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            }
+            .then(a.edition.cmp(&b.edition))
+        }),
+    }
+}
+
+/// First frame of an item. Video formats fall back to the first cell of the
+/// exported spritesheet.
+fn load_first_frame(
+    item: &SheetItem,
+    collection_path: &Path,
+    layout: Option<&SpritesheetLayout>,
+) -> Result<DynamicImage> {
+    if let Ok(image) = image::open(&item.image_path) {
+        return Ok(image);
+    }
+
+    let layout =
+        layout.ok_or_else(|| anyhow::anyhow!("Unsupported image and no spritesheet layout"))?;
+    let spritesheet_path = collection_path
+        .join("sprites")
+        .join(item.edition.to_string())
+        .join("spritesheet_0.png");
+    let spritesheet = image::open(&spritesheet_path)
+        .with_context(|| format!("Failed to open {}", spritesheet_path.display()))?;
+
+    Ok(spritesheet.crop_imm(0, 0, layout.frame_width, layout.frame_height))
+}
+
+fn caption_lines(item: &SheetItem, options: &ContactSheetOptions) -> Vec<String> {
+    let mut lines = Vec::new();
+    if options.show_edition {
+        match options.sort {
+            ContactSheetSort::Rarity => {
+                lines.push(format!("#{} · rank {}", item.edition, item.rarity_rank))
+            }
+            _ => lines.push(format!("#{}", item.edition)),
+        }
+    }
+    if options.show_traits {
+        lines.extend(
+            item.attributes
+                .iter()
+                .map(|(trait_type, value)| format!("{}: {}", trait_type, value)),
+        );
+    }
+    lines
+}
+
+fn text_width(font: &Font, scale: Scale, text: &str) -> f32 {
+    font.layout(text, scale, point(0.0, 0.0))
+        .last()
+        .map(|glyph| glyph.position().x + glyph.unpositioned().h_metrics().advance_width)
+        .unwrap_or(0.0)
+}
+
+/// Cuts `text` with an ellipsis so that it fits in `max_width` pixels.
+fn fit_text(font: &Font, scale: Scale, text: &str, max_width: f32) -> String {
+    if text_width(font, scale, text) <= max_width {
+        return text.to_string();
+    }
+
+    let mut chars: Vec<char> = text.chars().collect();
+    while !chars.is_empty() {
+        chars.pop();
+        let candidate = format!("{}…", chars.iter().collect::<String>());
+        if text_width(font, scale, &candidate) <= max_width {
+            return candidate;
+        }
+    }
+    String::new()
+}
+
+fn draw_text(canvas: &mut RgbaImage, font: &Font, scale: Scale, x: u32, y: u32, text: &str) {
+    let ascent = font.v_metrics(scale).ascent;
+    for glyph in font.layout(text, scale, point(x as f32, y as f32 + ascent)) {
+        let Some(bounds) = glyph.pixel_bounding_box() else {
+            continue;
+        };
+        glyph.draw(|gx, gy, coverage| {
+            let px = bounds.min.x + gx as i32;
+            let py = bounds.min.y + gy as i32;
+            if px < 0 || py < 0 || px >= canvas.width() as i32 || py >= canvas.height() as i32 {
+                return;
+            }
+            let pixel = canvas.get_pixel_mut(px as u32, py as u32);
+            for channel in 0..3 {
+                let background = pixel[channel] as f32;
+                let text = TEXT_COLOR[channel] as f32;
+                pixel[channel] = (background + (text - background) * coverage).round() as u8;
+            }
+        });
+    }
+}
+
+/// Size of a thumbnail that fits in a `size` square, keeping its aspect.
+fn fit_dimensions(width: u32, height: u32, size: u32) -> (u32, u32) {
+    let scale = size as f64 / width.max(height).max(1) as f64;
+    (
+        ((width as f64 * scale).round() as u32).clamp(1, size),
+        ((height as f64 * scale).round() as u32).clamp(1, size),
+    )
+}
+
+/// Pixel size of a page, `None` when it does not fit in `u32`.
+fn page_size(options: &ContactSheetOptions, cell_height: u32) -> Option<(u32, u32)> {
+    let side = |cells: u32, cell: u32| {
+        cells
+            .checked_mul(cell)?
+            .checked_add((cells - 1).checked_mul(CELL_GAP)?)?
+            .checked_add(options.margin.checked_mul(2)?)
+    };
+    Some((
+        side(options.cols, options.thumbnail_size)?,
+        side(options.rows, cell_height)?,
+    ))
+}
+
+fn render_page(
+    items: &[SheetItem],
+    thumbnails: Vec<Option<RgbaImage>>,
+    options: &ContactSheetOptions,
+    caption_rows: u32,
+) -> Result<RgbaImage> {
+    let scale = Scale::uniform(options.font_size);
+    let line_height = (options.font_size * 1.3).ceil() as u32;
+    let caption_height = if caption_rows > 0 {
+        CAPTION_PADDING + caption_rows * line_height
+    } else {
+        0
+    };
+    let cell_width = options.thumbnail_size;
+    let cell_height = options.thumbnail_size.saturating_add(caption_height);
+
+    let (width, height) = page_size(options, cell_height)
+        .context("Contact sheet pages would be too large for the thumbnail size")?;
+    let mut page = RgbaImage::from_pixel(width, height, BACKGROUND);
+
+    for (slot, (item, thumbnail)) in items.iter().zip(thumbnails).enumerate() {
+        let column = slot as u32 % options.cols;
+        let row = slot as u32 / options.cols;
+        let cell_x = options.margin + column * (cell_width + CELL_GAP);
+        let cell_y = options.margin + row * (cell_height + CELL_GAP);
+
+        if let Some(thumbnail) = thumbnail {
+            let x = cell_x + (cell_width - thumbnail.width()) / 2;
+            let y = cell_y + (options.thumbnail_size - thumbnail.height()) / 2;
+            imageops::overlay(&mut page, &thumbnail, x as i64, y as i64);
+        }
+
+        if let Some(font) = CAPTION_FONT.as_ref() {
+            let lines = caption_lines(item, options);
+            for (line_index, line) in lines.iter().take(caption_rows as usize).enumerate() {
+                let text = fit_text(font, scale, line, cell_width as f32);
+                draw_text(
+                    &mut page,
+                    font,
+                    scale,
+                    cell_x,
+                    cell_y
+                        + options.thumbnail_size
+                        + CAPTION_PADDING
+                        + line_index as u32 * line_height,
+                    &text,
+                );
+            }
+        }
+    }
+
+    Ok(page)
+}
+
+fn write_contact_sheets(
+    collection_path: &Path,
+    output_folder: &Path,
+    layout: Option<&SpritesheetLayout>,
+    options: &ContactSheetOptions,
+    resizer: &ThumbnailResizer,
+) -> Result<ContactSheetResult> {
+    let mut items = collect_items(collection_path)?;
+    if items.is_empty() {
+        return Err(anyhow::anyhow!(
+            "No images found in {}",
+            collection_path.join("images").display()
+        ));
+    }
+    sort_items(&mut items, &options.sort);
+
+    if CAPTION_FONT.is_none() && (options.show_edition || options.show_traits) {
+        tracing::warn!("⚠️ [CONTACT SHEET] Caption font could not be loaded, captions skipped");
+    }
+    let caption_rows = items
+        .iter()
+        .map(|item| caption_lines(item, options).len() as u32)
+        .max()
+        .unwrap_or(0);
+
+    fs::create_dir_all(output_folder)?;
+    let per_page = options.rows.checked_mul(options.cols).with_context(|| {
+        format!(
+            "{} rows of {} columns do not fit on one page",
+            options.rows, options.cols
+        )
+    })? as usize;
+    let page_count = items.len().div_ceil(per_page);
+    let mut pages = Vec::with_capacity(page_count);
+    let mut skipped = Vec::new();
+
+    for (page_index, page_items) in items.chunks(per_page).enumerate() {
+        let thumbnails: Vec<Result<RgbaImage>> = page_items
+            .par_iter()
+            .map(|item| {
+                let frame = load_first_frame(item, collection_path, layout)?;
+                let (width, height) =
+                    fit_dimensions(frame.width(), frame.height(), options.thumbnail_size);
+                Ok(resizer.resize(&frame, width, height))
+            })
+            .collect();
+
+        let thumbnails = thumbnails
+            .into_iter()
+            .zip(page_items)
+            .map(|(thumbnail, item)| {
+                thumbnail
+                    .map_err(|e| {
+                        tracing::warn!(
+                            "⚠️ [CONTACT SHEET] Skipping {}: {}",
+                            item.image_path.display(),
+                            e
+                        );
+                        skipped.push(item.image_path.to_string_lossy().to_string());
+                    })
+                    .ok()
+            })
+            .collect();
+
+        let page = render_page(page_items, thumbnails, options, caption_rows)?;
+        let page_path = output_folder.join(format!("contact_sheet_{:03}.png", page_index + 1));
+        page.save(&page_path)
+            .with_context(|| format!("Failed to save {}", page_path.display()))?;
+
+        tracing::info!(
+            "🗂️ [CONTACT SHEET] Page {}/{} written to {}",
+            page_index + 1,
+            page_count,
+            page_path.display()
+        );
+        pages.push(page_path.to_string_lossy().to_string());
+    }
+
+    Ok(ContactSheetResult {
+        pages,
+        items: items.len(),
+        skipped,
+    })
+}
+
+/// Lays the generated items out on paginated review grids.
+#[tauri::command]
+pub async fn export_contact_sheets(
+    registry: State<'_, ProjectRegistry>,
+    export_folder: String,
+    options: Option<ContactSheetOptions>,
+) -> Result<ContactSheetResult, String> {
+    let options = options.unwrap_or_default();
+    if options.rows == 0 || options.cols == 0 || options.thumbnail_size == 0 {
+        return Err("Rows, columns and thumbnail size must be greater than zero".to_string());
+    }
+
+    let export_path = PathBuf::from(&export_folder);
+    let collection_path = export_path.join("collection");
+    let output_folder = options
+        .output_folder
+        .as_ref()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| export_path.join("contact_sheets"));
+    let layout = load_projectsetup_state(registry)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|state| state.spritesheet_layout);
+    let resizer = ThumbnailResizer::new().await;
+
+    tokio::task::spawn_blocking(move || {
+        write_contact_sheets(
+            &collection_path,
+            &output_folder,
+            layout.as_ref(),
+            &options,
+            &resizer,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
@@ -1,19 +1,40 @@
 use std::{
+    fs,
     path::{Path, PathBuf},
-    time::{Duration, Instant},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime},
 };
 
 use anyhow::Result;
 use dashmap::DashMap;
+use image::DynamicImage;
 use once_cell::sync::Lazy;
 use walkdir::WalkDir;
 
+use crate::{
+    effects::core::transform::trim::TrimmedImage,
+    filesystem::{trait_files::scan_trait_files, utils::normalize_path},
+};
+
 static LAYER_FILES_CACHE: Lazy<DashMap<String, (Vec<String>, Instant)>> =
     Lazy::new(|| DashMap::new());
 
 static SPRITESHEET_PATHS_CACHE: Lazy<DashMap<String, (Vec<PathBuf>, Instant)>> =
     Lazy::new(|| DashMap::new());
 
+/// Trait images cropped to their visible pixels, `None` for fully transparent
+/// ones. Entries are dropped when the file changes on disk.
+static TRIMMED_TRAITS_CACHE: Lazy<DashMap<PathBuf, (Option<Arc<TrimmedImage>>, SystemTime)>> =
+    Lazy::new(|| DashMap::new());
+
+static TRIMMED_TRAITS_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// Past this, trimmed traits are still returned but no longer kept.
+const TRIMMED_TRAITS_BUDGET_BYTES: usize = 1024 * 1024 * 1024;
+
 const CACHE_DURATION: Duration = Duration::from_secs(300);
 
 fn is_cache_valid(timestamp: &Instant) -> bool {
@@ -23,6 +44,36 @@ fn is_cache_valid(timestamp: &Instant) -> bool {
 pub fn clear_generation_files_caches() {
     LAYER_FILES_CACHE.clear();
     SPRITESHEET_PATHS_CACHE.clear();
+    TRIMMED_TRAITS_CACHE.clear();
+    TRIMMED_TRAITS_BYTES.store(0, Ordering::Relaxed);
+}
+
+/// Trait image trimmed to its alpha bounding box, decoded with `decode` the
+/// first time and whenever the file changes.
+pub fn get_trimmed_trait_cached<F>(path: &Path, decode: F) -> Result<Option<Arc<TrimmedImage>>>
+where
+    F: FnOnce(&Path) -> Result<DynamicImage>,
+{
+    let modified = fs::metadata(path)?.modified()?;
+
+    if let Some(entry) = TRIMMED_TRAITS_CACHE.get(path) {
+        let (trimmed, cached_modified) = entry.value();
+        if *cached_modified == modified {
+            return Ok(trimmed.clone());
+        }
+    }
+
+    let trimmed = TrimmedImage::new(&decode(path)?.to_rgba8()).map(Arc::new);
+    let size = trimmed.as_ref().map_or(0, |t| t.byte_size());
+
+    if TRIMMED_TRAITS_BYTES.load(Ordering::Relaxed) + size <= TRIMMED_TRAITS_BUDGET_BYTES {
+        let previous = TRIMMED_TRAITS_CACHE.insert(path.to_path_buf(), (trimmed.clone(), modified));
+        let previous_size = previous.and_then(|(t, _)| t).map_or(0, |t| t.byte_size());
+        TRIMMED_TRAITS_BYTES.fetch_add(size, Ordering::Relaxed);
+        TRIMMED_TRAITS_BYTES.fetch_sub(previous_size, Ordering::Relaxed);
+    }
+
+    Ok(trimmed)
 }
 
 pub fn get_layer_files_cached(
@@ -95,46 +146,35 @@ pub fn get_trait_spritesheets_cached(
     Ok(paths)
 }
 
+/// Returns trait file keys relative to the layer folder, e.g. `Rare/Crown#Gold`.
 fn scan_layer_directory(layer_path: &Path, is_animated: bool) -> Result<Vec<String>> {
-    use walkdir::WalkDir;
-
-    let mut files = Vec::new();
-
     if is_animated {
-        let mut entries = WalkDir::new(layer_path)
+        let files = WalkDir::new(layer_path)
+            .min_depth(1)
             .into_iter()
             .filter_map(|e| e.ok())
             .filter(|e| e.file_type().is_dir())
-            .filter(|e| {
-                let spritesheet_path = e.path().join("spritesheet_0.png");
-                spritesheet_path.exists()
-            });
-
-        while let Some(entry) = entries.next() {
-            let dir_name = entry.file_name().to_string_lossy().into_owned();
-            files.push(dir_name);
-        }
-    } else {
-        let mut entries = WalkDir::new(layer_path)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .filter(|e| e.file_type().is_file())
-            .filter(|e| {
+            .filter(|e| e.path().join("spritesheet_0.png").exists())
+            .filter_map(|e| {
                 e.path()
-                    .extension()
-                    .and_then(|ext| ext.to_str())
-                    .map_or(false, |ext| {
-                        let ext = ext.to_lowercase();
-                        ext == "png" || ext == "webp"
-                    })
-            });
-
-        while let Some(entry) = entries.next() {
-            let file_name = entry.file_name().to_string_lossy().into_owned();
-            files.push(file_name);
-        }
+                    .strip_prefix(layer_path)
+                    .ok()
+                    .map(|relative| normalize_path(&relative.to_string_lossy()))
+            })
+            .collect();
+
+        return Ok(files);
     }
 
+    let files = scan_trait_files(layer_path)
+        .into_iter()
+        .filter(|file| {
+            let relative_path = file.relative_path.to_lowercase();
+            relative_path.ends_with(".png") || relative_path.ends_with(".webp")
+        })
+        .map(|file| file.key)
+        .collect();
+
     Ok(files)
 }
 
@@ -0,0 +1,139 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::{fs, path::Path, time::Duration};
+
+use crate::types::{FailureMode, FailurePolicy, NFTTrait};
+
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FailedItem {
+    /// Edition number of the item, as in its file names.
+    pub index: u32,
+    pub set_id: String,
+    /// Traits of the last failed attempt, empty when selection itself failed.
+    pub traits: Vec<NFTTrait>,
+    pub error_chain: Vec<String>,
+    pub attempts: u32,
+    /// Whether a later attempt produced the item.
+    pub recovered: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailureReport<'a> {
+    pub policy: &'a FailurePolicy,
+    pub failed: usize,
+    pub recovered: usize,
+    pub items: &'a [FailedItem],
+}
+
+impl FailedItem {
+    pub fn new(
+        index: u32,
+        set_id: &str,
+        traits: Vec<NFTTrait>,
+        error: &anyhow::Error,
+        attempts: u32,
+    ) -> Self {
+        Self {
+            index,
+            set_id: set_id.to_string(),
+            traits,
+            error_chain: error.chain().map(|cause| cause.to_string()).collect(),
+            attempts,
+            recovered: false,
+        }
+    }
+}
+
+pub fn max_attempts(policy: &FailurePolicy) -> u32 {
+    match policy.mode {
+        FailureMode::FailFast => 1,
+        FailureMode::Retry | FailureMode::Replace => policy.max_attempts.max(1),
+    }
+}
+
+/// Wait before `attempt`, doubling from `backoff_ms` after the second one.
+pub fn retry_delay(policy: &FailurePolicy, attempt: u32) -> Duration {
+    let doublings = attempt.saturating_sub(2).min(16);
+    Duration::from_millis(policy.backoff_ms.saturating_mul(1 << doublings)).min(MAX_BACKOFF)
+}
+
+pub fn write_failure_report(
+    collection_folder: &Path,
+    policy: &FailurePolicy,
+    items: &[FailedItem],
+) -> Result<()> {
+    let info_folder = collection_folder.join("collection infos");
+    fs::create_dir_all(&info_folder)?;
+
+    let recovered = items.iter().filter(|item| item.recovered).count();
+    let report = FailureReport {
+        policy,
+        failed: items.len() - recovered,
+        recovered,
+        items,
+    };
+
+    let path = info_folder.join("failures.json");
+    fs::write(&path, serde_json::to_string_pretty(&report)?)?;
+    tracing::info!(
+        "📝 [FAILURES] Wrote {} failed and {} recovered item(s) to {}",
+        report.failed,
+        recovered,
+        path.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(mode: FailureMode, max_attempts: u32, backoff_ms: u64) -> FailurePolicy {
+        FailurePolicy {
+            mode,
+            max_attempts,
+            backoff_ms,
+        }
+    }
+
+    #[test]
+    fn fail_fast_makes_a_single_attempt() {
+        assert_eq!(max_attempts(&policy(FailureMode::FailFast, 5, 0)), 1);
+        assert_eq!(max_attempts(&policy(FailureMode::Retry, 5, 0)), 5);
+        assert_eq!(max_attempts(&policy(FailureMode::Replace, 5, 0)), 5);
+        assert_eq!(max_attempts(&policy(FailureMode::Retry, 0, 0)), 1);
+    }
+
+    #[test]
+    fn retry_delay_doubles_after_the_second_attempt() {
+        let policy = policy(FailureMode::Retry, 5, 500);
+
+        let delays: Vec<u64> = (2..=5)
+            .map(|attempt| retry_delay(&policy, attempt).as_millis() as u64)
+            .collect();
+        assert_eq!(delays, [500, 1000, 2000, 4000]);
+    }
+
+    #[test]
+    fn retry_delay_is_capped() {
+        assert_eq!(
+            retry_delay(&policy(FailureMode::Retry, 50, 500), 40),
+            MAX_BACKOFF
+        );
+        assert_eq!(
+            retry_delay(&policy(FailureMode::Retry, 3, u64::MAX), 3),
+            MAX_BACKOFF
+        );
+    }
+
+    #[test]
+    fn missing_fields_use_the_default_policy() {
+        let parsed: FailurePolicy = serde_json::from_str("{}").unwrap();
+        assert_eq!(parsed, FailurePolicy::default());
+        assert_eq!(parsed.mode, FailureMode::Retry);
+    }
+}
@@ -1,48 +1,73 @@
 use anyhow::Result;
 use dashmap::DashMap;
 use futures::future::join_all;
+use parking_lot::Mutex;
 use rayon::prelude::*;
 use std::{
     collections::{HashMap, HashSet},
     env, fs,
-    path::PathBuf,
-    sync::Arc,
+    path::{Path, PathBuf},
+    sync::{atomic::Ordering, Arc},
     time::Duration,
     time::Instant,
 };
+use sysinfo::System;
 use tauri::Window;
+use tokio::time::sleep;
 use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 use walkdir::WalkDir;
 
 use crate::{
     effects::core::gpu::{blend_modes_gpu::GpuBlendContext, resize_gpu::ResizeConfig},
+    filesystem::projects::ProjectDirs,
     generation::{
         clean_up_contexts::cleanup_all_global_contexts,
         generate::{
+            failures::{max_attempts, retry_delay, write_failure_report, FailedItem},
             generate_single::{
                 generate_single_artwork::generate_single_artwork,
+                generate_traits::generate_traits_and_validate,
+                progress::{
+                    image_path, send_generation_progress, send_phase_progress, GenerationPhase,
+                },
                 static_single::get_or_init_shared_gpu_pipeline,
             },
-            layers::traits_selection::precompute_incompatibilities,
+            layers::{
+                traits_selection::precompute_incompatibilities,
+                unicity::{generate_dna, reserve_dna},
+            },
             metadata::{create_global::create_global_metadata, create_single::Blockchain},
-            pausecancel::{check_cancelled, set_export_folder_path, wait_for_pause},
+            pausecancel::GenerationSession,
             rarity::{calculate_image_rarity, create_rarity_files},
+            run_report::{
+                plan_editions, read_partial_report, remove_partial_report, resume_plan,
+                spawn_metrics_ticker, write_partial_report, write_run_report, ResumePlan,
+                RunMetrics, SetReport,
+            },
             shuffle::shuffle_and_rename,
+            staging::remove_partial_files,
             task_manager::{
-                create_generation_session, get_semaphore_info, get_system_info,
-                spawn_generation_task, MetricsUtils, PerformanceMetrics,
+                budget::RunBudget, configure_run_budget, get_semaphore_info, get_system_info,
+                spawn_generation_task, Admission, ItemMemoryProfile, MetricsUtils,
+                PerformanceMetrics, Stage,
             },
         },
         generation_main::GenerationPaths,
+        package::{write_generation_record, write_package},
     },
+    layerpreview::animations::{animated_frames_dir, timeline::AnimationTimeline},
     types::{
-        AnimationQualityConfig, ForcedCombinations, ForcedCombinationsBySets, GenerationResult,
-        Incompatibilities, IncompatibilitiesBySets, NFTGenerationArgs, NFTTrait, OrderedLayersSets,
-        RarityConfig, SolanaMetadataConfig, SpritesheetLayout,
+        AnimationQualityConfig, FailureMode, FailurePolicy, ForcedCombinations,
+        ForcedCombinationsBySets, GenerationResult, Incompatibilities, IncompatibilitiesBySets,
+        NFTGenerationArgs, NFTTrait, OrderedLayersSets, RarityConfig, SolanaMetadataConfig,
+        SpritesheetLayout,
     },
 };
 
-fn precompute_rarity_cache(rarity_config: &RarityConfig) -> HashMap<String, HashMap<String, bool>> {
+pub fn precompute_rarity_cache(
+    rarity_config: &RarityConfig,
+) -> HashMap<String, HashMap<String, bool>> {
     let mut cache = HashMap::new();
 
     for (layer_name, layer_config) in &rarity_config.layers {
@@ -60,6 +85,38 @@ fn precompute_rarity_cache(rarity_config: &RarityConfig) -> HashMap<String, Hash
     cache
 }
 
+/// Layers of a set that are switched on in the rarity config, in set order.
+pub fn active_layers_for_set(
+    layers: &[String],
+    rarity_config: &RarityConfig,
+    set_id: &str,
+) -> Vec<String> {
+    layers
+        .iter()
+        .filter(|layer| {
+            rarity_config
+                .layers
+                .get(*layer)
+                .and_then(|layer_config| layer_config.sets.get(set_id))
+                .map(|set_config| set_config.active)
+                .unwrap_or(false)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Items the stopped run kept, by set, and the editions each set left missing.
+fn resumed_items(collection: &Path, ordered_layers_sets: &OrderedLayersSets) -> Result<ResumePlan> {
+    let (kept, planned) = resume_plan(read_partial_report(collection)?, ordered_layers_sets)?;
+
+    tracing::info!(
+        "⏯️ [RESUME] {} items kept, generating the {} missing",
+        kept.values().map(Vec::len).sum::<usize>(),
+        planned.values().map(Vec::len).sum::<usize>()
+    );
+    Ok((kept, planned))
+}
+
 #[derive(Clone, Debug)]
 pub struct GlobalGenerationCaches {
     pub incompatibility_maps: Arc<HashMap<String, HashMap<String, HashSet<String>>>>,
@@ -81,7 +138,6 @@ impl Drop for GlobalGenerationCaches {
 
 #[derive(Clone)]
 pub struct WorkerParamsArc {
-    pub global_index: u32,
     pub rarity_config: Arc<RarityConfig>,
     pub incompatibility_map: Arc<HashMap<String, HashSet<String>>>,
     pub set_forced_combinations: Arc<ForcedCombinations>,
@@ -98,6 +154,7 @@ pub struct WorkerParamsArc {
     pub animation_quality: Option<Arc<AnimationQualityConfig>>,
     pub resize_config: Option<Arc<ResizeConfig>>,
     pub spritesheet_layout: Option<Arc<SpritesheetLayout>>,
+    pub frame_durations: Option<Arc<Vec<u32>>>,
     pub include_rarity: bool,
     pub final_width: u32,
     pub final_height: u32,
@@ -110,8 +167,10 @@ pub struct WorkerParamsArc {
     pub total_frames_count: u32,
     pub window: Window,
     pub global_caches: GlobalGenerationCaches,
+    pub run_metrics: Arc<RunMetrics>,
     pub base_width: u32,
     pub base_height: u32,
+    pub session: Arc<GenerationSession>,
 }
 
 pub async fn generate_nfts(
@@ -123,6 +182,8 @@ pub async fn generate_nfts(
     forced_combinations_by_sets: &ForcedCombinationsBySets,
     blockchain: &Blockchain,
     window: &tauri::Window,
+    session: &Arc<GenerationSession>,
+    project_dirs: &ProjectDirs,
 ) -> Result<(bool, String)> {
     println!("🚀 [DEBUG] Starting generate_nfts");
 
@@ -149,34 +210,34 @@ pub async fn generate_nfts(
             .and_then(|name| name.to_str())
             .ok_or_else(|| anyhow::anyhow!("Invalid input folder name"))?;
 
-        let app_data_dir = if cfg!(windows) {
-            dirs::data_dir()
-                .ok_or_else(|| anyhow::anyhow!("Could not find app data directory"))?
-                .join("com.pxlsyllab.blendgine")
-        } else if cfg!(target_os = "macos") {
-            dirs::home_dir()
-                .ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?
-                .join("Library")
-                .join("Application Support")
-                .join("com.pxlsyllab.blendgine")
-        } else {
-            dirs::home_dir()
-                .ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?
-                .join(".config")
-                .join("com.pxlsyllab.blendgine")
-        };
-
-        let spritesheets_path = app_data_dir
-            .join("animated")
-            .join(last_folder)
+        let spritesheets_path = animated_frames_dir(&project_dirs.cache, last_folder)
+            .map_err(|e| anyhow::anyhow!(e))?
             .join("spritesheets");
 
         fs::create_dir_all(&spritesheets_path)?;
         working_folder = spritesheets_path;
     }
 
+    let frame_durations = working_folder
+        .parent()
+        .filter(|_| args.is_animated_collection)
+        .and_then(AnimationTimeline::load)
+        .filter(|timeline| {
+            timeline.is_variable() && Some(timeline.frame_count()) == args.total_frames_count
+        })
+        .map(|timeline| {
+            tracing::info!(
+                "⏱️ Using variable frame timing: {} frames over {} ms",
+                timeline.frame_count(),
+                timeline.loop_duration_ms()
+            );
+            Arc::new(timeline.frame_durations_ms)
+        });
+
     let mut all_generated_nfts: Vec<GenerationResult> = Vec::new();
     let mut all_traits: Vec<Vec<NFTTrait>> = Vec::new();
+    let mut items_by_set: HashMap<String, Vec<GenerationResult>> = HashMap::new();
+    let mut failures: Vec<FailedItem> = Vec::new();
     let mut global_index: u32 = 0;
     let mut total_to_generate: u32 = 0;
 
@@ -194,12 +255,20 @@ pub async fn generate_nfts(
         total_to_generate += set_config.nft_count;
     }
 
-    if let Err(_) = check_cancelled().await {
+    if session.check_cancelled().is_err() {
         return Ok((false, "Generation cancelled by user".to_string()));
     }
 
-    set_export_folder_path(paths.export.to_path_buf());
+    // A resumed run keeps the items of the stopped one and each set only
+    // generates the editions it left missing.
+    let (mut kept_by_set, planned) = if args.resume {
+        resumed_items(&paths.collection, ordered_layers_sets)?
+    } else {
+        (HashMap::new(), plan_editions(ordered_layers_sets))
+    };
+    let resumed_count = kept_by_set.values().map(Vec::len).sum::<usize>() as u32;
 
+    let budget = configure_run_budget(&args.resource_limits);
     println!("{}", get_system_info());
 
     let default_incompatibilities = Incompatibilities::default();
@@ -252,36 +321,45 @@ pub async fn generate_nfts(
         file_lookup_cache: Arc::new(DashMap::new()),
     };
 
+    let run_metrics = Arc::new(RunMetrics::new(total_to_generate, resumed_count));
+    let _metrics_ticker = spawn_metrics_ticker(window.clone(), run_metrics.clone());
+    let mut set_reports: Vec<SetReport> = Vec::new();
+    let mut sys = System::new();
+
     for (set_id, set_config) in ordered_layers_sets {
-        wait_for_pause().await?;
-        check_cancelled().await?;
+        session.wait_for_pause().await?;
+        session.check_cancelled()?;
+        if session.is_stop_requested() {
+            break;
+        }
 
-        let active_layer_order: Vec<_> = set_config
-            .layers
-            .iter()
-            .filter(|layer| {
-                rarity_config
-                    .layers
-                    .get(*layer)
-                    .and_then(|layer_config| layer_config.sets.get::<str>(set_id))
-                    .map(|set_config| set_config.active)
-                    .unwrap_or(false)
-            })
-            .cloned()
-            .collect();
+        let active_layer_order = active_layers_for_set(&set_config.layers, &rarity_config, set_id);
+
+        let kept = kept_by_set.remove(set_id).unwrap_or_default();
+        for nft in &kept {
+            reserve_dna(generate_dna(
+                &nft.traits,
+                rarity_config,
+                set_id,
+                &active_layer_order,
+            ));
+        }
 
         let mut set_completed = 0;
 
         let nft_count = set_config.nft_count as usize;
+        let indices: Vec<u32> = planned
+            .get(set_id)
+            .map(|editions| editions.iter().map(|edition| edition - 1).collect())
+            .unwrap_or_default();
 
         tracing::info!(
             "🎯 [TOKIO_NATIVE] Set '{}': {} NFT tasks - Tokio optimizes automatically",
             set_id,
-            nft_count
+            indices.len()
         );
 
         let worker_params_arc = WorkerParamsArc {
-            global_index,
             rarity_config: Arc::new(rarity_config.clone()),
             incompatibility_map: Arc::new(
                 global_caches
@@ -313,6 +391,7 @@ pub async fn generate_nfts(
                 .spritesheet_layout
                 .as_ref()
                 .map(|c| Arc::new(c.clone())),
+            frame_durations: frame_durations.clone(),
             include_rarity: args.include_rarity,
             final_width: args.final_width,
             final_height: args.final_height,
@@ -327,88 +406,199 @@ pub async fn generate_nfts(
                 .expect("total_frames_count should be Some for animated collections"),
             window: window.clone(),
             global_caches: global_caches.clone(),
+            run_metrics: run_metrics.clone(),
             base_width: args.base_width,
             base_height: args.base_height,
+            session: session.clone(),
         };
 
-        let session_token = create_generation_session().await;
-        let pool_results = generate_nfts_with_tokio_native(
+        let set_start = Instant::now();
+        let (pool_results, set_failures) = generate_nfts_with_tokio_native(
             worker_params_arc.clone(),
-            set_config.nft_count as usize,
-            session_token,
+            &indices,
+            &args.failure_policy,
+            budget.clone(),
+            session.token().child_token(),
         )
         .await?;
-        let pool_metrics = PerformanceMetrics::new_generation(
-            nft_count as u32,
-            start_time.elapsed(),
-            0,
-            0.0,
-            0.0,
-            Duration::from_secs(0),
-            0,
-            set_config.nft_count as u32,
-        );
 
-        wait_for_pause().await?;
-        check_cancelled().await?;
+        failures.extend(set_failures);
+        let failed = failures.iter().filter(|item| !item.recovered).count();
+        if failed > 0 {
+            let error = format!(
+                "{} item(s) in set \"{}\" could not be generated, see collection infos/failures.json",
+                failed, set_id
+            );
+            write_failure_report(&paths.collection, &args.failure_policy, &failures)?;
+            write_run_report(
+                &paths.collection,
+                &run_metrics.report(set_reports, &failures, Some(error.clone())),
+            )?;
+            return Err(anyhow::anyhow!(error));
+        }
+
+        session.wait_for_pause().await?;
+        session.check_cancelled()?;
 
-        for nft in &pool_results {
+        let generated = pool_results.len() as u32;
+        let mut set_items = kept;
+        set_items.extend(pool_results);
+        for nft in &set_items {
             all_generated_nfts.push(nft.clone());
             set_completed += 1;
         }
 
         global_index += set_completed;
 
+        let set_duration = set_start.elapsed();
+        let snapshot = run_metrics.snapshot(&mut sys);
+        set_reports.push(SetReport {
+            set_id: set_id.to_string(),
+            count: set_completed,
+            duration_ms: set_duration.as_millis() as u64,
+            items_per_second: MetricsUtils::calculate_throughput(generated, set_duration),
+        });
+
         let metrics = PerformanceMetrics::new_generation(
-            nft_count as u32,
-            pool_metrics.execution_time,
-            pool_metrics.memory_usage,
-            pool_metrics.cpu_usage,
-            pool_metrics.throughput,
+            budget.generation_workers,
+            set_duration,
+            snapshot.ram_mb * 1024 * 1024,
+            snapshot.cpu_usage,
+            MetricsUtils::calculate_throughput(generated, set_duration),
             Duration::from_millis(0),
-            0,
-            set_completed as u32,
+            (nft_count as u32).saturating_sub(set_completed),
+            set_completed,
         );
 
         MetricsUtils::display_metrics(&metrics);
 
-        all_traits.extend(pool_results.iter().map(|nft| nft.traits.clone()));
+        all_traits.extend(set_items.iter().map(|nft| nft.traits.clone()));
+        items_by_set.insert(set_id.clone(), set_items);
 
         println!("🌍 [DEBUG] Global index updated: {}", global_index);
     }
 
-    if args.shuffle_sets {
+    let removed = remove_partial_files(&paths.collection);
+    if removed > 0 {
+        tracing::info!("🧹 [STAGING] Removed {} unfinished file(s)", removed);
+    }
+
+    // Sets the run did not reach keep the items of the run it resumed.
+    items_by_set.extend(kept_by_set);
+    let kept_count = items_by_set.values().map(Vec::len).sum::<usize>() as u32;
+
+    // A stop that came after the last item no longer changes the run.
+    if session.is_stop_requested() && kept_count < total_to_generate {
+        let message = format!(
+            "Generation stopped, {} of {} items kept",
+            kept_count, total_to_generate
+        );
+
+        if !failures.is_empty() {
+            write_failure_report(&paths.collection, &args.failure_policy, &failures)?;
+        }
+        create_global_metadata(
+            &paths.metadata,
+            &args.collection_name,
+            &args.collection_description,
+        )?;
+        write_partial_report(&paths.collection, &planned, items_by_set, total_to_generate)?;
+        write_generation_record(&paths.collection, args, None)?;
+        write_run_report(
+            &paths.collection,
+            &run_metrics.report(set_reports, &failures, Some(message.clone())),
+        )?;
+        send_phase_progress(
+            window,
+            GenerationPhase::Complete,
+            kept_count,
+            total_to_generate,
+        );
+
+        cleanup_all_global_contexts().await;
+        tracing::info!("🛑 [STOP] {}", message);
+        return Ok((false, message));
+    }
+
+    // Recorded with the collection so the shuffle can be reproduced.
+    let shuffle_seed = args.shuffle_sets.then(rand::random::<u64>);
+
+    if let Some(seed) = shuffle_seed {
+        let shuffle_total = all_generated_nfts.len() as u32;
+        send_phase_progress(window, GenerationPhase::Shuffling, 0, shuffle_total);
         shuffle_and_rename(
             &paths.export,
             &args.collection_name,
             &args.image_format,
             &mut all_generated_nfts,
             args.include_spritesheets,
+            seed,
+            &mut |edition, nft, final_path| {
+                let _ = send_generation_progress(
+                    window,
+                    &nft.traits,
+                    &args.collection_name,
+                    &args.image_format,
+                    final_path,
+                    edition,
+                    edition,
+                    shuffle_total,
+                    shuffle_total,
+                    GenerationPhase::Shuffling,
+                    None,
+                );
+            },
         )?;
+        send_phase_progress(
+            window,
+            GenerationPhase::Shuffling,
+            shuffle_total,
+            shuffle_total,
+        );
+
+        let editions: HashMap<u32, u32> = all_generated_nfts
+            .iter()
+            .enumerate()
+            .map(|(i, nft)| (nft.original_index, i as u32 + 1))
+            .collect();
+        for item in &mut failures {
+            if let Some(&edition) = editions.get(&item.index) {
+                item.index = edition;
+            }
+        }
     }
 
+    if !failures.is_empty() {
+        write_failure_report(&paths.collection, &args.failure_policy, &failures)?;
+    }
+
+    send_phase_progress(window, GenerationPhase::GlobalMetadata, 0, 1);
     create_global_metadata(
         &paths.metadata,
         &args.collection_name,
         &args.collection_description,
     )?;
+    send_phase_progress(window, GenerationPhase::GlobalMetadata, 1, 1);
 
     if args.include_rarity {
         let collection_info_path = paths.collection.join("collection infos");
         fs::create_dir_all(&collection_info_path)?;
 
+        send_phase_progress(window, GenerationPhase::RarityFiles, 0, 2);
         create_rarity_files(
             &paths.metadata,
             &collection_info_path.to_string_lossy(),
             global_index as usize,
             &rarity_config,
         )?;
+        send_phase_progress(window, GenerationPhase::RarityFiles, 1, 2);
 
         calculate_image_rarity(
             &paths.metadata,
             &collection_info_path.to_string_lossy(),
             global_index as usize,
         )?;
+        send_phase_progress(window, GenerationPhase::RarityFiles, 2, 2);
     }
 
     if global_index != total_to_generate {
@@ -433,27 +623,58 @@ pub async fn generate_nfts(
     );
 
     let total_generation_time = start_time.elapsed();
-    let (final_cpu, final_memory) = MetricsUtils::measure_system_performance();
+    let snapshot = run_metrics.snapshot(&mut sys);
+    let final_memory = snapshot.ram_mb * 1024 * 1024;
 
-    let generation_metrics = MetricsUtils::create_generation_metrics(
-        global_index,
+    let generation_metrics = PerformanceMetrics::new_generation(
+        budget.generation_workers,
         total_generation_time,
-        0,
         final_memory,
-        0.0,
-        final_cpu,
-        0,
+        snapshot.cpu_usage,
+        MetricsUtils::calculate_throughput(global_index, total_generation_time),
+        Duration::from_millis(0),
+        snapshot.failed,
         global_index,
     );
 
     MetricsUtils::display_metrics(&generation_metrics);
 
     MetricsUtils::display_generation_metrics(
-        global_index,
+        budget.generation_workers,
         total_generation_time,
         final_memory,
-        final_cpu,
-        MetricsUtils::calculate_throughput(global_index, total_generation_time),
+        snapshot.cpu_usage,
+        generation_metrics.throughput,
+        global_index,
+    );
+
+    write_run_report(
+        &paths.collection,
+        &run_metrics.report(set_reports, &failures, None),
+    )?;
+    write_generation_record(&paths.collection, args, shuffle_seed)?;
+    remove_partial_report(&paths.collection);
+
+    if args.packaging.enabled {
+        let package = write_package(
+            &paths.collection,
+            &args.packaging,
+            &mut |completed, total| {
+                send_phase_progress(window, GenerationPhase::Packaging, completed, total)
+            },
+        )?;
+        tracing::info!(
+            "📦 [PACKAGE] {} files ({} bytes) packaged in {} archive(s)",
+            package.files,
+            package.total_size,
+            package.archives.len()
+        );
+    }
+
+    send_phase_progress(
+        window,
+        GenerationPhase::Complete,
+        global_index,
         global_index,
     );
 
@@ -462,26 +683,56 @@ pub async fn generate_nfts(
     Ok((true, "Generation succesful!".to_string()))
 }
 
+fn item_memory_profile(params: &WorkerParamsArc) -> ItemMemoryProfile {
+    let (frame_width, frame_height, total_frames) = match params.spritesheet_layout.as_deref() {
+        Some(layout) if params.is_animated_collection => {
+            (layout.frame_width, layout.frame_height, layout.total_frames)
+        }
+        _ => (params.base_width, params.base_height, 1),
+    };
+
+    let interpolation_factor = params
+        .animation_quality
+        .as_deref()
+        .and_then(|quality| quality.interpolation_for(&params.image_format))
+        .filter(|settings| settings.enabled)
+        .map(|settings| settings.factor)
+        .unwrap_or(0);
+
+    ItemMemoryProfile {
+        base_width: frame_width,
+        base_height: frame_height,
+        final_width: params.final_width,
+        final_height: params.final_height,
+        layer_count: params.active_layer_order.len() as u32,
+        total_frames,
+        interpolation_factor,
+        is_animated: params.is_animated_collection,
+    }
+}
+
+type Selection = Arc<Mutex<Option<(Vec<NFTTrait>, String)>>>;
+
+/// Generates the items at `indices`, one edition number lower than the
+/// items' file names.
 async fn generate_nfts_with_tokio_native(
     params: WorkerParamsArc,
-    nft_count: usize,
+    indices: &[u32],
+    failure_policy: &FailurePolicy,
+    budget: Arc<RunBudget>,
     session_token: CancellationToken,
-) -> Result<Vec<GenerationResult>> {
-    let _cancel_token = session_token;
-    let mut tasks = Vec::new();
-
+) -> Result<(Vec<GenerationResult>, Vec<FailedItem>)> {
     tracing::info!(
         "🚀 [TOKIO_NATIVE] Starting {} NFT generation tasks",
-        nft_count
+        indices.len()
     );
 
     tracing::info!("{}", get_semaphore_info());
 
-    let num_cpus = num_cpus::get();
     tracing::info!(
         "🎯 [PARALLELISM] {} NFTs with max {} concurrent workers",
-        nft_count,
-        num_cpus
+        indices.len(),
+        budget.generation_workers
     );
 
     tracing::info!("🎮 [GPU INIT] Initializing global GPU contexts...");
@@ -500,52 +751,42 @@ async fn generate_nfts_with_tokio_native(
 
     tracing::info!("✅ [GPU INIT] Global GPU contexts initialized successfully");
 
-    for index in 0..nft_count {
-        let params_clone = params.clone();
-        let task_id = format!("generation_{}", index);
-
-        let task_handle = spawn_generation_task(task_id, move || async move {
-            let mut params_mut = params_clone.clone();
-            params_mut.global_index = index as u32;
-
-            generate_single_artwork(
-                params_mut.global_index,
-                &**params_mut.input_folder,
-                &**params_mut.export_folder,
-                &**params_mut.collection_name,
-                &**params_mut.collection_description,
-                params_mut.include_rarity,
-                &params_mut.rarity_config,
-                &params_mut.active_layer_order,
-                params_mut.base_width,
-                params_mut.base_height,
-                params_mut.final_width,
-                params_mut.final_height,
-                &**params_mut.image_format,
-                &params_mut.incompatibility_map,
-                &params_mut.set_forced_combinations,
-                params_mut.allow_duplicates,
-                &params_mut.set_id,
-                params_mut.total_to_generate,
-                params_mut.blockchain,
-                params_mut.is_animated_collection,
-                params_mut.include_spritesheets,
-                params_mut.sprites_path.as_ref().map(|p| &***p),
-                params_mut.fps,
-                params_mut.solana_config.as_deref(),
-                params_mut.animation_quality.as_deref(),
-                params_mut.resize_config.as_deref(),
-                Some(params_mut.total_frames_count),
-                params_mut.spritesheet_layout.as_deref(),
-                params_mut.working_folder.as_ref().map(|p| &***p),
-                &params_mut.window,
-                &params_mut.global_caches,
-            )
-            .await
-        })
-        .await?;
+    let profile = item_memory_profile(&params);
+    let (item_memory_mb, item_vram_mb) = (profile.estimate_mb(), profile.estimate_vram_mb());
+    tracing::info!(
+        "💾 [MEMORY] Estimated ~{} MB RAM and ~{} MB VRAM per NFT, admitting work against the budget",
+        item_memory_mb,
+        item_vram_mb
+    );
+
+    // Items are spawned as the budget admits them, so a large set never has
+    // more than a few items per worker in memory.
+    let mut tasks = Vec::with_capacity(indices.len());
+    for &index in indices {
+        // Pause and stop take effect here, between items. Admitted items
+        // always run to completion.
+        if params.session.wait_for_pause().await.is_err() || params.session.is_stop_requested() {
+            break;
+        }
+
+        let admission = tokio::select! {
+            admission = budget.admit(item_memory_mb, item_vram_mb) => admission?,
+            _ = session_token.cancelled() => break,
+        };
+        if params.session.is_stop_requested() {
+            break;
+        }
 
-        tasks.push(task_handle);
+        tasks.push(tokio::spawn(
+            generate_item(
+                params.clone(),
+                index,
+                admission,
+                failure_policy.clone(),
+                session_token.clone(),
+            )
+            .in_current_span(),
+        ));
     }
 
     tracing::info!(
@@ -556,20 +797,21 @@ async fn generate_nfts_with_tokio_native(
     let task_results = join_all(tasks).await;
 
     let mut successful_results = Vec::new();
+    let mut failures = Vec::new();
     let mut error_count = 0;
 
     for task_result in task_results {
         match task_result {
-            Ok(generation_result) => match generation_result {
-                Ok(Some(nft_result)) => successful_results.push(nft_result),
-                Ok(None) => {
-                    tracing::debug!("🔄 [TOKIO_NATIVE] NFT skipped (duplicate or filtered)");
+            Ok((generation_result, failure)) => {
+                failures.extend(failure);
+                match generation_result {
+                    Ok(nft_result) => successful_results.push(nft_result),
+                    Err(e) => {
+                        error_count += 1;
+                        tracing::error!("❌ [TOKIO_NATIVE] Generation error: {}", e);
+                    }
                 }
-                Err(e) => {
-                    error_count += 1;
-                    tracing::error!("❌ [TOKIO_NATIVE] Generation error: {}", e);
-                }
-            },
+            }
             Err(e) => {
                 error_count += 1;
                 tracing::error!("❌ [TOKIO_NATIVE] Task join error: {}", e);
@@ -590,7 +832,196 @@ async fn generate_nfts_with_tokio_native(
         );
     }
 
-    let results = successful_results;
+    Ok((successful_results, failures))
+}
+
+/// Generates the item at `index`, retrying it as `failure_policy` allows.
+/// Every attempt keeps the index, so a recovered item leaves no gap. An
+/// attempt that produces no image counts as failed for the same reason.
+async fn generate_item(
+    params: WorkerParamsArc,
+    index: u32,
+    _admission: Admission,
+    failure_policy: FailurePolicy,
+    session_token: CancellationToken,
+) -> (Result<GenerationResult>, Option<FailedItem>) {
+    let selection: Selection = Arc::new(Mutex::new(None));
+    let max_attempts = max_attempts(&failure_policy);
+    let mut failure: Option<FailedItem> = None;
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+        if failure_policy.mode == FailureMode::Replace {
+            *selection.lock() = None;
+        }
+
+        let attempt = run_item_attempt(&params, index, selection.clone(), &session_token).await;
+        let error = match attempt {
+            Ok(Some(nft)) => {
+                report_item_progress(&params, &nft);
+                if let Some(failure) = failure.as_mut() {
+                    failure.recovered = true;
+                    tracing::info!(
+                        "✅ [FAILURES] Item #{} generated on attempt {}",
+                        index + 1,
+                        attempt
+                    );
+                }
+                return (Ok(nft), failure);
+            }
+            Ok(None) => anyhow::anyhow!("No image was produced for item #{}", index + 1),
+            Err(e) => e,
+        };
+
+        // Cancelled by the user or by a fail-fast item, not a failure of this one.
+        if session_token.is_cancelled() {
+            return (Err(error), failure);
+        }
+
+        let traits = selection
+            .lock()
+            .as_ref()
+            .map(|(traits, _)| traits.clone())
+            .unwrap_or_default();
+        failure = Some(FailedItem::new(
+            index + 1,
+            &params.set_id,
+            traits,
+            &error,
+            attempt,
+        ));
+
+        if attempt >= max_attempts {
+            params.run_metrics.failed.fetch_add(1, Ordering::Relaxed);
+            tracing::error!(
+                "❌ [FAILURES] Item #{} failed after {} attempt(s): {:#}",
+                index + 1,
+                attempt,
+                error
+            );
+            if failure_policy.mode == FailureMode::FailFast {
+                session_token.cancel();
+            }
+            return (Err(error), failure);
+        }
+
+        let delay = retry_delay(&failure_policy, attempt + 1);
+        tracing::warn!(
+            "🔁 [FAILURES] Item #{} failed on attempt {}/{}, retrying in {:?}: {:#}",
+            index + 1,
+            attempt,
+            max_attempts,
+            delay,
+            error
+        );
+
+        tokio::select! {
+            _ = sleep(delay) => {}
+            _ = session_token.cancelled() => return (Err(error), failure),
+        }
+    }
+}
+
+fn report_item_progress(params: &WorkerParamsArc, nft: &GenerationResult) {
+    let completed = params.run_metrics.completed.fetch_add(1, Ordering::Relaxed) + 1;
+    let path = image_path(
+        &params.export_folder,
+        &params.collection_name,
+        nft.original_index,
+        &params.image_format,
+    );
+
+    if let Err(e) = send_generation_progress(
+        &params.window,
+        &nft.traits,
+        &params.collection_name,
+        &params.image_format,
+        &path,
+        nft.original_index,
+        completed,
+        params.total_to_generate,
+        params.run_metrics.estimated_count(),
+        GenerationPhase::Generating,
+        params.run_metrics.eta_ms(),
+    ) {
+        tracing::warn!("⚠️ [PROGRESS] Failed to send progress: {}", e);
+    }
+}
+
+/// Runs one attempt at `index`. Traits already in `selection` are rendered
+/// again, otherwise a new combination is selected and stored there.
+async fn run_item_attempt(
+    params: &WorkerParamsArc,
+    index: u32,
+    selection: Selection,
+    session_token: &CancellationToken,
+) -> Result<Option<GenerationResult>> {
+    // Pausing is handled before the item is admitted, an admitted item runs
+    // to completion.
+    params.session.check_cancelled()?;
+    let params = params.clone();
+    let task_id = format!("generation_{}", index);
+
+    let task_handle = spawn_generation_task(task_id, session_token.clone(), move || async move {
+        let selected = selection.lock().clone();
+        let (traits, dna) = match selected {
+            Some(selected) => selected,
+            None => {
+                let selection_start = Instant::now();
+                let selected = generate_traits_and_validate(
+                    &**params.input_folder,
+                    &params.active_layer_order,
+                    &params.rarity_config,
+                    &params.incompatibility_map,
+                    &params.set_forced_combinations,
+                    params.allow_duplicates,
+                    &params.set_id,
+                    params.is_animated_collection,
+                    params.working_folder.as_ref().map(|p| &***p),
+                    &params.global_caches,
+                )?;
+                MetricsUtils::record_stage(Stage::TraitSelection, selection_start.elapsed());
+                *selection.lock() = Some(selected.clone());
+                selected
+            }
+        };
+
+        generate_single_artwork(
+            index,
+            &traits,
+            &dna,
+            &**params.input_folder,
+            &**params.export_folder,
+            &**params.collection_name,
+            &**params.collection_description,
+            params.include_rarity,
+            &params.rarity_config,
+            &params.active_layer_order,
+            params.base_width,
+            params.base_height,
+            params.final_width,
+            params.final_height,
+            &**params.image_format,
+            &params.set_id,
+            params.blockchain,
+            params.is_animated_collection,
+            params.include_spritesheets,
+            params.sprites_path.as_ref().map(|p| &***p),
+            params.fps,
+            params.solana_config.as_deref(),
+            params.animation_quality.as_deref(),
+            params.resize_config.as_deref(),
+            Some(params.total_frames_count),
+            params.spritesheet_layout.as_deref(),
+            params.working_folder.as_ref().map(|p| &***p),
+            params.frame_durations.as_deref().map(|d| d.as_slice()),
+        )
+        .await
+    })
+    .await?;
 
-    Ok(results)
+    task_handle
+        .await
+        .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?
 }
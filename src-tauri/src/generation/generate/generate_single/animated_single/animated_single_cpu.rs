@@ -1,10 +1,13 @@
 use anyhow::Result;
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use crate::effects::core::{gpu::blend_modes_gpu::GpuBlendContext, gpu::resize_gpu::ResizeConfig};
 use crate::generation::generate::{
     generate_single::animated_single::spritesheets::{
-        frames::extract_and_process_all_frames, handle_spritesheets,
+        frames::collect_trait_spritesheets, spritesheet_blender::SpritesheetFrames,
     },
     layers::blend::LayerBlendProperties,
     save_animation::{save::structs::WorkerOptions, spawn_worker::spawn_animation_worker},
@@ -15,31 +18,17 @@ fn is_empty_trait(trait_value: &str) -> bool {
     trait_value == "None" || trait_value == "none" || trait_value.is_empty()
 }
 
-pub async fn process_animated_collection(
+/// Frames of a trait selection, blended from the trait spritesheets as they
+/// are read.
+pub fn compose_animated_frames(
     traits: &[NFTTrait],
     active_layer_order: &[String],
     input_folder: &Path,
     working_folder: Option<&Path>,
-    final_width: u32,
-    final_height: u32,
-    total_frames: u32,
     spritesheet_layout: &SpritesheetLayout,
     rarity_config: &RarityConfig,
     current_set_id: &str,
-    export_folder: &Path,
-    collection_name: &str,
-    image_format: &str,
-    fps: u32,
-    animation_quality: Option<&AnimationQualityConfig>,
-    resize_config: Option<&ResizeConfig>,
-    include_spritesheets: bool,
-    sprites_path: Option<&Path>,
-    index: u32,
-) -> Result<()> {
-    if total_frames == 0 {
-        return Ok(());
-    }
-
+) -> Result<SpritesheetFrames> {
     if GpuBlendContext::get_global().is_none() {
         return Err(anyhow::anyhow!(
             "GPU blend context not initialized for animated collection. GPU is required."
@@ -67,52 +56,63 @@ pub async fn process_animated_collection(
         }
     }
 
-    let (layer_frames, final_complete_spritesheet) = extract_and_process_all_frames(
+    let layers = collect_trait_spritesheets(
         active_layer_order,
         traits,
         input_folder,
         working_folder,
-        spritesheet_layout,
+        rarity_config,
         &blend_properties_cache,
     )?;
 
-    let composed_frames = layer_frames.values().next().unwrap().clone();
+    Ok(SpritesheetFrames::new(layers, spritesheet_layout))
+}
 
-    if composed_frames.len() != total_frames as usize {
+pub async fn process_animated_collection(
+    traits: &[NFTTrait],
+    active_layer_order: &[String],
+    input_folder: &Path,
+    working_folder: Option<&Path>,
+    final_width: u32,
+    final_height: u32,
+    total_frames: u32,
+    spritesheet_layout: &SpritesheetLayout,
+    rarity_config: &RarityConfig,
+    current_set_id: &str,
+    output_path: &Path,
+    image_format: &str,
+    fps: u32,
+    animation_quality: Option<&AnimationQualityConfig>,
+    resize_config: Option<&ResizeConfig>,
+    spritesheet_paths: &[PathBuf],
+    frame_durations_ms: Option<&[u32]>,
+) -> Result<()> {
+    if total_frames == 0 {
+        return Ok(());
+    }
+
+    let source = compose_animated_frames(
+        traits,
+        active_layer_order,
+        input_folder,
+        working_folder,
+        spritesheet_layout,
+        rarity_config,
+        current_set_id,
+    )?
+    .with_sheet_outputs(spritesheet_paths.to_vec());
+
+    if source.len() != total_frames as usize {
         return Err(anyhow::anyhow!(
             "Incorrect number of frames generated: expected {}, got {}",
             total_frames,
-            composed_frames.len()
+            source.len()
         ));
     }
 
-    let output_path = export_folder
-        .join("collection")
-        .join("images")
-        .join(format!(
-            "{}_{}.{}",
-            collection_name,
-            index + 1,
-            image_format
-        ));
     let delay = (1000.0 / fps as f32) as u32;
 
-    if include_spritesheets {
-        if let Some(sprites_path) = sprites_path {
-            let spritesheet_paths = handle_spritesheets(
-                &final_complete_spritesheet,
-                sprites_path,
-                collection_name,
-                index,
-            )?;
-            for path in spritesheet_paths {
-                println!("Final composed spritesheet generated: {}", path);
-            }
-        }
-    }
-
     let options = WorkerOptions {
-        frames: composed_frames,
         output_path: output_path.to_string_lossy().to_string(),
         width: final_width,
         height: final_height,
@@ -121,9 +121,12 @@ pub async fn process_animated_collection(
         format: Some(image_format.to_string()),
         quality_config: animation_quality.cloned(),
         resize_config: resize_config.cloned(),
+        frame_durations_ms: frame_durations_ms.map(|durations| durations.to_vec()),
     };
 
-    spawn_animation_worker(options).await?;
+    // Blending, resizing, encoding and writing the animation happen in the
+    // worker, one spritesheet at a time.
+    spawn_animation_worker(source, options).await?;
 
     Ok(())
 }
@@ -1,30 +1,22 @@
-use std::{fs::create_dir_all, path::Path};
+use std::{
+    fs::create_dir_all,
+    path::{Path, PathBuf},
+};
 
 use anyhow::Result;
-use image::DynamicImage;
+use image::RgbaImage;
 
-pub fn handle_spritesheets(
-    final_spritesheet: &DynamicImage,
-    sprites_path: &Path,
-    collection_name: &str,
-    index: u32,
-) -> Result<Vec<String>> {
-    let sprite_sheet_base_path = sprites_path.join((index + 1).to_string());
-    create_dir_all(&sprite_sheet_base_path)?;
-
-    let mut spritesheet_paths = Vec::new();
-    let sheet_index = 0;
-
-    let output_path = sprite_sheet_base_path.join(format!("spritesheet_{}.png", sheet_index));
-
-    final_spritesheet.save(&output_path)?;
+pub fn spritesheet_path(sprites_path: &Path, index: u32, sheet: u32) -> PathBuf {
+    sprites_path
+        .join((index + 1).to_string())
+        .join(format!("spritesheet_{}.png", sheet))
+}
 
-    spritesheet_paths.push(format!(
-        "{}/sprites/{}/spritesheet_{}.png",
-        collection_name,
-        index + 1,
-        sheet_index
-    ));
+pub fn handle_spritesheets(final_spritesheet: &RgbaImage, output_path: &Path) -> Result<()> {
+    if let Some(parent) = output_path.parent() {
+        create_dir_all(parent)?;
+    }
 
-    Ok(spritesheet_paths)
+    final_spritesheet.save(output_path)?;
+    Ok(())
 }
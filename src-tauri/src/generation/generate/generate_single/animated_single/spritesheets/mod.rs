@@ -2,4 +2,4 @@ pub mod frames;
 pub mod handle_sprites;
 pub mod spritesheet_blender;
 
-pub use handle_sprites::handle_spritesheets;
+pub use handle_sprites::{handle_spritesheets, spritesheet_path};
@@ -1,79 +1,213 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Instant};
 
 use anyhow::Result;
-use image::{open, DynamicImage, GenericImageView};
+use image::{imageops, open, DynamicImage, GenericImageView, RgbaImage};
 
 use crate::effects::core::{gpu::blend_modes_gpu::GpuBlendContext, transform::apply_offset};
-use crate::generation::generate::layers::blend::LayerBlendProperties;
+use crate::generation::generate::{
+    generate_single::animated_single::spritesheets::handle_spritesheets,
+    layers::blend::LayerBlendProperties,
+    task_manager::{MetricsUtils, Stage},
+};
 use crate::types::SpritesheetLayout;
 
-pub fn blend_spritesheets_with_individual_properties(
-    spritesheet_paths: &[PathBuf],
-    blend_properties_list: &[LayerBlendProperties],
-    metadata: &SpritesheetLayout,
-) -> Result<(DynamicImage, Vec<DynamicImage>)> {
-    if spritesheet_paths.is_empty() {
-        return Err(anyhow::anyhow!("No spritesheet paths provided"));
-    }
+/// Spritesheets of one drawn trait.
+struct TraitSheets {
+    paths: Vec<PathBuf>,
+    /// Layout the sheets were packed with when their cells are trimmed.
+    packed: Option<SpritesheetLayout>,
+    blend_properties: LayerBlendProperties,
+}
 
-    if spritesheet_paths.len() != blend_properties_list.len() {
-        return Err(anyhow::anyhow!(
-            "Mismatch: {} spritesheets but {} blend properties",
-            spritesheet_paths.len(),
-            blend_properties_list.len()
-        ));
+/// Frames of an animated item. Sheets are blended one at a time while the
+/// frames are read, so only the sheet being read is held in memory.
+pub struct SpritesheetFrames {
+    layers: Vec<TraitSheets>,
+    layout: SpritesheetLayout,
+    sheet_outputs: Vec<PathBuf>,
+}
+
+impl SpritesheetFrames {
+    pub fn new(
+        layers: Vec<(Vec<PathBuf>, LayerBlendProperties)>,
+        layout: &SpritesheetLayout,
+    ) -> Self {
+        let layers = layers
+            .into_iter()
+            .map(|(paths, blend_properties)| {
+                let packed = paths
+                    .first()
+                    .and_then(|path| path.parent())
+                    .and_then(SpritesheetLayout::read_from)
+                    .filter(SpritesheetLayout::is_trimmed);
+                TraitSheets {
+                    paths,
+                    packed,
+                    blend_properties,
+                }
+            })
+            .collect();
+
+        Self {
+            layers,
+            layout: layout.clone(),
+            sheet_outputs: Vec::new(),
+        }
     }
 
-    let mut blended_spritesheet = open(&spritesheet_paths[0])?;
+    /// Saves each blended sheet to the path of the same index once it is blended.
+    pub fn with_sheet_outputs(mut self, sheet_outputs: Vec<PathBuf>) -> Self {
+        self.sheet_outputs = sheet_outputs;
+        self
+    }
 
-    for (index, spritesheet_path) in spritesheet_paths[1..].iter().enumerate() {
-        let next_spritesheet = open(spritesheet_path)?;
-        let blend_properties = &blend_properties_list[index + 1];
+    pub fn len(&self) -> usize {
+        self.layout.total_frames as usize
+    }
 
-        blended_spritesheet =
-            blend_spritesheets(&blended_spritesheet, &next_spritesheet, blend_properties)?;
+    pub fn is_empty(&self) -> bool {
+        self.layout.total_frames == 0
     }
 
-    let frames = extract_frames_from_dynamic_spritesheet(&blended_spritesheet, metadata)?;
+    pub fn frame_dimensions(&self) -> (u32, u32) {
+        (self.layout.frame_width, self.layout.frame_height)
+    }
 
-    Ok((blended_spritesheet, frames))
-}
+    pub fn sheet_count(&self) -> u32 {
+        self.layout.total_sheets.max(1)
+    }
 
-fn extract_frames_from_dynamic_spritesheet(
-    spritesheet: &DynamicImage,
-    metadata: &SpritesheetLayout,
-) -> Result<Vec<DynamicImage>> {
-    let mut frame_positions = Vec::new();
-    for i in 0..metadata.total_frames {
-        let col = i % metadata.cols;
-        let row = i / metadata.cols;
-        let left = col * metadata.frame_width;
-        let top = row * metadata.frame_height;
-        frame_positions.push((i, left, top));
+    /// Sheet `sheet` of a trait in the item layout. Trimmed cells are drawn
+    /// back at their offset in canvas-sized cells.
+    fn trait_sheet(&self, layer: &TraitSheets, sheet: u32) -> Result<DynamicImage> {
+        match &layer.packed {
+            Some(packed) => Ok(DynamicImage::ImageRgba8(unpack_cells(
+                &layer.paths,
+                packed,
+                &self.layout,
+                sheet,
+            )?)),
+            None => Ok(open(sheet_path(&layer.paths, sheet)?)?),
+        }
     }
 
-    let mut frames = Vec::new();
-    frames.reserve(metadata.total_frames as usize);
+    pub fn blend_sheet(&self, sheet: u32) -> Result<RgbaImage> {
+        let blend_start = Instant::now();
+        let (first, rest) = self
+            .layers
+            .split_first()
+            .ok_or_else(|| anyhow::anyhow!("No spritesheet paths provided"))?;
 
-    for &(i, left, top) in &frame_positions {
-        if left >= spritesheet.width()
-            || top >= spritesheet.height()
-            || left + metadata.frame_width > spritesheet.width()
-            || top + metadata.frame_height > spritesheet.height()
-        {
-            continue;
+        let mut blended = self.trait_sheet(first, sheet)?;
+        for layer in rest {
+            let next = self.trait_sheet(layer, sheet)?;
+            blended = blend_spritesheets(&blended, &next, &layer.blend_properties)?;
         }
 
-        let frame = spritesheet.crop_imm(left, top, metadata.frame_width, metadata.frame_height);
+        let blended = blended.into_rgba8();
+        MetricsUtils::record_stage(Stage::Blend, blend_start.elapsed());
+        Ok(blended)
+    }
+
+    /// Calls `on_frame` with every frame in order, blending each sheet when
+    /// its first frame is reached and dropping it after its last one.
+    pub fn for_each_frame<F>(&self, mut on_frame: F) -> Result<()>
+    where
+        F: FnMut(usize, RgbaImage) -> Result<()>,
+    {
+        let (frame_width, frame_height) = self.frame_dimensions();
+
+        for sheet in 0..self.sheet_count() {
+            let frames = self.layout.sheet_frames(sheet);
+            if frames.is_empty() {
+                break;
+            }
+
+            let spritesheet = self.blend_sheet(sheet)?;
+            if let Some(output_path) = self.sheet_outputs.get(sheet as usize) {
+                let save_start = Instant::now();
+                handle_spritesheets(&spritesheet, output_path)?;
+                MetricsUtils::record_stage(Stage::Save, save_start.elapsed());
+            }
 
-        let dynamic_src_image = DynamicImage::ImageRgba8(frame.to_rgba8());
-        frames.push((i, dynamic_src_image));
+            let (sheet_width, sheet_height) = spritesheet.dimensions();
+            for index in frames {
+                let (_, left, top) = self.layout.cell_position(index);
+                if left + frame_width > sheet_width || top + frame_height > sheet_height {
+                    return Err(anyhow::anyhow!(
+                        "Frame {} lies outside spritesheet {} ({}x{})",
+                        index,
+                        sheet,
+                        sheet_width,
+                        sheet_height
+                    ));
+                }
+
+                let frame = imageops::crop_imm(&spritesheet, left, top, frame_width, frame_height)
+                    .to_image();
+                on_frame(index as usize, frame)?;
+            }
+        }
+
+        Ok(())
     }
+}
+
+fn sheet_path(paths: &[PathBuf], sheet: u32) -> Result<&PathBuf> {
+    paths.get(sheet as usize).ok_or_else(|| {
+        anyhow::anyhow!(
+            "{} has {} spritesheet(s), sheet {} is missing",
+            paths
+                .first()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+            paths.len(),
+            sheet
+        )
+    })
+}
 
-    frames.sort_by_key(|&(i, _)| i);
-    let frames: Vec<_> = frames.into_iter().map(|(_, img)| img).collect();
+/// Sheet `sheet` of `canvas`, rebuilt from sheets `packed` with trimmed cells.
+fn unpack_cells(
+    paths: &[PathBuf],
+    packed: &SpritesheetLayout,
+    canvas: &SpritesheetLayout,
+    sheet: u32,
+) -> Result<RgbaImage> {
+    let cell = packed.cell();
+    let mut unpacked = RgbaImage::new(
+        canvas.cols * canvas.frame_width,
+        canvas.rows * canvas.frame_height,
+    );
+    let mut source: Option<(u32, RgbaImage)> = None;
 
-    Ok(frames)
+    for frame in canvas.sheet_frames(sheet) {
+        let (packed_sheet, left, top) = packed.cell_position(frame);
+        let image = match source.take() {
+            Some((index, image)) if index == packed_sheet => image,
+            _ => open(sheet_path(paths, packed_sheet)?)?.into_rgba8(),
+        };
+        if left + cell.width > image.width() || top + cell.height > image.height() {
+            return Err(anyhow::anyhow!(
+                "Frame {} lies outside spritesheet {} ({}x{})",
+                frame,
+                packed_sheet,
+                image.width(),
+                image.height()
+            ));
+        }
+
+        let (_, x, y) = canvas.cell_position(frame);
+        imageops::replace(
+            &mut unpacked,
+            &*imageops::crop_imm(&image, left, top, cell.width, cell.height),
+            (x + cell.x) as i64,
+            (y + cell.y) as i64,
+        );
+        source = Some((packed_sheet, image));
+    }
+
+    Ok(unpacked)
 }
 
 fn blend_spritesheets(
@@ -121,3 +255,47 @@ fn blend_spritesheets(
 
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::core::transform::trim::TrimRegion;
+    use image::Rgba;
+    use std::fs;
+
+    #[test]
+    fn trimmed_cells_are_drawn_back_at_their_offset() {
+        let dir = std::env::temp_dir().join(format!("blendgine-unpack-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let canvas = SpritesheetLayout::calculate(8, 6, TrimRegion::full(8, 6), 4, 64);
+        let cell = TrimRegion {
+            x: 3,
+            y: 2,
+            width: 2,
+            height: 1,
+        };
+        let packed = SpritesheetLayout::calculate(8, 6, cell, 4, 64);
+
+        let mut sheet = RgbaImage::new(packed.cols * cell.width, packed.rows * cell.height);
+        for frame in 0..4 {
+            let (_, left, top) = packed.cell_position(frame);
+            for x in left..left + cell.width {
+                sheet.put_pixel(x, top, Rgba([frame as u8 + 1, 0, 0, 255]));
+            }
+        }
+        let path = dir.join("spritesheet_0.png");
+        sheet.save(&path).unwrap();
+
+        let unpacked = unpack_cells(&[path], &packed, &canvas, 0).unwrap();
+        assert_eq!(unpacked.dimensions(), (16, 12));
+        for frame in 0..4 {
+            let (_, x, y) = canvas.cell_position(frame);
+            assert_eq!(unpacked.get_pixel(x + 3, y + 2)[0], frame as u8 + 1);
+            assert_eq!(unpacked.get_pixel(x + 4, y + 2)[0], frame as u8 + 1);
+            assert_eq!(unpacked.get_pixel(x, y)[3], 0);
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,30 +1,31 @@
-use std::{
-    collections::{HashMap, HashSet},
-    path::Path,
-};
+use std::path::Path;
 
 use anyhow::Result;
 
 use crate::{
     effects::core::gpu::resize_gpu::ResizeConfig,
     generation::generate::{
-        generate::GlobalGenerationCaches,
         generate_single::{
-            animated_single::animated_single_cpu::process_animated_collection,
-            generate_traits::generate_traits_and_validate, save_metadata::save_metadata_file,
+            animated_single::{
+                animated_single_cpu::process_animated_collection, spritesheets::spritesheet_path,
+            },
+            progress::image_path,
+            save_metadata::save_metadata_file,
             static_single::process_static_single,
         },
         metadata::create_single::Blockchain,
-        pausecancel::{check_cancelled, wait_for_pause},
+        staging::StagedFiles,
     },
     types::{
-        AnimationQualityConfig, ForcedCombinations, GenerationResult, RarityConfig,
-        SolanaMetadataConfig, SpritesheetLayout,
+        AnimationQualityConfig, GenerationResult, NFTTrait, RarityConfig, SolanaMetadataConfig,
+        SpritesheetLayout,
     },
 };
 
 pub async fn generate_single_artwork(
     index: u32,
+    traits: &[NFTTrait],
+    dna: &str,
     input_folder: &Path,
     export_folder: &Path,
     collection_name: &str,
@@ -37,11 +38,7 @@ pub async fn generate_single_artwork(
     final_width: u32,
     final_height: u32,
     image_format: &str,
-    incompatibility_map: &HashMap<String, HashSet<String>>,
-    forced_combinations: &ForcedCombinations,
-    allow_duplicates: bool,
     current_set_id: &str,
-    total_to_generate: u32,
     blockchain: Blockchain,
     is_animated_collection: bool,
     include_spritesheets: bool,
@@ -53,27 +50,17 @@ pub async fn generate_single_artwork(
     total_frames_count: Option<u32>,
     spritesheet_layout: Option<&SpritesheetLayout>,
     working_folder: Option<&Path>,
-    window: &tauri::Window,
-    global_caches: &GlobalGenerationCaches,
+    frame_durations_ms: Option<&[u32]>,
 ) -> Result<Option<GenerationResult>> {
-    wait_for_pause().await?;
-    check_cancelled().await?;
-
-    let (traits, dna) = generate_traits_and_validate(
-        input_folder,
-        active_layer_order,
-        rarity_config,
-        incompatibility_map,
-        forced_combinations,
-        allow_duplicates,
-        current_set_id,
-        is_animated_collection,
-        working_folder,
-        global_caches,
-    )?;
-
-    wait_for_pause().await?;
-    check_cancelled().await?;
+    // Files are written under temporary names and only moved into place once
+    // the image and its metadata are both written.
+    let mut staged = StagedFiles::default();
+    let output_path = staged.stage(image_path(
+        export_folder,
+        collection_name,
+        index + 1,
+        image_format,
+    ));
 
     if is_animated_collection {
         let total_frames = total_frames_count.unwrap_or(0);
@@ -82,32 +69,38 @@ pub async fn generate_single_artwork(
             return Ok(None);
         }
 
+        let spritesheet_layout = spritesheet_layout
+            .ok_or_else(|| anyhow::anyhow!("Animated collection has no spritesheet layout"))?;
+        let spritesheet_outputs: Vec<_> = match sprites_path.filter(|_| include_spritesheets) {
+            Some(sprites_path) => (0..spritesheet_layout.total_sheets.max(1))
+                .map(|sheet| staged.stage(spritesheet_path(sprites_path, index, sheet)))
+                .collect(),
+            None => Vec::new(),
+        };
+
         process_animated_collection(
-            &traits,
+            traits,
             active_layer_order,
             input_folder,
             working_folder,
             final_width,
             final_height,
             total_frames,
-            spritesheet_layout.as_ref().unwrap(),
+            spritesheet_layout,
             rarity_config,
             current_set_id,
-            export_folder,
-            collection_name,
+            &output_path,
             image_format,
             fps,
             animation_quality,
             resize_config,
-            include_spritesheets,
-            sprites_path,
-            index,
+            &spritesheet_outputs,
+            frame_durations_ms,
         )
         .await?;
     } else {
-        let images_path = export_folder.join("collection").join("images");
         process_static_single(
-            &traits,
+            traits,
             active_layer_order,
             input_folder,
             base_width,
@@ -116,8 +109,7 @@ pub async fn generate_single_artwork(
             final_height,
             rarity_config,
             current_set_id,
-            images_path,
-            collection_name,
+            output_path,
             image_format,
             index,
             resize_config,
@@ -125,29 +117,27 @@ pub async fn generate_single_artwork(
         .await?;
     }
 
-    let nft_result = GenerationResult {
-        traits: traits.to_vec(),
-        original_index: index + 1,
-    };
-
-    if let Err(e) = save_metadata_file(
-        &traits,
-        &dna,
+    let metadata_path = staged.stage(
+        export_folder
+            .join("collection")
+            .join("metadata")
+            .join(format!("{}_{}.json", collection_name, index + 1)),
+    );
+    let nft_result = save_metadata_file(
+        traits,
+        dna,
         collection_name,
         collection_description,
         blockchain,
         solana_config,
         image_format,
         index,
-        total_to_generate,
         include_rarity,
         rarity_config,
         current_set_id,
-        export_folder,
-        window,
-    ) {
-        eprintln!("⚠️ [METADATA] Failed to save metadata: {}", e);
-    }
+        &metadata_path,
+    )?;
 
+    staged.commit()?;
     Ok(Some(nft_result))
 }
@@ -4,14 +4,17 @@ use std::{
 };
 
 use anyhow::Result;
+use serde::Serialize;
 
 use crate::{
     generation::generate::{
         generate::GlobalGenerationCaches,
         layers::{
             handle_files::handle_layer_files,
+            parts::trait_parts,
             traits_selection::{
                 check_compatibility, handle_forced_combinations, select_random_with_rarity,
+                select_variant,
             },
             unicity::{generate_dna, is_unique_combination},
         },
@@ -19,7 +22,30 @@ use crate::{
     types::{ForcedCombinations, NFTTrait, RarityConfig},
 };
 
-const MAX_ATTEMPTS: u32 = 1000;
+pub const MAX_ATTEMPTS: u32 = 1000;
+
+/// Why a selection attempt was thrown away and retried.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Rejection {
+    EmptyLayer {
+        layer: String,
+    },
+    NoCandidate {
+        layer: String,
+    },
+    Incompatible {
+        layer: String,
+        #[serde(rename = "traitName")]
+        trait_name: String,
+    },
+    ForcedCombination {
+        layer: String,
+        #[serde(rename = "traitName")]
+        trait_name: String,
+    },
+    Duplicate,
+}
 
 pub fn generate_traits_and_validate(
     input_folder: &Path,
@@ -33,6 +59,51 @@ pub fn generate_traits_and_validate(
     working_folder: Option<&Path>,
     global_caches: &GlobalGenerationCaches,
 ) -> Result<(Vec<NFTTrait>, String)> {
+    let mut is_unique = |dna: &str, traits: &[NFTTrait]| {
+        *global_caches
+            .uniqueness_cache
+            .entry(dna.to_string())
+            .or_insert_with(|| {
+                is_unique_combination(traits, rarity_config, current_set_id, active_layer_order)
+            })
+    };
+
+    select_traits(
+        input_folder,
+        active_layer_order,
+        rarity_config,
+        incompatibility_map,
+        forced_combinations,
+        current_set_id,
+        is_animated_collection,
+        working_folder,
+        global_caches,
+        if allow_duplicates {
+            None
+        } else {
+            Some(&mut is_unique)
+        },
+        &mut |_| {},
+    )
+    .map(|(traits, dna, _)| (traits, dna))
+}
+
+/// Selection loop of `generate_traits_and_validate`. `is_unique` is `None`
+/// when duplicates are allowed, and every failed attempt goes to `on_reject`.
+/// Returns the traits, their DNA and the number of attempts used.
+pub fn select_traits(
+    input_folder: &Path,
+    active_layer_order: &[String],
+    rarity_config: &RarityConfig,
+    incompatibility_map: &HashMap<String, HashSet<String>>,
+    forced_combinations: &ForcedCombinations,
+    current_set_id: &str,
+    is_animated_collection: bool,
+    working_folder: Option<&Path>,
+    global_caches: &GlobalGenerationCaches,
+    mut is_unique: Option<&mut dyn FnMut(&str, &[NFTTrait]) -> bool>,
+    on_reject: &mut dyn FnMut(Rejection),
+) -> Result<(Vec<NFTTrait>, String, u32)> {
     let mut traits = Vec::new();
     let mut attempts = 0;
     let mut is_valid = false;
@@ -59,6 +130,9 @@ pub fn generate_traits_and_validate(
             )?;
 
             if layer_files.is_empty() {
+                on_reject(Rejection::EmptyLayer {
+                    layer: layer.to_string(),
+                });
                 is_valid = false;
                 break;
             }
@@ -71,22 +145,19 @@ pub fn generate_traits_and_validate(
                 global_caches,
             );
 
-            if selected_file.is_none() {
+            let Some(trait_name) = selected_file else {
+                on_reject(Rejection::NoCandidate {
+                    layer: layer.to_string(),
+                });
                 is_valid = false;
                 break;
-            }
-
-            let trait_name = if is_animated_collection {
-                selected_file.unwrap()
-            } else {
-                Path::new(&selected_file.unwrap())
-                    .file_stem()
-                    .unwrap()
-                    .to_string_lossy()
-                    .into_owned()
             };
 
             if !check_compatibility(incompatibility_map, &trait_name, &traits) {
+                on_reject(Rejection::Incompatible {
+                    layer: layer.to_string(),
+                    trait_name,
+                });
                 is_valid = false;
                 break;
             }
@@ -101,13 +172,27 @@ pub fn generate_traits_and_validate(
                 current_set_id,
                 global_caches,
             ) {
+                on_reject(Rejection::ForcedCombination {
+                    layer: layer.to_string(),
+                    trait_name,
+                });
                 is_valid = false;
                 break;
             }
 
+            let trait_config = rarity_config
+                .layers
+                .get(layer)
+                .and_then(|l| l.traits.get(&trait_name));
+            let file = select_variant(&layer_files, &trait_name, trait_config)
+                .filter(|file| *file != trait_name);
+            let parts = trait_parts(&layer_files, file.as_deref().unwrap_or(&trait_name));
+
             traits.push(NFTTrait {
                 trait_type: layer.to_string(),
                 value: trait_name,
+                file,
+                parts,
             });
         }
 
@@ -117,20 +202,9 @@ pub fn generate_traits_and_validate(
 
         dna = generate_dna(&traits, rarity_config, current_set_id, active_layer_order);
 
-        if !allow_duplicates {
-            let is_unique = global_caches
-                .uniqueness_cache
-                .entry(dna.clone())
-                .or_insert_with(|| {
-                    is_unique_combination(
-                        &traits,
-                        rarity_config,
-                        current_set_id,
-                        active_layer_order,
-                    )
-                });
-
-            if !*is_unique {
+        if let Some(is_unique) = is_unique.as_mut() {
+            if !is_unique(&dna, &traits) {
+                on_reject(Rejection::Duplicate);
                 is_valid = false;
                 continue;
             }
@@ -146,5 +220,5 @@ pub fn generate_traits_and_validate(
         ));
     }
 
-    Ok((traits, dna))
+    Ok((traits, dna, attempts))
 }
@@ -1,21 +1,112 @@
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::path::Path;
-use tauri::{Emitter, Window};
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use tauri::{Emitter, Runtime};
 
 use crate::{
     generation::generation_main::{ImageInfo, NFTProgressInfo},
     types::NFTTrait,
 };
 
-pub fn send_generation_progress(
+const ETA_SMOOTHING: f64 = 0.2;
+const ETA_MIN_WINDOW: Duration = Duration::from_millis(500);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum GenerationPhase {
+    Generating,
+    Shuffling,
+    GlobalMetadata,
+    RarityFiles,
+    LegendaryMixing,
+    Packaging,
+    Complete,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseProgress {
+    pub phase: GenerationPhase,
+    pub completed: u32,
+    pub total: u32,
+}
+
+/// Completion rate as an exponential moving average over windows of at
+/// least `ETA_MIN_WINDOW`, so bursts of items finishing together do not
+/// make the ETA jump.
+pub struct EtaEstimator {
+    window_start: Instant,
+    window_count: u32,
+    rate: Option<f64>,
+}
+
+impl Default for EtaEstimator {
+    fn default() -> Self {
+        Self {
+            window_start: Instant::now(),
+            window_count: 0,
+            rate: None,
+        }
+    }
+}
+
+impl EtaEstimator {
+    /// Estimator of a run that already has `completed` items.
+    pub fn starting_at(completed: u32) -> Self {
+        Self {
+            window_count: completed,
+            ..Self::default()
+        }
+    }
+
+    pub fn update(&mut self, completed: u32, remaining: u32) -> Option<u64> {
+        let elapsed = self.window_start.elapsed();
+        if elapsed >= ETA_MIN_WINDOW && completed > self.window_count {
+            let rate = (completed - self.window_count) as f64 / elapsed.as_secs_f64();
+            self.rate = Some(match self.rate {
+                Some(previous) => ETA_SMOOTHING * rate + (1.0 - ETA_SMOOTHING) * previous,
+                None => rate,
+            });
+            self.window_start = Instant::now();
+            self.window_count = completed;
+        }
+
+        self.rate
+            .filter(|rate| *rate > 0.0)
+            .map(|rate| (remaining as f64 / rate * 1000.0) as u64)
+    }
+}
+
+pub fn image_path(
+    export_folder: &Path,
+    collection_name: &str,
+    edition: u32,
+    image_format: &str,
+) -> PathBuf {
+    export_folder
+        .join("collection")
+        .join("images")
+        .join(format!("{}_{}.{}", collection_name, edition, image_format))
+}
+
+/// Reports one finished item. `completed` counts items of the whole run,
+/// `edition` is the number in the item's file name.
+pub fn send_generation_progress<R: Runtime>(
+    emitter: &impl Emitter<R>,
     traits: &[NFTTrait],
     collection_name: &str,
     image_format: &str,
-    index: u32,
+    image_path: &Path,
+    edition: u32,
+    completed: u32,
     total_to_generate: u32,
-    export_folder: &Path,
-    window: &Window,
+    estimated_count: u32,
+    phase: GenerationPhase,
+    eta_ms: Option<u64>,
 ) -> Result<()> {
     let mut traits_obj = json!({});
     for t in traits {
@@ -23,27 +114,36 @@ pub fn send_generation_progress(
     }
 
     let progress_info = NFTProgressInfo {
-        current_count: index + 1,
+        current_count: completed,
         total_count: total_to_generate,
-        estimated_count: total_to_generate,
-        sequence_number: index + 1,
+        estimated_count,
+        sequence_number: edition,
         current_image: ImageInfo {
-            path: export_folder
-                .join("collection")
-                .join("images")
-                .join(format!(
-                    "{}_{}.{}",
-                    collection_name,
-                    index + 1,
-                    image_format
-                ))
-                .to_string_lossy()
-                .to_string(),
-            name: format!("{} #{}.{}", collection_name, index + 1, image_format),
+            path: image_path.to_string_lossy().to_string(),
+            name: format!("{} #{}.{}", collection_name, edition, image_format),
             traits: traits_obj,
         },
+        phase,
+        eta_ms,
     };
 
-    let _ = window.emit("nft-generation-progress", progress_info);
+    emitter.emit("nft-generation-progress", progress_info)?;
     Ok(())
 }
+
+/// Reports the progress of a step that runs after the items are generated.
+pub fn send_phase_progress<R: Runtime>(
+    emitter: &impl Emitter<R>,
+    phase: GenerationPhase,
+    completed: u32,
+    total: u32,
+) {
+    let _ = emitter.emit(
+        "nft-generation-phase",
+        PhaseProgress {
+            phase,
+            completed,
+            total,
+        },
+    );
+}
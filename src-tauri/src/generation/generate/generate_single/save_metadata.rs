@@ -6,10 +6,7 @@ use std::{
 };
 
 use crate::{
-    generation::generate::{
-        generate_single::progress::send_generation_progress,
-        metadata::create_single::{generate_metadata, Blockchain},
-    },
+    generation::generate::metadata::create_single::{generate_metadata, Blockchain},
     types::{GenerationResult, NFTTrait, RarityConfig, SolanaMetadataConfig},
 };
 
@@ -22,12 +19,10 @@ pub fn save_metadata_file(
     solana_config: Option<&SolanaMetadataConfig>,
     image_format: &str,
     index: u32,
-    total_to_generate: u32,
     include_rarity: bool,
     rarity_config: &RarityConfig,
     current_set_id: &str,
-    export_folder: &Path,
-    window: &tauri::Window,
+    metadata_filepath: &Path,
 ) -> Result<GenerationResult> {
     let metadata = generate_metadata(
         traits,
@@ -43,26 +38,12 @@ pub fn save_metadata_file(
         current_set_id,
     )?;
 
-    let metadata_path = export_folder.join("collection").join("metadata");
-    create_dir_all(&metadata_path)?;
-
-    let metadata_filename = format!("{}_{}.json", collection_name, index + 1);
-    let metadata_filepath = metadata_path.join(&metadata_filename);
-
-    write(&metadata_filepath, to_string_pretty(&metadata)?)?;
-
-    if let Err(e) = send_generation_progress(
-        traits,
-        collection_name,
-        image_format,
-        index,
-        total_to_generate,
-        export_folder,
-        window,
-    ) {
-        eprintln!("⚠️ [PROGRESS] Failed to send progress: {}", e);
+    if let Some(metadata_path) = metadata_filepath.parent() {
+        create_dir_all(metadata_path)?;
     }
 
+    write(metadata_filepath, to_string_pretty(&metadata)?)?;
+
     Ok(GenerationResult {
         traits: traits.to_vec(),
         original_index: index + 1,
@@ -1,6 +1,7 @@
 pub mod static_gpu;
 
 pub use static_gpu::{
-    get_or_init_shared_gpu_pipeline, process_static_single_gpu as process_static_single,
+    get_or_init_shared_gpu_pipeline, output_image,
+    process_static_single_gpu as process_static_single, render_static_image,
     reset_shared_gpu_pipeline,
 };
@@ -1,6 +1,7 @@
 use rayon::prelude::*;
 use std::{
-    fs::read,
+    fs::{read, write},
+    io::Cursor,
     iter::once,
     path::{Path, PathBuf},
     sync::{mpsc::channel, Arc},
@@ -15,19 +16,20 @@ use image::{load_from_memory, load_from_memory_with_format, DynamicImage, ImageF
 use once_cell::sync::{Lazy, OnceCell};
 
 use crate::{
-    effects::core::{
-        gpu::{
-            blend_modes_gpu::{clear_blend_caches, GpuBlendProcessor},
-            common::{clear_staging_buffer, GpuTexture},
-            resize_gpu::{ResizeConfig, ResizeGpu},
-            shaders::{
-                clear_shader_cache, get_global_device, get_global_queue, initialize_global_device,
-            },
-            GpuEffectManager, GpuImage,
+    effects::core::gpu::{
+        blend_modes_gpu::{clear_blend_caches, GpuBlendProcessor},
+        common::{clear_staging_buffer, GpuTexture},
+        resize_gpu::{ResizeConfig, ResizeGpu},
+        shaders::{
+            clear_shader_cache, get_global_device, get_global_queue, initialize_global_device,
         },
-        transform::apply_offset,
+        GpuEffectManager, GpuImage,
+    },
+    generation::generate::{
+        cache::get_trimmed_trait_cached,
+        layers::{blend::LayerBlendProperties, parts::render_order},
+        task_manager::{MetricsUtils, Stage},
     },
-    generation::generate::layers::blend::LayerBlendProperties,
     types::{BlendMode, NFTTrait, RarityConfig},
 };
 
@@ -117,14 +119,6 @@ where
     queue.submit(command_buffers);
 }
 
-fn get_texture(pipeline: &Arc<StaticGpuPipeline>, path: &Path) -> Result<Arc<GpuTexture>> {
-    tracing::debug!("🔨 [TEXTURE] Création directe pour: {}", path.display());
-    let texture = pipeline.process_layer_native(path)?;
-    let arc_texture = Arc::new(texture);
-    tracing::debug!("✅ [TEXTURE] Texture créée avec succès");
-    Ok(arc_texture)
-}
-
 pub struct StaticGpuPipeline {
     gpu_manager: GpuEffectManager,
     blend_processor: Arc<GpuBlendProcessor>,
@@ -154,14 +148,48 @@ impl StaticGpuPipeline {
         })
     }
 
-    fn process_layer_native(&self, image_path: &Path) -> Result<GpuTexture> {
-        let image = decode_image_from_path(image_path)?;
+    /// Uploads the visible part of a trait moved by `offset`, with its
+    /// position on the canvas. `None` when nothing of it lands on the canvas.
+    fn process_layer_native(
+        &self,
+        image_path: &Path,
+        offset: (i32, i32),
+    ) -> Result<Option<(GpuTexture, (u32, u32))>> {
+        let Some(trimmed) = get_trimmed_trait_cached(image_path, decode_image_from_path)? else {
+            return Ok(None);
+        };
+        let Some((image, origin)) = trimmed.placed(offset.0, offset.1) else {
+            return Ok(None);
+        };
 
-        let input_texture =
-            GpuTexture::from_image(self.gpu_manager.device(), self.gpu_manager.queue(), &image)
-                .map_err(|e| anyhow::anyhow!("Failed to create input GPU texture: {}", e))?;
+        let (width, height) = image.dimensions();
+        let texture = GpuTexture::new(
+            self.gpu_manager.device(),
+            width,
+            height,
+            wgpu::TextureFormat::Rgba8Unorm,
+        );
+        self.gpu_manager.queue().write_texture(
+            wgpu::ImageCopyTexture {
+                texture: texture.texture(),
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            image.as_raw(),
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(height),
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
 
-        Ok(input_texture)
+        Ok(Some((texture, origin)))
     }
 
     fn resize_final_image(
@@ -332,120 +360,34 @@ impl StaticGpuPipeline {
 }
 
 impl StaticGpuPipeline {
-    fn process_layers_batch(&self, layer_paths: &[&Path]) -> Result<Vec<Arc<GpuTexture>>> {
-        tracing::info!(
-            "🔄 [GPU BATCH] Starting parallel image decoding for {} images",
-            layer_paths.len()
-        );
-        let decode_start = Instant::now();
-
-        let decoded_images: Result<Vec<(PathBuf, image::DynamicImage)>> = layer_paths
-            .par_iter()
-            .map(|path| {
-                let path_buf = path.to_path_buf();
-                let image = decode_image_from_path(path)?;
-                Ok((path_buf, image))
-            })
-            .collect();
-
-        let decoded_images = decoded_images?;
-        let decode_duration = decode_start.elapsed();
+    fn process_layers_batch(
+        &self,
+        layers: &[(&Path, (i32, i32))],
+    ) -> Result<Vec<Option<(Arc<GpuTexture>, (u32, u32))>>> {
         tracing::info!(
-            "✅ [GPU BATCH] Parallel decoding completed in {:?}",
-            decode_duration
+            "🔄 [GPU BATCH] Starting parallel upload of {} trimmed layers",
+            layers.len()
         );
-
-        tracing::info!("🎮 [GPU BATCH] Starting parallel GPU upload pipeline");
         let upload_start = Instant::now();
 
-        const CHUNK_SIZE: usize = 4;
-        let chunks: Vec<_> = decoded_images.chunks(CHUNK_SIZE).collect();
-
-        tracing::info!(
-            "🧵 [GPU PARALLEL] Processing {} chunks of up to {} images each",
-            chunks.len(),
-            CHUNK_SIZE
-        );
-
-        let texture_results: Result<Vec<_>> = chunks
+        let textures = layers
             .par_iter()
-            .enumerate()
-            .map(|(chunk_idx, chunk)| {
-                let chunk_start = Instant::now();
-                let mut chunk_textures = Vec::with_capacity(chunk.len());
-
-                for (path, image) in chunk.iter() {
-                    let texture_start = Instant::now();
-
-                    let texture = GpuTexture::new(
-                        self.gpu_manager.device(),
-                        image.width(),
-                        image.height(),
-                        wgpu::TextureFormat::Rgba8Unorm,
-                    );
-
-                    let rgba_data = image.to_rgba8();
-
-                    self.gpu_manager.queue().write_texture(
-                        wgpu::ImageCopyTexture {
-                            texture: texture.texture(),
-                            mip_level: 0,
-                            origin: wgpu::Origin3d::ZERO,
-                            aspect: wgpu::TextureAspect::All,
-                        },
-                        &rgba_data,
-                        wgpu::ImageDataLayout {
-                            offset: 0,
-                            bytes_per_row: Some(4 * image.width()),
-                            rows_per_image: Some(image.height()),
-                        },
-                        wgpu::Extent3d {
-                            width: image.width(),
-                            height: image.height(),
-                            depth_or_array_layers: 1,
-                        },
-                    );
-
-                    let texture_duration = texture_start.elapsed();
-                    tracing::trace!(
-                        "📸 [GPU PARALLEL] Chunk {} texture uploaded in {:?} for {}",
-                        chunk_idx,
-                        texture_duration,
-                        path.display()
-                    );
-
-                    chunk_textures.push(Arc::new(texture));
-                }
-
-                let chunk_duration = chunk_start.elapsed();
-                tracing::debug!(
-                    "🧵 [GPU PARALLEL] Chunk {} ({} textures) completed in {:?}",
-                    chunk_idx,
-                    chunk.len(),
-                    chunk_duration
-                );
-
-                Ok(chunk_textures)
+            .map(|(path, offset)| {
+                let placed = self.process_layer_native(path, *offset)?;
+                Ok(placed.map(|(texture, origin)| (Arc::new(texture), origin)))
             })
-            .collect();
-
-        let all_textures: Vec<Arc<GpuTexture>> = texture_results?.into_iter().flatten().collect();
+            .collect::<Result<Vec<_>>>()?;
 
         self.gpu_manager.device().poll(wgpu::Maintain::Poll);
 
         let upload_duration = upload_start.elapsed();
+        MetricsUtils::record_stage(Stage::TextureUpload, upload_duration);
         tracing::info!(
-            "✅ [GPU BATCH] Parallel GPU upload completed in {:?}",
+            "✅ [GPU BATCH] Trimmed layers uploaded in {:?}",
             upload_duration
         );
-        tracing::info!(
-            "🎯 [GPU BATCH] Total batch processing: decode {:?} + upload {:?} = {:?}",
-            decode_duration,
-            upload_duration,
-            decode_start.elapsed()
-        );
 
-        Ok(all_textures)
+        Ok(textures)
     }
 }
 
@@ -483,21 +425,11 @@ fn is_empty_trait(trait_value: &str) -> bool {
     trait_value == "None" || trait_value == "none" || trait_value.is_empty()
 }
 
-pub async fn process_static_single_gpu(
-    traits: &[NFTTrait],
-    active_layer_order: &[String],
-    input_folder: &Path,
+async fn init_static_gpu(
     base_width: u32,
     base_height: u32,
     final_width: u32,
     final_height: u32,
-    rarity_config: &RarityConfig,
-    current_set_id: &str,
-    images_path: PathBuf,
-    collection_name: &str,
-    image_format: &str,
-    index: u32,
-    resize_config: Option<&ResizeConfig>,
 ) -> Result<()> {
     let _ = get_or_init_shared_gpu_pipeline().await?;
 
@@ -517,13 +449,91 @@ pub async fn process_static_single_gpu(
             base_height
         );
     }
+    Ok(())
+}
+
+/// Composes a trait selection exactly like `process_static_single_gpu` and
+/// returns the pixels instead of writing a file.
+pub async fn render_static_image(
+    traits: &[NFTTrait],
+    active_layer_order: &[String],
+    input_folder: &Path,
+    base_width: u32,
+    base_height: u32,
+    final_width: u32,
+    final_height: u32,
+    rarity_config: &RarityConfig,
+    current_set_id: &str,
+    resize_config: Option<&ResizeConfig>,
+) -> Result<RgbaImage> {
+    init_static_gpu(base_width, base_height, final_width, final_height).await?;
+
+    let traits_owned = traits.to_vec();
+    let active_layer_order_owned = active_layer_order.to_vec();
+    let input_folder_owned = input_folder.to_path_buf();
+    let rarity_config_owned = rarity_config.clone();
+    let current_set_id_owned = current_set_id.to_string();
+    let resize_config_owned = resize_config.cloned();
+
+    spawn_blocking(move || {
+        compose_static_image_blocking(
+            &traits_owned,
+            &active_layer_order_owned,
+            &input_folder_owned,
+            base_width,
+            base_height,
+            final_width,
+            final_height,
+            &rarity_config_owned,
+            &current_set_id_owned,
+            resize_config_owned.as_ref(),
+        )
+    })
+    .await
+    .map_err(|e| anyhow::anyhow!("GPU task join error: {}", e))?
+}
+
+/// Pixels as they are written for `image_format`: PNG and JPEG drop alpha.
+pub fn output_image(image: RgbaImage, image_format: &str) -> Result<(DynamicImage, ImageFormat)> {
+    match image_format.to_lowercase().as_str() {
+        "png" => Ok((
+            DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(image).to_rgb8()),
+            ImageFormat::Png,
+        )),
+        "jpg" | "jpeg" => Ok((
+            DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(image).to_rgb8()),
+            ImageFormat::Jpeg,
+        )),
+        "webp" => Ok((DynamicImage::ImageRgba8(image), ImageFormat::WebP)),
+        _ => Err(anyhow::anyhow!(
+            "Unsupported image format: {}",
+            image_format
+        )),
+    }
+}
+
+pub async fn process_static_single_gpu(
+    traits: &[NFTTrait],
+    active_layer_order: &[String],
+    input_folder: &Path,
+    base_width: u32,
+    base_height: u32,
+    final_width: u32,
+    final_height: u32,
+    rarity_config: &RarityConfig,
+    current_set_id: &str,
+    output_path: PathBuf,
+    image_format: &str,
+    index: u32,
+    resize_config: Option<&ResizeConfig>,
+) -> Result<()> {
+    init_static_gpu(base_width, base_height, final_width, final_height).await?;
 
     let traits_owned = traits.to_vec();
     let active_layer_order_owned = active_layer_order.to_vec();
     let input_folder_owned = input_folder.to_path_buf();
     let rarity_config_owned = rarity_config.clone();
     let current_set_id_owned = current_set_id.to_string();
-    let collection_name_owned = collection_name.to_string();
     let image_format_owned = image_format.to_string();
     let resize_config_owned = resize_config.cloned();
 
@@ -538,8 +548,7 @@ pub async fn process_static_single_gpu(
             final_height,
             &rarity_config_owned,
             &current_set_id_owned,
-            images_path,
-            &collection_name_owned,
+            output_path,
             &image_format_owned,
             index,
             resize_config_owned.as_ref(),
@@ -549,7 +558,7 @@ pub async fn process_static_single_gpu(
     .map_err(|e| anyhow::anyhow!("GPU task join error: {}", e))?
 }
 
-fn process_static_single_gpu_blocking(
+fn compose_static_image_blocking(
     traits: &[NFTTrait],
     active_layer_order: &[String],
     input_folder: &Path,
@@ -559,12 +568,8 @@ fn process_static_single_gpu_blocking(
     final_height: u32,
     rarity_config: &RarityConfig,
     current_set_id: &str,
-    images_path: PathBuf,
-    collection_name: &str,
-    image_format: &str,
-    index: u32,
     resize_config: Option<&ResizeConfig>,
-) -> Result<()> {
+) -> Result<RgbaImage> {
     let pipeline_arc = get_shared_gpu_pipeline().clone();
 
     let base_width_arc = Arc::new(base_width);
@@ -579,7 +584,7 @@ fn process_static_single_gpu_blocking(
     let resize_config_arc = Arc::new(resize_config);
 
     let result = {
-        let base_texture = GpuTexture::new(
+        let mut base_texture = GpuTexture::new(
             pipeline_arc.gpu_manager.device(),
             *base_width_arc,
             *base_height_arc,
@@ -614,8 +619,10 @@ fn process_static_single_gpu_blocking(
         let layer_count = active_layer_order_arc.len();
         let mut valid_layers: Vec<_> = Vec::with_capacity(layer_count);
 
-        valid_layers.extend(active_layer_order_arc.iter().filter_map(|layer| {
-            let trait_data = traits_arc.iter().find(|t| t.trait_type == *layer)?;
+        let render_entries = render_order(&active_layer_order_arc, &traits_arc, &rarity_config_arc);
+        valid_layers.extend(render_entries.into_iter().filter_map(|entry| {
+            let layer = entry.layer;
+            let trait_data = entry.trait_data;
             if is_empty_trait(&trait_data.value) {
                 return None;
             }
@@ -635,10 +642,10 @@ fn process_static_single_gpu_blocking(
 
             let png_path = input_folder_arc
                 .join(layer)
-                .join(format!("{}.png", trait_data.value));
+                .join(format!("{}.png", entry.file_key));
             let webp_path = input_folder_arc
                 .join(layer)
-                .join(format!("{}.webp", trait_data.value));
+                .join(format!("{}.webp", entry.file_key));
 
             let path = if png_path.exists() {
                 png_path
@@ -654,172 +661,108 @@ fn process_static_single_gpu_blocking(
             return Err(anyhow::anyhow!("No valid layer found"));
         }
 
-        let mut layer_textures: Vec<(&str, Arc<GpuTexture>)> =
-            Vec::with_capacity(valid_layers.len());
+        // Layers are uploaded trimmed and already moved by their offset. The
+        // first one is copied as is, like the base it used to be.
+        let placements: Vec<(&Path, (i32, i32))> = valid_layers
+            .iter()
+            .enumerate()
+            .map(|(i, (_, blend_key, path))| {
+                let offset = match BLEND_PROPERTIES_CACHE.get(blend_key) {
+                    Some(properties) if i > 0 => (properties.offset_x, properties.offset_y),
+                    _ => (0, 0),
+                };
+                (path.as_path(), offset)
+            })
+            .collect();
+        let layer_textures = pipeline_arc.process_layers_batch(&placements)?;
 
-        if valid_layers.len() > 3 {
-            let layer_paths: Vec<&Path> = valid_layers
-                .iter()
-                .map(|(_, _, path)| path.as_ref())
-                .collect();
-            let batch_textures = pipeline_arc.process_layers_batch(&layer_paths)?;
+        let mut remaining_layers: Vec<(&GpuTexture, BlendMode, f32, (u32, u32))> =
+            Vec::with_capacity(layer_textures.len());
 
-            for (i, (layer, _, _)) in valid_layers.iter().enumerate() {
-                layer_textures.push((layer, Arc::clone(&batch_textures[i])));
-            }
-        } else {
-            for (layer, _, image_path) in &valid_layers {
-                let layer_texture = get_texture(&pipeline_arc, image_path)?;
-                layer_textures.push((layer, layer_texture));
+        // Parts repeat a layer, so blend keys are matched by position.
+        for (i, ((layer_name, blend_key, _), placed)) in
+            valid_layers.iter().zip(layer_textures.iter()).enumerate()
+        {
+            let Some((layer_texture, (x, y))) = placed else {
+                tracing::debug!(
+                    "⏭️ [TRIM] Layer '{}' has no visible pixels on the canvas",
+                    layer_name
+                );
+                continue;
+            };
+
+            if i == 0 {
+                let mut copy_encoder = pipeline_arc.gpu_manager.device().create_command_encoder(
+                    &wgpu::CommandEncoderDescriptor {
+                        label: Some("Base Layer Copy"),
+                    },
+                );
+                copy_encoder.copy_texture_to_texture(
+                    wgpu::ImageCopyTexture {
+                        texture: layer_texture.texture(),
+                        mip_level: 0,
+                        origin: wgpu::Origin3d::ZERO,
+                        aspect: wgpu::TextureAspect::All,
+                    },
+                    wgpu::ImageCopyTexture {
+                        texture: base_texture.texture(),
+                        mip_level: 0,
+                        origin: wgpu::Origin3d { x: *x, y: *y, z: 0 },
+                        aspect: wgpu::TextureAspect::All,
+                    },
+                    layer_texture.texture().size(),
+                );
+                submit_gpu_command_safe(
+                    pipeline_arc.gpu_manager.queue(),
+                    once(copy_encoder.finish()),
+                    "base layer copy",
+                );
+                continue;
             }
-        }
 
-        let mut base_texture_mutable: Option<GpuTexture> = None;
+            let blend_properties = BLEND_PROPERTIES_CACHE
+                .get(blend_key)
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Blend properties not found for key: {}", blend_key)
+                })?
+                .value()
+                .clone();
 
-        if let Some((_first_layer_name, first_layer_texture)) = layer_textures.first() {
-            base_texture_mutable = Some(GpuTexture::new(
-                pipeline_arc.gpu_manager.device(),
-                first_layer_texture.texture().size().width,
-                first_layer_texture.texture().size().height,
-                first_layer_texture.format(),
+            remaining_layers.push((
+                layer_texture.as_ref(),
+                blend_properties.mode,
+                blend_properties.opacity,
+                (*x, *y),
             ));
-
-            let mut copy_encoder = pipeline_arc.gpu_manager.device().create_command_encoder(
-                &wgpu::CommandEncoderDescriptor {
-                    label: Some("Base Layer Copy"),
-                },
-            );
-            copy_encoder.copy_texture_to_texture(
-                wgpu::ImageCopyTexture {
-                    texture: &first_layer_texture.texture(),
-                    mip_level: 0,
-                    origin: wgpu::Origin3d::ZERO,
-                    aspect: wgpu::TextureAspect::All,
-                },
-                wgpu::ImageCopyTexture {
-                    texture: &base_texture_mutable
-                        .as_ref()
-                        .ok_or_else(|| anyhow::anyhow!("Base texture not available"))?
-                        .texture(),
-                    mip_level: 0,
-                    origin: wgpu::Origin3d::ZERO,
-                    aspect: wgpu::TextureAspect::All,
-                },
-                first_layer_texture.texture().size(),
-            );
-            submit_gpu_command_safe(
-                pipeline_arc.gpu_manager.queue(),
-                once(copy_encoder.finish()),
-                "base layer copy",
+            tracing::debug!(
+                "🎨 [GPU] Couche '{}' ajoutée au blending en lot",
+                layer_name
             );
         }
 
-        if layer_textures.len() > 1 {
-            tracing::info!(
-                "🚀 [GPU OPTIM] Préparation du blending en lot de {} couches",
-                layer_textures.len() - 1
-            );
-
-            let mut layer_textures_with_props: Vec<(Arc<GpuTexture>, BlendMode, f32)> = Vec::new();
-
-            for (layer_name, layer_texture) in layer_textures.iter().skip(1) {
-                let found_layer = valid_layers
-                    .iter()
-                    .find(|(l, _, _)| l == layer_name)
-                    .ok_or_else(|| {
-                        anyhow::anyhow!("Blend key not found for layer: {}", layer_name)
-                    })?;
-                let (_, blend_key, _) = found_layer;
-                let blend_key = format!("{}_{}_{}", current_set_id_arc, layer_name, blend_key);
-
-                let blend_properties = BLEND_PROPERTIES_CACHE
-                    .get(&blend_key)
-                    .ok_or_else(|| {
-                        anyhow::anyhow!("Blend properties not found for key: {}", blend_key)
-                    })?
-                    .value()
-                    .clone();
-
-                let final_texture = if blend_properties.offset_x != 0
-                    || blend_properties.offset_y != 0
-                {
-                    tracing::debug!(
-                        "🔄 [OFFSET] Applying offset X={}, Y={} to layer '{}'",
-                        blend_properties.offset_x,
-                        blend_properties.offset_y,
-                        layer_name
-                    );
-
-                    let image = layer_texture
-                        .to_image(
-                            pipeline_arc.gpu_manager.device(),
-                            pipeline_arc.gpu_manager.queue(),
-                        )
-                        .map_err(|e| anyhow::anyhow!("to_image failed: {}", e))?;
-                    let offset_image =
-                        apply_offset(&image, blend_properties.offset_x, blend_properties.offset_y);
-                    let new_texture = GpuTexture::from_image(
-                        pipeline_arc.gpu_manager.device(),
-                        pipeline_arc.gpu_manager.queue(),
-                        &offset_image,
-                    )
-                    .map_err(|e| anyhow::anyhow!("from_image failed: {}", e))?;
-                    Arc::new(new_texture)
-                } else {
-                    Arc::clone(layer_texture)
-                };
-
-                layer_textures_with_props.push((
-                    final_texture,
-                    blend_properties.mode,
-                    blend_properties.opacity,
-                ));
-                tracing::debug!(
-                    "🎨 [GPU] Couche '{}' ajoutée au blending en lot",
-                    layer_name
-                );
-            }
+        if !remaining_layers.is_empty() {
+            let start_time = Instant::now();
 
-            let remaining_layers: Vec<(&GpuTexture, BlendMode, f32)> = layer_textures_with_props
-                .iter()
-                .map(|(tex, mode, opacity)| (tex.as_ref(), *mode, *opacity))
-                .collect();
+            pipeline_arc
+                .blend_processor
+                .apply_multiple_blends_inplace(
+                    pipeline_arc.gpu_manager.device(),
+                    &mut base_texture,
+                    &remaining_layers,
+                    pipeline_arc.gpu_manager.queue(),
+                )
+                .map_err(|e| anyhow::anyhow!("GPU multiple blend failed: {}", e))?;
 
+            let blend_duration = start_time.elapsed();
+            MetricsUtils::record_stage(Stage::Blend, blend_duration);
             tracing::info!(
-                "🎯 [GPU] {} couches prêtes pour le blending en lot",
-                remaining_layers.len()
+                "⚡ [GPU OPTIM] Blending en lot de {} couches terminé en {:?}",
+                remaining_layers.len(),
+                blend_duration
             );
-
-            if let Some(ref mut base_mutable) = base_texture_mutable {
-                if !remaining_layers.is_empty() {
-                    let start_time = Instant::now();
-
-                    pipeline_arc
-                        .blend_processor
-                        .apply_multiple_blends_inplace(
-                            pipeline_arc.gpu_manager.device(),
-                            base_mutable,
-                            &remaining_layers,
-                            pipeline_arc.gpu_manager.queue(),
-                        )
-                        .map_err(|e| anyhow::anyhow!("GPU multiple blend failed: {}", e))?;
-
-                    pipeline_arc.gpu_manager.device().poll(wgpu::Maintain::Poll);
-
-                    let blend_duration = start_time.elapsed();
-                    tracing::info!(
-                        "⚡ [GPU OPTIM] Blending en lot de {} couches terminé en {:?}",
-                        remaining_layers.len(),
-                        blend_duration
-                    );
-                } else {
-                    tracing::info!("ℹ️ [GPU] Aucune couche supplémentaire à blender");
-                }
-            }
         }
 
-        let final_blended_texture = base_texture_mutable
-            .ok_or_else(|| anyhow::anyhow!("No final blended texture available"))?;
+        let final_blended_texture = base_texture;
 
         tracing::info!(
             "🎯 [GPU] Traitement terminé : {} couches traitées sur {} attendues",
@@ -847,13 +790,16 @@ fn process_static_single_gpu_blocking(
                     *final_height_arc,
                 );
 
-                pipeline_arc.resize_final_image(
+                let resize_start = Instant::now();
+                let resized = pipeline_arc.resize_final_image(
                     &final_blended_texture,
                     destination_texture.texture().size().width,
                     destination_texture.texture().size().height,
                     resize_config_arc.as_deref(),
                     &resize_gpu,
-                )?
+                )?;
+                MetricsUtils::record_stage(Stage::Resize, resize_start.elapsed());
+                resized
             } else {
                 tracing::info!(
                     "✅ [RESIZE] Skipping resize - dimensions unchanged ({}x{})",
@@ -863,14 +809,13 @@ fn process_static_single_gpu_blocking(
                 final_blended_texture
             };
 
-        let total_image_start = Instant::now();
-
         tracing::debug!("🔄 [GPU READBACK] Starting deferred texture readback");
         let readback_start = Instant::now();
 
         let final_image = pipeline_arc.texture_to_image_optimized(&final_texture)?;
 
         let readback_duration = readback_start.elapsed();
+        MetricsUtils::record_stage(Stage::Readback, readback_duration);
 
         if readback_duration.as_millis() > 1000 {
             tracing::error!(
@@ -889,124 +834,141 @@ fn process_static_single_gpu_blocking(
             );
         }
 
-        let output_path = images_path.join(format!(
-            "{}_{}.{}",
-            collection_name,
-            index + 1,
-            image_format
-        ));
+        Ok(final_image)
+    };
 
-        let save_start = Instant::now();
+    result
+}
 
-        tracing::info!(
-            "💾 [SAVE DIAGNOSTIC] Starting save for {}x{} image to {}",
-            final_image.width(),
-            final_image.height(),
-            output_path.display()
-        );
+fn process_static_single_gpu_blocking(
+    traits: &[NFTTrait],
+    active_layer_order: &[String],
+    input_folder: &Path,
+    base_width: u32,
+    base_height: u32,
+    final_width: u32,
+    final_height: u32,
+    rarity_config: &RarityConfig,
+    current_set_id: &str,
+    output_path: PathBuf,
+    image_format: &str,
+    index: u32,
+    resize_config: Option<&ResizeConfig>,
+) -> Result<()> {
+    let total_image_start = Instant::now();
+
+    let final_image = compose_static_image_blocking(
+        traits,
+        active_layer_order,
+        input_folder,
+        base_width,
+        base_height,
+        final_width,
+        final_height,
+        rarity_config,
+        current_set_id,
+        resize_config,
+    )?;
+
+    let (width, height) = final_image.dimensions();
+
+    let encode_start = Instant::now();
+    let (output, output_format) = output_image(final_image, image_format).map_err(|e| {
+        tracing::error!("⚠️ [SAVE] {}", e);
+        e
+    })?;
+    let mut encoded = Vec::new();
+    output
+        .write_to(&mut Cursor::new(&mut encoded), output_format)
+        .map_err(|e| anyhow::anyhow!("Failed to encode image: {}", e))?;
+    MetricsUtils::record_stage(Stage::Encode, encode_start.elapsed());
+
+    let save_start = Instant::now();
+
+    tracing::info!(
+        "💾 [SAVE DIAGNOSTIC] Starting save for {}x{} image to {}",
+        width,
+        height,
+        output_path.display()
+    );
 
-        let save_result = match image_format.to_lowercase().as_str() {
-            "png" => {
-                tracing::debug!("🖼️ [SAVE] Saving as PNG with fast compression...");
-                let rgb_image = DynamicImage::ImageRgba8(final_image.clone()).to_rgb8();
-                rgb_image.save_with_format(&output_path, ImageFormat::Png)
-            }
-            "jpg" | "jpeg" => {
-                tracing::debug!("🖼️ [SAVE] Saving as JPEG with optimized quality...");
-                let rgb_image = DynamicImage::ImageRgba8(final_image.clone()).to_rgb8();
-                rgb_image.save_with_format(&output_path, ImageFormat::Jpeg)
-            }
-            "webp" => {
-                tracing::debug!("🖼️ [SAVE] Saving as WebP with fast preset...");
-                final_image.save_with_format(&output_path, ImageFormat::WebP)
-            }
-            _ => {
-                tracing::error!("⚠️ [SAVE] Unsupported image format: {}", image_format);
-                return Err(anyhow::anyhow!(
-                    "Unsupported image format: {}",
-                    image_format
-                ));
-            }
-        };
+    let save_result = write(&output_path, &encoded);
 
-        let save_duration = save_start.elapsed();
+    let save_duration = save_start.elapsed();
+    MetricsUtils::record_stage(Stage::Save, save_duration);
 
-        if save_duration.as_millis() > 1000 {
-            tracing::error!(
-                "🚨 [SAVE DIAGNOSTIC] EXTREMELY SLOW SAVE: {:?} for {}x{} {} - THIS IS ABNORMAL!",
-                save_duration,
-                final_image.width(),
-                final_image.height(),
-                image_format
-            );
-        } else if save_duration.as_millis() > 200 {
-            tracing::warn!(
-                "🐌 [SAVE DIAGNOSTIC] Slow save: {:?} for {}x{} {} - could be optimized",
-                save_duration,
-                final_image.width(),
-                final_image.height(),
-                image_format
-            );
-        } else {
-            tracing::debug!(
-                "⚡ [SAVE DIAGNOSTIC] Fast save: {:?} for {}x{} {} - GOOD!",
-                save_duration,
-                final_image.width(),
-                final_image.height(),
-                image_format
-            );
-        }
+    if save_duration.as_millis() > 1000 {
+        tracing::error!(
+            "🚨 [SAVE DIAGNOSTIC] EXTREMELY SLOW SAVE: {:?} for {}x{} {} - THIS IS ABNORMAL!",
+            save_duration,
+            width,
+            height,
+            image_format
+        );
+    } else if save_duration.as_millis() > 200 {
+        tracing::warn!(
+            "🐌 [SAVE DIAGNOSTIC] Slow save: {:?} for {}x{} {} - could be optimized",
+            save_duration,
+            width,
+            height,
+            image_format
+        );
+    } else {
+        tracing::debug!(
+            "⚡ [SAVE DIAGNOSTIC] Fast save: {:?} for {}x{} {} - GOOD!",
+            save_duration,
+            width,
+            height,
+            image_format
+        );
+    }
 
-        match save_result {
-            Ok(_) => {
-                tracing::info!(
-                    "✅ [SAVE] Image saved successfully to {}",
-                    output_path.display()
-                );
-            }
-            Err(e) => {
-                tracing::error!(
-                    "❌ [SAVE] Failed to save image {}: {}",
-                    output_path.display(),
-                    e
-                );
-                return Err(anyhow::anyhow!("Failed to save image: {}", e));
-            }
+    match save_result {
+        Ok(_) => {
+            tracing::info!(
+                "✅ [SAVE] Image saved successfully to {}",
+                output_path.display()
+            );
         }
-
-        let total_image_duration = total_image_start.elapsed();
-
-        if total_image_duration.as_millis() > 5000 {
+        Err(e) => {
             tracing::error!(
-                "🚨 [TOTAL DIAGNOSTIC] EXTREMELY SLOW TOTAL: {:?} for image {} - THIS IS ABNORMAL!",
-                total_image_duration,
-                index + 1
-            );
-        } else if total_image_duration.as_millis() > 1000 {
-            tracing::warn!(
-                "🐌 [TOTAL DIAGNOSTIC] Slow total: {:?} for image {} - could be optimized",
-                total_image_duration,
-                index + 1
-            );
-        } else {
-            tracing::info!(
-                "⚡ [TOTAL DIAGNOSTIC] Fast total: {:?} for image {} - GOOD!",
-                total_image_duration,
-                index + 1
+                "❌ [SAVE] Failed to save image {}: {}",
+                output_path.display(),
+                e
             );
+            return Err(anyhow::anyhow!("Failed to save image: {}", e));
         }
+    }
 
+    let total_image_duration = total_image_start.elapsed();
+
+    if total_image_duration.as_millis() > 5000 {
+        tracing::error!(
+            "🚨 [TOTAL DIAGNOSTIC] EXTREMELY SLOW TOTAL: {:?} for image {} - THIS IS ABNORMAL!",
+            total_image_duration,
+            index + 1
+        );
+    } else if total_image_duration.as_millis() > 1000 {
+        tracing::warn!(
+            "🐌 [TOTAL DIAGNOSTIC] Slow total: {:?} for image {} - could be optimized",
+            total_image_duration,
+            index + 1
+        );
+    } else {
         tracing::info!(
-            "✅ [GPU] Processing completed successfully for image {} in {:?} (readback: {:?}, save: {:?})",
-            index + 1,
+            "⚡ [TOTAL DIAGNOSTIC] Fast total: {:?} for image {} - GOOD!",
             total_image_duration,
-            readback_duration,
-            save_duration
+            index + 1
         );
-        Ok(())
-    };
+    }
 
-    result
+    tracing::info!(
+        "✅ [GPU] Processing completed successfully for image {} in {:?} (save: {:?})",
+        index + 1,
+        total_image_duration,
+        save_duration
+    );
+    Ok(())
 }
 
 fn get_or_create_global_resize_texture(
@@ -1,5 +1,6 @@
 use std::{collections::HashMap, path::Path};
 
+use crate::filesystem::utils::normalize_path;
 use crate::generation::generate::{
     cache::{get_layer_files_cached, get_spritesheet_paths_cached},
     generate::GlobalGenerationCaches,
@@ -26,11 +27,17 @@ pub fn handle_layer_files(
             anyhow::anyhow!("Working folder is required for animated collections")
         })?;
         let spritesheet_paths = get_spritesheet_paths_cached(layer, working_folder)?;
+        let layer_dir = working_folder.join(layer);
 
         for spritesheet_path in spritesheet_paths {
-            if let Some(dir_name) = spritesheet_path.parent().and_then(|p| p.file_name()) {
-                let dir_name = dir_name.to_string_lossy().into_owned();
-                layer_files.push(dir_name);
+            let trait_dir = spritesheet_path
+                .parent()
+                .and_then(|p| p.strip_prefix(&layer_dir).ok());
+            if let Some(trait_dir) = trait_dir {
+                let key = normalize_path(&trait_dir.to_string_lossy());
+                if !layer_files.contains(&key) {
+                    layer_files.push(key);
+                }
             }
         }
 
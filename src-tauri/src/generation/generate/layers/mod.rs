@@ -1,4 +1,5 @@
 pub mod blend;
 pub mod handle_files;
+pub mod parts;
 pub mod traits_selection;
 pub mod unicity;
@@ -0,0 +1,88 @@
+use crate::{
+    filesystem::trait_files::{part_file_key, split_part},
+    types::{NFTTrait, RarityConfig},
+};
+
+/// One image of the final stack: the main file of a trait or one of its parts.
+pub struct RenderEntry<'a> {
+    pub layer: &'a str,
+    pub trait_data: &'a NFTTrait,
+    pub file_key: String,
+}
+
+/// Parts available for `file_key` among the files of its layer.
+pub fn trait_parts(files: &[String], file_key: &str) -> Vec<String> {
+    files
+        .iter()
+        .filter_map(|file| match split_part(file) {
+            (main_key, Some(part)) if main_key == file_key => Some(part.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Compositing order of the selected traits, bottom first. A trait counts as
+/// one attribute but contributes an entry for each of its parts, placed
+/// according to `LayerConfig::parts`.
+pub fn render_order<'a>(
+    active_layer_order: &'a [String],
+    traits: &'a [NFTTrait],
+    rarity_config: &RarityConfig,
+) -> Vec<RenderEntry<'a>> {
+    let layer_index =
+        |layer: &str| -> Option<usize> { active_layer_order.iter().position(|l| l == layer) };
+
+    // (anchor index, -1 below / 0 on / 1 above the anchor, entry)
+    let mut positioned: Vec<(usize, i8, RenderEntry<'a>)> = Vec::new();
+
+    for (index, layer) in active_layer_order.iter().enumerate() {
+        let Some(trait_data) = traits.iter().find(|t| t.trait_type == *layer) else {
+            continue;
+        };
+
+        positioned.push((
+            index,
+            0,
+            RenderEntry {
+                layer,
+                trait_data,
+                file_key: trait_data.file_key().to_string(),
+            },
+        ));
+
+        for part in &trait_data.parts {
+            let config = rarity_config
+                .layers
+                .get(layer)
+                .and_then(|l| l.parts.get(part));
+            let below = config
+                .and_then(|c| c.below_layer.as_deref())
+                .and_then(layer_index);
+            let above = config
+                .and_then(|c| c.above_layer.as_deref())
+                .and_then(layer_index);
+
+            let (anchor, side) = match (below, above) {
+                (Some(anchor), _) => (anchor, -1),
+                (None, Some(anchor)) => (anchor, 1),
+                (None, None) if part.starts_with("back") => (index, -1),
+                (None, None) => (index, 1),
+            };
+
+            positioned.push((
+                anchor,
+                side,
+                RenderEntry {
+                    layer,
+                    trait_data,
+                    file_key: part_file_key(trait_data.file_key(), part),
+                },
+            ));
+        }
+    }
+
+    // Stable, so parts sharing an anchor keep the order of their layers.
+    positioned.sort_by_key(|(anchor, side, _)| (*anchor, *side));
+
+    positioned.into_iter().map(|(_, _, entry)| entry).collect()
+}
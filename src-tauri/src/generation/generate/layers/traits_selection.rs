@@ -1,6 +1,9 @@
 use crate::{
+    filesystem::trait_files::{split_part, split_variant, trait_group},
     generation::generate::generate::GlobalGenerationCaches,
-    types::{ForcedCombinations, Incompatibilities, NFTTrait, RarityConfig},
+    types::{
+        ForcedCombinations, Incompatibilities, LayerConfig, NFTTrait, RarityConfig, TraitConfig,
+    },
 };
 use rand::prelude::*;
 use std::collections::{HashMap, HashSet};
@@ -213,21 +216,23 @@ pub fn calculate_adjusted_probabilities(
         return HashMap::new();
     }
 
-    let mut adjusted_probabilities = HashMap::new();
-    let enabled_traits: Vec<_> = layer_config
-        .unwrap()
-        .traits
-        .iter()
-        .filter(|(_, config)| config.sets.get(set_id).map_or(true, |s| s.enabled))
-        .collect();
-
-    let enabled_traits_clone = enabled_traits.clone();
-
-    for (trait_name, config) in enabled_traits {
-        if let Some(value) = config.sets.get(set_id).map(|s| s.value) {
-            adjusted_probabilities.insert(trait_name.clone(), value as f64);
-        }
-    }
+    let layer_config = layer_config.unwrap();
+    let base_values = apply_group_weights(
+        layer_config,
+        set_id,
+        layer_config
+            .traits
+            .iter()
+            .filter(|(_, config)| config.sets.get(set_id).map_or(true, |s| s.enabled))
+            .filter_map(|(trait_name, config)| {
+                config
+                    .sets
+                    .get(set_id)
+                    .map(|s| (trait_name.clone(), s.value as f64))
+            })
+            .collect(),
+    );
+    let mut adjusted_probabilities = base_values.clone();
 
     for (forcing_layer, forcing_traits) in &forced_combinations.forced_combinations {
         let is_forcing_layer_active = global_caches
@@ -282,18 +287,12 @@ pub fn calculate_adjusted_probabilities(
     }
 
     if forced_combinations.forced_combinations.is_empty() || total_probability == 0.0 {
-        let active_total_probability: f64 = enabled_traits_clone
-            .iter()
-            .map(|(_, config)| config.sets.get(set_id).map_or(0.0, |s| s.value as f64))
-            .sum();
+        let active_total_probability: f64 = base_values.values().sum();
 
         if active_total_probability > 0.0 {
-            return enabled_traits_clone
-                .iter()
-                .map(|(trait_name, config)| {
-                    let value = config.sets.get(set_id).map_or(0.0, |s| s.value as f64);
-                    (trait_name.to_string(), value / active_total_probability)
-                })
+            return base_values
+                .into_iter()
+                .map(|(trait_name, value)| (trait_name, value / active_total_probability))
                 .collect();
         }
     }
@@ -301,6 +300,76 @@ pub fn calculate_adjusted_probabilities(
     adjusted_probabilities
 }
 
+/// Redistributes trait values so that every subfolder with a configured weight
+/// gets exactly that weight, shared among its children in proportion to their
+/// own values. Subfolders without a weight count as the sum of their children.
+pub fn apply_group_weights(
+    layer_config: &LayerConfig,
+    set_id: &str,
+    values: HashMap<String, f64>,
+) -> HashMap<String, f64> {
+    let configured: HashMap<&str, f64> = layer_config
+        .groups
+        .iter()
+        .filter_map(|(group, config)| {
+            config
+                .sets
+                .get(set_id)
+                .map(|weight| (group.as_str(), *weight as f64))
+        })
+        .collect();
+
+    if configured.is_empty() {
+        return values;
+    }
+
+    let mut groups: Vec<String> = values
+        .keys()
+        .flat_map(|trait_name| {
+            trait_name
+                .match_indices('/')
+                .map(move |(index, _)| trait_name[..index].to_string())
+        })
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    groups.sort_by_key(|group| std::cmp::Reverse(group.matches('/').count()));
+
+    // Deepest groups first, so every subgroup is totalled before its parent.
+    let mut children_totals: HashMap<String, f64> = HashMap::new();
+    for (trait_name, value) in &values {
+        if let Some(group) = trait_group(trait_name) {
+            *children_totals.entry(group.to_string()).or_insert(0.0) += value;
+        }
+    }
+    for group in &groups {
+        let total = children_totals.get(group).copied().unwrap_or(0.0);
+        let weight = configured.get(group.as_str()).copied().unwrap_or(total);
+        if let Some(parent) = trait_group(group) {
+            *children_totals.entry(parent.to_string()).or_insert(0.0) += weight;
+        }
+    }
+
+    let mut scaled_values = HashMap::with_capacity(values.len());
+    for (trait_name, value) in values {
+        let mut scaled = value;
+        let mut group = trait_group(&trait_name);
+        while let Some(current) = group {
+            if let (Some(weight), Some(total)) =
+                (configured.get(current), children_totals.get(current))
+            {
+                if *total > 0.0 {
+                    scaled *= weight / total;
+                }
+            }
+            group = trait_group(current);
+        }
+        scaled_values.insert(trait_name, scaled);
+    }
+
+    scaled_values
+}
+
 pub fn check_equal_percentages(
     layer1: &str,
     trait1: &str,
@@ -371,13 +440,8 @@ pub fn select_random_with_rarity(
         .or_insert_with(|| {
             files
                 .iter()
-                .filter_map(|f| {
-                    if let Some(stem) = std::path::Path::new(f).file_stem() {
-                        Some(stem.to_string_lossy().into_owned())
-                    } else {
-                        Some(f.clone())
-                    }
-                })
+                .filter(|f| split_part(f).1.is_none())
+                .map(|f| split_variant(f).0.to_string())
                 .collect()
         });
 
@@ -394,10 +458,187 @@ pub fn select_random_with_rarity(
         }
     }
 
-    if !files.is_empty() {
-        let random_index = rng.gen_range(0..files.len());
-        return Some(files[random_index].clone());
+    let main_files: Vec<&String> = files.iter().filter(|f| split_part(f).1.is_none()).collect();
+    if !main_files.is_empty() {
+        let random_index = rng.gen_range(0..main_files.len());
+        return Some(split_variant(main_files[random_index]).0.to_string());
     }
 
     None
 }
+
+/// Picks the file of `trait_name` to render. A trait with `Name#Variant` files
+/// chooses among them by the weights in its config, or uniformly.
+pub fn select_variant(
+    files: &[String],
+    trait_name: &str,
+    trait_config: Option<&TraitConfig>,
+) -> Option<String> {
+    let candidates: Vec<(&String, Option<&str>)> = files
+        .iter()
+        .filter(|file| split_part(file).1.is_none())
+        .filter_map(|file| {
+            let (name, variant) = split_variant(file);
+            (name == trait_name).then_some((file, variant))
+        })
+        .collect();
+
+    let weights: Vec<f64> = candidates
+        .iter()
+        .map(|(_, variant)| {
+            variant
+                .and_then(|v| trait_config.and_then(|c| c.variants.get(v)))
+                .map_or(1.0, |weight| weight.max(0.0) as f64)
+        })
+        .collect();
+
+    let mut rng = thread_rng();
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 {
+        return candidates.choose(&mut rng).map(|(file, _)| (*file).clone());
+    }
+
+    let mut random_value = rng.gen_range(0.0..total);
+    for ((file, _), weight) in candidates.iter().zip(&weights) {
+        if random_value < *weight {
+            return Some((*file).clone());
+        }
+        random_value -= weight;
+    }
+
+    candidates.last().map(|(file, _)| (*file).clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn layer_config(groups: serde_json::Value) -> LayerConfig {
+        serde_json::from_value(json!({
+            "sets": {},
+            "traits": {},
+            "defaultBlend": { "mode": "source-over", "opacity": 1.0 },
+            "groups": groups,
+        }))
+        .unwrap()
+    }
+
+    fn values(entries: &[(&str, f64)]) -> HashMap<String, f64> {
+        entries
+            .iter()
+            .map(|(name, value)| (name.to_string(), *value))
+            .collect()
+    }
+
+    fn assert_close(actual: &HashMap<String, f64>, expected: &[(&str, f64)]) {
+        assert_eq!(actual.len(), expected.len());
+        for (name, value) in expected {
+            let got = actual[*name];
+            assert!((got - value).abs() < 1e-9, "{}: {} != {}", name, got, value);
+        }
+    }
+
+    #[test]
+    fn group_weight_is_shared_in_proportion_to_children() {
+        let config = layer_config(json!({ "Rare": { "sets": { "set1": 4.0 } } }));
+        let scaled = apply_group_weights(
+            &config,
+            "set1",
+            values(&[
+                ("Rare/A", 1.0),
+                ("Rare/B", 3.0),
+                ("Common/C", 5.0),
+                ("Top", 2.0),
+            ]),
+        );
+
+        assert_close(
+            &scaled,
+            &[
+                ("Rare/A", 1.0),
+                ("Rare/B", 3.0),
+                ("Common/C", 5.0),
+                ("Top", 2.0),
+            ],
+        );
+
+        let scaled = apply_group_weights(
+            &config,
+            "set1",
+            values(&[("Rare/A", 1.0), ("Rare/B", 1.0), ("Top", 2.0)]),
+        );
+        assert_close(&scaled, &[("Rare/A", 2.0), ("Rare/B", 2.0), ("Top", 2.0)]);
+    }
+
+    #[test]
+    fn nested_group_weights_apply_from_the_deepest_group() {
+        let config = layer_config(json!({
+            "Rare": { "sets": { "set1": 6.0 } },
+            "Rare/Gold": { "sets": { "set1": 1.0 } },
+        }));
+        let scaled = apply_group_weights(
+            &config,
+            "set1",
+            values(&[("Rare/Gold/X", 1.0), ("Rare/Gold/Y", 3.0), ("Rare/Z", 2.0)]),
+        );
+
+        // Gold weighs 1 inside Rare, next to Z's 2, and Rare as a whole weighs 6.
+        assert_close(
+            &scaled,
+            &[("Rare/Gold/X", 0.5), ("Rare/Gold/Y", 1.5), ("Rare/Z", 4.0)],
+        );
+    }
+
+    #[test]
+    fn group_weights_of_other_sets_are_ignored() {
+        let config = layer_config(json!({ "Rare": { "sets": { "set2": 10.0 } } }));
+        let input = values(&[("Rare/A", 1.0), ("Top", 2.0)]);
+
+        assert_eq!(apply_group_weights(&config, "set1", input.clone()), input);
+    }
+
+    fn trait_config(variants: serde_json::Value) -> TraitConfig {
+        serde_json::from_value(json!({ "sets": {}, "variants": variants })).unwrap()
+    }
+
+    fn files(keys: &[&str]) -> Vec<String> {
+        keys.iter().map(|key| key.to_string()).collect()
+    }
+
+    #[test]
+    fn variant_with_zero_weight_is_never_selected() {
+        let files = files(&["Shirt#Red", "Shirt#Blue", "Shirt__back", "Hat"]);
+        let config = trait_config(json!({ "Red": 0.0, "Blue": 1.0 }));
+
+        for _ in 0..200 {
+            assert_eq!(
+                select_variant(&files, "Shirt", Some(&config)).as_deref(),
+                Some("Shirt#Blue")
+            );
+        }
+    }
+
+    #[test]
+    fn variants_without_weights_are_picked_uniformly() {
+        let files = files(&["Shirt#Red", "Shirt#Blue", "Shirt__back"]);
+        let mut picked = HashSet::new();
+
+        for _ in 0..200 {
+            picked.insert(select_variant(&files, "Shirt", None).unwrap());
+        }
+
+        assert_eq!(
+            picked,
+            HashSet::from(["Shirt#Red".into(), "Shirt#Blue".into()])
+        );
+    }
+
+    #[test]
+    fn select_variant_only_considers_files_of_the_trait() {
+        let files = files(&["Hat", "Hats/Crown", "Shirt#Red"]);
+
+        assert_eq!(select_variant(&files, "Hat", None).as_deref(), Some("Hat"));
+        assert_eq!(select_variant(&files, "Cape", None), None);
+    }
+}
@@ -38,7 +38,9 @@ pub fn generate_dna(
 
             format!(
                 "{}:{}:{:.2}",
-                nft_trait.trait_type, nft_trait.value, trait_rarity
+                nft_trait.trait_type,
+                nft_trait.file_key(),
+                trait_rarity
             )
         })
         .collect::<Vec<_>>()
@@ -49,6 +51,11 @@ pub fn generate_dna(
     format!("{:x}", hasher.finalize())
 }
 
+/// Marks `dna` as taken, by an item a resumed run keeps.
+pub fn reserve_dna(dna: String) {
+    UNIQUE_DNA_SET.insert(dna, ());
+}
+
 pub fn is_unique_combination(
     nft_traits: &[NFTTrait],
     rarity_config: &RarityConfig,
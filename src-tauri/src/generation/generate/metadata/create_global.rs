@@ -4,6 +4,8 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_json::{from_str, to_string_pretty, Value};
 
+use crate::generation::generate::staging::is_partial_file;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GlobalMetadata {
     pub name: String,
@@ -45,7 +47,10 @@ pub fn create_global_metadata(
     for entry in fs::read_dir(metadata_folder)? {
         let entry = entry?;
         let path = entry.path();
-        if path.is_file() && path.extension().map_or(false, |ext| ext == "json") {
+        if path.is_file()
+            && path.extension().map_or(false, |ext| ext == "json")
+            && !is_partial_file(&path)
+        {
             metadata_files.push(path);
         }
     }
@@ -86,7 +91,9 @@ pub fn create_global_metadata(
     });
 
     let global_metadata_path = metadata_folder.join("_metadata.json");
-    fs::write(global_metadata_path, to_string_pretty(&global_metadata)?)?;
+    let temp_path = global_metadata_path.with_extension("json.tmp");
+    fs::write(&temp_path, to_string_pretty(&global_metadata)?)?;
+    fs::rename(&temp_path, &global_metadata_path)?;
 
     Ok(())
 }
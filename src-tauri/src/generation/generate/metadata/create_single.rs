@@ -1,3 +1,4 @@
+use super::display_names::{metadata_trait_type, metadata_value};
 use crate::types::{NFTTrait, RarityConfig, SolanaMetadataConfig};
 use anyhow::Result;
 use serde_json::{json, Map, Value};
@@ -36,8 +37,8 @@ pub fn generate_metadata(
             })
             .map(|trait_data| {
                 json!({
-                    "trait_type": trait_data.trait_type,
-                    "value": trait_data.value
+                    "trait_type": metadata_trait_type(rarity_config, &trait_data.trait_type),
+                    "value": metadata_value(rarity_config, &trait_data.trait_type, &trait_data.value)
                 })
             })
             .collect()
@@ -46,8 +47,8 @@ pub fn generate_metadata(
             .iter()
             .map(|trait_data| {
                 json!({
-                    "trait_type": trait_data.trait_type,
-                    "value": trait_data.value
+                    "trait_type": metadata_trait_type(rarity_config, &trait_data.trait_type),
+                    "value": metadata_value(rarity_config, &trait_data.trait_type, &trait_data.value)
                 })
             })
             .collect()
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+use crate::{filesystem::trait_files::trait_label, types::RarityConfig};
+
+/// `trait_type` written in metadata for a layer.
+pub fn metadata_trait_type<'a>(rarity_config: &'a RarityConfig, layer: &'a str) -> &'a str {
+    rarity_config
+        .layers
+        .get(layer)
+        .and_then(|layer_config| layer_config.trait_type.as_deref())
+        .filter(|name| !name.is_empty())
+        .unwrap_or(layer)
+}
+
+/// `value` written in metadata for a trait. Falls back to the trait name
+/// without its groups.
+pub fn metadata_value<'a>(
+    rarity_config: &'a RarityConfig,
+    layer: &str,
+    trait_name: &'a str,
+) -> &'a str {
+    rarity_config
+        .layers
+        .get(layer)
+        .and_then(|layer_config| layer_config.traits.get(trait_name))
+        .and_then(|trait_config| trait_config.display_name.as_deref())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| trait_label(trait_name))
+}
+
+/// Replaces the display names with their `locale` translation where one
+/// exists, so the metadata of a run is written in a single language.
+pub fn apply_locale(rarity_config: &mut RarityConfig, locale: &str) {
+    for layer_config in rarity_config.layers.values_mut() {
+        if let Some(trait_type) = layer_config.localized_trait_types.get(locale) {
+            layer_config.trait_type = Some(trait_type.clone());
+        }
+        for trait_config in layer_config.traits.values_mut() {
+            if let Some(name) = trait_config.localized_names.get(locale) {
+                trait_config.display_name = Some(name.clone());
+            }
+        }
+    }
+}
+
+/// Rarity defined in the config, averaged over the active sets, keyed by the
+/// `(trait_type, value)` pair found in metadata. Traits sharing a display
+/// name add up.
+pub fn defined_rarities(rarity_config: &RarityConfig) -> HashMap<(String, String), f64> {
+    let mut rarities: HashMap<(String, String), f64> = HashMap::new();
+
+    for (layer_name, layer_config) in &rarity_config.layers {
+        let trait_type = metadata_trait_type(rarity_config, layer_name);
+
+        for (trait_name, trait_config) in &layer_config.traits {
+            let values: Vec<f64> = trait_config
+                .sets
+                .iter()
+                .filter(|(set_id, _)| layer_config.sets.get(*set_id).map_or(false, |s| s.active))
+                .map(|(_, set_config)| set_config.value as f64)
+                .collect();
+            if values.is_empty() {
+                continue;
+            }
+
+            let value = metadata_value(rarity_config, layer_name, trait_name);
+            *rarities
+                .entry((trait_type.to_string(), value.to_string()))
+                .or_insert(0.0) += values.iter().sum::<f64>() / values.len() as f64;
+        }
+    }
+
+    rarities
+}
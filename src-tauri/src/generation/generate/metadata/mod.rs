@@ -1,2 +1,3 @@
 pub mod create_global;
 pub mod create_single;
+pub mod display_names;
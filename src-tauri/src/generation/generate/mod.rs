@@ -1,11 +1,14 @@
 pub mod cache;
+pub mod failures;
 pub mod generate;
 pub mod generate_single;
 pub mod layers;
 pub mod metadata;
 pub mod pausecancel;
 pub mod rarity;
+pub mod run_report;
 pub mod save_animation;
 pub mod shuffle;
+pub mod staging;
 pub mod task_manager;
 pub mod utils;
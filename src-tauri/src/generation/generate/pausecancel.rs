@@ -1,6 +1,9 @@
 use std::{
     path::PathBuf,
-    sync::atomic::{AtomicBool, Ordering},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     time::Duration,
 };
 
@@ -11,49 +14,109 @@ use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use tauri::{Emitter, WebviewWindow};
 use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
 use tracing;
 
 use crate::{
     filesystem::temp_dir::cleanup_old_temp_dirs,
     generation::{
         clean_up_contexts::cleanup_all_global_contexts,
-        generate::{
-            generate_single::file_watcher::stop_file_watcher,
-            task_manager::{
-                cancel_all_tasks, create_generation_session, get_current_session_token,
-            },
-            utils::clear_directory,
-        },
+        generate::{generate_single::file_watcher::stop_file_watcher, utils::clear_directory},
     },
 };
 
-pub static IS_PAUSED: AtomicBool = AtomicBool::new(false);
-
 pub static WINDOW: Lazy<ParkingMutex<Option<WebviewWindow>>> =
     Lazy::new(|| ParkingMutex::new(None));
 
-pub static EXPORT_FOLDER_PATH: Lazy<ParkingMutex<Option<PathBuf>>> =
+/// Session of the run in progress, the one the pause and cancel commands act on.
+static ACTIVE_SESSION: Lazy<ParkingMutex<Option<Arc<GenerationSession>>>> =
     Lazy::new(|| ParkingMutex::new(None));
 
-pub fn set_export_folder_path(export_folder: PathBuf) {
-    *EXPORT_FOLDER_PATH.lock() = Some(export_folder);
+/// Pause, stop and cancel state of one generation run.
+pub struct GenerationSession {
+    pub export_folder: PathBuf,
+    paused: AtomicBool,
+    /// Set by a cancel that keeps completed items. The run stops admitting new
+    /// items, lets the admitted ones finish and leaves a partial collection.
+    stop_requested: AtomicBool,
+    token: CancellationToken,
 }
 
-pub fn clear_export_folder_path() {
-    *EXPORT_FOLDER_PATH.lock() = None;
+/// Keeps a session active until dropped.
+pub struct ActiveSession(Arc<GenerationSession>);
+
+impl Drop for ActiveSession {
+    fn drop(&mut self) {
+        let mut active = ACTIVE_SESSION.lock();
+        if active
+            .as_ref()
+            .is_some_and(|session| Arc::ptr_eq(session, &self.0))
+        {
+            *active = None;
+        }
+    }
+}
+
+impl GenerationSession {
+    pub fn new(export_folder: PathBuf) -> Arc<Self> {
+        Arc::new(Self {
+            export_folder,
+            paused: AtomicBool::new(false),
+            stop_requested: AtomicBool::new(false),
+            token: CancellationToken::new(),
+        })
+    }
+
+    /// Makes this the session the commands act on.
+    pub fn activate(self: &Arc<Self>) -> ActiveSession {
+        *ACTIVE_SESSION.lock() = Some(self.clone());
+        ActiveSession(self.clone())
+    }
+
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    pub fn is_stop_requested(&self) -> bool {
+        self.stop_requested.load(Ordering::SeqCst)
+    }
+
+    pub async fn wait_for_pause(&self) -> Result<()> {
+        while self.paused.load(Ordering::SeqCst) {
+            sleep(Duration::from_millis(100)).await;
+            self.check_cancelled()?;
+        }
+        Ok(())
+    }
+
+    pub fn check_cancelled(&self) -> Result<()> {
+        if self.token.is_cancelled() {
+            Err(anyhow!("Operation cancelled"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+pub fn active_session() -> Option<Arc<GenerationSession>> {
+    ACTIVE_SESSION.lock().clone()
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum GenerationStatus {
+    Idle,
     Running,
     Paused,
+    Stopping,
     Cancelled,
     Error(String),
     Complete,
 }
 #[tauri::command]
 pub async fn toggle_generation_pause(is_paused: bool) -> Result<Value, String> {
-    IS_PAUSED.store(is_paused, Ordering::SeqCst);
+    if let Some(session) = active_session() {
+        session.paused.store(is_paused, Ordering::SeqCst);
+    }
 
     if let Some(window) = WINDOW.lock().as_ref() {
         let _ = window.emit(
@@ -70,8 +133,26 @@ pub async fn toggle_generation_pause(is_paused: bool) -> Result<Value, String> {
     }))
 }
 
+/// Cancels the run in progress. With `keep_completed`, items already written
+/// stay in the export folder with a matching `_metadata.json` instead of
+/// being cleared.
 #[tauri::command]
-pub async fn cancel_nft_generation() -> Result<(), String> {
+pub async fn cancel_nft_generation(keep_completed: Option<bool>) -> Result<(), String> {
+    match active_session() {
+        Some(session) => cancel_session(&session, keep_completed.unwrap_or(false)).await,
+        None => Ok(()),
+    }
+}
+
+pub async fn cancel_session(
+    session: &GenerationSession,
+    keep_completed: bool,
+) -> Result<(), String> {
+    if keep_completed {
+        stop_nft_generation(session);
+        return Ok(());
+    }
+
     if let Some(window) = WINDOW.lock().as_ref() {
         let _ = window.emit(
             "generation-cancelling",
@@ -83,7 +164,7 @@ pub async fn cancel_nft_generation() -> Result<(), String> {
         tracing::warn!("⚠️ [BACKEND] No window found, cannot emit generation-cancelling event");
     }
 
-    reset_all_states_and_cleanup().await;
+    reset_all_states_and_cleanup(session).await;
 
     if let Some(window) = WINDOW.lock().as_ref() {
         let _ = window.emit(
@@ -101,35 +182,42 @@ pub async fn cancel_nft_generation() -> Result<(), String> {
     Ok(())
 }
 
-pub async fn reset_all_states_and_cleanup() {
-    if let Err(e) = cancel_all_tasks().await {
-        tracing::error!("⚠️ [PAUSE_CANCEL] Failed to cancel save workers: {}", e);
-    } else {
-        tracing::info!("ℹ️ [PAUSE_CANCEL] All save workers cancelled successfully");
-    }
+fn stop_nft_generation(session: &GenerationSession) {
+    session.stop_requested.store(true, Ordering::SeqCst);
+    // A paused run has to resume for the admitted items to finish.
+    session.paused.store(false, Ordering::SeqCst);
 
-    if let Some(export_folder) = EXPORT_FOLDER_PATH.lock().as_ref() {
-        if let Err(e) = clear_directory(export_folder) {
-            tracing::error!(
-                "⚠️ [PAUSE_CANCEL] Failed to cleanup export folder {}: {}",
-                export_folder.display(),
-                e
-            );
-        } else {
-            tracing::info!("✅ [PAUSE_CANCEL] Export folder cleaned up successfully");
-        }
+    if let Some(window) = WINDOW.lock().as_ref() {
+        let _ = window.emit(
+            "generation-stopping",
+            json!({
+                "message": "Finishing the items in progress, completed items are kept"
+            }),
+        );
     }
 
-    let _ = create_generation_session();
+    tracing::info!("🛑 [PAUSE_CANCEL] Stop requested, keeping completed items");
+}
+
+pub async fn reset_all_states_and_cleanup(session: &GenerationSession) {
+    session.token.cancel();
+    session.paused.store(false, Ordering::SeqCst);
+    tracing::info!("ℹ️ [PAUSE_CANCEL] Session cancellation signal sent");
 
-    IS_PAUSED.store(false, Ordering::SeqCst);
+    if let Err(e) = clear_directory(&session.export_folder) {
+        tracing::error!(
+            "⚠️ [PAUSE_CANCEL] Failed to cleanup export folder {}: {}",
+            session.export_folder.display(),
+            e
+        );
+    } else {
+        tracing::info!("✅ [PAUSE_CANCEL] Export folder cleaned up successfully");
+    }
 
     if let Err(e) = cleanup_old_temp_dirs() {
         tracing::warn!("Warning: Failed to cleanup temp dirs: {}", e);
     }
 
-    clear_export_folder_path();
-
     tracing::info!("🧹 [PAUSE_CANCEL] Stopping grid file watcher...");
     stop_file_watcher();
 
@@ -139,26 +227,17 @@ pub async fn reset_all_states_and_cleanup() {
 
 #[tauri::command]
 pub async fn get_generation_status() -> Result<GenerationStatus, String> {
-    if get_current_session_token().await.is_cancelled() {
+    let Some(session) = active_session() else {
+        return Ok(GenerationStatus::Idle);
+    };
+    if session.token.is_cancelled() {
         return Ok(GenerationStatus::Cancelled);
     }
-    if IS_PAUSED.load(Ordering::SeqCst) {
+    if session.is_stop_requested() {
+        return Ok(GenerationStatus::Stopping);
+    }
+    if session.paused.load(Ordering::SeqCst) {
         return Ok(GenerationStatus::Paused);
     }
     Ok(GenerationStatus::Running)
 }
-
-pub async fn wait_for_pause() -> Result<()> {
-    while IS_PAUSED.load(Ordering::SeqCst) {
-        sleep(Duration::from_millis(100)).await;
-        check_cancelled().await?;
-    }
-    Ok(())
-}
-pub async fn check_cancelled() -> Result<()> {
-    if get_current_session_token().await.is_cancelled() {
-        Err(anyhow!("Operation cancelled"))
-    } else {
-        Ok(())
-    }
-}
@@ -10,7 +10,7 @@ use rust_xlsxwriter::Workbook;
 use serde::{Deserialize, Serialize};
 use serde_json::{from_str, Value};
 
-use crate::types::RarityConfig;
+use crate::{generation::generate::metadata::display_names::defined_rarities, types::RarityConfig};
 
 #[derive(Debug, Serialize, Deserialize)]
 struct RarityData {
@@ -67,31 +67,18 @@ pub fn create_rarity_files(
         }
     }
 
+    // Metadata holds display names, so the config is keyed the same way.
+    let defined_rarities = defined_rarities(rarity_config);
     let mut data: Vec<RarityData> = Vec::new();
 
     for (layer_name, layer_traits) in &all_traits {
         for (trait_name, count) in layer_traits {
             let effective_rarity = (*count as f64 / nft_count as f64) * 100.0;
 
-            let mut defined_rarity_total = 0.0;
-            let mut active_set_count = 0;
-
-            if let Some(layer_config) = rarity_config.layers.get(layer_name) {
-                if let Some(trait_config) = layer_config.traits.get(trait_name) {
-                    for (set_name, set_config) in &trait_config.sets {
-                        if layer_config.sets.get(set_name).map_or(false, |s| s.active) {
-                            defined_rarity_total += set_config.value as f64;
-                            active_set_count += 1;
-                        }
-                    }
-                }
-            }
-
-            let defined_rarity = if active_set_count > 0 {
-                defined_rarity_total / active_set_count as f64
-            } else {
-                0.0
-            };
+            let defined_rarity = defined_rarities
+                .get(&(layer_name.clone(), trait_name.clone()))
+                .copied()
+                .unwrap_or(0.0);
 
             data.push(RarityData {
                 layer: layer_name.clone(),
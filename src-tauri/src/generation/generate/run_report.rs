@@ -0,0 +1,469 @@
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use sysinfo::System;
+use tauri::{Emitter, Window};
+use tokio::{task::JoinHandle, time::interval};
+
+use crate::{
+    effects::core::gpu::common::gpu_texture_bytes,
+    generation::generate::{
+        failures::FailedItem,
+        generate_single::progress::EtaEstimator,
+        task_manager::{run_budget, MetricsUtils, StageTiming},
+    },
+    types::{GenerationResult, OrderedLayersSets},
+};
+
+const METRICS_INTERVAL: Duration = Duration::from_secs(1);
+const BYTES_PER_MB: u64 = 1024 * 1024;
+const PARTIAL_REPORT_FILE: &str = "partial.json";
+
+/// Counters shared by the workers of one generation run.
+pub struct RunMetrics {
+    pub started: Instant,
+    pub started_at: String,
+    pub total: u32,
+    /// Items a resumed run started with, left out of the throughput.
+    pub resumed: u32,
+    pub completed: AtomicU32,
+    pub failed: AtomicU32,
+    pub peak_ram: AtomicU64,
+    pub peak_vram: AtomicU64,
+    pub eta: Mutex<EtaEstimator>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationMetrics {
+    pub completed: u32,
+    pub failed: u32,
+    pub total: u32,
+    pub elapsed_ms: u64,
+    pub items_per_second: f64,
+    pub eta_ms: Option<u64>,
+    pub cpu_usage: f64,
+    pub ram_mb: u64,
+    pub vram_mb: u64,
+    pub memory_budget_used_mb: u32,
+    pub memory_budget_mb: u32,
+    pub stages: Vec<StageTiming>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetReport {
+    pub set_id: String,
+    pub count: u32,
+    pub duration_ms: u64,
+    pub items_per_second: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunReport {
+    pub success: bool,
+    pub error: Option<String>,
+    pub started_at: String,
+    pub finished_at: String,
+    pub duration_ms: u64,
+    pub total: u32,
+    pub generated: u32,
+    pub failed: u32,
+    pub recovered: u32,
+    pub items_per_second: f64,
+    pub workers: u32,
+    pub save_workers: u32,
+    pub memory_budget_mb: u32,
+    pub vram_budget_mb: Option<u32>,
+    pub peak_ram_mb: u64,
+    pub peak_vram_mb: u64,
+    pub sets: Vec<SetReport>,
+    pub stages: Vec<StageTiming>,
+}
+
+/// Editions a stopped run kept and the ones it did not generate. `sets`
+/// holds the kept items and missing editions of each set, a resumed run
+/// starts from them.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialReport {
+    pub total: u32,
+    pub completed: Vec<u32>,
+    pub missing: Vec<u32>,
+    #[serde(default)]
+    pub sets: HashMap<String, PartialSet>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialSet {
+    pub items: Vec<GenerationResult>,
+    pub missing: Vec<u32>,
+}
+
+/// Stops the periodic `generation-metrics` events when dropped.
+pub struct MetricsTicker(JoinHandle<()>);
+
+impl Drop for MetricsTicker {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+impl RunMetrics {
+    /// `completed` counts the items a resumed run starts with.
+    pub fn new(total: u32, completed: u32) -> Self {
+        MetricsUtils::reset_stage_timings();
+        Self {
+            started: Instant::now(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+            total,
+            resumed: completed,
+            completed: AtomicU32::new(completed),
+            failed: AtomicU32::new(0),
+            peak_ram: AtomicU64::new(0),
+            peak_vram: AtomicU64::new(0),
+            eta: Mutex::new(EtaEstimator::starting_at(completed)),
+        }
+    }
+
+    /// Items the run will produce, counting out those that failed for good.
+    pub fn estimated_count(&self) -> u32 {
+        self.total
+            .saturating_sub(self.failed.load(Ordering::Relaxed))
+    }
+
+    /// Smoothed time left for the items that are still running.
+    pub fn eta_ms(&self) -> Option<u64> {
+        let completed = self.completed.load(Ordering::Relaxed);
+        let remaining = self.estimated_count().saturating_sub(completed);
+        self.eta.lock().update(completed, remaining)
+    }
+
+    pub fn snapshot(&self, sys: &mut System) -> GenerationMetrics {
+        sys.refresh_cpu();
+        let ram = MetricsUtils::process_memory(sys);
+        let vram = gpu_texture_bytes();
+        self.peak_ram.fetch_max(ram, Ordering::Relaxed);
+        self.peak_vram.fetch_max(vram, Ordering::Relaxed);
+
+        let completed = self.completed.load(Ordering::Relaxed);
+        let failed = self.failed.load(Ordering::Relaxed);
+        let elapsed = self.started.elapsed();
+        let items_per_second =
+            MetricsUtils::calculate_throughput(completed.saturating_sub(self.resumed), elapsed);
+        let eta_ms = self.eta_ms();
+        let budget = run_budget();
+
+        GenerationMetrics {
+            completed,
+            failed,
+            total: self.total,
+            elapsed_ms: elapsed.as_millis() as u64,
+            items_per_second,
+            eta_ms,
+            cpu_usage: sys.global_cpu_info().cpu_usage().into(),
+            ram_mb: ram / BYTES_PER_MB,
+            vram_mb: vram / BYTES_PER_MB,
+            memory_budget_used_mb: budget.memory_used_mb(),
+            memory_budget_mb: budget.memory_budget_mb,
+            stages: MetricsUtils::stage_timings(),
+        }
+    }
+
+    pub fn report(
+        &self,
+        sets: Vec<SetReport>,
+        failures: &[FailedItem],
+        error: Option<String>,
+    ) -> RunReport {
+        let mut sys = System::new();
+        let last = self.snapshot(&mut sys);
+        let recovered = failures.iter().filter(|item| item.recovered).count() as u32;
+        let budget = run_budget();
+
+        RunReport {
+            success: error.is_none(),
+            error,
+            started_at: self.started_at.clone(),
+            finished_at: chrono::Utc::now().to_rfc3339(),
+            duration_ms: last.elapsed_ms,
+            total: self.total,
+            generated: last.completed,
+            failed: last.failed,
+            recovered,
+            items_per_second: last.items_per_second,
+            workers: budget.generation_workers,
+            save_workers: budget.save_workers,
+            memory_budget_mb: budget.memory_budget_mb,
+            vram_budget_mb: budget.vram_budget_mb,
+            peak_ram_mb: self.peak_ram.load(Ordering::Relaxed) / BYTES_PER_MB,
+            peak_vram_mb: self.peak_vram.load(Ordering::Relaxed) / BYTES_PER_MB,
+            sets,
+            stages: last.stages,
+        }
+    }
+}
+
+/// Emits a `generation-metrics` event every second until the ticker is dropped.
+pub fn spawn_metrics_ticker(window: Window, metrics: Arc<RunMetrics>) -> MetricsTicker {
+    MetricsTicker(tokio::spawn(async move {
+        let mut sys = System::new();
+        let mut ticks = interval(METRICS_INTERVAL);
+        loop {
+            ticks.tick().await;
+            let snapshot = metrics.snapshot(&mut sys);
+            let _ = window.emit("generation-metrics", snapshot);
+        }
+    }))
+}
+
+pub fn write_run_report(collection_folder: &Path, report: &RunReport) -> Result<()> {
+    let info_folder = collection_folder.join("collection infos");
+    fs::create_dir_all(&info_folder)?;
+
+    let path = info_folder.join("run_report.json");
+    fs::write(&path, serde_json::to_string_pretty(report)?)?;
+    tracing::info!(
+        "📊 [METRICS] Run report written to {} ({} generated in {} ms)",
+        path.display(),
+        report.generated,
+        report.duration_ms
+    );
+    Ok(())
+}
+
+/// Editions each set fills in a fresh run, the sets are numbered one after
+/// another.
+pub fn plan_editions(ordered_layers_sets: &OrderedLayersSets) -> HashMap<String, Vec<u32>> {
+    let mut next = 1;
+    ordered_layers_sets
+        .iter()
+        .map(|(set_id, set)| {
+            let editions = (next..next + set.nft_count).collect();
+            next += set.nft_count;
+            (set_id.clone(), editions)
+        })
+        .collect()
+}
+
+/// Items the stopped run kept and the editions each set still has to fill,
+/// both by set.
+pub type ResumePlan = (
+    HashMap<String, Vec<GenerationResult>>,
+    HashMap<String, Vec<u32>>,
+);
+
+/// Fails when the sets no longer match the stopped run.
+pub fn resume_plan(
+    partial: PartialReport,
+    ordered_layers_sets: &OrderedLayersSets,
+) -> Result<ResumePlan> {
+    let total = ordered_layers_sets
+        .values()
+        .map(|set| set.nft_count)
+        .sum::<u32>();
+    if partial.total != total || partial.sets.len() != ordered_layers_sets.len() {
+        return Err(anyhow::anyhow!(
+            "Cannot resume: the stopped run does not match the current sets ({} items, now {})",
+            partial.total,
+            total
+        ));
+    }
+
+    let mut kept = HashMap::new();
+    let mut planned = HashMap::new();
+    for (set_id, set) in partial.sets {
+        let fits = ordered_layers_sets.get(&set_id).is_some_and(|current| {
+            (set.items.len() + set.missing.len()) as u32 == current.nft_count
+        });
+        if !fits {
+            return Err(anyhow::anyhow!(
+                "Cannot resume: set \"{}\" no longer matches the stopped run",
+                set_id
+            ));
+        }
+        kept.insert(set_id.clone(), set.items);
+        planned.insert(set_id, set.missing);
+    }
+    Ok((kept, planned))
+}
+
+/// Writes the items each set kept and the editions of its plan that are
+/// still missing.
+pub fn write_partial_report(
+    collection_folder: &Path,
+    planned: &HashMap<String, Vec<u32>>,
+    mut items_by_set: HashMap<String, Vec<GenerationResult>>,
+    total: u32,
+) -> Result<()> {
+    let info_folder = collection_folder.join("collection infos");
+    fs::create_dir_all(&info_folder)?;
+
+    let sets: HashMap<String, PartialSet> = planned
+        .iter()
+        .map(|(set_id, editions)| {
+            let items = items_by_set.remove(set_id).unwrap_or_default();
+            let done: HashSet<u32> = items.iter().map(|nft| nft.original_index).collect();
+            let missing = editions
+                .iter()
+                .copied()
+                .filter(|edition| !done.contains(edition))
+                .collect();
+            (set_id.clone(), PartialSet { items, missing })
+        })
+        .collect();
+
+    let mut completed: Vec<u32> = sets
+        .values()
+        .flat_map(|set| set.items.iter().map(|nft| nft.original_index))
+        .collect();
+    completed.sort_unstable();
+    let mut missing: Vec<u32> = sets.values().flat_map(|set| set.missing.clone()).collect();
+    missing.sort_unstable();
+    let report = PartialReport {
+        total,
+        completed,
+        missing,
+        sets,
+    };
+
+    let path = info_folder.join(PARTIAL_REPORT_FILE);
+    fs::write(&path, serde_json::to_string_pretty(&report)?)?;
+    tracing::info!(
+        "📊 [METRICS] Partial collection report written to {} ({} of {} kept)",
+        path.display(),
+        report.completed.len(),
+        total
+    );
+    Ok(())
+}
+
+pub fn read_partial_report(collection_folder: &Path) -> Result<PartialReport> {
+    let path = collection_folder
+        .join("collection infos")
+        .join(PARTIAL_REPORT_FILE);
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("No stopped run to resume, {} is missing", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// Removes the partial report once the collection is complete.
+pub fn remove_partial_report(collection_folder: &Path) {
+    let path = collection_folder
+        .join("collection infos")
+        .join(PARTIAL_REPORT_FILE);
+    if path.exists() {
+        if let Err(e) = fs::remove_file(&path) {
+            tracing::warn!("⚠️ [METRICS] Failed to remove {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::OrderedLayersSet;
+
+    fn item(edition: u32) -> GenerationResult {
+        GenerationResult {
+            traits: Vec::new(),
+            original_index: edition,
+        }
+    }
+
+    fn sets(counts: &[(&str, u32)]) -> OrderedLayersSets {
+        counts
+            .iter()
+            .map(|(id, nft_count)| {
+                let set = OrderedLayersSet {
+                    id: id.to_string(),
+                    name: id.to_string(),
+                    custom_name: None,
+                    created_at: String::new(),
+                    layers: vec!["Background".to_string()],
+                    nft_count: *nft_count,
+                };
+                (id.to_string(), set)
+            })
+            .collect()
+    }
+
+    fn temp_collection(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("blendgine_{}_{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn partial_report_round_trips_the_kept_items() {
+        let collection = temp_collection("partial_report");
+        let planned = HashMap::from([
+            ("set1".to_string(), vec![1, 2, 3, 4]),
+            ("set2".to_string(), vec![5, 6]),
+        ]);
+        let items = HashMap::from([
+            ("set1".to_string(), vec![item(3), item(1)]),
+            ("set2".to_string(), vec![item(5)]),
+        ]);
+
+        write_partial_report(&collection, &planned, items, 6).unwrap();
+        let report = read_partial_report(&collection).unwrap();
+
+        assert_eq!(report.total, 6);
+        assert_eq!(report.completed, vec![1, 3, 5]);
+        assert_eq!(report.missing, vec![2, 4, 6]);
+        assert_eq!(report.sets["set1"].items.len(), 2);
+        assert_eq!(report.sets["set1"].missing, vec![2, 4]);
+        assert_eq!(report.sets["set2"].items[0].original_index, 5);
+        assert_eq!(report.sets["set2"].missing, vec![6]);
+
+        remove_partial_report(&collection);
+        assert!(read_partial_report(&collection).is_err());
+
+        fs::remove_dir_all(&collection).unwrap();
+    }
+
+    #[test]
+    fn resuming_a_two_set_run_keeps_each_set_on_its_own_editions() {
+        let collection = temp_collection("resume_two_sets");
+        let current = sets(&[("set1", 3), ("set2", 2)]);
+        let planned = plan_editions(&current);
+        let set1 = planned["set1"].clone();
+        let set2 = planned["set2"].clone();
+        let mut all: Vec<u32> = set1.iter().chain(&set2).copied().collect();
+        all.sort_unstable();
+        assert_eq!(all, vec![1, 2, 3, 4, 5]);
+
+        // The first run stopped after one item of each set.
+        let items = HashMap::from([
+            ("set1".to_string(), vec![item(set1[1])]),
+            ("set2".to_string(), vec![item(set2[0])]),
+        ]);
+        write_partial_report(&collection, &planned, items, 5).unwrap();
+
+        let partial = read_partial_report(&collection).unwrap();
+        let (kept, resumed) = resume_plan(partial, &current).unwrap();
+        assert_eq!(kept["set1"][0].original_index, set1[1]);
+        assert_eq!(kept["set2"][0].original_index, set2[0]);
+        assert_eq!(resumed["set1"], vec![set1[0], set1[2]]);
+        assert_eq!(resumed["set2"], vec![set2[1]]);
+
+        // Sets that changed since the stop cannot resume it.
+        let partial = read_partial_report(&collection).unwrap();
+        assert!(resume_plan(partial, &sets(&[("set1", 2), ("set2", 3)])).is_err());
+        let partial = read_partial_report(&collection).unwrap();
+        assert!(resume_plan(partial, &sets(&[("set1", 3), ("set3", 2)])).is_err());
+
+        fs::remove_dir_all(&collection).unwrap();
+    }
+}
@@ -1,2 +1,3 @@
+pub mod pipeline;
 pub mod save;
 pub mod spawn_worker;
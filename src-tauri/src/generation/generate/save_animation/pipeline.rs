@@ -0,0 +1,196 @@
+use anyhow::Result;
+use image::{DynamicImage, RgbaImage};
+use std::{
+    sync::{
+        mpsc::{sync_channel, SyncSender},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use wgpu::{Device, Queue};
+
+use crate::{
+    effects::core::{
+        gpu::resize_gpu::{ResizeConfig, ResizeGpu},
+        interpolate::{InterpolationBackend, InterpolationOptions},
+    },
+    generation::generate::{
+        generate_single::animated_single::spritesheets::spritesheet_blender::SpritesheetFrames,
+        save_animation::save::sink::FrameSink,
+        task_manager::{MetricsUtils, Stage},
+    },
+};
+
+pub const FRAME_QUEUE_CAPACITY: usize = 4;
+const CACHE_CLEANUP_INTERVAL: usize = 16;
+
+pub struct FrameInterpolator {
+    pub backend: InterpolationBackend,
+    pub options: InterpolationOptions,
+}
+
+pub struct FrameResizer {
+    pub resize_gpu: ResizeGpu,
+    pub device: Arc<Device>,
+    pub queue: Arc<Queue>,
+    pub width: u32,
+    pub height: u32,
+    pub resize_config: Option<ResizeConfig>,
+}
+
+impl FrameResizer {
+    fn resize(&self, frame: &DynamicImage) -> Result<DynamicImage> {
+        self.resize_gpu
+            .resize_image(
+                &self.device,
+                &self.queue,
+                frame,
+                self.width,
+                self.height,
+                &self.resize_config,
+            )
+            .map_err(|e| anyhow::anyhow!("Failed to resize frame: {}", e))
+    }
+}
+
+pub struct FramePipeline {
+    pub interpolator: Option<FrameInterpolator>,
+    pub resizer: Option<FrameResizer>,
+    pub frame_delay_ms: u32,
+    pub frame_durations_ms: Option<Vec<u32>>,
+}
+
+fn split_duration(duration_ms: u32, parts: u32) -> Vec<u32> {
+    let parts = parts.max(1);
+    (0..parts)
+        .map(|k| ((k + 1) * duration_ms) / parts - (k * duration_ms) / parts)
+        .collect()
+}
+
+impl FramePipeline {
+    pub fn output_dimensions(&self, source: &SpritesheetFrames) -> (u32, u32) {
+        match &self.resizer {
+            Some(resizer) => (resizer.width, resizer.height),
+            None => source.frame_dimensions(),
+        }
+    }
+
+    pub fn has_variable_timing(&self) -> bool {
+        self.frame_durations_ms.is_some()
+    }
+
+    pub fn output_frame_count(&self, source_frames: usize) -> usize {
+        match &self.interpolator {
+            Some(interpolator) if source_frames > 1 => {
+                source_frames + (source_frames - 1) * interpolator.options.factor as usize
+            }
+            _ => source_frames,
+        }
+    }
+
+    pub fn run<F>(self, source: SpritesheetFrames, open_sink: F) -> Result<u32>
+    where
+        F: FnOnce() -> Result<Box<dyn FrameSink>> + Send,
+    {
+        let (sender, receiver) = sync_channel::<(RgbaImage, u32)>(FRAME_QUEUE_CAPACITY);
+
+        thread::scope(|scope| {
+            let encoder_handle = scope.spawn(move || -> Result<u32> {
+                let mut sink = open_sink()?;
+                let mut frames_written = 0;
+                let mut encode_time = Duration::ZERO;
+                for (frame, duration_ms) in receiver {
+                    let encode_start = Instant::now();
+                    sink.write_frame(&frame, duration_ms)?;
+                    encode_time += encode_start.elapsed();
+                    frames_written += 1;
+                }
+                let encode_start = Instant::now();
+                sink.finish()?;
+                MetricsUtils::record_stage(Stage::Encode, encode_time + encode_start.elapsed());
+                Ok(frames_written)
+            });
+
+            let produce_result = self.produce(&source, &sender);
+            drop(sender);
+
+            let encode_result = encoder_handle
+                .join()
+                .map_err(|_| anyhow::anyhow!("Frame encoder thread panicked"))?;
+
+            match (encode_result, produce_result) {
+                (Err(e), _) => Err(e),
+                (Ok(_), Err(e)) => Err(e),
+                (Ok(frames_written), Ok(())) => Ok(frames_written),
+            }
+        })
+    }
+
+    fn sub_frame_durations(&self, index: usize, parts: u32) -> Vec<u32> {
+        match &self.frame_durations_ms {
+            Some(durations) => split_duration(durations[index], parts),
+            None => vec![self.frame_delay_ms; parts as usize],
+        }
+    }
+
+    fn produce(
+        &self,
+        source: &SpritesheetFrames,
+        sender: &SyncSender<(RgbaImage, u32)>,
+    ) -> Result<()> {
+        let mut previous: Option<DynamicImage> = None;
+        let frame_count = source.len();
+
+        source.for_each_frame(|index, frame| {
+            let current = DynamicImage::ImageRgba8(frame);
+            let mut duration_ms = self.sub_frame_durations(index, 1)[0];
+
+            if let Some(interpolator) = &self.interpolator {
+                let factor = interpolator.options.factor;
+
+                if let Some(previous) = &previous {
+                    let previous_durations = self.sub_frame_durations(index - 1, factor + 1);
+                    for f in 1..=factor {
+                        let alpha = f as f32 / (factor + 1) as f32;
+                        let interpolated = interpolator.backend.interpolate_single_pair(
+                            previous,
+                            &current,
+                            alpha,
+                            interpolator.options.method,
+                        )?;
+                        self.emit(interpolated, previous_durations[f as usize], sender)?;
+                    }
+                }
+
+                if index + 1 < frame_count {
+                    duration_ms = self.sub_frame_durations(index, factor + 1)[0];
+                }
+
+                if index > 0 && index % CACHE_CLEANUP_INTERVAL == 0 {
+                    interpolator.backend.smart_cache_cleanup();
+                }
+
+                previous = Some(current.clone());
+            }
+
+            self.emit(current, duration_ms, sender)
+        })
+    }
+
+    fn emit(
+        &self,
+        frame: DynamicImage,
+        duration_ms: u32,
+        sender: &SyncSender<(RgbaImage, u32)>,
+    ) -> Result<()> {
+        let frame = match &self.resizer {
+            Some(resizer) => resizer.resize(&frame)?,
+            None => frame,
+        };
+
+        sender
+            .send((frame.into_rgba8(), duration_ms))
+            .map_err(|_| anyhow::anyhow!("Frame encoder stopped before all frames were sent"))
+    }
+}
@@ -1,6 +1,8 @@
+pub mod save_apng;
 pub mod save_file;
 pub mod save_gif;
 pub mod save_mp4;
 pub mod save_webm;
 pub mod save_webp;
+pub mod sink;
 pub mod structs;
@@ -0,0 +1,54 @@
+use anyhow::{Context, Result};
+use image::RgbaImage;
+use png::{BitDepth, ColorType, Encoder, Writer};
+use std::{fs::File, io::BufWriter, path::Path};
+
+use crate::generation::generate::save_animation::save::{sink::FrameSink, structs::WorkerOptions};
+
+pub struct ApngSink {
+    writer: Writer<BufWriter<File>>,
+}
+
+impl FrameSink for ApngSink {
+    fn write_frame(&mut self, frame: &RgbaImage, duration_ms: u32) -> Result<()> {
+        self.writer
+            .set_frame_delay(duration_ms.min(u16::MAX as u32) as u16, 1000)
+            .context("Failed to set APNG frame delay")?;
+        self.writer
+            .write_image_data(frame.as_raw())
+            .context("Failed to encode APNG frame")
+    }
+
+    fn finish(self: Box<Self>) -> Result<()> {
+        self.writer.finish().context("Failed to finalize APNG")
+    }
+}
+
+pub fn open_apng_sink(
+    output_path: &Path,
+    width: u32,
+    height: u32,
+    frame_count: usize,
+    options: &WorkerOptions,
+) -> Result<ApngSink> {
+    let autoloop = options
+        .quality_config
+        .as_ref()
+        .map(|quality_config| quality_config.format_specific_settings.apng.autoloop)
+        .unwrap_or(true);
+
+    let file = File::create(output_path).context("Failed to create APNG file")?;
+
+    let mut encoder = Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(ColorType::Rgba);
+    encoder.set_depth(BitDepth::Eight);
+    encoder
+        .set_animated(frame_count as u32, if autoloop { 0 } else { 1 })
+        .context("Failed to configure APNG animation")?;
+
+    let writer = encoder
+        .write_header()
+        .context("Failed to write APNG header")?;
+
+    Ok(ApngSink { writer })
+}
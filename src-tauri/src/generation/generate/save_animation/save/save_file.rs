@@ -1,17 +1,87 @@
 use anyhow::{Context, Result};
-use image::DynamicImage;
-use std::path::Path;
+use std::{path::Path, thread::sleep, time::Duration};
 use tokio::fs::create_dir_all;
 
-use crate::generation::generate::{
-    save_animation::save::{
-        save_gif::save_gif_animation, save_mp4::save_mp4_animation, save_webm::save_webm_animation,
-        save_webp::save_webp_animation, structs::WorkerOptions,
+use crate::{
+    ffmpeg_wrapper::RawVideoEncoder,
+    filesystem::utils::ensure_file_ready,
+    generation::generate::{
+        generate_single::animated_single::spritesheets::spritesheet_blender::SpritesheetFrames,
+        save_animation::{
+            pipeline::FramePipeline,
+            save::{
+                save_apng::open_apng_sink,
+                save_gif::{open_gif_sink, open_gif_stream},
+                save_mp4::open_mp4_stream,
+                save_webm::open_webm_stream,
+                save_webp::{open_webp_sink, open_webp_stream},
+                sink::{ConstantRateSink, FrameSink},
+                structs::WorkerOptions,
+            },
+        },
+        task_manager::spawn_save_task,
     },
-    task_manager::spawn_save_task,
 };
 
-pub async fn save_animation(frames: &[DynamicImage], options: &WorkerOptions) -> Result<()> {
+fn open_animation_stream(
+    format: &str,
+    output_path: &Path,
+    width: u32,
+    height: u32,
+    options: &WorkerOptions,
+) -> Result<RawVideoEncoder> {
+    match format {
+        "gif" => open_gif_stream(output_path, width, height, options),
+        "webp" => open_webp_stream(output_path, width, height, options),
+        "mp4" => open_mp4_stream(output_path, width, height, options),
+        "webm" => open_webm_stream(output_path, width, height, options),
+        _ => Err(anyhow::anyhow!("Unsupported format: {}", format)),
+    }
+}
+
+fn open_animation_sink(
+    format: &str,
+    output_path: &Path,
+    width: u32,
+    height: u32,
+    frame_count: usize,
+    variable_timing: bool,
+    options: &WorkerOptions,
+) -> Result<Box<dyn FrameSink>> {
+    match format {
+        "apng" => Ok(Box::new(open_apng_sink(
+            output_path,
+            width,
+            height,
+            frame_count,
+            options,
+        )?)),
+        "gif" if variable_timing => Ok(Box::new(open_gif_sink(output_path, options)?)),
+        "webp" if variable_timing => Ok(Box::new(open_webp_sink(
+            output_path,
+            width,
+            height,
+            options,
+        )?)),
+        _ => {
+            if variable_timing {
+                tracing::info!(
+                    "⏱️ [SAVE] {} has no per-frame delays, resampling timeline to {} fps",
+                    format,
+                    options.fps()
+                );
+            }
+            let encoder = open_animation_stream(format, output_path, width, height, options)?;
+            Ok(Box::new(ConstantRateSink::new(encoder, options.fps())))
+        }
+    }
+}
+
+pub async fn save_animation(
+    source: SpritesheetFrames,
+    pipeline: FramePipeline,
+    options: &WorkerOptions,
+) -> Result<()> {
     let format = options.format.clone().unwrap_or_else(|| "webp".to_string());
     let output_path = Path::new(&options.output_path);
 
@@ -21,10 +91,8 @@ pub async fn save_animation(frames: &[DynamicImage], options: &WorkerOptions) ->
             .context("Failed to create output directory")?;
     }
 
-    let frames_clone = frames.to_vec();
     let options_clone = options.clone();
     let output_path_clone = output_path.to_path_buf();
-    let format_clone = format.clone();
     let task_id = format!(
         "save_anim_{}",
         output_path
@@ -33,13 +101,34 @@ pub async fn save_animation(frames: &[DynamicImage], options: &WorkerOptions) ->
             .to_string_lossy()
     );
     let handle = spawn_save_task(task_id.clone(), move || {
-        let save_result = match format_clone.as_str() {
-            "gif" => save_gif_animation(&frames_clone, &output_path_clone, &options_clone),
-            "webp" => save_webp_animation(&frames_clone, &output_path_clone, &options_clone),
-            "mp4" => save_mp4_animation(&frames_clone, &output_path_clone, &options_clone),
-            "webm" => save_webm_animation(&frames_clone, &output_path_clone, &options_clone),
-            _ => Err(anyhow::anyhow!("Unsupported format: {}", format_clone)),
-        };
+        let (width, height) = pipeline.output_dimensions(&source);
+        let expected_frames = pipeline.output_frame_count(source.len());
+        let variable_timing = pipeline.has_variable_timing();
+
+        let save_result = pipeline
+            .run(source, || {
+                open_animation_sink(
+                    &format,
+                    &output_path_clone,
+                    width,
+                    height,
+                    expected_frames,
+                    variable_timing,
+                    &options_clone,
+                )
+            })
+            .and_then(|frames_written| {
+                if frames_written as usize != expected_frames {
+                    return Err(anyhow::anyhow!(
+                        "Encoded {} frames but expected {}",
+                        frames_written,
+                        expected_frames
+                    ));
+                }
+                ensure_file_ready(&output_path_clone)?;
+                sleep(Duration::from_millis(200));
+                Ok(())
+            });
 
         match save_result {
             Ok(_) => {
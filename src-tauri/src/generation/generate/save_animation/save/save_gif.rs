@@ -1,36 +1,75 @@
 use anyhow::{Context, Result};
-use image::DynamicImage;
-use std::{path::Path, thread::sleep, time::Duration};
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    Delay, Frame, RgbaImage,
+};
+use std::{fs::File, io::BufWriter, path::Path};
 
 use crate::{
-    ffmpeg_wrapper::FFmpegWrapper, filesystem::utils::ensure_file_ready,
-    generation::generate::save_animation::save::structs::WorkerOptions,
+    ffmpeg_wrapper::{FFmpegWrapper, RawVideoEncoder},
+    generation::generate::save_animation::save::{sink::FrameSink, structs::WorkerOptions},
 };
 
-pub fn save_gif_animation(
-    frames: &[DynamicImage],
+const GIF_ENCODER_SPEED: i32 = 10;
+
+pub struct GifSink {
+    encoder: GifEncoder<BufWriter<File>>,
+}
+
+impl FrameSink for GifSink {
+    fn write_frame(&mut self, frame: &RgbaImage, duration_ms: u32) -> Result<()> {
+        let delay = Delay::from_numer_denom_ms(duration_ms, 1);
+        self.encoder
+            .encode_frame(Frame::from_parts(frame.clone(), 0, 0, delay))
+            .context("Failed to encode GIF frame")
+    }
+
+    fn finish(self: Box<Self>) -> Result<()> {
+        // The GIF trailer is written when the encoder is dropped
+        drop(self.encoder);
+        Ok(())
+    }
+}
+
+pub fn open_gif_sink(output_path: &Path, options: &WorkerOptions) -> Result<GifSink> {
+    let autoloop = options
+        .quality_config
+        .as_ref()
+        .map(|quality_config| quality_config.format_specific_settings.gif.autoloop)
+        .unwrap_or(true);
+
+    let file = File::create(output_path).context("Failed to create GIF file")?;
+    let mut encoder = GifEncoder::new_with_speed(BufWriter::new(file), GIF_ENCODER_SPEED);
+    encoder
+        .set_repeat(if autoloop {
+            Repeat::Infinite
+        } else {
+            Repeat::Finite(0)
+        })
+        .context("Failed to set GIF loop mode")?;
+
+    Ok(GifSink { encoder })
+}
+
+pub fn open_gif_stream(
     output_path: &Path,
+    width: u32,
+    height: u32,
     options: &WorkerOptions,
-) -> Result<()> {
-    let fps = 1000.0 / options.delay as f32;
-
+) -> Result<RawVideoEncoder> {
     let ffmpeg = FFmpegWrapper::new().context("Failed to initialize FFmpeg")?;
 
     ffmpeg
-        .encode_animation_direct(
-            frames,
+        .open_animation_stream(
             output_path,
             "gif",
-            fps,
+            width,
+            height,
+            options.fps(),
             None,
             options.optimize,
             None,
             None,
         )
-        .context("Failed to encode GIF with FFmpeg")?;
-
-    ensure_file_ready(output_path)?;
-    sleep(Duration::from_millis(200));
-
-    Ok(())
+        .context("Failed to open GIF stream with FFmpeg")
 }
@@ -1,42 +1,37 @@
 use anyhow::{Context, Result};
-use image::DynamicImage;
-use std::{path::Path, thread::sleep, time::Duration};
+use std::path::Path;
 
 use crate::{
-    ffmpeg_wrapper::FFmpegWrapper, filesystem::utils::ensure_file_ready,
+    ffmpeg_wrapper::{FFmpegWrapper, RawVideoEncoder},
     generation::generate::save_animation::save::structs::WorkerOptions,
 };
 
-pub fn save_mp4_animation(
-    frames: &[DynamicImage],
+pub fn open_mp4_stream(
     output_path: &Path,
+    width: u32,
+    height: u32,
     options: &WorkerOptions,
-) -> Result<()> {
+) -> Result<RawVideoEncoder> {
     let quality_config = options
         .quality_config
         .as_ref()
         .ok_or_else(|| anyhow::anyhow!("Quality config required for MP4"))?;
 
     let settings = &quality_config.format_specific_settings.mp4;
-    let fps = 1000.0 / options.delay as f32;
 
     let ffmpeg = FFmpegWrapper::new().context("Failed to initialize FFmpeg")?;
 
     ffmpeg
-        .encode_animation_direct(
-            frames,
+        .open_animation_stream(
             output_path,
             "mp4",
-            fps,
+            width,
+            height,
+            options.fps(),
             Some(settings.quality.try_into().unwrap()),
             options.optimize,
             None,
             None,
         )
-        .context("Failed to encode MP4 with FFmpeg")?;
-
-    ensure_file_ready(output_path)?;
-    sleep(Duration::from_millis(200));
-
-    Ok(())
+        .context("Failed to open MP4 stream with FFmpeg")
 }
@@ -1,41 +1,108 @@
 use anyhow::{Context, Result};
-use image::DynamicImage;
-use std::{path::Path, thread::sleep, time::Duration};
+use image::RgbaImage;
+use std::{fs, path::Path, path::PathBuf};
+use webp_animation::{
+    AnimParams, Encoder, EncoderOptions, EncodingConfig, EncodingType, LossyEncodingConfig,
+};
 
 use crate::{
-    ffmpeg_wrapper::FFmpegWrapper, filesystem::utils::ensure_file_ready,
-    generation::generate::save_animation::save::structs::WorkerOptions,
+    ffmpeg_wrapper::{FFmpegWrapper, RawVideoEncoder},
+    generation::generate::save_animation::save::{sink::FrameSink, structs::WorkerOptions},
 };
-pub fn save_webp_animation(
-    frames: &[DynamicImage],
+
+pub struct WebPSink {
+    encoder: Encoder,
+    output_path: PathBuf,
+    timestamp_ms: i32,
+}
+
+impl FrameSink for WebPSink {
+    fn write_frame(&mut self, frame: &RgbaImage, duration_ms: u32) -> Result<()> {
+        self.encoder
+            .add_frame(frame.as_raw(), self.timestamp_ms)
+            .map_err(|e| anyhow::anyhow!("Failed to encode WebP frame: {:?}", e))?;
+        self.timestamp_ms += duration_ms as i32;
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<()> {
+        let data = self
+            .encoder
+            .finalize(self.timestamp_ms)
+            .map_err(|e| anyhow::anyhow!("Failed to finalize WebP: {:?}", e))?;
+
+        fs::write(&self.output_path, &*data).context("Failed to write WebP file")
+    }
+}
+
+pub fn open_webp_sink(
+    output_path: &Path,
+    width: u32,
+    height: u32,
+    options: &WorkerOptions,
+) -> Result<WebPSink> {
+    let quality_config = options
+        .quality_config
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Quality config required for WebP"))?;
+
+    let settings = &quality_config.format_specific_settings.webp;
+
+    let encoding_type = if settings.lossless {
+        EncodingType::Lossless
+    } else {
+        EncodingType::Lossy(LossyEncodingConfig::default())
+    };
+
+    let encoder_options = EncoderOptions {
+        anim_params: AnimParams {
+            loop_count: if settings.autoloop { 0 } else { 1 },
+        },
+        minimize_size: options.optimize,
+        encoding_config: Some(EncodingConfig {
+            encoding_type,
+            quality: settings.quality.min(100) as f32,
+            method: settings.method.min(6) as usize,
+        }),
+        ..Default::default()
+    };
+
+    let encoder = Encoder::new_with_options((width, height), encoder_options)
+        .map_err(|e| anyhow::anyhow!("Failed to create WebP encoder: {:?}", e))?;
+
+    Ok(WebPSink {
+        encoder,
+        output_path: output_path.to_path_buf(),
+        timestamp_ms: 0,
+    })
+}
+
+pub fn open_webp_stream(
     output_path: &Path,
+    width: u32,
+    height: u32,
     options: &WorkerOptions,
-) -> Result<()> {
+) -> Result<RawVideoEncoder> {
     let quality_config = options
         .quality_config
         .as_ref()
         .ok_or_else(|| anyhow::anyhow!("Quality config required for WebP"))?;
 
     let settings = &quality_config.format_specific_settings.webp;
-    let fps = 1000.0 / options.delay as f32;
 
     let ffmpeg = FFmpegWrapper::new().context("Failed to initialize FFmpeg")?;
 
     ffmpeg
-        .encode_animation_direct(
-            frames,
+        .open_animation_stream(
             output_path,
             "webp",
-            fps,
+            width,
+            height,
+            options.fps(),
             Some(settings.quality.try_into().unwrap()),
             options.optimize,
             None,
             None,
         )
-        .context("Failed to encode WebP with FFmpeg")?;
-
-    ensure_file_ready(output_path)?;
-    sleep(Duration::from_millis(200));
-
-    Ok(())
+        .context("Failed to open WebP stream with FFmpeg")
 }
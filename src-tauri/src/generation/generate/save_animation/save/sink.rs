@@ -0,0 +1,49 @@
+use anyhow::Result;
+use image::RgbaImage;
+
+use crate::ffmpeg_wrapper::RawVideoEncoder;
+
+pub trait FrameSink {
+    fn write_frame(&mut self, frame: &RgbaImage, duration_ms: u32) -> Result<()>;
+    fn finish(self: Box<Self>) -> Result<()>;
+}
+
+pub struct ConstantRateSink {
+    encoder: RawVideoEncoder,
+    frame_interval_ms: f64,
+    elapsed_ms: f64,
+    frames_emitted: u64,
+}
+
+impl ConstantRateSink {
+    pub fn new(encoder: RawVideoEncoder, fps: f32) -> Self {
+        Self {
+            encoder,
+            frame_interval_ms: 1000.0 / fps.max(0.001) as f64,
+            elapsed_ms: 0.0,
+            frames_emitted: 0,
+        }
+    }
+}
+
+impl FrameSink for ConstantRateSink {
+    fn write_frame(&mut self, frame: &RgbaImage, duration_ms: u32) -> Result<()> {
+        self.elapsed_ms += duration_ms as f64;
+
+        let mut target_frames = (self.elapsed_ms / self.frame_interval_ms).round() as u64;
+        if self.frames_emitted == 0 {
+            target_frames = target_frames.max(1);
+        }
+
+        while self.frames_emitted < target_frames {
+            self.encoder.write_frame(frame)?;
+            self.frames_emitted += 1;
+        }
+
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<()> {
+        self.encoder.finish()
+    }
+}
@@ -1,9 +1,7 @@
 use crate::{effects::core::gpu::resize_gpu::ResizeConfig, types::AnimationQualityConfig};
-use image::DynamicImage;
 
 #[derive(Debug, Clone)]
 pub struct WorkerOptions {
-    pub frames: Vec<DynamicImage>,
     pub output_path: String,
     pub width: u32,
     pub height: u32,
@@ -12,4 +10,11 @@ pub struct WorkerOptions {
     pub format: Option<String>,
     pub quality_config: Option<AnimationQualityConfig>,
     pub resize_config: Option<ResizeConfig>,
+    pub frame_durations_ms: Option<Vec<u32>>,
+}
+
+impl WorkerOptions {
+    pub fn fps(&self) -> f32 {
+        1000.0 / self.delay.max(1) as f32
+    }
 }
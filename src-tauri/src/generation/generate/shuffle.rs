@@ -11,8 +11,10 @@ pub fn shuffle_and_rename(
     image_format: &str,
     all_generated_nfts: &mut [GenerationResult],
     include_spritesheets: bool,
+    seed: u64,
+    on_renamed: &mut dyn FnMut(u32, &GenerationResult, &Path),
 ) -> Result<()> {
-    let mut rng = StdRng::from_entropy();
+    let mut rng = StdRng::seed_from_u64(seed);
     all_generated_nfts.shuffle(&mut rng);
 
     let temp_dir = export_folder.join("temp");
@@ -124,7 +126,7 @@ pub fn shuffle_and_rename(
         }
     }
 
-    for (i, _) in all_generated_nfts.iter().enumerate() {
+    for (i, nft) in all_generated_nfts.iter().enumerate() {
         let new_index = i + 1;
 
         let temp_image = temp_dir.join(format!(
@@ -150,6 +152,8 @@ pub fn shuffle_and_rename(
         if include_spritesheets && fs::metadata(&temp_sprite).is_ok() {
             fs::rename(&temp_sprite, &final_sprite)?;
         }
+
+        on_renamed(new_index as u32, nft, &final_image);
     }
 
     fs::remove_dir_all(&temp_dir)?;
@@ -0,0 +1,76 @@
+use anyhow::{Context, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use walkdir::WalkDir;
+
+/// Prefix of files an item writes before it completes. The extension is
+/// kept so encoders still pick the format from the name.
+pub const PARTIAL_PREFIX: &str = ".partial_";
+
+pub fn is_partial_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with(PARTIAL_PREFIX))
+}
+
+/// Files of one item, written under temporary names and renamed together
+/// once the item is complete. Dropped without `commit`, it removes them, so
+/// an interrupted item leaves nothing behind.
+#[derive(Default)]
+pub struct StagedFiles {
+    files: Vec<(PathBuf, PathBuf)>,
+    committed: bool,
+}
+
+impl StagedFiles {
+    /// Registers `path` and returns the temporary path to write it to.
+    pub fn stage(&mut self, path: PathBuf) -> PathBuf {
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let partial = path.with_file_name(format!("{}{}", PARTIAL_PREFIX, file_name));
+        self.files.push((partial.clone(), path));
+        partial
+    }
+
+    pub fn commit(mut self) -> Result<()> {
+        for (partial, path) in &self.files {
+            fs::rename(partial, path)
+                .with_context(|| format!("Failed to move {} into place", path.display()))?;
+        }
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for StagedFiles {
+    fn drop(&mut self) {
+        if self.committed {
+            return;
+        }
+        for (partial, path) in &self.files {
+            // Files an earlier rename of this item already moved are removed too.
+            for file in [partial, path] {
+                if file.exists() {
+                    if let Err(e) = fs::remove_file(file) {
+                        tracing::warn!("⚠️ [STAGING] Failed to remove {}: {}", file.display(), e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Removes temporary files that outlived their item, such as encoder output
+/// still being written when the item was cancelled.
+pub fn remove_partial_files(folder: &Path) -> usize {
+    WalkDir::new(folder)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file() && is_partial_file(entry.path()))
+        .filter(|entry| fs::remove_file(entry.path()).is_ok())
+        .count()
+}
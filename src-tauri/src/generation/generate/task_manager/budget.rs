@@ -0,0 +1,197 @@
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{
+    generation::generate::task_manager::memory::{acquire_memory, default_memory_budget_mb},
+    types::ResourceLimits,
+};
+
+/// Items admitted per generation worker, so a worker always has the next
+/// item ready without the whole set being in memory.
+const ITEMS_PER_WORKER: u32 = 2;
+
+static RUN_BUDGET: Lazy<RwLock<Arc<RunBudget>>> =
+    Lazy::new(|| RwLock::new(Arc::new(RunBudget::new(&ResourceLimits::default()))));
+
+/// Worker counts and memory budgets of the current run.
+pub struct RunBudget {
+    pub generation_workers: u32,
+    pub save_workers: u32,
+    pub memory_budget_mb: u32,
+    pub vram_budget_mb: Option<u32>,
+    pub generation: Arc<Semaphore>,
+    pub save: Arc<Semaphore>,
+    items: Arc<Semaphore>,
+    memory: Arc<Semaphore>,
+    vram: Option<Arc<Semaphore>>,
+}
+
+/// Budget an admitted item holds until it is generated or given up.
+pub struct Admission {
+    _item: OwnedSemaphorePermit,
+    _memory: OwnedSemaphorePermit,
+    _vram: Option<OwnedSemaphorePermit>,
+}
+
+impl RunBudget {
+    pub fn new(limits: &ResourceLimits) -> Self {
+        let generation_workers = limits
+            .generation_workers
+            .unwrap_or(num_cpus::get() as u32)
+            .max(1);
+        let save_workers = limits
+            .save_workers
+            .unwrap_or(num_cpus::get() as u32 * 2)
+            .max(1);
+        let memory_budget_mb = limits
+            .memory_budget_mb
+            .unwrap_or_else(default_memory_budget_mb)
+            .max(1);
+        let vram_budget_mb = limits.vram_budget_mb.map(|mb| mb.max(1));
+
+        Self {
+            generation_workers,
+            save_workers,
+            memory_budget_mb,
+            vram_budget_mb,
+            generation: Arc::new(Semaphore::new(generation_workers as usize)),
+            save: Arc::new(Semaphore::new(save_workers as usize)),
+            items: Arc::new(Semaphore::new(
+                (generation_workers * ITEMS_PER_WORKER) as usize,
+            )),
+            memory: Arc::new(Semaphore::new(memory_budget_mb as usize)),
+            vram: vram_budget_mb.map(|mb| Arc::new(Semaphore::new(mb as usize))),
+        }
+    }
+
+    /// Waits until an item of the given size fits in the budget.
+    pub async fn admit(&self, memory_mb: u32, vram_mb: u32) -> Result<Admission> {
+        let item = self
+            .items
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to admit item: {}", e))?;
+        let memory = acquire_memory(&self.memory, self.memory_budget_mb, memory_mb, "RAM").await?;
+        let vram = match (&self.vram, self.vram_budget_mb) {
+            (Some(semaphore), Some(budget_mb)) => {
+                Some(acquire_memory(semaphore, budget_mb, vram_mb, "VRAM").await?)
+            }
+            _ => None,
+        };
+
+        Ok(Admission {
+            _item: item,
+            _memory: memory,
+            _vram: vram,
+        })
+    }
+
+    pub fn memory_used_mb(&self) -> u32 {
+        self.memory_budget_mb
+            .saturating_sub(self.memory.available_permits() as u32)
+    }
+
+    pub fn info(&self) -> String {
+        let vram = match (&self.vram, self.vram_budget_mb) {
+            (Some(semaphore), Some(budget_mb)) => {
+                format!(
+                    "{}/{} MB available",
+                    semaphore.available_permits(),
+                    budget_mb
+                )
+            }
+            _ => "unbounded".to_string(),
+        };
+
+        format!(
+            "💾 Memory budget: {}/{} MB available, VRAM budget: {}",
+            self.memory.available_permits(),
+            self.memory_budget_mb,
+            vram
+        )
+    }
+}
+
+/// Replaces the budget for the run that is starting. Tasks of a previous
+/// run keep the budget they were spawned with.
+pub fn configure_run_budget(limits: &ResourceLimits) -> Arc<RunBudget> {
+    let budget = Arc::new(RunBudget::new(limits));
+    tracing::info!(
+        "🎯 [BUDGET] {} generation workers, {} save workers, {} MB RAM, VRAM {}",
+        budget.generation_workers,
+        budget.save_workers,
+        budget.memory_budget_mb,
+        budget
+            .vram_budget_mb
+            .map_or("unbounded".to_string(), |mb| format!("{} MB", mb))
+    );
+    *RUN_BUDGET.write() = budget.clone();
+    budget
+}
+
+pub fn run_budget() -> Arc<RunBudget> {
+    RUN_BUDGET.read().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(memory_budget_mb: u32, vram_budget_mb: Option<u32>) -> ResourceLimits {
+        ResourceLimits {
+            generation_workers: Some(2),
+            save_workers: Some(2),
+            memory_budget_mb: Some(memory_budget_mb),
+            vram_budget_mb,
+        }
+    }
+
+    #[test]
+    fn zero_limits_are_raised_to_one() {
+        let budget = RunBudget::new(&ResourceLimits {
+            generation_workers: Some(0),
+            save_workers: Some(0),
+            memory_budget_mb: Some(0),
+            vram_budget_mb: Some(0),
+        });
+
+        assert_eq!(budget.generation_workers, 1);
+        assert_eq!(budget.save_workers, 1);
+        assert_eq!(budget.memory_budget_mb, 1);
+        assert_eq!(budget.vram_budget_mb, Some(1));
+    }
+
+    #[tokio::test]
+    async fn admission_holds_memory_until_dropped() {
+        let budget = RunBudget::new(&limits(64, Some(32)));
+
+        let admission = budget.admit(16, 8).await.unwrap();
+        assert_eq!(budget.memory_used_mb(), 16);
+
+        drop(admission);
+        assert_eq!(budget.memory_used_mb(), 0);
+    }
+
+    #[tokio::test]
+    async fn oversized_item_is_admitted_alone() {
+        let budget = RunBudget::new(&limits(64, Some(32)));
+
+        let admission = budget.admit(1000, 1000).await.unwrap();
+        assert_eq!(budget.memory_used_mb(), 64);
+        assert_eq!(budget.vram.as_ref().unwrap().available_permits(), 0);
+
+        // A second item waits for the first to be released.
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(50), budget.admit(1, 1))
+                .await
+                .is_err()
+        );
+
+        drop(admission);
+        assert!(budget.admit(1, 1).await.is_ok());
+    }
+}
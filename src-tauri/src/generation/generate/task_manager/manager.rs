@@ -1,29 +1,22 @@
 use anyhow::Result;
 use num_cpus;
-use once_cell::sync::Lazy;
 use std::{
     future::Future,
-    sync::Arc,
     time::{Duration, Instant},
 };
 use tokio::{
     spawn,
-    sync::{RwLock, Semaphore, SemaphorePermit},
+    sync::{Semaphore, SemaphorePermit},
     task::{spawn_blocking, JoinHandle},
     time::sleep,
 };
 use tokio_util::sync::CancellationToken;
+use tracing::{Instrument, Span};
 
-use crate::generation::generate::task_manager::MetricsUtils;
-
-static CURRENT_SESSION_TOKEN: Lazy<Arc<RwLock<CancellationToken>>> =
-    Lazy::new(|| Arc::new(RwLock::new(CancellationToken::new())));
-
-pub static GENERATION_SEMAPHORE: Lazy<Arc<Semaphore>> =
-    Lazy::new(|| Arc::new(Semaphore::new(num_cpus::get())));
-
-pub static SAVE_SEMAPHORE: Lazy<Arc<Semaphore>> =
-    Lazy::new(|| Arc::new(Semaphore::new(num_cpus::get() * 2)));
+use crate::generation::generate::{
+    pausecancel::active_session,
+    task_manager::{budget::run_budget, MetricsUtils},
+};
 
 const TASK_TIMEOUT: Duration = Duration::from_secs(300);
 const BACKPRESSURE_SLEEP: Duration = Duration::from_millis(10);
@@ -67,6 +60,7 @@ async fn acquire_with_backpressure<'a>(
 async fn run_with_semaphore<F, Fut, T>(
     sema: &Semaphore,
     task_id: String,
+    cancel_token: CancellationToken,
     task: F,
     task_type: &str,
 ) -> Result<T>
@@ -77,7 +71,6 @@ where
 {
     let permit = acquire_with_backpressure(sema, task_type).await?;
 
-    let cancel_token = get_current_session_token().await;
     let id = task_id.clone();
 
     let result = tokio::select! {
@@ -105,6 +98,7 @@ where
 async fn run_save_with_semaphore<F, C>(
     sema: &Semaphore,
     task_id: String,
+    cancel_token: CancellationToken,
     save_fn: F,
     cancellation_check: Option<C>,
 ) -> Result<()>
@@ -114,7 +108,6 @@ where
 {
     let _permit = acquire_with_backpressure(sema, "save").await?;
 
-    let cancel_token = get_current_session_token().await;
     let id = task_id.clone();
     let id_for_logging = id.clone();
 
@@ -126,7 +119,9 @@ where
         return Err(anyhow::anyhow!("Save task cancelled"));
     }
 
+    let span = Span::current();
     let result = spawn_blocking(move || {
+        let _entered = span.enter();
         let start_time = Instant::now();
         let task_id_for_inner = id.clone();
         tracing::debug!("🔄 [SAVE] Executing task: {}", task_id_for_inner);
@@ -169,8 +164,12 @@ where
     }
 }
 
+/// Runs `task_fn` on a generation worker until `cancel_token` is cancelled.
+/// Callers admit the item against the memory budget first, see
+/// `RunBudget::admit`.
 pub async fn spawn_generation_task<F, Fut, T>(
     task_id: String,
+    cancel_token: CancellationToken,
     task_fn: F,
 ) -> Result<JoinHandle<Result<T>>>
 where
@@ -181,9 +180,13 @@ where
     tracing::debug!("🚀 [GENERATION] Starting task: {}", task_id);
 
     let task_id_for_logging = task_id.clone();
-    let handle = spawn(async move {
-        run_with_semaphore(&GENERATION_SEMAPHORE, task_id, task_fn, "generation").await
-    });
+    let semaphore = run_budget().generation.clone();
+    let handle = spawn(
+        async move {
+            run_with_semaphore(&semaphore, task_id, cancel_token, task_fn, "generation").await
+        }
+        .in_current_span(),
+    );
 
     tracing::debug!(
         "🚀 [GENERATION] Task {} spawned successfully",
@@ -211,17 +214,26 @@ where
     tracing::debug!("🔄 [SAVE] Starting task: {}", task_id);
 
     let task_id_for_logging = task_id.clone();
-    let handle = spawn(async move {
-        let result = run_save_with_semaphore(
-            &SAVE_SEMAPHORE,
-            task_id.clone(),
-            save_fn,
-            cancellation_check,
-        )
-        .await;
-        tracing::debug!("🧹 [SAVE] Task completed: {}", task_id);
-        result
-    });
+    let semaphore = run_budget().save.clone();
+    // Saves belong to the run that spawned them.
+    let cancel_token = active_session()
+        .map(|session| session.token().clone())
+        .unwrap_or_default();
+    let handle = spawn(
+        async move {
+            let result = run_save_with_semaphore(
+                &semaphore,
+                task_id.clone(),
+                cancel_token,
+                save_fn,
+                cancellation_check,
+            )
+            .await;
+            tracing::debug!("🧹 [SAVE] Task completed: {}", task_id);
+            result
+        }
+        .in_current_span(),
+    );
 
     tracing::debug!(
         "🚀 [SAVE] Task {} spawned successfully",
@@ -230,35 +242,13 @@ where
     Ok(handle)
 }
 
-pub async fn cancel_all_tasks() -> Result<()> {
-    tracing::info!("🔄 [CANCEL] Cancelling current session tasks...");
-    let session_token = CURRENT_SESSION_TOKEN.read().await;
-    session_token.cancel();
-    tracing::info!("✅ [CANCEL] Session cancellation signal sent");
-    Ok(())
-}
-
-pub async fn create_generation_session() -> CancellationToken {
-    let new_token = CancellationToken::new();
-    {
-        let mut session_token = CURRENT_SESSION_TOKEN.write().await;
-        *session_token = new_token.clone();
-    }
-    tracing::info!("🆕 [SESSION] New generation session created");
-    new_token
-}
-
-pub async fn get_current_session_token() -> CancellationToken {
-    let session_token = CURRENT_SESSION_TOKEN.read().await;
-    session_token.clone()
-}
-
 pub fn get_system_info() -> String {
     let num_cpus = num_cpus::get();
     let (cpu_usage, memory_usage) = MetricsUtils::measure_system_performance();
 
-    let generation_available = GENERATION_SEMAPHORE.available_permits();
-    let save_available = SAVE_SEMAPHORE.available_permits();
+    let budget = run_budget();
+    let generation_available = budget.generation.available_permits();
+    let save_available = budget.save.available_permits();
 
     format!(
         "🖥️ System Info:\n\
@@ -267,31 +257,36 @@ pub fn get_system_info() -> String {
          💾 Memory: {} MB\n\
          🎯 Generation Workers: {}/{} available\n\
          💾 Save Workers: {}/{} available\n\
-         🚀 Task Management: Auto-Adaptive Semaphores",
+         {}\n\
+         🚀 Task Management: Memory-Budgeted Semaphores",
         num_cpus,
         cpu_usage,
         memory_usage / 1024 / 1024,
         generation_available,
-        num_cpus,
+        budget.generation_workers,
         save_available,
-        num_cpus * 2
+        budget.save_workers,
+        budget.info()
     )
 }
 
 pub fn get_semaphore_info() -> String {
     let num_cpus = num_cpus::get();
-    let generation_available = GENERATION_SEMAPHORE.available_permits();
-    let save_available = SAVE_SEMAPHORE.available_permits();
+    let budget = run_budget();
+    let generation_available = budget.generation.available_permits();
+    let save_available = budget.save.available_permits();
 
     format!(
         "🎯 Semaphore Status:\n\
          - Generation: {}/{} workers available\n\
          - Save: {}/{} workers available\n\
+         - {}\n\
          - Total CPU Cores: {}",
         generation_available,
-        num_cpus,
+        budget.generation_workers,
         save_available,
-        num_cpus * 2,
+        budget.save_workers,
+        budget.info(),
         num_cpus
     )
 }
@@ -0,0 +1,193 @@
+use anyhow::Result;
+use std::sync::Arc;
+use sysinfo::System;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::generation::generate::save_animation::pipeline::FRAME_QUEUE_CAPACITY;
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+const MEMORY_BUDGET_RATIO: f64 = 0.6;
+const MIN_MEMORY_BUDGET_MB: u32 = 512;
+const SPRITESHEET_BLEND_COPIES: u64 = 4;
+const STATIC_LAYER_OVERHEAD: u64 = 3;
+const GPU_SPRITESHEET_COPIES: u64 = 2;
+const GPU_STATIC_OVERHEAD: u64 = 2;
+
+/// Share of the currently available RAM, used when a run sets no budget.
+pub fn default_memory_budget_mb() -> u32 {
+    let mut sys = System::new();
+    sys.refresh_memory();
+
+    let available_mb = sys.available_memory() / BYTES_PER_MB;
+    let budget_mb = (available_mb as f64 * MEMORY_BUDGET_RATIO) as u64;
+
+    budget_mb.clamp(MIN_MEMORY_BUDGET_MB as u64, u32::MAX as u64) as u32
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ItemMemoryProfile {
+    pub base_width: u32,
+    pub base_height: u32,
+    pub final_width: u32,
+    pub final_height: u32,
+    pub layer_count: u32,
+    pub total_frames: u32,
+    pub interpolation_factor: u32,
+    pub is_animated: bool,
+}
+
+impl ItemMemoryProfile {
+    pub fn estimate_mb(&self) -> u32 {
+        let base_frame_bytes = self.base_frame_bytes();
+        let final_frame_bytes = self.final_frame_bytes();
+
+        let bytes = if self.is_animated {
+            let spritesheet_bytes = base_frame_bytes * self.total_frames.max(1) as u64;
+            let in_flight_frames =
+                FRAME_QUEUE_CAPACITY as u64 + self.interpolation_factor as u64 + 2;
+
+            spritesheet_bytes * SPRITESHEET_BLEND_COPIES
+                + in_flight_frames * base_frame_bytes.max(final_frame_bytes)
+        } else {
+            base_frame_bytes * (self.layer_count as u64 + STATIC_LAYER_OVERHEAD) + final_frame_bytes
+        };
+
+        to_mb(bytes)
+    }
+
+    /// Textures an item keeps on the GPU: the layers and blend targets of a
+    /// static item, the spritesheets and resized frames of an animated one.
+    pub fn estimate_vram_mb(&self) -> u32 {
+        let base_frame_bytes = self.base_frame_bytes();
+        let final_frame_bytes = self.final_frame_bytes();
+
+        let bytes = if self.is_animated {
+            base_frame_bytes * self.total_frames.max(1) as u64 * GPU_SPRITESHEET_COPIES
+                + (base_frame_bytes + final_frame_bytes) * (self.interpolation_factor as u64 + 1)
+        } else {
+            base_frame_bytes * (self.layer_count as u64 + GPU_STATIC_OVERHEAD) + final_frame_bytes
+        };
+
+        to_mb(bytes)
+    }
+
+    fn base_frame_bytes(&self) -> u64 {
+        self.base_width as u64 * self.base_height as u64 * 4
+    }
+
+    fn final_frame_bytes(&self) -> u64 {
+        self.final_width as u64 * self.final_height as u64 * 4
+    }
+}
+
+fn to_mb(bytes: u64) -> u32 {
+    bytes.div_ceil(BYTES_PER_MB).clamp(1, u32::MAX as u64) as u32
+}
+
+/// Takes `estimated_mb` from a budget of `budget_mb`. An item larger than
+/// the whole budget takes all of it and runs alone.
+pub async fn acquire_memory(
+    semaphore: &Arc<Semaphore>,
+    budget_mb: u32,
+    estimated_mb: u32,
+    kind: &str,
+) -> Result<OwnedSemaphorePermit> {
+    let permits = estimated_mb.clamp(1, budget_mb.max(1));
+
+    if permits < estimated_mb {
+        tracing::warn!(
+            "⚠️ [MEMORY] Item needs ~{} MB of {} but budget is {} MB, running it alone",
+            estimated_mb,
+            kind,
+            budget_mb
+        );
+    }
+
+    semaphore
+        .clone()
+        .acquire_many_owned(permits)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to acquire {} budget: {}", kind, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(is_animated: bool) -> ItemMemoryProfile {
+        ItemMemoryProfile {
+            base_width: 1000,
+            base_height: 1000,
+            final_width: 1000,
+            final_height: 1000,
+            layer_count: 5,
+            total_frames: 10,
+            interpolation_factor: 1,
+            is_animated,
+        }
+    }
+
+    #[test]
+    fn static_estimate_counts_layers_and_final_frame() {
+        // 4 MB frames: 5 layers, 3 blend buffers and the final frame.
+        assert_eq!(profile(false).estimate_mb(), 35);
+
+        let mut more_layers = profile(false);
+        more_layers.layer_count = 10;
+        assert!(more_layers.estimate_mb() > profile(false).estimate_mb());
+    }
+
+    #[test]
+    fn animated_estimate_counts_spritesheets_and_frames_in_flight() {
+        let mut animated = profile(true);
+        animated.base_width = 500;
+        animated.base_height = 500;
+
+        // 4 copies of a 10 MB spritesheet and 7 frames in flight of 4 MB.
+        assert_eq!(animated.estimate_mb(), 65);
+
+        let mut longer = animated;
+        longer.total_frames = 20;
+        assert!(longer.estimate_mb() > animated.estimate_mb());
+    }
+
+    #[test]
+    fn estimate_is_at_least_one_mb() {
+        let tiny = ItemMemoryProfile {
+            base_width: 1,
+            base_height: 1,
+            final_width: 1,
+            final_height: 1,
+            layer_count: 0,
+            total_frames: 0,
+            interpolation_factor: 0,
+            is_animated: true,
+        };
+
+        assert_eq!(tiny.estimate_mb(), 1);
+        assert_eq!(tiny.estimate_vram_mb(), 1);
+    }
+
+    #[tokio::test]
+    async fn oversized_item_takes_the_whole_budget() {
+        let semaphore = Arc::new(Semaphore::new(100));
+
+        let permit = acquire_memory(&semaphore, 100, 500, "RAM").await.unwrap();
+        assert_eq!(permit.num_permits(), 100);
+        assert_eq!(semaphore.available_permits(), 0);
+
+        drop(permit);
+        assert_eq!(semaphore.available_permits(), 100);
+    }
+
+    #[tokio::test]
+    async fn empty_item_takes_one_mb() {
+        let semaphore = Arc::new(Semaphore::new(100));
+
+        let permit = acquire_memory(&semaphore, 100, 0, "RAM").await.unwrap();
+        assert_eq!(permit.num_permits(), 1);
+
+        let permit = acquire_memory(&semaphore, 0, 10, "VRAM").await.unwrap();
+        assert_eq!(permit.num_permits(), 1);
+    }
+}
@@ -1,6 +1,45 @@
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::Serialize;
 use std::time::Duration;
 use sysinfo::System;
 
+static STAGE_TIMINGS: Lazy<DashMap<Stage, (u64, Duration)>> = Lazy::new(DashMap::new);
+
+/// Steps of producing one item, timed across the run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Stage {
+    TraitSelection,
+    TextureUpload,
+    Blend,
+    Resize,
+    Readback,
+    Encode,
+    Save,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 7] = [
+        Stage::TraitSelection,
+        Stage::TextureUpload,
+        Stage::Blend,
+        Stage::Resize,
+        Stage::Readback,
+        Stage::Encode,
+        Stage::Save,
+    ];
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StageTiming {
+    pub stage: Stage,
+    pub count: u64,
+    pub total_ms: f64,
+    pub average_ms: f64,
+}
+
 #[derive(Debug, Clone)]
 pub enum WorkerType {
     Generation,
@@ -47,6 +86,50 @@ impl PerformanceMetrics {
 pub struct MetricsUtils;
 
 impl MetricsUtils {
+    pub fn record_stage(stage: Stage, duration: Duration) {
+        let mut timing = STAGE_TIMINGS.entry(stage).or_insert((0, Duration::ZERO));
+        timing.0 += 1;
+        timing.1 += duration;
+    }
+
+    pub fn reset_stage_timings() {
+        STAGE_TIMINGS.clear();
+    }
+
+    pub fn stage_timings() -> Vec<StageTiming> {
+        Stage::ALL
+            .iter()
+            .map(|&stage| {
+                let (count, total) = STAGE_TIMINGS
+                    .get(&stage)
+                    .map(|timing| *timing)
+                    .unwrap_or((0, Duration::ZERO));
+                let total_ms = total.as_secs_f64() * 1000.0;
+                StageTiming {
+                    stage,
+                    count,
+                    total_ms,
+                    average_ms: if count > 0 {
+                        total_ms / count as f64
+                    } else {
+                        0.0
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Resident memory of this process in bytes.
+    pub fn process_memory(sys: &mut System) -> u64 {
+        let Ok(pid) = sysinfo::get_current_pid() else {
+            return 0;
+        };
+        sys.refresh_process(pid);
+        sys.process(pid)
+            .map(|process| process.memory())
+            .unwrap_or(0)
+    }
+
     pub fn calculate_throughput(items_processed: u32, execution_time: Duration) -> f64 {
         if execution_time.as_secs_f64() > 0.0 {
             items_processed as f64 / execution_time.as_secs_f64()
@@ -55,18 +138,6 @@ impl MetricsUtils {
         }
     }
 
-    pub fn calculate_memory_usage(initial_memory: u64, final_memory: u64) -> u64 {
-        if final_memory > initial_memory {
-            final_memory - initial_memory
-        } else {
-            0
-        }
-    }
-
-    pub fn calculate_cpu_usage(initial_cpu: f64, final_cpu: f64) -> f64 {
-        (initial_cpu + final_cpu) / 2.0
-    }
-
     pub fn measure_system_performance() -> (f64, u64) {
         let mut sys = System::new_all();
         sys.refresh_all();
@@ -77,32 +148,6 @@ impl MetricsUtils {
         (cpu_usage, memory_usage)
     }
 
-    pub fn create_generation_metrics(
-        worker_count: u32,
-        execution_time: Duration,
-        initial_memory: u64,
-        final_memory: u64,
-        initial_cpu: f64,
-        final_cpu: f64,
-        errors_count: u32,
-        items_processed: u32,
-    ) -> PerformanceMetrics {
-        let memory_usage = Self::calculate_memory_usage(initial_memory, final_memory);
-        let cpu_usage = Self::calculate_cpu_usage(initial_cpu, final_cpu);
-        let throughput = Self::calculate_throughput(items_processed, execution_time);
-
-        PerformanceMetrics::new_generation(
-            worker_count,
-            execution_time,
-            memory_usage,
-            cpu_usage,
-            throughput,
-            Duration::from_millis(0),
-            errors_count,
-            items_processed,
-        )
-    }
-
     pub fn display_metrics(metrics: &PerformanceMetrics) {
         let worker_type_str = match metrics.worker_type {
             WorkerType::Generation => "🎨 Génération",
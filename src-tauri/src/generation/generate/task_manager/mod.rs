@@ -1,8 +1,9 @@
+pub mod budget;
 pub mod manager;
+pub mod memory;
 pub mod metrics;
 
-pub use manager::{
-    cancel_all_tasks, create_generation_session, get_current_session_token, get_semaphore_info,
-    get_system_info, spawn_generation_task, spawn_save_task,
-};
-pub use metrics::{MetricsUtils, PerformanceMetrics};
+pub use budget::{configure_run_budget, run_budget, Admission};
+pub use manager::{get_semaphore_info, get_system_info, spawn_generation_task, spawn_save_task};
+pub use memory::ItemMemoryProfile;
+pub use metrics::{MetricsUtils, PerformanceMetrics, Stage, StageTiming};
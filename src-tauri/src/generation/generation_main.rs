@@ -1,31 +1,53 @@
 use anyhow::{Context, Result};
 use chrono;
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
     future::Future,
     path::{Path, PathBuf},
     str::FromStr,
+    sync::Arc,
 };
 use tauri::{Manager, Window};
-use tokio::{fs::create_dir_all, try_join};
+use tokio::{fs::create_dir_all, sync::Mutex, try_join};
 use tracing;
 
+static GENERATION_RUNNING: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
 use crate::{
     filesystem::{
         constants::StorageFiles,
         persist::{
-            load_forced_combination_state, load_incompatibility_state, load_layer_order_state,
-            load_rarity_config,
+            read_forced_combination_state, read_incompatibility_state, read_layer_order_state,
+            read_rarity_config,
         },
+        projects::{ProjectDirs, ProjectRegistry},
     },
     generation::generate::{
-        generate::generate_nfts, generate_single::file_watcher::start_file_watcher,
-        metadata::create_single::Blockchain, utils::clear_directory,
+        generate::generate_nfts,
+        generate_single::{file_watcher::start_file_watcher, progress::GenerationPhase},
+        metadata::{create_single::Blockchain, display_names::apply_locale},
+        pausecancel::GenerationSession,
+        utils::clear_directory,
+    },
+    layerpreview::validation::lint::{lint_folder, LintSeverity},
+    types::{
+        ForcedCombinationsBySets, IncompatibilitiesBySets, NFTGenerationArgs, OrderedLayersSet,
+        RarityConfig, SetsStorage,
     },
-    types::{NFTGenerationArgs, OrderedLayersSet},
 };
 
+/// Rule files a generation reads from its project.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationRules {
+    pub rarity_config: RarityConfig,
+    pub layer_order: SetsStorage,
+    pub incompatibilities: IncompatibilitiesBySets,
+    pub forced_combinations: ForcedCombinationsBySets,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct NFTProgressInfo {
@@ -34,6 +56,8 @@ pub struct NFTProgressInfo {
     pub estimated_count: u32,
     pub sequence_number: u32,
     pub current_image: ImageInfo,
+    pub phase: GenerationPhase,
+    pub eta_ms: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -131,10 +155,85 @@ pub async fn start_nft_generation(
         .map_err(|e| e.to_string())
 }
 
+/// Refuses to generate from layers that `lint_layers` reports errors for.
+async fn check_layer_assets(input_folder: &str) -> Result<()> {
+    let folder = PathBuf::from(input_folder);
+    let report = tokio::task::spawn_blocking(move || lint_folder(&folder))
+        .await?
+        .map_err(|e| anyhow::anyhow!("Failed to check layer assets: {}", e))?;
+
+    if let Some(first) = report
+        .diagnostics
+        .iter()
+        .find(|d| d.severity == LintSeverity::Error)
+    {
+        return Err(anyhow::anyhow!(
+            "{} asset error(s) found, first in {}/{}: {}. Fix them or start with ignoreLintErrors.",
+            report.errors,
+            first.layer,
+            first.file.as_deref().unwrap_or(""),
+            first.message
+        ));
+    }
+    Ok(())
+}
+
+pub fn is_generation_running() -> bool {
+    GENERATION_RUNNING.try_lock().is_err()
+}
+
+/// Reads the rule files from `storage_files`, the config of one project.
+pub async fn load_generation_rules(storage_files: &StorageFiles) -> Result<GenerationRules> {
+    let (rarity_config, layer_order, incompatibilities, forced_combinations) = try_join!(
+        load_state(|| read_rarity_config(storage_files), "rarity configuration"),
+        load_state(|| read_layer_order_state(storage_files), "layer order"),
+        load_state(
+            || read_incompatibility_state(storage_files),
+            "incompatibilities"
+        ),
+        load_state(
+            || read_forced_combination_state(storage_files),
+            "forced combinations"
+        ),
+    )?;
+
+    Ok(GenerationRules {
+        rarity_config,
+        layer_order,
+        incompatibilities,
+        forced_combinations,
+    })
+}
+
 pub async fn invoke_generation(
     window: Window,
     args: &NFTGenerationArgs,
 ) -> Result<GenerationResponse> {
+    if is_generation_running() {
+        return Err(anyhow::anyhow!("A generation is already running"));
+    }
+
+    let registry = window.state::<ProjectRegistry>();
+    let dirs = registry.active_dirs();
+    let rules = load_generation_rules(&registry.storage_files_in(&dirs)).await?;
+    let session = GenerationSession::new(PathBuf::from(&args.export_folder));
+    run_generation(window, args, rules, session, &dirs).await
+}
+
+/// Runs a generation from `rules` instead of the project's current files.
+/// Only one run is active at a time, later calls wait for it to finish.
+/// `session` becomes the active session once the run starts, `dirs` are the
+/// directories of the project the rules come from.
+pub async fn run_generation(
+    window: Window,
+    args: &NFTGenerationArgs,
+    rules: GenerationRules,
+    session: Arc<GenerationSession>,
+    dirs: &ProjectDirs,
+) -> Result<GenerationResponse> {
+    let _running = GENERATION_RUNNING.lock().await;
+    let _active = session.activate();
+
     let export_path = PathBuf::from(&args.export_folder);
 
     if export_path == PathBuf::from("/") || export_path == PathBuf::from("C:\\") {
@@ -144,25 +243,25 @@ pub async fn invoke_generation(
         ));
     }
 
-    clear_directory(&export_path)?;
+    if !args.ignore_lint_errors {
+        check_layer_assets(&args.input_folder).await?;
+    }
+
+    // A resumed run keeps what the stopped run left in the export folder.
+    if !args.resume {
+        clear_directory(&export_path)?;
+    }
 
-    let app_state = window.state::<StorageFiles>();
+    let GenerationRules {
+        mut rarity_config,
+        layer_order,
+        incompatibilities,
+        forced_combinations,
+    } = rules;
 
-    let (rarity_config, layer_order, incompatibilities, forced_combinations) = try_join!(
-        load_state(
-            || load_rarity_config(app_state.clone()),
-            "rarity configuration"
-        ),
-        load_state(|| load_layer_order_state(app_state.clone()), "layer order"),
-        load_state(
-            || load_incompatibility_state(app_state.clone()),
-            "incompatibilities"
-        ),
-        load_state(
-            || load_forced_combination_state(app_state.clone()),
-            "forced combinations"
-        ),
-    )?;
+    if let Some(locale) = &args.metadata_locale {
+        apply_locale(&mut rarity_config, locale);
+    }
 
     let ordered_layers_sets: HashMap<_, _> = layer_order
         .sets
@@ -212,6 +311,8 @@ pub async fn invoke_generation(
         &forced_combinations,
         &blockchain,
         &window,
+        &session,
+        dirs,
     )
     .await;
 
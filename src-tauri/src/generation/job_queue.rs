@@ -0,0 +1,509 @@
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+    sync::Arc,
+};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::Notify;
+use tracing::{Instrument, Metadata, Subscriber};
+use tracing_subscriber::{layer::Context, registry::LookupSpan};
+
+use crate::{
+    filesystem::projects::{ProjectDirs, ProjectRegistry},
+    generation::{
+        generate::pausecancel::{cancel_session, GenerationSession},
+        generation_main::{load_generation_rules, run_generation, GenerationRules},
+    },
+    types::NFTGenerationArgs,
+};
+
+const JOBS_DIR_NAME: &str = "jobs";
+const QUEUE_FILE_NAME: &str = "queue.json";
+const SNAPSHOT_FILE_NAME: &str = "snapshot.json";
+const LOG_FILE_NAME: &str = "job.log";
+
+/// Span a job runs in, only events inside it reach the job log.
+const JOB_SPAN_NAME: &str = "generation_job";
+
+/// Log file of the job that is running, written to by `JobLogWriter`.
+static JOB_LOG: Lazy<Mutex<Option<File>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JobInfo {
+    pub id: String,
+    pub label: String,
+    pub project_id: Option<String>,
+    pub project_name: Option<String>,
+    pub collection_name: String,
+    pub export_folder: String,
+    pub total_supply: u32,
+    pub status: JobStatus,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub message: Option<String>,
+}
+
+/// Everything a job needs to run, captured when it is enqueued so later
+/// edits to the project do not change it.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JobSnapshot {
+    args: NFTGenerationArgs,
+    rules: GenerationRules,
+    dirs: ProjectDirs,
+}
+
+/// Generations waiting to run one after another. The queue lives in
+/// `jobs/queue.json`, each job keeps its snapshot and log in `jobs/<id>`.
+pub struct JobQueue {
+    jobs_dir: PathBuf,
+    jobs: RwLock<Vec<JobInfo>>,
+    wake: Notify,
+    /// Job being run and its session.
+    running: Mutex<Option<(String, Arc<GenerationSession>)>>,
+}
+
+/// Tracing writer that copies events into the log of the running job.
+pub struct JobLogWriter;
+
+impl Write for JobLogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(file) = JOB_LOG.lock().as_mut() {
+            file.write_all(buf)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match JOB_LOG.lock().as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+pub fn is_job_logging() -> bool {
+    JOB_LOG.lock().is_some()
+}
+
+/// Filter of the job log layer: the job span and what happens inside it, so
+/// other work done while a job runs stays out of its log.
+pub fn is_job_event<S>(metadata: &Metadata<'_>, cx: &Context<'_, S>) -> bool
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    if !is_job_logging() {
+        return false;
+    }
+    (metadata.is_span() && metadata.name() == JOB_SPAN_NAME)
+        || cx
+            .lookup_current()
+            .is_some_and(|span| span.scope().any(|span| span.name() == JOB_SPAN_NAME))
+}
+
+impl JobQueue {
+    pub fn load(app_handle: &AppHandle) -> Result<Self, String> {
+        let jobs_dir = app_handle
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get app data directory: {}", e))?
+            .join(JOBS_DIR_NAME);
+
+        fs::create_dir_all(&jobs_dir)
+            .map_err(|e| format!("Failed to create jobs directory: {}", e))?;
+
+        let mut jobs: Vec<JobInfo> = match fs::read_to_string(jobs_dir.join(QUEUE_FILE_NAME)) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse job queue: {}", e))?,
+            Err(_) => Vec::new(),
+        };
+
+        // A job still marked running was interrupted by the app closing.
+        for job in jobs.iter_mut().filter(|j| j.status == JobStatus::Running) {
+            job.status = JobStatus::Queued;
+            job.started_at = None;
+            job.message = Some("Interrupted by an app restart, queued again".to_string());
+        }
+
+        let queue = Self {
+            jobs_dir,
+            jobs: RwLock::new(jobs),
+            wake: Notify::new(),
+            running: Mutex::new(None),
+        };
+        queue.save()?;
+
+        tracing::info!(
+            "[Jobs] Loaded {} jobs, {} queued",
+            queue.jobs.read().len(),
+            queue.queued_count()
+        );
+
+        Ok(queue)
+    }
+
+    fn job_dir(&self, id: &str) -> PathBuf {
+        self.jobs_dir.join(id)
+    }
+
+    fn queued_count(&self) -> usize {
+        self.jobs
+            .read()
+            .iter()
+            .filter(|j| j.status == JobStatus::Queued)
+            .count()
+    }
+
+    fn next_queued(&self) -> Option<JobInfo> {
+        self.jobs
+            .read()
+            .iter()
+            .find(|j| j.status == JobStatus::Queued)
+            .cloned()
+    }
+
+    fn find(&self, id: &str) -> Result<JobInfo, String> {
+        self.jobs
+            .read()
+            .iter()
+            .find(|j| j.id == id)
+            .cloned()
+            .ok_or_else(|| format!("Unknown job: {}", id))
+    }
+
+    fn update(&self, id: &str, f: impl FnOnce(&mut JobInfo)) -> Result<JobInfo, String> {
+        let job = {
+            let mut jobs = self.jobs.write();
+            let job = jobs
+                .iter_mut()
+                .find(|j| j.id == id)
+                .ok_or_else(|| format!("Unknown job: {}", id))?;
+            f(job);
+            job.clone()
+        };
+        self.save()?;
+        Ok(job)
+    }
+
+    /// Marks job `id` running with `session`, false when it is no longer
+    /// queued. Holds `running` so a cancel sees either the queued job or the
+    /// running one with its session.
+    fn start(&self, id: &str, session: &Arc<GenerationSession>) -> Result<bool, String> {
+        let mut running = self.running.lock();
+        let mut started = false;
+        self.update(id, |job| {
+            if job.status == JobStatus::Queued {
+                job.status = JobStatus::Running;
+                job.started_at = Some(Utc::now().to_rfc3339());
+                job.finished_at = None;
+                job.message = None;
+                started = true;
+            }
+        })?;
+        if started {
+            *running = Some((id.to_string(), session.clone()));
+        }
+        Ok(started)
+    }
+
+    fn read_snapshot(&self, id: &str) -> Result<JobSnapshot, String> {
+        let content = fs::read_to_string(self.job_dir(id).join(SNAPSHOT_FILE_NAME))
+            .map_err(|e| format!("Failed to read job snapshot: {}", e))?;
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse job snapshot: {}", e))
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(&*self.jobs.read())
+            .map_err(|e| format!("Failed to serialize job queue: {}", e))?;
+
+        let path = self.jobs_dir.join(QUEUE_FILE_NAME);
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, json).map_err(|e| format!("Failed to write job queue: {}", e))?;
+        fs::rename(&temp_path, &path).map_err(|e| format!("Failed to replace job queue: {}", e))
+    }
+}
+
+fn emit_jobs_changed(app_handle: &AppHandle, queue: &JobQueue) {
+    let _ = app_handle.emit("generation-jobs-changed", queue.jobs.read().clone());
+}
+
+/// Runs queued jobs one at a time for as long as the app is open.
+pub async fn run_job_queue(app_handle: AppHandle) {
+    let queue = app_handle.state::<JobQueue>();
+    loop {
+        match queue.next_queued() {
+            Some(job) => run_job(&app_handle, &queue, job).await,
+            None => queue.wake.notified().await,
+        }
+    }
+}
+
+async fn run_job(app_handle: &AppHandle, queue: &JobQueue, job: JobInfo) {
+    let session = GenerationSession::new(PathBuf::from(&job.export_folder));
+    match queue.start(&job.id, &session) {
+        Ok(true) => {}
+        // Cancelled before it could start.
+        Ok(false) => return,
+        Err(e) => {
+            tracing::error!("[Jobs] Failed to start job {}: {}", job.id, e);
+            return;
+        }
+    }
+    emit_jobs_changed(app_handle, queue);
+
+    match OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(queue.job_dir(&job.id).join(LOG_FILE_NAME))
+    {
+        Ok(file) => *JOB_LOG.lock() = Some(file),
+        Err(e) => tracing::warn!("[Jobs] Failed to open log of job {}: {}", job.id, e),
+    }
+    let span = tracing::info_span!(JOB_SPAN_NAME, job_id = %job.id);
+    span.in_scope(|| tracing::info!("[Jobs] Starting job {} ({})", job.id, job.label));
+
+    let result = match (queue.read_snapshot(&job.id), app_handle.get_window("main")) {
+        (Ok(snapshot), Some(window)) => run_generation(
+            window,
+            &snapshot.args,
+            snapshot.rules,
+            session,
+            &snapshot.dirs,
+        )
+        .instrument(span.clone())
+        .await
+        .map_err(|e| format!("{:#}", e)),
+        (Err(e), _) => Err(e),
+        (_, None) => Err("Main window not found".to_string()),
+    };
+    *queue.running.lock() = None;
+
+    let (status, message) = match result {
+        Ok(response) if response.success => (JobStatus::Completed, response.message),
+        Ok(response) => match response.error {
+            Some(error) => (JobStatus::Failed, Some(error)),
+            None => (JobStatus::Cancelled, response.message),
+        },
+        Err(e) => (JobStatus::Failed, Some(e)),
+    };
+    span.in_scope(|| {
+        tracing::info!(
+            "[Jobs] Job {} finished as {:?}: {}",
+            job.id,
+            status,
+            message.as_deref().unwrap_or("")
+        )
+    });
+    *JOB_LOG.lock() = None;
+
+    let finished = queue.update(&job.id, |job| {
+        // A job cancelled while running keeps that status.
+        if job.status == JobStatus::Running {
+            job.status = status;
+            job.message = message;
+        }
+        job.finished_at = Some(Utc::now().to_rfc3339());
+    });
+    if let Err(e) = finished {
+        tracing::error!("[Jobs] Failed to record result of job {}: {}", job.id, e);
+    }
+    emit_jobs_changed(app_handle, queue);
+}
+
+#[tauri::command]
+pub async fn enqueue_generation_job(
+    app_handle: AppHandle,
+    registry: State<'_, ProjectRegistry>,
+    queue: State<'_, JobQueue>,
+    args: NFTGenerationArgs,
+    label: Option<String>,
+) -> Result<JobInfo, String> {
+    // The job keeps the directories of the project it was queued from, even
+    // when another project is open by the time it runs.
+    let project = registry
+        .active_project()
+        .ok_or_else(|| "No active project to queue a generation from".to_string())?;
+    let dirs = registry.project_dirs(&project.id);
+    let rules = load_generation_rules(&registry.storage_files_in(&dirs))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let job = JobInfo {
+        id: format!("{:016x}", rand::random::<u64>()),
+        label: label
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty())
+            .unwrap_or_else(|| args.collection_name.clone()),
+        project_id: Some(project.id),
+        project_name: Some(project.name),
+        collection_name: args.collection_name.clone(),
+        export_folder: args.export_folder.clone(),
+        total_supply: rules
+            .layer_order
+            .sets
+            .values()
+            .map(|set| set.nft_count)
+            .sum(),
+        status: JobStatus::Queued,
+        created_at: Utc::now().to_rfc3339(),
+        started_at: None,
+        finished_at: None,
+        message: None,
+    };
+
+    let job_dir = queue.job_dir(&job.id);
+    fs::create_dir_all(&job_dir).map_err(|e| format!("Failed to create job directory: {}", e))?;
+    let snapshot = serde_json::to_string_pretty(&JobSnapshot { args, rules, dirs })
+        .map_err(|e| format!("Failed to serialize job snapshot: {}", e))?;
+    fs::write(job_dir.join(SNAPSHOT_FILE_NAME), snapshot)
+        .map_err(|e| format!("Failed to write job snapshot: {}", e))?;
+
+    queue.jobs.write().push(job.clone());
+    queue.save()?;
+    queue.wake.notify_one();
+    emit_jobs_changed(&app_handle, &queue);
+
+    tracing::info!("[Jobs] Queued job {} ({})", job.id, job.label);
+    Ok(job)
+}
+
+#[tauri::command]
+pub async fn list_generation_jobs(queue: State<'_, JobQueue>) -> Result<Vec<JobInfo>, String> {
+    Ok(queue.jobs.read().clone())
+}
+
+#[tauri::command]
+pub async fn get_generation_job(queue: State<'_, JobQueue>, id: String) -> Result<JobInfo, String> {
+    queue.find(&id)
+}
+
+/// Moves a job to `position` in the queue, clamped to its length.
+#[tauri::command]
+pub async fn move_generation_job(
+    app_handle: AppHandle,
+    queue: State<'_, JobQueue>,
+    id: String,
+    position: usize,
+) -> Result<Vec<JobInfo>, String> {
+    {
+        let mut jobs = queue.jobs.write();
+        let from = jobs
+            .iter()
+            .position(|j| j.id == id)
+            .ok_or_else(|| format!("Unknown job: {}", id))?;
+        let job = jobs.remove(from);
+        let to = position.min(jobs.len());
+        jobs.insert(to, job);
+    }
+    queue.save()?;
+    emit_jobs_changed(&app_handle, &queue);
+
+    Ok(queue.jobs.read().clone())
+}
+
+#[tauri::command]
+pub async fn cancel_generation_job(
+    app_handle: AppHandle,
+    queue: State<'_, JobQueue>,
+    id: String,
+    keep_completed: Option<bool>,
+) -> Result<JobInfo, String> {
+    // `running` is held until the status is updated so the job cannot start
+    // or finish in between. Only the session of this job is cancelled, never
+    // the run that follows it.
+    let (job, session) = {
+        let running = queue.running.lock();
+        let session = running
+            .as_ref()
+            .filter(|(running_id, _)| running_id == &id)
+            .map(|(_, session)| session.clone());
+        let previous = queue.find(&id)?.status;
+        let finished = match previous {
+            JobStatus::Queued => false,
+            JobStatus::Running => session.is_none(),
+            _ => true,
+        };
+        if finished {
+            return Err(format!("Job {} has already finished", id));
+        }
+
+        let job = queue.update(&id, |job| {
+            job.status = JobStatus::Cancelled;
+            job.message = Some(
+                if previous == JobStatus::Running && keep_completed.unwrap_or(false) {
+                    "Stopped by user, completed items kept".to_string()
+                } else {
+                    "Cancelled by user".to_string()
+                },
+            );
+            if previous == JobStatus::Queued {
+                job.finished_at = Some(Utc::now().to_rfc3339());
+            }
+        })?;
+        (job, session)
+    };
+    emit_jobs_changed(&app_handle, &queue);
+
+    if let Some(session) = session {
+        cancel_session(&session, keep_completed.unwrap_or(false)).await?;
+    }
+
+    tracing::info!("[Jobs] Cancelled job {} ({})", job.id, job.label);
+    Ok(job)
+}
+
+/// Removes finished jobs together with their snapshots and logs.
+#[tauri::command]
+pub async fn clear_finished_generation_jobs(
+    app_handle: AppHandle,
+    queue: State<'_, JobQueue>,
+) -> Result<Vec<JobInfo>, String> {
+    let removed: Vec<JobInfo> = {
+        let mut jobs = queue.jobs.write();
+        let (active, finished) = jobs
+            .drain(..)
+            .partition(|j| matches!(j.status, JobStatus::Queued | JobStatus::Running));
+        *jobs = active;
+        finished
+    };
+    queue.save()?;
+
+    for job in &removed {
+        if let Err(e) = fs::remove_dir_all(queue.job_dir(&job.id)) {
+            tracing::warn!("[Jobs] Failed to remove files of job {}: {}", job.id, e);
+        }
+    }
+    emit_jobs_changed(&app_handle, &queue);
+
+    Ok(queue.jobs.read().clone())
+}
+
+#[tauri::command]
+pub async fn get_generation_job_log(
+    queue: State<'_, JobQueue>,
+    id: String,
+) -> Result<String, String> {
+    queue.find(&id)?;
+    match fs::read_to_string(queue.job_dir(&id).join(LOG_FILE_NAME)) {
+        Ok(log) => Ok(log),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(format!("Failed to read job log: {}", e)),
+    }
+}
@@ -1,3 +1,8 @@
 pub mod clean_up_contexts;
+pub mod contact_sheet;
 pub mod generate;
 pub mod generation_main;
+pub mod job_queue;
+pub mod package;
+pub mod preview;
+pub mod simulate;
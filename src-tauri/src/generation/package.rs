@@ -0,0 +1,540 @@
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+use tauri::Window;
+use walkdir::WalkDir;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::{
+    filesystem::{manifest::hash_file, utils::normalize_path},
+    generation::generate::{
+        generate_single::progress::{send_phase_progress, GenerationPhase},
+        staging::{is_partial_file, StagedFiles},
+    },
+    types::{NFTGenerationArgs, PackageFormat, PackagingConfig},
+};
+
+pub const MANIFEST_FILE_NAME: &str = "MANIFEST.json";
+const GENERATION_RECORD_FILE_NAME: &str = "generation.json";
+const ARCHIVE_NAME: &str = "collection";
+const BYTES_PER_MB: u64 = 1024 * 1024;
+const ZSTD_LEVEL: i32 = 3;
+/// Room left in a volume for the entry headers of a file, on top of its path.
+const ENTRY_OVERHEAD: u64 = 1024;
+/// Extensions already compressed, stored as is in zip archives.
+const STORED_EXTENSIONS: &[&str] = &[
+    "png", "apng", "jpg", "jpeg", "webp", "gif", "mp4", "webm", "zip",
+];
+
+/// Arguments and shuffle seed of the run that produced a collection, kept in
+/// `collection infos` so it can be packaged later with the same description.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationRecord {
+    pub app_version: String,
+    pub generated_at: String,
+    /// Seed of the shuffle across sets, absent when the sets kept their order.
+    pub shuffle_seed: Option<u64>,
+    pub args: Value,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageFile {
+    /// Path inside the archive, starting with `collection/`.
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+    /// Index of the volume holding the file, from 1.
+    pub volume: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageManifest {
+    pub app_version: String,
+    pub created_at: String,
+    pub format: PackageFormat,
+    pub volumes: Vec<String>,
+    pub generation_args: Option<Value>,
+    /// Seed of the shuffle across sets, trait selection is not seeded.
+    pub shuffle_seed: Option<u64>,
+    pub total_size: u64,
+    pub files: Vec<PackageFile>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageResult {
+    pub archives: Vec<String>,
+    pub manifest: String,
+    pub files: usize,
+    pub total_size: u64,
+}
+
+pub fn write_generation_record(
+    collection_folder: &Path,
+    args: &NFTGenerationArgs,
+    shuffle_seed: Option<u64>,
+) -> Result<()> {
+    let info_folder = collection_folder.join("collection infos");
+    fs::create_dir_all(&info_folder)?;
+
+    let record = GenerationRecord {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        shuffle_seed,
+        args: serde_json::to_value(args)?,
+    };
+    fs::write(
+        info_folder.join(GENERATION_RECORD_FILE_NAME),
+        serde_json::to_string_pretty(&record)?,
+    )?;
+    Ok(())
+}
+
+fn read_generation_record(collection_folder: &Path) -> Option<GenerationRecord> {
+    let path = collection_folder
+        .join("collection infos")
+        .join(GENERATION_RECORD_FILE_NAME);
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn archive_extension(format: PackageFormat) -> &'static str {
+    match format {
+        PackageFormat::Zip => "zip",
+        PackageFormat::TarZst => "tar.zst",
+    }
+}
+
+fn volume_names(format: PackageFormat, count: u32) -> Vec<String> {
+    let extension = archive_extension(format);
+    if count <= 1 {
+        return vec![format!("{}.{}", ARCHIVE_NAME, extension)];
+    }
+    (1..=count)
+        .map(|volume| format!("{}-part{}.{}", ARCHIVE_NAME, volume, extension))
+        .collect()
+}
+
+/// Hashes every finished file of the collection, sorted by archive path.
+fn collect_files(collection_folder: &Path) -> Result<Vec<PackageFile>> {
+    let root = collection_folder.parent().unwrap_or(collection_folder);
+    let paths: Vec<PathBuf> = WalkDir::new(collection_folder)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file() && !is_partial_file(entry.path()))
+        .map(|entry| entry.into_path())
+        .collect();
+
+    let mut files = paths
+        .par_iter()
+        .map(|path| {
+            let relative = path.strip_prefix(root).unwrap_or(path);
+            Ok(PackageFile {
+                path: normalize_path(&relative.to_string_lossy()),
+                size: fs::metadata(path)?.len(),
+                sha256: hash_file(path)
+                    .with_context(|| format!("Failed to hash {}", path.display()))?,
+                volume: 1,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// Spreads the files over volumes of at most `max_bytes`, keeping `reserved`
+/// bytes in each for the manifest. Fails on a file larger than a volume.
+fn assign_volumes(files: &mut [PackageFile], max_bytes: Option<u64>, reserved: u64) -> Result<u32> {
+    let Some(max_bytes) = max_bytes else {
+        return Ok(1);
+    };
+    let capacity = max_bytes.saturating_sub(reserved).max(1);
+
+    let mut volume = 1;
+    let mut used = 0;
+    for file in files.iter_mut() {
+        let size = file.size + ENTRY_OVERHEAD + file.path.len() as u64;
+        if size > capacity {
+            return Err(anyhow::anyhow!(
+                "{} does not fit in a volume, the volume size must be at least {} MB",
+                file.path,
+                (size + reserved).div_ceil(BYTES_PER_MB)
+            ));
+        }
+        if used > 0 && used + size > capacity {
+            volume += 1;
+            used = 0;
+        }
+        file.volume = volume;
+        used += size;
+    }
+    Ok(volume)
+}
+
+fn is_volume_name(name: &str) -> bool {
+    [PackageFormat::Zip, PackageFormat::TarZst]
+        .into_iter()
+        .filter_map(|format| name.strip_suffix(&format!(".{}", archive_extension(format))))
+        .any(|stem| {
+            stem == ARCHIVE_NAME
+                || stem
+                    .strip_prefix(&format!("{}-part", ARCHIVE_NAME))
+                    .is_some_and(|part| {
+                        !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit())
+                    })
+        })
+}
+
+/// Removes the archives of an earlier packaging. Packaging into fewer volumes
+/// would otherwise leave the last ones of the old set next to the new set.
+fn remove_old_volumes(root: &Path) -> Result<()> {
+    for entry in fs::read_dir(root)? {
+        let path = entry?.path();
+        let is_volume = path.is_file()
+            && path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(is_volume_name);
+        if is_volume {
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+            tracing::info!("🗑️ [PACKAGE] Removed old volume {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+fn write_zip_volume(
+    path: &Path,
+    root: &Path,
+    files: &[&PackageFile],
+    manifest_json: &[u8],
+) -> Result<()> {
+    let mut zip = ZipWriter::new(BufWriter::new(File::create(path)?));
+
+    for file in files {
+        let stored = Path::new(&file.path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| STORED_EXTENSIONS.contains(&ext.to_lowercase().as_str()));
+        let options = SimpleFileOptions::default()
+            .compression_method(if stored {
+                CompressionMethod::Stored
+            } else {
+                CompressionMethod::Deflated
+            })
+            .large_file(file.size >= u32::MAX as u64);
+
+        zip.start_file(file.path.as_str(), options)?;
+        io::copy(&mut File::open(root.join(&file.path))?, &mut zip)?;
+    }
+
+    zip.start_file(MANIFEST_FILE_NAME, SimpleFileOptions::default())?;
+    zip.write_all(manifest_json)?;
+    zip.finish()?.flush()?;
+    Ok(())
+}
+
+fn write_tar_zst_volume(
+    path: &Path,
+    root: &Path,
+    files: &[&PackageFile],
+    manifest_json: &[u8],
+) -> Result<()> {
+    let encoder = zstd::Encoder::new(BufWriter::new(File::create(path)?), ZSTD_LEVEL)?;
+    let mut tar = tar::Builder::new(encoder);
+
+    for file in files {
+        tar.append_path_with_name(root.join(&file.path), &file.path)?;
+    }
+
+    let mut header = tar::Header::new_gnu();
+    header.set_size(manifest_json.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    tar.append_data(&mut header, MANIFEST_FILE_NAME, manifest_json)?;
+
+    tar.into_inner()?.finish()?.flush()?;
+    Ok(())
+}
+
+/// Archives `collection_folder` next to it with a `MANIFEST.json` in every
+/// volume and beside the archives. Each volume can be extracted on its own.
+pub fn write_package(
+    collection_folder: &Path,
+    config: &PackagingConfig,
+    on_progress: &mut dyn FnMut(u32, u32),
+) -> Result<PackageResult> {
+    if !collection_folder.is_dir() {
+        return Err(anyhow::anyhow!(
+            "Collection folder not found: {}",
+            collection_folder.display()
+        ));
+    }
+    let root = collection_folder.parent().unwrap_or(collection_folder);
+
+    let mut files = collect_files(collection_folder)?;
+    let total_size = files.iter().map(|file| file.size).sum();
+    let record = read_generation_record(collection_folder);
+
+    let mut manifest = PackageManifest {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        format: config.format,
+        volumes: Vec::new(),
+        shuffle_seed: record.as_ref().and_then(|record| record.shuffle_seed),
+        generation_args: record.map(|record| record.args),
+        total_size,
+        files: Vec::new(),
+    };
+
+    // The manifest is measured with every file in it before volumes are known.
+    manifest.files = files.clone();
+    let reserved = serde_json::to_vec_pretty(&manifest)?.len() as u64 + ENTRY_OVERHEAD;
+    let volume_count = assign_volumes(
+        &mut files,
+        config.max_volume_mb.map(|mb| mb.max(1) * BYTES_PER_MB),
+        reserved,
+    )?;
+    manifest.volumes = volume_names(config.format, volume_count);
+    manifest.files = files;
+    let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+
+    remove_old_volumes(root)?;
+
+    // Volumes are written under temporary names and moved into place together.
+    let mut staged = StagedFiles::default();
+    let mut archives = Vec::new();
+    on_progress(0, volume_count);
+
+    for (index, name) in manifest.volumes.iter().enumerate() {
+        let volume = index as u32 + 1;
+        let volume_files: Vec<&PackageFile> = manifest
+            .files
+            .iter()
+            .filter(|file| file.volume == volume)
+            .collect();
+        let final_path = root.join(name);
+        let path = staged.stage(final_path.clone());
+
+        match config.format {
+            PackageFormat::Zip => write_zip_volume(&path, root, &volume_files, &manifest_json),
+            PackageFormat::TarZst => {
+                write_tar_zst_volume(&path, root, &volume_files, &manifest_json)
+            }
+        }
+        .with_context(|| format!("Failed to write {}", final_path.display()))?;
+
+        tracing::info!(
+            "📦 [PACKAGE] Volume {}/{} written to {} ({} files)",
+            volume,
+            volume_count,
+            final_path.display(),
+            volume_files.len()
+        );
+        archives.push(final_path.to_string_lossy().to_string());
+        on_progress(volume, volume_count);
+    }
+
+    let manifest_path = root.join(MANIFEST_FILE_NAME);
+    fs::write(staged.stage(manifest_path.clone()), &manifest_json)?;
+    staged.commit()?;
+
+    Ok(PackageResult {
+        archives,
+        manifest: manifest_path.to_string_lossy().to_string(),
+        files: manifest.files.len(),
+        total_size,
+    })
+}
+
+/// Packages an existing collection, for example after legendaries were mixed in.
+#[tauri::command]
+pub async fn package_collection(
+    window: Window,
+    export_folder: String,
+    packaging: PackagingConfig,
+) -> Result<PackageResult, String> {
+    let collection_folder = PathBuf::from(&export_folder).join("collection");
+
+    tokio::task::spawn_blocking(move || {
+        write_package(&collection_folder, &packaging, &mut |completed, total| {
+            send_phase_progress(&window, GenerationPhase::Packaging, completed, total)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use zip::ZipArchive;
+
+    fn file(path: &str, size: u64) -> PackageFile {
+        PackageFile {
+            path: path.to_string(),
+            size,
+            sha256: String::new(),
+            volume: 1,
+        }
+    }
+
+    fn volumes(files: &[PackageFile]) -> Vec<u32> {
+        files.iter().map(|file| file.volume).collect()
+    }
+
+    #[test]
+    fn assign_volumes_without_limit_uses_one_volume() {
+        let mut files = vec![file("a", 10 * BYTES_PER_MB), file("b", 10 * BYTES_PER_MB)];
+
+        assert_eq!(assign_volumes(&mut files, None, 0).unwrap(), 1);
+        assert_eq!(volumes(&files), vec![1, 1]);
+    }
+
+    #[test]
+    fn assign_volumes_fills_volumes_in_order() {
+        // Each file takes 4025 bytes with its entry overhead.
+        let mut files = vec![file("a", 3000), file("b", 3000), file("c", 3000)];
+        assert_eq!(assign_volumes(&mut files, Some(10_000), 0).unwrap(), 2);
+        assert_eq!(volumes(&files), vec![1, 1, 2]);
+
+        // The space kept for the manifest leaves room for one file only.
+        assert_eq!(assign_volumes(&mut files, Some(10_000), 2000).unwrap(), 3);
+        assert_eq!(volumes(&files), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn assign_volumes_rejects_files_larger_than_a_volume() {
+        let mut files = vec![
+            file("a", 3000),
+            file("images/b.png", 20_000),
+            file("c", 3000),
+        ];
+
+        let error = assign_volumes(&mut files, Some(10_000), 0)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("images/b.png"), "{}", error);
+    }
+
+    #[test]
+    fn volume_names_are_recognized() {
+        assert!(is_volume_name("collection.zip"));
+        assert!(is_volume_name("collection-part12.tar.zst"));
+        assert!(!is_volume_name("collection-part.zip"));
+        assert!(!is_volume_name("collection-partial.zip"));
+        assert!(!is_volume_name("other-part1.zip"));
+        assert!(!is_volume_name(MANIFEST_FILE_NAME));
+    }
+
+    #[test]
+    fn manifest_round_trips_through_every_volume() {
+        let root = std::env::temp_dir().join(format!("blendgine_package_{}", std::process::id()));
+        let collection = root.join("collection");
+        fs::create_dir_all(collection.join("images")).unwrap();
+        fs::create_dir_all(collection.join("collection infos")).unwrap();
+        fs::write(collection.join("images/1.png"), vec![1u8; 700 * 1024]).unwrap();
+        fs::write(collection.join("images/2.png"), vec![2u8; 700 * 1024]).unwrap();
+        let record = GenerationRecord {
+            app_version: "test".to_string(),
+            generated_at: "now".to_string(),
+            shuffle_seed: Some(42),
+            args: serde_json::json!({ "collectionName": "Test" }),
+        };
+        fs::write(
+            collection
+                .join("collection infos")
+                .join(GENERATION_RECORD_FILE_NAME),
+            serde_json::to_string(&record).unwrap(),
+        )
+        .unwrap();
+
+        let config = PackagingConfig {
+            enabled: true,
+            format: PackageFormat::Zip,
+            max_volume_mb: Some(1),
+        };
+        let result = write_package(&collection, &config, &mut |_, _| {}).unwrap();
+        assert_eq!(result.archives.len(), 2);
+        assert_eq!(result.files, 3);
+
+        let manifest_json = fs::read(root.join(MANIFEST_FILE_NAME)).unwrap();
+        let manifest: PackageManifest = serde_json::from_slice(&manifest_json).unwrap();
+        assert_eq!(manifest.shuffle_seed, Some(42));
+        assert_eq!(manifest.generation_args, Some(record.args));
+        assert_eq!(
+            manifest.volumes,
+            vec!["collection-part1.zip", "collection-part2.zip"]
+        );
+        for file in &manifest.files {
+            assert_eq!(file.sha256, hash_file(&root.join(&file.path)).unwrap());
+        }
+
+        for (index, name) in manifest.volumes.iter().enumerate() {
+            let mut archive = ZipArchive::new(File::open(root.join(name)).unwrap()).unwrap();
+            let mut embedded = Vec::new();
+            archive
+                .by_name(MANIFEST_FILE_NAME)
+                .unwrap()
+                .read_to_end(&mut embedded)
+                .unwrap();
+            assert_eq!(embedded, manifest_json);
+
+            for file in manifest
+                .files
+                .iter()
+                .filter(|file| file.volume == index as u32 + 1)
+            {
+                assert_eq!(archive.by_name(&file.path).unwrap().size(), file.size);
+            }
+        }
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn repackaging_into_fewer_volumes_removes_the_old_ones() {
+        let root = std::env::temp_dir().join(format!("blendgine_repackage_{}", std::process::id()));
+        let collection = root.join("collection");
+        fs::create_dir_all(collection.join("images")).unwrap();
+        fs::write(collection.join("images/1.png"), vec![1u8; 700 * 1024]).unwrap();
+        fs::write(collection.join("images/2.png"), vec![2u8; 700 * 1024]).unwrap();
+
+        let mut config = PackagingConfig {
+            enabled: true,
+            format: PackageFormat::Zip,
+            max_volume_mb: Some(1),
+        };
+        let split = write_package(&collection, &config, &mut |_, _| {}).unwrap();
+        assert_eq!(split.archives.len(), 2);
+
+        config.max_volume_mb = None;
+        let single = write_package(&collection, &config, &mut |_, _| {}).unwrap();
+        assert_eq!(single.archives.len(), 1);
+
+        let mut archives: Vec<String> = fs::read_dir(&root)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| is_volume_name(name))
+            .collect();
+        archives.sort();
+        assert_eq!(archives, vec!["collection.zip"]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,221 @@
+use anyhow::{Context, Result};
+use image::ImageFormat;
+use std::{
+    fs,
+    io::Cursor,
+    path::{Path, PathBuf},
+};
+use tauri::{ipc::Response, AppHandle, State};
+
+use crate::{
+    effects::core::gpu::blend_modes_gpu::GpuBlendContext,
+    filesystem::{
+        persist::{
+            load_image_setup_state, load_layer_order_state, load_projectsetup_state,
+            load_rarity_config,
+        },
+        projects::ProjectRegistry,
+        temp_dir::get_secure_working_dir,
+    },
+    generation::{
+        generate::{
+            generate::active_layers_for_set,
+            generate_single::{
+                animated_single::animated_single_cpu::compose_animated_frames,
+                static_single::{
+                    get_or_init_shared_gpu_pipeline, output_image, render_static_image,
+                },
+            },
+            save_animation::{save::structs::WorkerOptions, spawn_worker::spawn_animation_worker},
+        },
+        generation_main::load_state,
+    },
+    layerpreview::animations::{get_animated_frames_dir, timeline::AnimationTimeline},
+    types::NFTTrait,
+};
+
+/// Scales `width`x`height` down so that its longest side is `size`.
+fn preview_dimensions(width: u32, height: u32, size: Option<u32>) -> (u32, u32) {
+    let longest = width.max(height);
+    match size {
+        Some(size) if size > 0 && size < longest => {
+            let scale = size as f64 / longest as f64;
+            (
+                ((width as f64 * scale).round() as u32).max(1),
+                ((height as f64 * scale).round() as u32).max(1),
+            )
+        }
+        _ => (width, height),
+    }
+}
+
+/// Spritesheets folder that animated generation reads its traits from.
+pub fn animated_working_folder(app_handle: &AppHandle, input_folder: &Path) -> Result<PathBuf> {
+    let last_folder = input_folder
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| anyhow::anyhow!("Invalid input folder name"))?;
+    Ok(get_animated_frames_dir(app_handle, last_folder)
+        .map_err(|e| anyhow::anyhow!(e))?
+        .join("spritesheets"))
+}
+
+/// Renders a trait selection through the generation path. Static collections
+/// give a PNG, animated ones an animated WebP. Without `size` the pixels are
+/// the ones `generate_nfts` writes for the same traits.
+#[tauri::command]
+pub async fn render_preview(
+    app_handle: AppHandle,
+    registry: State<'_, ProjectRegistry>,
+    set_id: String,
+    traits: Vec<NFTTrait>,
+    size: Option<u32>,
+    fps: Option<u32>,
+) -> Result<Response, String> {
+    render_preview_bytes(&app_handle, registry, &set_id, &traits, size, fps)
+        .await
+        .map(Response::new)
+        .map_err(|e| {
+            tracing::error!("❌ [PREVIEW] Failed to render set {}: {:#}", set_id, e);
+            e.to_string()
+        })
+}
+
+async fn render_preview_bytes(
+    app_handle: &AppHandle,
+    registry: State<'_, ProjectRegistry>,
+    set_id: &str,
+    traits: &[NFTTrait],
+    size: Option<u32>,
+    fps: Option<u32>,
+) -> Result<Vec<u8>> {
+    let project_setup = load_state(
+        || load_projectsetup_state(registry.clone()),
+        "project setup",
+    )
+    .await?
+    .ok_or_else(|| anyhow::anyhow!("Project setup not found"))?;
+    let image_setup =
+        load_state(|| load_image_setup_state(registry.clone()), "image setup").await?;
+    let layer_order =
+        load_state(|| load_layer_order_state(registry.clone()), "layer order").await?;
+    let rarity_config = load_state(
+        || load_rarity_config(registry.clone()),
+        "rarity configuration",
+    )
+    .await?;
+
+    let input_folder = project_setup
+        .selected_folder
+        .as_deref()
+        .map(PathBuf::from)
+        .ok_or_else(|| anyhow::anyhow!("No layer folder selected"))?;
+    let set_layers = &layer_order
+        .sets
+        .get(set_id)
+        .ok_or_else(|| anyhow::anyhow!("Unknown set: {}", set_id))?
+        .layers;
+    let active_layer_order = active_layers_for_set(set_layers, &rarity_config, set_id);
+
+    let (width, height) =
+        preview_dimensions(image_setup.final_width, image_setup.final_height, size);
+
+    if !project_setup.is_animated_collection {
+        let image = render_static_image(
+            traits,
+            &active_layer_order,
+            &input_folder,
+            image_setup.base_width,
+            image_setup.base_height,
+            width,
+            height,
+            &rarity_config,
+            set_id,
+            image_setup.resize_config.as_ref(),
+        )
+        .await?;
+
+        let (image, _) = output_image(image, &image_setup.image_format)?;
+        let mut bytes = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .context("Failed to encode preview PNG")?;
+
+        tracing::debug!(
+            "🖼️ [PREVIEW] Rendered {}x{} PNG for set {} ({} bytes)",
+            width,
+            height,
+            set_id,
+            bytes.len()
+        );
+        return Ok(bytes);
+    }
+
+    let spritesheet_layout = project_setup
+        .spritesheet_layout
+        .ok_or_else(|| anyhow::anyhow!("Animated collection has no spritesheet layout"))?;
+
+    get_or_init_shared_gpu_pipeline()
+        .await
+        .context("Failed to initialize GPU pipeline")?;
+    if GpuBlendContext::initialize_global().await.is_none() {
+        return Err(anyhow::anyhow!("Failed to initialize GPU blend context"));
+    }
+
+    let working_folder = animated_working_folder(app_handle, &input_folder)?;
+    let timeline = working_folder.parent().and_then(AnimationTimeline::load);
+
+    let source = {
+        let traits = traits.to_vec();
+        let set_id = set_id.to_string();
+        tokio::task::spawn_blocking(move || {
+            compose_animated_frames(
+                &traits,
+                &active_layer_order,
+                &input_folder,
+                Some(&working_folder),
+                &spritesheet_layout,
+                &rarity_config,
+                &set_id,
+            )
+        })
+        .await??
+    };
+
+    let frame_durations_ms = timeline
+        .filter(|timeline| {
+            timeline.is_variable() && timeline.frame_count() as usize == source.len()
+        })
+        .map(|timeline| timeline.frame_durations_ms);
+
+    let quality_config = image_setup.animation_quality.unwrap_or_default();
+    let output_path = tempfile::Builder::new()
+        .prefix("preview_")
+        .suffix(".webp")
+        .tempfile_in(get_secure_working_dir()?)?
+        .into_temp_path();
+
+    let options = WorkerOptions {
+        output_path: output_path.to_string_lossy().to_string(),
+        width,
+        height,
+        delay: (1000.0 / fps.unwrap_or(24) as f32) as u32,
+        optimize: quality_config.optimize,
+        format: Some("webp".to_string()),
+        quality_config: Some(quality_config),
+        resize_config: image_setup.resize_config.clone(),
+        frame_durations_ms,
+    };
+
+    spawn_animation_worker(source, options).await?;
+
+    let bytes = fs::read(&output_path).context("Failed to read preview WebP")?;
+    tracing::debug!(
+        "🎞️ [PREVIEW] Rendered {}x{} WebP for set {} ({} bytes)",
+        width,
+        height,
+        set_id,
+        bytes.len()
+    );
+    Ok(bytes)
+}
@@ -0,0 +1,456 @@
+use anyhow::Result;
+use dashmap::DashMap;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::PathBuf,
+    sync::Arc,
+    time::Instant,
+};
+use tauri::{AppHandle, State};
+
+use crate::{
+    filesystem::{
+        persist::{
+            load_forced_combination_state, load_image_setup_state, load_incompatibility_state,
+            load_layer_order_state, load_projectsetup_state, load_rarity_config,
+        },
+        projects::ProjectRegistry,
+    },
+    generation::{
+        generate::{
+            generate::{active_layers_for_set, precompute_rarity_cache, GlobalGenerationCaches},
+            generate_single::generate_traits::{select_traits, Rejection, MAX_ATTEMPTS},
+            layers::traits_selection::{
+                calculate_adjusted_probabilities, precompute_incompatibilities,
+            },
+        },
+        generation_main::load_state,
+        preview::animated_working_folder,
+    },
+    types::{ForcedCombinations, Incompatibilities, NFTTrait, RarityConfig},
+};
+
+const MAX_SAMPLES: u32 = 1_000_000;
+const TOP_REJECTIONS: usize = 20;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraitFrequency {
+    pub count: u32,
+    /// Share of the generated items that got this trait.
+    pub frequency: f64,
+    /// Share configured in the rarity config, before rules are applied.
+    pub expected: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttemptsBucket {
+    pub max_attempts: u32,
+    pub count: u32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RejectionCount {
+    pub rejection: Rejection,
+    pub count: u32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetSimulation {
+    pub set_id: String,
+    pub samples: u32,
+    pub generated: u32,
+    pub failures: u32,
+    pub failure_rate: f64,
+    /// Draws whose DNA was already taken, retried unless duplicates are allowed.
+    pub duplicates: u32,
+    pub duplicate_rate: f64,
+    pub average_attempts: f64,
+    pub attempts_histogram: Vec<AttemptsBucket>,
+    pub trait_frequencies: BTreeMap<String, BTreeMap<String, TraitFrequency>>,
+    pub top_rejections: Vec<RejectionCount>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationReport {
+    pub samples: u32,
+    pub elapsed_ms: u64,
+    pub sets: Vec<SetSimulation>,
+}
+
+#[derive(Default)]
+struct SetTally {
+    generated: u32,
+    failures: u32,
+    duplicates: u32,
+    total_attempts: u64,
+    attempts: BTreeMap<u32, u32>,
+    traits: HashMap<(String, String), u32>,
+    rejections: HashMap<Rejection, u32>,
+    first_error: Option<String>,
+}
+
+impl SetTally {
+    fn merge(mut self, other: Self) -> Self {
+        self.generated += other.generated;
+        self.failures += other.failures;
+        self.duplicates += other.duplicates;
+        self.total_attempts += other.total_attempts;
+        for (bucket, count) in other.attempts {
+            *self.attempts.entry(bucket).or_insert(0) += count;
+        }
+        for (key, count) in other.traits {
+            *self.traits.entry(key).or_insert(0) += count;
+        }
+        for (rejection, count) in other.rejections {
+            *self.rejections.entry(rejection).or_insert(0) += count;
+        }
+        self.first_error = self.first_error.or(other.first_error);
+        self
+    }
+}
+
+struct SimulationInputs {
+    input_folder: PathBuf,
+    working_folder: Option<PathBuf>,
+    is_animated_collection: bool,
+    allow_duplicates: bool,
+    rarity_config: RarityConfig,
+    sets: Vec<(String, Vec<String>, u32)>,
+    caches: GlobalGenerationCaches,
+}
+
+fn attempts_bucket(attempts: u32) -> u32 {
+    attempts.next_power_of_two().min(MAX_ATTEMPTS)
+}
+
+fn rate(count: u32, total: u32) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 / total as f64
+    }
+}
+
+/// Splits `samples` across the sets in proportion to their NFT counts.
+fn split_samples(samples: u32, counts: &[u32]) -> Vec<u32> {
+    let total: u64 = counts.iter().map(|&count| count as u64).sum();
+    if total == 0 {
+        return vec![0; counts.len()];
+    }
+
+    let mut shares: Vec<u32> = counts
+        .iter()
+        .map(|&count| (samples as u64 * count as u64 / total) as u32)
+        .collect();
+    let mut remaining = samples - shares.iter().sum::<u32>();
+    for (share, &count) in shares.iter_mut().zip(counts) {
+        if remaining == 0 {
+            break;
+        }
+        if count > 0 {
+            *share += 1;
+            remaining -= 1;
+        }
+    }
+    shares
+}
+
+/// Samples are drawn in batches of `nft_count`, each batch standing for one
+/// generated collection, so duplicates are only counted within a batch.
+fn simulate_set(
+    inputs: &SimulationInputs,
+    set_id: &str,
+    active_layer_order: &[String],
+    nft_count: u32,
+    samples: u32,
+) -> SetSimulation {
+    let default_incompatibilities = HashMap::new();
+    let default_forced_combinations = ForcedCombinations::default();
+    let incompatibility_map = inputs
+        .caches
+        .incompatibility_maps
+        .get(set_id)
+        .unwrap_or(&default_incompatibilities);
+    let forced_combinations = inputs
+        .caches
+        .forced_combinations_maps
+        .get(set_id)
+        .unwrap_or(&default_forced_combinations);
+    let batch_size = nft_count.max(1);
+    // Batches run one after another so only one of them keeps its DNAs.
+    let seen_dna: DashMap<String, ()> = DashMap::new();
+
+    let mut tally = SetTally::default();
+    for batch_start in (0..samples).step_by(batch_size as usize) {
+        seen_dna.clear();
+        let batch_end = samples.min(batch_start.saturating_add(batch_size));
+        let batch = (batch_start..batch_end)
+            .into_par_iter()
+            .fold(SetTally::default, |mut tally, _| {
+                let mut is_unique =
+                    |dna: &str, _: &[NFTTrait]| seen_dna.insert(dna.to_string(), ()).is_none();
+                let result = select_traits(
+                    &inputs.input_folder,
+                    active_layer_order,
+                    &inputs.rarity_config,
+                    incompatibility_map,
+                    forced_combinations,
+                    set_id,
+                    inputs.is_animated_collection,
+                    inputs.working_folder.as_deref(),
+                    &inputs.caches,
+                    if inputs.allow_duplicates {
+                        None
+                    } else {
+                        Some(&mut is_unique)
+                    },
+                    &mut |rejection| {
+                        if rejection == Rejection::Duplicate {
+                            tally.duplicates += 1;
+                        }
+                        *tally.rejections.entry(rejection).or_insert(0) += 1;
+                    },
+                );
+
+                match result {
+                    Ok((traits, dna, attempts)) => {
+                        if inputs.allow_duplicates && seen_dna.insert(dna, ()).is_some() {
+                            tally.duplicates += 1;
+                        }
+                        tally.generated += 1;
+                        tally.total_attempts += attempts as u64;
+                        *tally.attempts.entry(attempts_bucket(attempts)).or_insert(0) += 1;
+                        for nft_trait in traits {
+                            *tally
+                                .traits
+                                .entry((nft_trait.trait_type, nft_trait.value))
+                                .or_insert(0) += 1;
+                        }
+                    }
+                    Err(e) => {
+                        tally.failures += 1;
+                        if tally.first_error.is_none() {
+                            tally.first_error = Some(e.to_string());
+                        }
+                    }
+                }
+                tally
+            })
+            .reduce(SetTally::default, SetTally::merge);
+        tally = tally.merge(batch);
+    }
+
+    let mut trait_frequencies: BTreeMap<String, BTreeMap<String, TraitFrequency>> = BTreeMap::new();
+    for layer in active_layer_order {
+        let expected = calculate_adjusted_probabilities(
+            layer,
+            &inputs.rarity_config,
+            &ForcedCombinations::default(),
+            &[],
+            set_id,
+            &inputs.caches,
+        );
+        let layer_frequencies = trait_frequencies.entry(layer.clone()).or_default();
+        for (trait_name, probability) in expected {
+            layer_frequencies.insert(
+                trait_name,
+                TraitFrequency {
+                    count: 0,
+                    frequency: 0.0,
+                    expected: probability,
+                },
+            );
+        }
+    }
+    for ((layer, trait_name), count) in tally.traits {
+        let frequency = trait_frequencies
+            .entry(layer)
+            .or_default()
+            .entry(trait_name)
+            .or_insert(TraitFrequency {
+                count: 0,
+                frequency: 0.0,
+                expected: 0.0,
+            });
+        frequency.count = count;
+        frequency.frequency = rate(count, tally.generated);
+    }
+
+    let mut top_rejections: Vec<RejectionCount> = tally
+        .rejections
+        .into_iter()
+        .map(|(rejection, count)| RejectionCount { rejection, count })
+        .collect();
+    top_rejections.sort_by(|a, b| b.count.cmp(&a.count));
+    top_rejections.truncate(TOP_REJECTIONS);
+
+    SetSimulation {
+        set_id: set_id.to_string(),
+        samples,
+        generated: tally.generated,
+        failures: tally.failures,
+        failure_rate: rate(tally.failures, samples),
+        duplicates: tally.duplicates,
+        duplicate_rate: rate(tally.duplicates, samples),
+        average_attempts: if tally.generated == 0 {
+            0.0
+        } else {
+            tally.total_attempts as f64 / tally.generated as f64
+        },
+        attempts_histogram: tally
+            .attempts
+            .into_iter()
+            .map(|(max_attempts, count)| AttemptsBucket {
+                max_attempts,
+                count,
+            })
+            .collect(),
+        trait_frequencies,
+        top_rejections,
+        first_error: tally.first_error,
+    }
+}
+
+fn run_simulation(inputs: &SimulationInputs, samples: u32) -> SimulationReport {
+    let start = Instant::now();
+    let counts: Vec<u32> = inputs.sets.iter().map(|(_, _, count)| *count).collect();
+
+    let sets = inputs
+        .sets
+        .iter()
+        .zip(split_samples(samples, &counts))
+        .filter(|(_, set_samples)| *set_samples > 0)
+        .map(|((set_id, layers, nft_count), set_samples)| {
+            simulate_set(inputs, set_id, layers, *nft_count, set_samples)
+        })
+        .collect();
+
+    SimulationReport {
+        samples,
+        elapsed_ms: start.elapsed().as_millis() as u64,
+        sets,
+    }
+}
+
+/// Runs the trait selection of every set `n_runs` times in total, without
+/// rendering or writing anything, and reports how the rules shape the result.
+#[tauri::command]
+pub async fn simulate_generation(
+    app_handle: AppHandle,
+    registry: State<'_, ProjectRegistry>,
+    n_runs: u32,
+) -> Result<SimulationReport, String> {
+    let samples = n_runs.min(MAX_SAMPLES);
+    let inputs = load_simulation_inputs(&app_handle, registry)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let report = tokio::task::spawn_blocking(move || run_simulation(&inputs, samples))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    tracing::info!(
+        "🎲 [SIMULATION] {} samples over {} sets in {} ms",
+        report.samples,
+        report.sets.len(),
+        report.elapsed_ms
+    );
+    Ok(report)
+}
+
+async fn load_simulation_inputs(
+    app_handle: &AppHandle,
+    registry: State<'_, ProjectRegistry>,
+) -> Result<SimulationInputs> {
+    let project_setup = load_state(
+        || load_projectsetup_state(registry.clone()),
+        "project setup",
+    )
+    .await?
+    .ok_or_else(|| anyhow::anyhow!("Project setup not found"))?;
+    let image_setup =
+        load_state(|| load_image_setup_state(registry.clone()), "image setup").await?;
+    let layer_order =
+        load_state(|| load_layer_order_state(registry.clone()), "layer order").await?;
+    let rarity_config = load_state(
+        || load_rarity_config(registry.clone()),
+        "rarity configuration",
+    )
+    .await?;
+    let incompatibilities = load_state(
+        || load_incompatibility_state(registry.clone()),
+        "incompatibilities",
+    )
+    .await?;
+    let forced_combinations = load_state(
+        || load_forced_combination_state(registry.clone()),
+        "forced combinations",
+    )
+    .await?;
+
+    let input_folder = project_setup
+        .selected_folder
+        .as_deref()
+        .map(PathBuf::from)
+        .ok_or_else(|| anyhow::anyhow!("No layer folder selected"))?;
+    let working_folder = if project_setup.is_animated_collection {
+        Some(animated_working_folder(app_handle, &input_folder)?)
+    } else {
+        None
+    };
+
+    let mut sets: Vec<(String, Vec<String>, u32)> = layer_order
+        .sets
+        .iter()
+        .map(|(set_id, set_info)| {
+            (
+                set_id.clone(),
+                active_layers_for_set(&set_info.layers, &rarity_config, set_id),
+                set_info.nft_count,
+            )
+        })
+        .collect();
+    sets.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let default_incompatibilities = Incompatibilities::default();
+    let incompatibility_maps: HashMap<String, HashMap<String, HashSet<String>>> = sets
+        .iter()
+        .map(|(set_id, _, _)| {
+            let set_incompatibilities = incompatibilities
+                .sets
+                .get(set_id)
+                .unwrap_or(&default_incompatibilities);
+            (
+                set_id.clone(),
+                precompute_incompatibilities(set_incompatibilities),
+            )
+        })
+        .collect();
+
+    let caches = GlobalGenerationCaches {
+        incompatibility_maps: Arc::new(incompatibility_maps),
+        forced_combinations_maps: Arc::new(forced_combinations.sets),
+        rarity_probability_cache: Arc::new(precompute_rarity_cache(&rarity_config)),
+        uniqueness_cache: Arc::new(DashMap::new()),
+        file_lookup_cache: Arc::new(DashMap::new()),
+    };
+
+    Ok(SimulationInputs {
+        input_folder,
+        working_folder,
+        is_animated_collection: project_setup.is_animated_collection,
+        allow_duplicates: image_setup.allow_duplicates,
+        rarity_config,
+        sets,
+        caches,
+    })
+}
@@ -1,22 +1,34 @@
-use crate::types::SpritesheetLayout;
+use std::{fs, ops::Range, path::Path};
+
+use crate::{effects::core::transform::trim::TrimRegion, types::SpritesheetLayout};
+
+/// Layout a trait's spritesheets were packed with, saved next to them.
+pub const TRAIT_LAYOUT_FILE: &str = "layout.json";
 
 impl SpritesheetLayout {
+    /// Grid of cells holding the `cell` part of each
+    /// `frame_width` x `frame_height` frame.
     pub fn calculate(
         frame_width: u32,
         frame_height: u32,
+        cell: TrimRegion,
         total_frames: u32,
         max_texture_size: u32,
     ) -> Self {
+        let cell_width = cell.width.max(1);
+        let cell_height = cell.height.max(1);
         let frames_per_side = (total_frames as f32).sqrt().ceil() as u32;
         let mut rows = frames_per_side;
         let mut cols = frames_per_side;
 
-        let total_width = cols * frame_width;
-        let total_height = rows * frame_height;
+        let total_width = cols * cell_width;
+        let total_height = rows * cell_height;
 
-        if total_width > max_texture_size || total_height > max_texture_size {
-            let max_frames_per_row = max_texture_size / frame_width;
-            let max_frames_per_col = max_texture_size / frame_height;
+        let (rows, cols, total_sheets, frames_per_sheet) = if total_width > max_texture_size
+            || total_height > max_texture_size
+        {
+            let max_frames_per_row = max_texture_size / cell_width;
+            let max_frames_per_col = max_texture_size / cell_height;
             let max_frames_per_sheet = max_frames_per_row * max_frames_per_col;
 
             let total_sheets = (total_frames as f32 / max_frames_per_sheet as f32).ceil() as u32;
@@ -25,32 +37,144 @@ impl SpritesheetLayout {
             rows = ((max_frames_per_sheet as f32) / cols as f32).ceil() as u32;
 
             while cols * rows < max_frames_per_sheet {
-                if cols * frame_width < max_texture_size {
+                if cols * cell_width < max_texture_size {
                     cols += 1;
                 } else {
                     rows += 1;
                 }
             }
 
-            Self {
-                rows,
-                cols,
-                total_sheets,
-                frames_per_sheet: max_frames_per_sheet,
-                frame_width,
-                frame_height,
-                total_frames,
-            }
+            (rows, cols, total_sheets, max_frames_per_sheet)
         } else {
-            Self {
-                rows,
-                cols,
-                total_sheets: 1,
-                frames_per_sheet: total_frames,
-                frame_width,
-                frame_height,
-                total_frames,
-            }
+            (rows, cols, 1, total_frames)
+        };
+
+        Self {
+            rows,
+            cols,
+            frame_width,
+            frame_height,
+            total_sheets,
+            frames_per_sheet,
+            total_frames,
+            cell_x: cell.x,
+            cell_y: cell.y,
+            cell_width,
+            cell_height,
+        }
+    }
+
+    pub fn cell(&self) -> TrimRegion {
+        if self.cell_width == 0 || self.cell_height == 0 {
+            return TrimRegion::full(self.frame_width, self.frame_height);
+        }
+
+        TrimRegion {
+            x: self.cell_x,
+            y: self.cell_y,
+            width: self.cell_width,
+            height: self.cell_height,
         }
     }
+
+    pub fn is_trimmed(&self) -> bool {
+        self.cell() != TrimRegion::full(self.frame_width, self.frame_height)
+    }
+
+    fn sheet_capacity(&self) -> u32 {
+        if self.total_sheets > 1 {
+            self.frames_per_sheet.max(1)
+        } else {
+            self.total_frames.max(1)
+        }
+    }
+
+    /// Frames held by spritesheet `sheet`.
+    pub fn sheet_frames(&self, sheet: u32) -> Range<u32> {
+        let start = sheet.saturating_mul(self.sheet_capacity());
+        start
+            ..start
+                .saturating_add(self.sheet_capacity())
+                .min(self.total_frames)
+    }
+
+    /// Spritesheet holding `frame` and the top-left corner of its cell there.
+    pub fn cell_position(&self, frame: u32) -> (u32, u32, u32) {
+        let cell = self.cell();
+        let cols = self.cols.max(1);
+        let offset = frame % self.sheet_capacity();
+        (
+            frame / self.sheet_capacity(),
+            (offset % cols) * cell.width,
+            (offset / cols) * cell.height,
+        )
+    }
+
+    /// Layout saved in a trait's spritesheet folder, `None` for sheets
+    /// packed before layouts were saved, which use the project layout.
+    pub fn read_from(dir: &Path) -> Option<Self> {
+        let path = dir.join(TRAIT_LAYOUT_FILE);
+        let content = fs::read_to_string(&path).ok()?;
+        serde_json::from_str(&content)
+            .map_err(|e| {
+                tracing::warn!(
+                    "⚠️ [SPRITESHEET] Ignoring unreadable layout {}: {}",
+                    path.display(),
+                    e
+                )
+            })
+            .ok()
+    }
+
+    pub fn write_to(&self, dir: &Path) -> Result<(), String> {
+        let path = dir.join(TRAIT_LAYOUT_FILE);
+        let content = serde_json::to_string(self)
+            .map_err(|e| format!("Failed to serialize spritesheet layout: {}", e))?;
+        fs::write(&path, content).map_err(|e| format!("write {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trimmed_cells_shrink_the_sheet() {
+        let cell = TrimRegion {
+            x: 100,
+            y: 200,
+            width: 50,
+            height: 40,
+        };
+        let layout = SpritesheetLayout::calculate(1000, 1000, cell, 16, 8192);
+
+        assert_eq!((layout.cols, layout.rows, layout.total_sheets), (4, 4, 1));
+        assert_eq!(layout.cell(), cell);
+        assert!(layout.is_trimmed());
+        assert_eq!(layout.cell_position(5), (0, 50, 40));
+        assert_eq!(layout.sheet_frames(0), 0..16);
+    }
+
+    #[test]
+    fn frames_past_the_texture_size_move_to_the_next_sheet() {
+        let layout =
+            SpritesheetLayout::calculate(4000, 4000, TrimRegion::full(4000, 4000), 10, 8192);
+
+        assert_eq!((layout.cols, layout.rows, layout.total_sheets), (2, 2, 3));
+        assert!(!layout.is_trimmed());
+        assert_eq!(layout.cell_position(5), (1, 4000, 0));
+        assert_eq!(layout.sheet_frames(2), 8..10);
+    }
+
+    #[test]
+    fn layouts_saved_without_cells_hold_the_whole_frame() {
+        let layout: SpritesheetLayout = serde_json::from_str(
+            r#"{"rows":2,"cols":2,"frameWidth":64,"frameHeight":32,"totalSheets":1,"framesPerSheet":4,"totalFrames":4}"#,
+        )
+        .unwrap();
+
+        assert_eq!(layout.cell(), TrimRegion::full(64, 32));
+        assert!(!layout.is_trimmed());
+        assert_eq!(layout.cell_position(3), (0, 64, 32));
+    }
 }
@@ -1,7 +1,7 @@
 use super::{CURRENT_LAYOUT, FRAME_DIMENSIONS, GLOBAL_MAX_FRAMES};
 use crate::{
     layerpreview::animations::{get_animated_frames_dir, FrameProcessor},
-    types::SpritesheetLayout,
+    types::{LayerPlayback, SpritesheetLayout},
 };
 use std::sync::atomic::Ordering;
 use tauri::{AppHandle, Runtime};
@@ -40,6 +40,10 @@ pub async fn get_spritesheet_metadata() -> Result<SpritesheetLayout, String> {
         total_sheets: layout.total_sheets,
         frames_per_sheet: layout.frames_per_sheet,
         total_frames: frame_count,
+        cell_x: layout.cell_x,
+        cell_y: layout.cell_y,
+        cell_width: layout.cell_width,
+        cell_height: layout.cell_height,
     };
 
     Ok(metadata)
@@ -55,6 +59,7 @@ pub async fn extract_frames<R: Runtime>(
     max_frames: u32,
     should_recreate_spritesheets: bool,
     total_files: u32,
+    playback: Option<LayerPlayback>,
 ) -> Result<Vec<String>, String> {
     let base_dir = get_animated_frames_dir(&app_handle, &project_id)?;
 
@@ -67,6 +72,7 @@ pub async fn extract_frames<R: Runtime>(
         max_frames,
         should_recreate_spritesheets,
         total_files,
+        playback.unwrap_or_default(),
     )
     .await;
 
@@ -52,9 +52,11 @@ impl WatcherState {
     }
 }
 
+/// Only spritesheets directly inside the folder count, so group folders are
+/// not mistaken for the traits they contain.
 fn check_spritesheets(dir_path: &PathBuf) -> bool {
     WalkDir::new(dir_path)
-        .max_depth(3)
+        .max_depth(1)
         .into_iter()
         .filter_map(|e| e.ok())
         .filter(|e| e.file_type().is_file())
@@ -160,39 +162,42 @@ pub fn start_animation_file_watcher<R: Runtime>(
                         if layer_entry.file_type().map_or(false, |ft| ft.is_dir()) {
                             let layer_name = layer_entry.file_name().to_string_lossy().to_string();
 
-                            if let Ok(trait_entries) = read_dir(&layer_entry.path()) {
-                                for trait_entry in trait_entries.flatten() {
-                                    if trait_entry.file_type().map_or(false, |ft| ft.is_dir()) {
-                                        total_dirs_found += 1;
-                                        let trait_name = format!(
-                                            "{}/{}",
-                                            layer_name,
-                                            trait_entry.file_name().to_string_lossy()
-                                        );
-
-                                        let trait_spritesheets_dir = trait_entry.path();
-
-                                        let has_spritesheets = check_spritesheets_with_cache(
-                                            &mut directory_cache,
-                                            &trait_spritesheets_dir,
-                                            should_full_scan,
-                                            now,
-                                        );
-
-                                        if has_spritesheets {
-                                            dirs_with_spritesheets += 1;
-                                        }
-
-                                        if has_spritesheets
-                                            && !watcher_state.contains_trait(&trait_name)
-                                        {
-                                            if watcher_state.add_completed_trait(
-                                                trait_name.clone(),
-                                                max_cache_size,
-                                            ) {
-                                                newly_completed_traits.push(trait_name);
-                                            }
-                                        }
+                            // Traits in subfolder groups sit deeper than the layer folder.
+                            let layer_path = layer_entry.path();
+                            let trait_entries = WalkDir::new(&layer_path)
+                                .min_depth(1)
+                                .into_iter()
+                                .filter_map(|e| e.ok())
+                                .filter(|e| e.file_type().is_dir());
+
+                            for trait_entry in trait_entries {
+                                let trait_spritesheets_dir = trait_entry.path().to_path_buf();
+                                total_dirs_found += 1;
+                                let trait_name = format!(
+                                    "{}/{}",
+                                    layer_name,
+                                    trait_spritesheets_dir
+                                        .strip_prefix(&layer_path)
+                                        .unwrap_or(&trait_spritesheets_dir)
+                                        .to_string_lossy()
+                                );
+
+                                let has_spritesheets = check_spritesheets_with_cache(
+                                    &mut directory_cache,
+                                    &trait_spritesheets_dir,
+                                    should_full_scan,
+                                    now,
+                                );
+
+                                if has_spritesheets {
+                                    dirs_with_spritesheets += 1;
+                                }
+
+                                if has_spritesheets && !watcher_state.contains_trait(&trait_name) {
+                                    if watcher_state
+                                        .add_completed_trait(trait_name.clone(), max_cache_size)
+                                    {
+                                        newly_completed_traits.push(trait_name);
                                     }
                                 }
                             }
@@ -39,8 +39,8 @@ pub struct FrameData {
     pub frame_index: u32,
     pub col: u32,
     pub row: u32,
-    pub frame_width: u32,
-    pub frame_height: u32,
+    pub cell_width: u32,
+    pub cell_height: u32,
     pub spritesheet_width: u32,
     pub spritesheet_height: u32,
 }
@@ -6,8 +6,9 @@ struct FrameData {
     frame_index: u32,
     col: u32,
     row: u32,
-    frame_width: u32,
-    frame_height: u32,
+    // Size of the trimmed cell, the only part of each frame uploaded.
+    cell_width: u32,
+    cell_height: u32,
     spritesheet_width: u32,
     spritesheet_height: u32,
 };
@@ -20,11 +21,11 @@ fn main(@builtin(global_invocation_id) gid: vec3<u32>) {
 
     let x = gid.x;
     let y = gid.y;
-    if (x >= fd.frame_width || y >= fd.frame_height) { return; }
+    if (x >= fd.cell_width || y >= fd.cell_height) { return; }
 
-    let src = fd.frame_index * fd.frame_width * fd.frame_height + y * fd.frame_width + x;
-    let tx = fd.col * fd.frame_width + x;
-    let ty = fd.row * fd.frame_height + y;
+    let src = fd.frame_index * fd.cell_width * fd.cell_height + y * fd.cell_width + x;
+    let tx = fd.col * fd.cell_width + x;
+    let ty = fd.row * fd.cell_height + y;
     let dst = ty * fd.spritesheet_width + tx;
 
     if (src < arrayLength(&input_frames) && dst < arrayLength(&output_spritesheet)) {
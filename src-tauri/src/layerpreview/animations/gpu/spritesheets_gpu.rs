@@ -16,6 +16,7 @@ use tokio::{
 use wgpu::*;
 
 use crate::{
+    effects::core::transform::trim::{alpha_bounds, TrimRegion},
     layerpreview::animations::{
         gpu::renderer::{FrameData, GpuSpritesheetRenderer},
         FrameProcessor, CURRENT_LAYOUT, FRAME_DIMENSIONS, MAX_TEXTURE_SIZE,
@@ -34,11 +35,32 @@ impl FrameProcessor {
         let fh = frames[0].height();
         *FRAME_DIMENSIONS.write().await = Some((fw, fh));
 
-        let layout = SpritesheetLayout::calculate(fw, fh, final_frame_count, MAX_TEXTURE_SIZE);
-        *CURRENT_LAYOUT.write().await = Some(layout.clone());
-
         let rgba_frames: Vec<_> = frames.iter().map(|f| f.to_rgba8()).collect();
 
+        // Cells hold only the area any frame draws on. The layout saved next to
+        // the sheets says where that area sits on the frame.
+        let region = rgba_frames
+            .iter()
+            .filter_map(alpha_bounds)
+            .reduce(TrimRegion::union)
+            .unwrap_or(TrimRegion {
+                x: 0,
+                y: 0,
+                width: 1,
+                height: 1,
+            });
+
+        *CURRENT_LAYOUT.write().await = Some(SpritesheetLayout::calculate(
+            fw,
+            fh,
+            TrimRegion::full(fw, fh),
+            final_frame_count,
+            MAX_TEXTURE_SIZE,
+        ));
+        let layout =
+            SpritesheetLayout::calculate(fw, fh, region, final_frame_count, MAX_TEXTURE_SIZE);
+        layout.write_to(&self.trait_spritesheet_dir)?;
+
         let (mut renderer, max_in_flight) =
             GpuSpritesheetRenderer::new_with_max_in_flight(frames).await?;
         tracing::info!("Using dynamic MAX_IN_FLIGHT: {}", max_in_flight);
@@ -69,7 +91,6 @@ impl FrameProcessor {
                                  final_frame_count: u32,
                                  frame_indices: &[usize],
                                  fw: u32,
-                                 fh: u32,
                                  rgba_frames: &[image::RgbaImage]|
          -> (Vec<FrameData>, Vec<u32>) {
             let start = sheet_idx * layout.frames_per_sheet;
@@ -78,7 +99,8 @@ impl FrameProcessor {
             let mut frame_data_vec: Vec<FrameData> = Vec::with_capacity((end - start) as usize);
             let mut needed_u32s = 0usize;
 
-            for (i, &src_idx) in frame_indices.iter().enumerate() {
+            let sheet_indices = frame_indices.get(start as usize..).unwrap_or_default();
+            for (i, &src_idx) in sheet_indices.iter().enumerate() {
                 let fi = start + i as u32;
                 if fi >= end {
                     break;
@@ -90,12 +112,12 @@ impl FrameProcessor {
                     frame_index: src_idx as u32,
                     col,
                     row,
-                    frame_width: fw,
-                    frame_height: fh,
-                    spritesheet_width: fw * layout.cols,
-                    spritesheet_height: fh * layout.rows,
+                    cell_width: region.width,
+                    cell_height: region.height,
+                    spritesheet_width: region.width * layout.cols,
+                    spritesheet_height: region.height * layout.rows,
                 });
-                needed_u32s += (fw * fh) as usize;
+                needed_u32s += (region.width * region.height) as usize;
             }
 
             if frame_data_vec.is_empty() {
@@ -107,7 +129,11 @@ impl FrameProcessor {
                 let fr = &rgba_frames[fd.frame_index as usize];
                 let raw = fr.as_raw();
                 let u32s = unsafe { from_raw_parts(raw.as_ptr() as *const u32, raw.len() / 4) };
-                input_u32.extend_from_slice(u32s);
+                for y in region.y..region.y + region.height {
+                    let row_start = (y * fw + region.x) as usize;
+                    input_u32
+                        .extend_from_slice(&u32s[row_start..row_start + region.width as usize]);
+                }
             }
 
             let mut remapped = frame_data_vec.clone();
@@ -213,7 +239,6 @@ impl FrameProcessor {
                 final_frame_count,
                 frame_indices,
                 fw,
-                fh,
                 &rgba_frames,
             );
 
@@ -223,13 +248,13 @@ impl FrameProcessor {
 
             let frame_bytes: &[u8] = cast_slice(&remapped);
 
-            let out_w = fw * layout.cols;
-            let out_h = fh * layout.rows;
+            let out_w = region.width * layout.cols;
+            let out_h = region.height * layout.rows;
             let output_pixels_count = (out_w * out_h) as usize;
 
             let (staging, rx) = renderer.dispatch_and_stage(
-                fw,
-                fh,
+                region.width,
+                region.height,
                 remapped.len() as u32,
                 frame_bytes,
                 &input_u32,
@@ -288,7 +313,6 @@ impl FrameProcessor {
                     final_frame_count,
                     frame_indices,
                     fw,
-                    fh,
                     &rgba_frames,
                 );
 
@@ -297,8 +321,8 @@ impl FrameProcessor {
                     let output_pixels_count = (failed.width * failed.height) as usize;
 
                     let (staging, rx) = renderer.dispatch_and_stage(
-                        fw,
-                        fh,
+                        region.width,
+                        region.height,
                         remapped.len() as u32,
                         frame_bytes,
                         &input_u32,
@@ -343,6 +367,17 @@ impl FrameProcessor {
 
         renderer.device.poll(Maintain::Wait);
 
+        // Trimmed cells can need fewer sheets than the last time this trait was
+        // packed; sheets past the layout are left over from then.
+        let mut stale = layout.total_sheets;
+        loop {
+            let path = out_dir.join(format!("spritesheet_{}.png", stale));
+            if fs::remove_file(&path).is_err() {
+                break;
+            }
+            stale += 1;
+        }
+
         Ok(())
     }
 }
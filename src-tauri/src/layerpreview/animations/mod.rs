@@ -6,3 +6,4 @@ pub use utils::*;
 pub mod calculate_layout;
 pub mod file_watcher;
 pub mod gpu;
+pub mod timeline;
@@ -1,4 +1,6 @@
-use image::{self, codecs::gif::GifDecoder, open, AnimationDecoder, DynamicImage, RgbaImage};
+use image::{
+    self, codecs::gif::GifDecoder, open, AnimationDecoder, DynamicImage, Frame, RgbaImage,
+};
 use std::{
     io::Cursor,
     path::Path,
@@ -10,14 +12,50 @@ use webp_animation::Decoder;
 
 use crate::ffmpeg_wrapper::FFmpegWrapper;
 use crate::filesystem::temp_dir::get_secure_working_dir;
+use crate::layerpreview::animations::{timeline::DEFAULT_FRAME_DURATION_MS, ImageFormat};
+
+pub type ExtractedFrames = (Vec<DynamicImage>, Vec<u32>);
+
+fn webp_durations(timestamps: &[i32]) -> Vec<u32> {
+    let mut previous = 0;
+    timestamps
+        .iter()
+        .map(|&timestamp| {
+            let duration = (timestamp - previous).max(0) as u32;
+            previous = timestamp;
+            duration
+        })
+        .collect()
+}
+
+fn gif_frame_duration(frame: &Frame) -> u32 {
+    let (numer, denom) = frame.delay().numer_denom_ms();
+    if denom == 0 {
+        0
+    } else {
+        numer / denom
+    }
+}
 
-pub fn extract_webp_frames(data: &[u8]) -> Result<Vec<DynamicImage>, String> {
+fn video_durations(fps: f32, frame_count: usize) -> Vec<u32> {
+    let duration = if fps > 0.0 {
+        (1000.0 / fps).round() as u32
+    } else {
+        DEFAULT_FRAME_DURATION_MS
+    };
+    vec![duration; frame_count]
+}
+
+pub fn extract_webp_frames(data: &[u8]) -> Result<ExtractedFrames, String> {
     let decoder = Decoder::new(data).map_err(|e| format!("Decoder error: {:?}", e))?;
     let mut result = Vec::new();
+    let mut timestamps = Vec::new();
 
     let (width, height) = decoder.dimensions();
 
     for frame in decoder.into_iter() {
+        timestamps.push(frame.timestamp());
+
         let frame_rgba_bytes = frame
             .into_image()
             .map_err(|_| "Failed to convert WebP frame to RGBA bytes".to_string())?;
@@ -27,10 +65,10 @@ pub fn extract_webp_frames(data: &[u8]) -> Result<Vec<DynamicImage>, String> {
 
         result.push(DynamicImage::ImageRgba8(rgba_image));
     }
-    Ok(result)
+    Ok((result, webp_durations(&timestamps)))
 }
 
-pub fn extract_gif_frames(data: &[u8]) -> Result<Vec<DynamicImage>, String> {
+pub fn extract_gif_frames(data: &[u8]) -> Result<ExtractedFrames, String> {
     let decoder = GifDecoder::new(Cursor::new(data))
         .map_err(|e| format!("Failed to create GIF decoder: {}", e))?;
     let frames = decoder
@@ -39,14 +77,56 @@ pub fn extract_gif_frames(data: &[u8]) -> Result<Vec<DynamicImage>, String> {
         .map_err(|e| format!("Failed to collect GIF frames: {}", e))?;
 
     let mut frame_images = Vec::new();
+    let mut durations = Vec::new();
     for frame in frames.iter() {
         let dynamic_image = DynamicImage::ImageRgba8(frame.buffer().clone());
         frame_images.push(dynamic_image);
+        durations.push(gif_frame_duration(frame));
     }
-    Ok(frame_images)
+    Ok((frame_images, durations))
 }
 
-pub async fn extract_video_frames(video_path: &Path) -> Result<Vec<DynamicImage>, String> {
+pub fn probe_frame_durations(path: &Path) -> Result<Vec<u32>, String> {
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    let format = ImageFormat::from_extension(extension)
+        .ok_or_else(|| format!("Unsupported file extension: {}", extension))?;
+
+    if format.is_video() {
+        let ffmpeg =
+            FFmpegWrapper::new().map_err(|e| format!("Failed to initialize FFmpeg: {}", e))?;
+        let (fps, frame_count) = ffmpeg
+            .probe_video_timing(path)
+            .map_err(|e| format!("Failed to probe video timing: {}", e))?;
+        return Ok(video_durations(fps, frame_count.max(1) as usize));
+    }
+
+    match format {
+        ImageFormat::Gif => {
+            let data = std::fs::read(path).map_err(|e| format!("Failed to read GIF: {}", e))?;
+            let decoder = GifDecoder::new(Cursor::new(data))
+                .map_err(|e| format!("Failed to create GIF decoder: {}", e))?;
+
+            decoder
+                .into_frames()
+                .map(|frame| {
+                    frame
+                        .map(|frame| gif_frame_duration(&frame))
+                        .map_err(|e| format!("Failed to read GIF frame: {}", e))
+                })
+                .collect()
+        }
+        ImageFormat::WebP => {
+            let data = std::fs::read(path).map_err(|e| format!("Failed to read WebP: {}", e))?;
+            let decoder = Decoder::new(&data).map_err(|e| format!("Decoder error: {:?}", e))?;
+            let timestamps: Vec<i32> = decoder.into_iter().map(|frame| frame.timestamp()).collect();
+
+            Ok(webp_durations(&timestamps))
+        }
+        _ => Ok(vec![DEFAULT_FRAME_DURATION_MS]),
+    }
+}
+
+pub async fn extract_video_frames(video_path: &Path) -> Result<ExtractedFrames, String> {
     let base_temp_dir = get_secure_working_dir()
         .map_err(|e| format!("Failed to get secure working directory: {}", e))?;
 
@@ -64,7 +144,7 @@ pub async fn extract_video_frames(video_path: &Path) -> Result<Vec<DynamicImage>
 
     let ffmpeg = FFmpegWrapper::new().map_err(|e| format!("Failed to initialize FFmpeg: {}", e))?;
 
-    let _fps = ffmpeg
+    let fps = ffmpeg
         .extract_frames(video_path, &temp_frames_dir, None, None, None)
         .map_err(|e| format!("Failed to extract video frames: {}", e))?;
 
@@ -106,5 +186,7 @@ pub async fn extract_video_frames(video_path: &Path) -> Result<Vec<DynamicImage>
         return Err("No frames extracted from video".to_string());
     }
 
-    Ok(frames)
+    let durations = video_durations(fps, frames.len());
+
+    Ok((frames, durations))
 }
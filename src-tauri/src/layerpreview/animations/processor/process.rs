@@ -1,4 +1,4 @@
-use image::{self, DynamicImage, ImageReader};
+use image::{self, ImageReader};
 use std::{
     io::Cursor,
     path::{Path, PathBuf},
@@ -10,15 +10,19 @@ use tracing;
 
 use crate::layerpreview::animations::{
     extract_gif_frames, extract_video_frames, extract_webp_frames,
-    file_watcher::start_animation_file_watcher, utils::update_global_max_frames, ImageFormat,
-    GLOBAL_MAX_FRAMES, RAYON_POOL,
+    file_watcher::start_animation_file_watcher,
+    timeline::{PlaybackSequence, DEFAULT_FRAME_DURATION_MS},
+    utils::update_global_max_frames,
+    ExtractedFrames, ImageFormat, CURRENT_TIMELINE, GLOBAL_MAX_FRAMES, RAYON_POOL,
 };
+use crate::types::LayerPlayback;
 
 pub struct FrameProcessor {
     pub frame_count: u32,
     pub max_frames: u32,
     pub image_path: String,
     pub trait_spritesheet_dir: PathBuf,
+    pub playback: LayerPlayback,
 }
 
 impl FrameProcessor {
@@ -31,6 +35,7 @@ impl FrameProcessor {
         max_frames: u32,
         _should_recreate_spritesheets: bool,
         total_files: u32,
+        playback: LayerPlayback,
     ) -> Result<Self, String> {
         let trait_spritesheet_dir = base_dir
             .join("spritesheets")
@@ -61,15 +66,16 @@ impl FrameProcessor {
             max_frames,
             image_path,
             trait_spritesheet_dir,
+            playback,
         })
     }
 
     fn process_frames_result(
         &mut self,
-        frames: Vec<DynamicImage>,
-    ) -> Result<Vec<DynamicImage>, String> {
-        self.frame_count = frames.len() as u32;
-        Ok(frames)
+        extracted: ExtractedFrames,
+    ) -> Result<ExtractedFrames, String> {
+        self.frame_count = extracted.0.len() as u32;
+        Ok(extracted)
     }
 
     async fn process_by_format(
@@ -77,7 +83,7 @@ impl FrameProcessor {
         data: &[u8],
         format: ImageFormat,
         file_path: Option<&Path>,
-    ) -> Result<Vec<DynamicImage>, String> {
+    ) -> Result<ExtractedFrames, String> {
         if format.is_video() {
             let video_path =
                 file_path.ok_or_else(|| "Video path required for video format".to_string())?;
@@ -97,7 +103,7 @@ impl FrameProcessor {
         &mut self,
         data: &[u8],
         format: ImageFormat,
-    ) -> Result<Vec<DynamicImage>, String> {
+    ) -> Result<ExtractedFrames, String> {
         let data_clone = data.to_vec();
         let extract_fn = match format {
             ImageFormat::Gif => extract_gif_frames,
@@ -113,18 +119,18 @@ impl FrameProcessor {
         let result = RAYON_POOL.install(|| extract_fn(&data_clone));
 
         match result {
-            Ok(frames) => self.process_frames_result(frames),
+            Ok(extracted) => self.process_frames_result(extracted),
             Err(e) => Err(format!("Failed to decode {}: {}", format.as_str(), e)),
         }
     }
 
-    async fn process_video(&mut self, video_path: &Path) -> Result<Vec<DynamicImage>, String> {
+    async fn process_video(&mut self, video_path: &Path) -> Result<ExtractedFrames, String> {
         tracing::info!("🎬 [VIDEO] Processing video file: {}", video_path.display());
-        let frames = extract_video_frames(video_path).await?;
-        self.process_frames_result(frames)
+        let extracted = extract_video_frames(video_path).await?;
+        self.process_frames_result(extracted)
     }
 
-    async fn process_png(&mut self, data: &[u8]) -> Result<Vec<DynamicImage>, String> {
+    async fn process_png(&mut self, data: &[u8]) -> Result<ExtractedFrames, String> {
         let img = ImageReader::new(Cursor::new(data))
             .with_guessed_format()
             .map_err(|e| {
@@ -140,10 +146,10 @@ impl FrameProcessor {
             })?;
 
         let frame_images = vec![img];
-        self.process_frames_result(frame_images)
+        self.process_frames_result((frame_images, vec![DEFAULT_FRAME_DURATION_MS]))
     }
 
-    async fn process_files(&mut self) -> Result<Vec<DynamicImage>, String> {
+    async fn process_files(&mut self) -> Result<ExtractedFrames, String> {
         let image_path = PathBuf::from(&self.image_path);
 
         tracing::info!(
@@ -200,7 +206,7 @@ impl FrameProcessor {
             target_path.display()
         );
 
-        let frames = if format.is_video() {
+        let extracted = if format.is_video() {
             tracing::info!(
                 "🎬 [VIDEO] Processing video via FFmpeg: {}",
                 target_path.display()
@@ -225,36 +231,28 @@ impl FrameProcessor {
                 .await?
         };
 
-        update_global_max_frames(self.frame_count, self.max_frames);
-        Ok(frames)
-    }
-
-    fn repeat_frames_to_count(
-        frames: &[DynamicImage],
-        target_count: u32,
-    ) -> Result<Vec<usize>, String> {
-        if frames.is_empty() {
-            return Err("No frames available for processing".to_string());
-        }
-
-        let mut result = Vec::with_capacity(target_count as usize);
-
-        for i in 0..target_count {
-            let frame_index = (i % frames.len() as u32) as usize;
-            result.push(frame_index);
-        }
-
-        Ok(result)
+        Ok(extracted)
     }
 
     pub async fn process(&mut self) -> Result<Vec<String>, String> {
-        let frames = self.process_files().await?;
+        let (frames, durations) = self.process_files().await?;
 
         tracing::info!("Processing animation file with {} frames", frames.len());
 
-        let final_frame_count = GLOBAL_MAX_FRAMES.load(Ordering::SeqCst);
+        if frames.is_empty() {
+            return Err("No frames available for processing".to_string());
+        }
 
-        let frame_indices = Self::repeat_frames_to_count(&frames, final_frame_count)?;
+        let sequence = PlaybackSequence::new(&durations, &self.playback);
+        let timeline = CURRENT_TIMELINE.read().await.clone();
+        let (final_frame_count, frame_indices) = match timeline {
+            Some(timeline) => (timeline.frame_count(), timeline.frame_indices(&sequence)),
+            None => {
+                update_global_max_frames(self.frame_count, self.max_frames);
+                let final_frame_count = GLOBAL_MAX_FRAMES.load(Ordering::SeqCst);
+                (final_frame_count, sequence.step_indices(final_frame_count))
+            }
+        };
         tracing::info!(
             "Creating spritesheets: {} original frames -> {} frame indices",
             frames.len(),
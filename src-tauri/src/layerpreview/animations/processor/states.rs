@@ -4,7 +4,7 @@ use rayon::{ThreadPool, ThreadPoolBuilder};
 use std::sync::atomic::AtomicU32;
 use tokio::sync::RwLock;
 
-use crate::types::SpritesheetLayout;
+use crate::{layerpreview::animations::timeline::AnimationTimeline, types::SpritesheetLayout};
 pub const MAX_TEXTURE_SIZE: u32 = 8192;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -66,6 +66,8 @@ pub static GLOBAL_MAX_FRAMES: AtomicU32 = AtomicU32::new(0);
 pub static FRAME_DIMENSIONS: Lazy<RwLock<Option<(u32, u32)>>> = Lazy::new(|| RwLock::new(None));
 pub static CURRENT_LAYOUT: Lazy<RwLock<Option<SpritesheetLayout>>> =
     Lazy::new(|| RwLock::new(None));
+pub static CURRENT_TIMELINE: Lazy<RwLock<Option<AnimationTimeline>>> =
+    Lazy::new(|| RwLock::new(None));
 
 pub static RAYON_POOL: Lazy<ThreadPool> = Lazy::new(|| {
     let rayon_threads = num_cpus::get();
@@ -0,0 +1,393 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, fs, path::Path};
+
+use crate::types::{LayerPlayback, PlaybackMode};
+
+pub const DEFAULT_FRAME_DURATION_MS: u32 = 100;
+const BROWSER_MIN_FRAME_DURATION_MS: u32 = 10;
+const MAX_TIMELINE_FRAMES: u32 = 1200;
+const MAX_LOOP_DURATION_MS: u64 = 60_000;
+const MIN_PLAYBACK_SPEED: f32 = 0.1;
+const MAX_PLAYBACK_SPEED: f32 = 10.0;
+const TIMELINE_FILE_NAME: &str = "timeline.json";
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimationTimeline {
+    pub frame_durations_ms: Vec<u32>,
+}
+
+pub fn normalize_frame_durations(durations: &[u32]) -> Vec<u32> {
+    durations
+        .iter()
+        .map(|&duration| {
+            if duration <= BROWSER_MIN_FRAME_DURATION_MS {
+                DEFAULT_FRAME_DURATION_MS
+            } else {
+                duration
+            }
+        })
+        .collect()
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn lcm(a: u64, b: u64) -> u64 {
+    if a == 0 || b == 0 {
+        return a.max(b);
+    }
+    (a / gcd(a, b)).saturating_mul(b)
+}
+
+/// Loop length every layer fits into a whole number of times, `None` when
+/// the loops only line up after more than `MAX_LOOP_DURATION_MS` and the
+/// timeline falls back to the longest loop.
+pub fn common_loop_duration(loop_durations: &[u64]) -> Option<u64> {
+    let longest_loop = loop_durations.iter().copied().max().unwrap_or(0);
+    let common_loop = loop_durations.iter().copied().fold(1, lcm);
+    (common_loop <= MAX_LOOP_DURATION_MS.max(longest_loop)).then_some(common_loop)
+}
+
+fn split_evenly(total: u64, parts: u32) -> Vec<u32> {
+    let parts = parts.max(1) as u64;
+    (0..parts)
+        .map(|k| (((k + 1) * total) / parts - (k * total) / parts) as u32)
+        .collect()
+}
+
+/// Order and timing in which a layer walks through its source frames.
+#[derive(Clone, Debug)]
+pub struct PlaybackSequence {
+    frames: Vec<usize>,
+    frame_ends_ms: Vec<u64>,
+    offset_ms: u64,
+    offset_steps: usize,
+    repeats: bool,
+}
+
+impl PlaybackSequence {
+    pub fn new(source_durations: &[u32], playback: &LayerPlayback) -> Self {
+        let source_len = source_durations.len().max(1);
+        let speed = if playback.speed.is_finite() {
+            playback.speed.clamp(MIN_PLAYBACK_SPEED, MAX_PLAYBACK_SPEED)
+        } else {
+            1.0
+        };
+
+        let frames: Vec<usize> = match playback.mode {
+            PlaybackMode::PingPong if source_len > 2 => {
+                (0..source_len).chain((1..source_len - 1).rev()).collect()
+            }
+            _ => (0..source_len).collect(),
+        };
+
+        let durations = if source_durations.is_empty() {
+            vec![DEFAULT_FRAME_DURATION_MS]
+        } else {
+            normalize_frame_durations(source_durations)
+        };
+
+        let mut frame_ends_ms = Vec::with_capacity(frames.len());
+        let mut elapsed = 0u64;
+        for &frame in &frames {
+            elapsed += ((durations[frame] as f32 / speed).round() as u64).max(1);
+            frame_ends_ms.push(elapsed);
+        }
+
+        let repeats = playback.mode != PlaybackMode::HoldLastFrame;
+        let offset_steps = if repeats {
+            playback.start_offset as usize % frames.len()
+        } else {
+            (playback.start_offset as usize).min(frames.len() - 1)
+        };
+        let offset_ms = match offset_steps {
+            0 => 0,
+            steps => frame_ends_ms[steps - 1],
+        };
+
+        Self {
+            frames,
+            frame_ends_ms,
+            offset_ms,
+            offset_steps,
+            repeats,
+        }
+    }
+
+    pub fn is_animated(&self) -> bool {
+        self.frames.len() > 1
+    }
+
+    fn total_ms(&self) -> u64 {
+        self.frame_ends_ms.last().copied().unwrap_or(0)
+    }
+
+    pub fn loop_duration_ms(&self) -> u64 {
+        if self.repeats {
+            self.total_ms()
+        } else {
+            self.total_ms() - self.offset_ms
+        }
+    }
+
+    pub fn frame_at(&self, time_ms: u64) -> usize {
+        let total = self.total_ms();
+        let mut local = time_ms + self.offset_ms;
+
+        if self.repeats {
+            local %= total.max(1);
+        } else if local >= total {
+            return self.frames[self.frames.len() - 1];
+        }
+
+        let step = self.frame_ends_ms.partition_point(|&end| end <= local);
+        self.frames[step.min(self.frames.len() - 1)]
+    }
+
+    pub fn step_indices(&self, count: u32) -> Vec<usize> {
+        let len = self.frames.len();
+        (0..count as usize)
+            .map(|i| {
+                let step = i + self.offset_steps;
+                if self.repeats {
+                    self.frames[step % len]
+                } else {
+                    self.frames[step.min(len - 1)]
+                }
+            })
+            .collect()
+    }
+
+    fn change_points(&self, until_ms: u64) -> Vec<u64> {
+        let mut starts = vec![0u64];
+        starts.extend_from_slice(&self.frame_ends_ms[..self.frame_ends_ms.len() - 1]);
+
+        let mut points = vec![0];
+        if !self.repeats {
+            points.extend(
+                starts
+                    .iter()
+                    .filter(|&&start| start > self.offset_ms && start - self.offset_ms < until_ms)
+                    .map(|&start| start - self.offset_ms),
+            );
+            return points;
+        }
+
+        let mut cycle_start = 0u64;
+        while cycle_start < until_ms + self.offset_ms {
+            for &start in &starts {
+                let time = cycle_start + start;
+                if time >= self.offset_ms && time - self.offset_ms < until_ms {
+                    points.push(time - self.offset_ms);
+                }
+            }
+            cycle_start += self.total_ms().max(1);
+        }
+
+        points
+    }
+}
+
+impl AnimationTimeline {
+    pub fn build(sequences: &[PlaybackSequence], max_frames: u32) -> Self {
+        let animated: Vec<&PlaybackSequence> = sequences
+            .iter()
+            .filter(|sequence| sequence.is_animated())
+            .collect();
+
+        if animated.is_empty() {
+            return Self {
+                frame_durations_ms: vec![DEFAULT_FRAME_DURATION_MS],
+            };
+        }
+
+        let frame_limit = if max_frames > 0 {
+            max_frames
+        } else {
+            MAX_TIMELINE_FRAMES
+        };
+
+        let loop_durations: Vec<u64> = animated
+            .iter()
+            .map(|sequence| sequence.loop_duration_ms().max(1))
+            .collect();
+        let longest_loop = loop_durations.iter().copied().max().unwrap_or(0);
+
+        let loop_duration = common_loop_duration(&loop_durations).unwrap_or_else(|| {
+            tracing::warn!(
+                "Layer loops only align after {} ms, falling back to the longest loop ({} ms)",
+                loop_durations.iter().copied().fold(1, lcm),
+                longest_loop
+            );
+            longest_loop
+        });
+
+        let boundaries: BTreeSet<u64> = animated
+            .iter()
+            .flat_map(|sequence| sequence.change_points(loop_duration))
+            .collect();
+
+        let frame_durations_ms = if boundaries.len() > frame_limit as usize {
+            tracing::info!(
+                "Timeline has {} distinct frames, resampling to {}",
+                boundaries.len(),
+                frame_limit
+            );
+            split_evenly(loop_duration, frame_limit)
+        } else {
+            let starts: Vec<u64> = boundaries.into_iter().collect();
+            starts
+                .iter()
+                .enumerate()
+                .map(|(i, &start)| {
+                    let end = starts.get(i + 1).copied().unwrap_or(loop_duration);
+                    (end - start) as u32
+                })
+                .collect()
+        };
+
+        Self { frame_durations_ms }
+    }
+
+    pub fn frame_count(&self) -> u32 {
+        self.frame_durations_ms.len() as u32
+    }
+
+    pub fn loop_duration_ms(&self) -> u64 {
+        self.frame_durations_ms.iter().map(|&d| d as u64).sum()
+    }
+
+    pub fn is_variable(&self) -> bool {
+        self.frame_durations_ms
+            .windows(2)
+            .any(|pair| pair[0] != pair[1])
+    }
+
+    pub fn frame_indices(&self, sequence: &PlaybackSequence) -> Vec<usize> {
+        let mut indices = Vec::with_capacity(self.frame_durations_ms.len());
+        let mut time = 0u64;
+
+        for &duration in &self.frame_durations_ms {
+            indices.push(sequence.frame_at(time));
+            time += duration as u64;
+        }
+
+        indices
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<(), String> {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create timeline directory: {}", e))?;
+
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize timeline: {}", e))?;
+
+        fs::write(dir.join(TIMELINE_FILE_NAME), json)
+            .map_err(|e| format!("Failed to write timeline: {}", e))
+    }
+
+    pub fn load(dir: &Path) -> Option<Self> {
+        let content = fs::read_to_string(dir.join(TIMELINE_FILE_NAME)).ok()?;
+        serde_json::from_str(&content)
+            .map_err(|e| tracing::warn!("Ignoring invalid timeline file: {}", e))
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sequence(
+        frame_count: usize,
+        mode: PlaybackMode,
+        start_offset: u32,
+        speed: f32,
+    ) -> PlaybackSequence {
+        PlaybackSequence::new(
+            &vec![100; frame_count],
+            &LayerPlayback {
+                mode,
+                start_offset,
+                speed,
+            },
+        )
+    }
+
+    #[test]
+    fn loops_of_three_and_four_frames_meet_after_twelve() {
+        let three = sequence(3, PlaybackMode::Loop, 0, 1.0);
+        let four = sequence(4, PlaybackMode::Loop, 0, 1.0);
+
+        let timeline = AnimationTimeline::build(&[three.clone(), four.clone()], 0);
+        assert_eq!(timeline.frame_durations_ms, vec![100; 12]);
+        assert_eq!(timeline.loop_duration_ms(), 1200);
+        assert_eq!(
+            timeline.frame_indices(&three),
+            vec![0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2]
+        );
+        assert_eq!(
+            timeline.frame_indices(&four),
+            vec![0, 1, 2, 3, 0, 1, 2, 3, 0, 1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn ping_pong_plays_back_without_repeating_the_ends() {
+        let ping_pong = sequence(4, PlaybackMode::PingPong, 0, 1.0);
+
+        let timeline = AnimationTimeline::build(std::slice::from_ref(&ping_pong), 0);
+        assert_eq!(timeline.frame_count(), 6);
+        assert_eq!(timeline.frame_indices(&ping_pong), vec![0, 1, 2, 3, 2, 1]);
+    }
+
+    #[test]
+    fn start_offset_wraps_around_the_loop() {
+        let offset = sequence(4, PlaybackMode::Loop, 6, 1.0);
+
+        let timeline = AnimationTimeline::build(std::slice::from_ref(&offset), 0);
+        assert_eq!(timeline.frame_indices(&offset), vec![2, 3, 0, 1]);
+        assert_eq!(offset.step_indices(5), vec![2, 3, 0, 1, 2]);
+
+        // Held animations stop on their last frame instead.
+        let held = sequence(4, PlaybackMode::HoldLastFrame, 6, 1.0);
+        assert_eq!(held.step_indices(3), vec![3, 3, 3]);
+        assert_eq!(held.frame_at(1000), 3);
+    }
+
+    #[test]
+    fn non_integer_speed_rounds_each_frame() {
+        let fast = sequence(3, PlaybackMode::Loop, 0, 1.5);
+
+        let timeline = AnimationTimeline::build(std::slice::from_ref(&fast), 0);
+        assert_eq!(timeline.frame_durations_ms, vec![67, 67, 67]);
+        assert_eq!(fast.loop_duration_ms(), 201);
+        assert_eq!(fast.frame_at(200), 2);
+        assert_eq!(fast.frame_at(201), 0);
+    }
+
+    #[test]
+    fn coprime_loops_fall_back_to_the_longest_one() {
+        assert_eq!(common_loop_duration(&[300, 400]), Some(1200));
+        assert_eq!(common_loop_duration(&[59_900, 60_100]), None);
+
+        let sequences = [
+            sequence(599, PlaybackMode::Loop, 0, 1.0),
+            sequence(601, PlaybackMode::Loop, 0, 1.0),
+        ];
+
+        let timeline = AnimationTimeline::build(&sequences, 0);
+        assert_eq!(timeline.loop_duration_ms(), 60_100);
+        assert_eq!(timeline.frame_count(), 601);
+
+        let capped = AnimationTimeline::build(&sequences, 100);
+        assert_eq!(capped.loop_duration_ms(), 60_100);
+        assert_eq!(capped.frame_count(), 100);
+    }
+}
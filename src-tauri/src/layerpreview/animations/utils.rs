@@ -1,10 +1,11 @@
 use super::{
-    CURRENT_LAYOUT, FRAMES_PROCESSED_COUNT, FRAME_DIMENSIONS, GLOBAL_MAX_FRAMES,
-    TOTAL_TRAITS_TO_PROCESS,
+    timeline::AnimationTimeline, CURRENT_LAYOUT, CURRENT_TIMELINE, FRAMES_PROCESSED_COUNT,
+    FRAME_DIMENSIONS, GLOBAL_MAX_FRAMES, TOTAL_TRAITS_TO_PROCESS,
 };
+use crate::filesystem::projects::ProjectRegistry;
 use std::{
     fs::{self},
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::atomic::Ordering,
 };
 
@@ -31,13 +32,18 @@ pub fn get_animated_frames_dir<R: Runtime>(
     app_handle: &AppHandle<R>,
     project_id: &str,
 ) -> Result<PathBuf, String> {
-    let app_data_dir = app_handle.path().app_data_dir().map_err(|e| {
-        let msg = format!("Failed to get app data directory: {}", e);
+    let registry = app_handle.try_state::<ProjectRegistry>().ok_or_else(|| {
+        let msg = "Project registry is not initialized".to_string();
         tracing::error!("{}", msg);
         msg
     })?;
 
-    let animated_dir = app_data_dir.join("animated").join(project_id);
+    animated_frames_dir(&registry.cache_dir(), project_id)
+}
+
+/// Frames directory of `project_id` inside the given project cache.
+pub fn animated_frames_dir(cache_dir: &Path, project_id: &str) -> Result<PathBuf, String> {
+    let animated_dir = cache_dir.join("animated").join(project_id);
 
     if !animated_dir.exists() {
         fs::create_dir_all(&animated_dir).map_err(|e| {
@@ -60,6 +66,16 @@ pub async fn reset_animation_state() {
 
     let mut layout = CURRENT_LAYOUT.write().await;
     *layout = None;
+
+    let mut timeline = CURRENT_TIMELINE.write().await;
+    *timeline = None;
+}
+
+pub async fn set_animation_timeline(timeline: AnimationTimeline) {
+    GLOBAL_MAX_FRAMES.store(timeline.frame_count(), Ordering::SeqCst);
+
+    let mut current = CURRENT_TIMELINE.write().await;
+    *current = Some(timeline);
 }
 
 pub async fn set_total_traits_to_process(count: u32) {
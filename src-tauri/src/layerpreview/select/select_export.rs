@@ -1,4 +1,4 @@
-use crate::filesystem::{constants::StorageFiles, persist::load_projectsetup_state};
+use crate::filesystem::{persist::load_projectsetup_state, projects::ProjectRegistry};
 use anyhow::Result;
 use tokio::sync::oneshot;
 use std::fs::read_dir;
@@ -8,9 +8,9 @@ use tauri_plugin_dialog::DialogExt;
 #[tauri::command]
 pub async fn select_export_folder(
     app_handle: tauri::AppHandle,
-    storage_files: State<'_, StorageFiles>,
+    registry: State<'_, ProjectRegistry>,
 ) -> Result<Option<String>, String> {
-    let import_folder = match load_projectsetup_state(storage_files).await {
+    let import_folder = match load_projectsetup_state(registry).await {
         Ok(Some(state)) => state.selected_folder,
         _ => Some(String::new()),
     };
@@ -1,8 +1,10 @@
 use anyhow::Result;
 use std::path::PathBuf;
-use tokio::fs::{metadata, read_dir};
+use tokio::fs::metadata;
 use tracing;
 
+use crate::filesystem::trait_files::scan_trait_files;
+
 #[tauri::command]
 pub async fn get_layer_image_names(
     folder_path: String,
@@ -20,34 +22,11 @@ pub async fn get_layer_image_names(
         return Ok(Vec::new());
     }
 
-    let mut images = Vec::new();
-    let mut dir_stream = read_dir(&path).await.map_err(|e| {
-        let msg = format!("Failed to read directory: {}", e);
-        tracing::error!("{}", msg);
-        msg
-    })?;
-
-    while let Some(entry) = dir_stream.next_entry().await.map_err(|e| {
-        let msg = format!("Failed to read entry: {}", e);
-        tracing::error!("{}", msg);
-        msg
-    })? {
-        let file_type = entry.file_type().await.map_err(|e| {
-            let msg = format!("Failed to get file type: {}", e);
-            tracing::error!("{}", msg);
-            msg
-        })?;
-        if file_type.is_file() {
-            if let Some(ext) = entry.path().extension().and_then(|e| e.to_str()) {
-                let ext_lower = ext.to_ascii_lowercase();
-                if ["png", "webp", "gif", "mp4", "webm", "mov", "avi", "mkv"]
-                    .contains(&ext_lower.as_str())
-                {
-                    images.push(entry.file_name().to_string_lossy().to_string());
-                }
-            }
-        }
-    }
+    let images: Vec<String> = scan_trait_files(&path)
+        .into_iter()
+        .filter(|file| file.part.is_none())
+        .map(|file| file.relative_path)
+        .collect();
 
     tracing::debug!("Found {} images in layer: {}", images.len(), layer_name);
     Ok(images)
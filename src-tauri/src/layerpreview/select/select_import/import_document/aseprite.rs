@@ -0,0 +1,751 @@
+use anyhow::{bail, Context, Result};
+use flate2::read::ZlibDecoder;
+use image::RgbaImage;
+use std::{collections::HashMap, io::Read};
+use tracing;
+
+use super::{check_canvas, reader::ByteReader, DocumentFrame, DocumentNode, LayeredDocument};
+use crate::{
+    filesystem::trait_files::VARIANT_SEPARATOR,
+    types::{BlendMode, BlendProperties},
+};
+
+const HEADER_MAGIC: u16 = 0xA5E0;
+const FRAME_MAGIC: u16 = 0xF1FA;
+
+const CHUNK_OLD_PALETTE: u16 = 0x0004;
+const CHUNK_LAYER: u16 = 0x2004;
+const CHUNK_CEL: u16 = 0x2005;
+const CHUNK_TAGS: u16 = 0x2018;
+const CHUNK_PALETTE: u16 = 0x2019;
+
+const LAYER_IMAGE: u16 = 0;
+const LAYER_GROUP: u16 = 1;
+const LAYER_FLAG_BACKGROUND: u16 = 8;
+const LAYER_FLAG_REFERENCE: u16 = 64;
+const HEADER_FLAG_LAYER_OPACITY: u32 = 1;
+
+/// Indexed images address at most 256 colours.
+const PALETTE_SIZE: usize = 256;
+/// Deeper group nesting is refused rather than recursed into.
+const MAX_GROUP_DEPTH: u16 = 64;
+
+struct AseLayer {
+    name: String,
+    flags: u16,
+    kind: u16,
+    child_level: u16,
+    blend_mode: u16,
+    opacity: u8,
+}
+
+enum AseCel {
+    Image {
+        x: i32,
+        y: i32,
+        opacity: u8,
+        width: u32,
+        height: u32,
+        pixels: Vec<u8>,
+    },
+    Linked(usize),
+}
+
+struct AseFrame {
+    duration_ms: u32,
+    cels: HashMap<usize, AseCel>,
+}
+
+struct AseTag {
+    name: String,
+    from: usize,
+    to: usize,
+    direction: u8,
+}
+
+struct AseDocument {
+    width: u32,
+    height: u32,
+    color_depth: u16,
+    transparent_index: u8,
+    layers: Vec<AseLayer>,
+    frames: Vec<AseFrame>,
+    tags: Vec<AseTag>,
+    palette: Vec<[u8; 4]>,
+}
+
+/// Reads an Aseprite file. Groups become `Group` nodes and every image layer
+/// becomes an animated `Image` node, one per tag when the file has tags.
+pub fn read_aseprite(data: &[u8]) -> Result<LayeredDocument> {
+    let document = parse(data)?;
+
+    let mut index = 0;
+    let nodes = build_nodes(&document, &mut index, 0)?;
+
+    Ok(LayeredDocument {
+        width: document.width,
+        height: document.height,
+        nodes,
+    })
+}
+
+fn parse(data: &[u8]) -> Result<AseDocument> {
+    let mut reader = ByteReader::little_endian(data);
+
+    let _file_size = reader.u32()?;
+    if reader.u16()? != HEADER_MAGIC {
+        bail!("Not an Aseprite file");
+    }
+    let frame_count = reader.u16()? as usize;
+    let width = reader.u16()? as u32;
+    let height = reader.u16()? as u32;
+    let color_depth = reader.u16()?;
+    let header_flags = reader.u32()?;
+    let _speed = reader.u16()?;
+    reader.skip(8)?;
+    let transparent_index = reader.u8()?;
+    reader.seek(128)?;
+
+    if !matches!(color_depth, 8 | 16 | 32) {
+        bail!("Unsupported colour depth: {}", color_depth);
+    }
+    check_canvas(width, height, u16::MAX as u32)?;
+
+    let mut document = AseDocument {
+        width,
+        height,
+        color_depth,
+        transparent_index,
+        layers: Vec::new(),
+        frames: Vec::with_capacity(frame_count),
+        tags: Vec::new(),
+        palette: vec![[0, 0, 0, 0]; PALETTE_SIZE],
+    };
+    let mut has_palette_chunk = false;
+
+    for frame_index in 0..frame_count {
+        let frame_start = reader.position();
+        let frame_size = reader.u32()? as usize;
+        if reader.u16()? != FRAME_MAGIC {
+            bail!("Invalid header for frame {}", frame_index);
+        }
+        let old_chunk_count = reader.u16()? as usize;
+        let duration_ms = reader.u16()? as u32;
+        reader.skip(2)?;
+        let new_chunk_count = reader.u32()? as usize;
+        let chunk_count = if new_chunk_count == 0 {
+            old_chunk_count
+        } else {
+            new_chunk_count
+        };
+
+        let mut frame = AseFrame {
+            duration_ms,
+            cels: HashMap::new(),
+        };
+
+        for _ in 0..chunk_count {
+            let chunk_start = reader.position();
+            let chunk_size = reader.u32()? as usize;
+            let chunk_type = reader.u16()?;
+            let chunk_end = chunk_start + chunk_size;
+
+            match chunk_type {
+                CHUNK_LAYER => {
+                    let flags = reader.u16()?;
+                    let kind = reader.u16()?;
+                    let child_level = reader.u16()?;
+                    reader.skip(4)?;
+                    let blend_mode = reader.u16()?;
+                    let opacity = reader.u8()?;
+                    reader.skip(3)?;
+                    let name = read_string(&mut reader)?;
+
+                    document.layers.push(AseLayer {
+                        name,
+                        flags,
+                        kind,
+                        child_level,
+                        blend_mode,
+                        opacity: if header_flags & HEADER_FLAG_LAYER_OPACITY != 0 {
+                            opacity
+                        } else {
+                            255
+                        },
+                    });
+                }
+                CHUNK_CEL => {
+                    let layer = reader.u16()? as usize;
+                    let x = reader.i16()? as i32;
+                    let y = reader.i16()? as i32;
+                    let opacity = reader.u8()?;
+                    let cel_type = reader.u16()?;
+                    reader.skip(7)?;
+
+                    let cel = match cel_type {
+                        0 | 2 => {
+                            let width = reader.u16()? as u32;
+                            let height = reader.u16()? as u32;
+                            let raw = reader.bytes(chunk_end.saturating_sub(reader.position()))?;
+                            let pixels = if cel_type == 2 {
+                                let mut pixels = Vec::new();
+                                ZlibDecoder::new(raw)
+                                    .read_to_end(&mut pixels)
+                                    .context("Failed to inflate cel")?;
+                                pixels
+                            } else {
+                                raw.to_vec()
+                            };
+                            Some(AseCel::Image {
+                                x,
+                                y,
+                                opacity,
+                                width,
+                                height,
+                                pixels,
+                            })
+                        }
+                        1 => Some(AseCel::Linked(reader.u16()? as usize)),
+                        _ => None,
+                    };
+
+                    if let Some(cel) = cel {
+                        frame.cels.insert(layer, cel);
+                    }
+                }
+                CHUNK_TAGS => {
+                    let count = reader.u16()?;
+                    reader.skip(8)?;
+                    for _ in 0..count {
+                        let from = reader.u16()? as usize;
+                        let to = reader.u16()? as usize;
+                        let direction = reader.u8()?;
+                        reader.skip(12)?;
+                        let name = read_string(&mut reader)?;
+                        document.tags.push(AseTag {
+                            name,
+                            from,
+                            to,
+                            direction,
+                        });
+                    }
+                }
+                CHUNK_PALETTE => {
+                    has_palette_chunk = true;
+                    let size = reader.u32()? as usize;
+                    let first = reader.u32()? as usize;
+                    let last = reader.u32()? as usize;
+                    reader.skip(8)?;
+                    if size > PALETTE_SIZE || first > last || last >= PALETTE_SIZE {
+                        bail!(
+                            "Invalid palette of {} colours ({} to {}), at most {} are supported",
+                            size,
+                            first,
+                            last,
+                            PALETTE_SIZE
+                        );
+                    }
+                    for index in first..=last {
+                        let flags = reader.u16()?;
+                        let color = reader.array::<4>()?;
+                        if flags & 1 != 0 {
+                            read_string(&mut reader)?;
+                        }
+                        if let Some(entry) = document.palette.get_mut(index) {
+                            *entry = color;
+                        }
+                    }
+                }
+                CHUNK_OLD_PALETTE if !has_palette_chunk => {
+                    let packets = reader.u16()?;
+                    let mut index = 0usize;
+                    for _ in 0..packets {
+                        index += reader.u8()? as usize;
+                        let count = match reader.u8()? {
+                            0 => 256,
+                            count => count as usize,
+                        };
+                        for _ in 0..count {
+                            let [r, g, b] = reader.array::<3>()?;
+                            if let Some(entry) = document.palette.get_mut(index) {
+                                *entry = [r, g, b, 255];
+                            }
+                            index += 1;
+                        }
+                    }
+                }
+                _ => {}
+            }
+
+            reader.seek(chunk_end)?;
+        }
+
+        document.frames.push(frame);
+        reader.seek(frame_start + frame_size)?;
+    }
+
+    Ok(document)
+}
+
+fn read_string(reader: &mut ByteReader) -> Result<String> {
+    let len = reader.u16()? as usize;
+    Ok(String::from_utf8_lossy(reader.bytes(len)?).into_owned())
+}
+
+/// Builds the nodes of one nesting level, starting at `index`. Layers are
+/// stored bottom to top with each group followed by its children.
+fn build_nodes(document: &AseDocument, index: &mut usize, level: u16) -> Result<Vec<DocumentNode>> {
+    if level > MAX_GROUP_DEPTH {
+        bail!("Layer groups are nested more than {} deep", MAX_GROUP_DEPTH);
+    }
+    let mut nodes = Vec::new();
+
+    while let Some(layer) = document.layers.get(*index) {
+        if layer.child_level < level {
+            break;
+        }
+        let layer_index = *index;
+        *index += 1;
+
+        if layer.flags & LAYER_FLAG_REFERENCE != 0 {
+            continue;
+        }
+
+        let blend = BlendProperties {
+            mode: blend_mode(layer.blend_mode),
+            opacity: layer.opacity as f32 / 255.0,
+        };
+
+        match layer.kind {
+            LAYER_GROUP => {
+                let child_level = layer
+                    .child_level
+                    .checked_add(1)
+                    .with_context(|| format!("Invalid nesting level of group '{}'", layer.name))?;
+                nodes.push(DocumentNode::Group {
+                    name: layer.name.clone(),
+                    blend,
+                    children: build_nodes(document, index, child_level)?,
+                });
+            }
+            LAYER_IMAGE => nodes.extend(layer_images(document, layer_index, blend)),
+            _ => tracing::warn!(
+                "[Import] Skipping tilemap layer '{}', tilemaps are not supported",
+                layer.name
+            ),
+        }
+    }
+
+    Ok(nodes)
+}
+
+/// One animated image for the whole timeline, or one per tag named
+/// `Layer#Tag` so the tags become variants of a single trait.
+fn layer_images(
+    document: &AseDocument,
+    layer_index: usize,
+    blend: BlendProperties,
+) -> Vec<DocumentNode> {
+    let layer = &document.layers[layer_index];
+    let has_cels = document
+        .frames
+        .iter()
+        .any(|frame| frame.cels.contains_key(&layer_index));
+    if !has_cels {
+        return Vec::new();
+    }
+
+    let frame_image = |frame_index: usize| DocumentFrame {
+        image: compose_frame(document, layer_index, frame_index),
+        left: 0,
+        top: 0,
+        duration_ms: document.frames[frame_index].duration_ms,
+    };
+
+    if document.tags.is_empty() {
+        return vec![DocumentNode::Image {
+            name: layer.name.clone(),
+            blend,
+            frames: (0..document.frames.len()).map(frame_image).collect(),
+        }];
+    }
+
+    document
+        .tags
+        .iter()
+        .map(|tag| DocumentNode::Image {
+            name: format!("{}{}{}", layer.name, VARIANT_SEPARATOR, tag.name),
+            blend: blend.clone(),
+            frames: tag_frames(tag, document.frames.len())
+                .into_iter()
+                .map(frame_image)
+                .collect(),
+        })
+        .collect()
+}
+
+/// Frame indices of a tag in playback order.
+fn tag_frames(tag: &AseTag, frame_count: usize) -> Vec<usize> {
+    let last = tag.to.min(frame_count.saturating_sub(1));
+    let forward: Vec<usize> = (tag.from.min(last)..=last).collect();
+    let backward: Vec<usize> = forward.iter().rev().copied().collect();
+
+    match tag.direction {
+        1 => backward,
+        2 => forward
+            .iter()
+            .chain(
+                backward
+                    .iter()
+                    .skip(1)
+                    .take(backward.len().saturating_sub(2)),
+            )
+            .copied()
+            .collect(),
+        3 => backward
+            .iter()
+            .chain(forward.iter().skip(1).take(forward.len().saturating_sub(2)))
+            .copied()
+            .collect(),
+        _ => forward,
+    }
+}
+
+/// Draws the layer's cel for `frame_index` on a transparent canvas.
+fn compose_frame(document: &AseDocument, layer_index: usize, frame_index: usize) -> RgbaImage {
+    let mut image = RgbaImage::new(document.width, document.height);
+
+    let mut cel = document.frames[frame_index].cels.get(&layer_index);
+    for _ in 0..document.frames.len() {
+        let Some(AseCel::Linked(source)) = cel else {
+            break;
+        };
+        cel = document
+            .frames
+            .get(*source)
+            .and_then(|frame| frame.cels.get(&layer_index));
+    }
+    let Some(AseCel::Image {
+        x,
+        y,
+        opacity,
+        width,
+        height,
+        pixels,
+    }) = cel
+    else {
+        return image;
+    };
+
+    let is_background = document.layers[layer_index].flags & LAYER_FLAG_BACKGROUND != 0;
+    let bytes_per_pixel = (document.color_depth / 8) as usize;
+
+    for row in 0..*height {
+        let canvas_y = y + row as i32;
+        if canvas_y < 0 || canvas_y >= document.height as i32 {
+            continue;
+        }
+        for column in 0..*width {
+            let canvas_x = x + column as i32;
+            if canvas_x < 0 || canvas_x >= document.width as i32 {
+                continue;
+            }
+
+            let offset = (row * width + column) as usize * bytes_per_pixel;
+            let Some(source) = pixels.get(offset..offset + bytes_per_pixel) else {
+                continue;
+            };
+            let [r, g, b, a] = match document.color_depth {
+                32 => [source[0], source[1], source[2], source[3]],
+                16 => [source[0], source[0], source[0], source[1]],
+                _ if source[0] == document.transparent_index && !is_background => [0, 0, 0, 0],
+                _ => document
+                    .palette
+                    .get(source[0] as usize)
+                    .copied()
+                    .unwrap_or([0, 0, 0, 0]),
+            };
+            let alpha = (a as u32 * *opacity as u32 / 255) as u8;
+
+            image.put_pixel(
+                canvas_x as u32,
+                canvas_y as u32,
+                image::Rgba([r, g, b, alpha]),
+            );
+        }
+    }
+
+    image
+}
+
+fn blend_mode(mode: u16) -> BlendMode {
+    match mode {
+        1 => BlendMode::Multiply,
+        2 => BlendMode::Screen,
+        3 => BlendMode::Overlay,
+        4 => BlendMode::Darken,
+        5 => BlendMode::Lighten,
+        6 => BlendMode::ColorDodge,
+        7 => BlendMode::ColorBurn,
+        8 => BlendMode::HardLight,
+        9 => BlendMode::SoftLight,
+        10 => BlendMode::Difference,
+        11 => BlendMode::Exclusion,
+        12 => BlendMode::Hue,
+        13 => BlendMode::Saturation,
+        14 => BlendMode::Color,
+        15 => BlendMode::Luminosity,
+        16 => BlendMode::Lighter,
+        _ => BlendMode::SourceOver,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(kind: u16, body: &[u8]) -> Vec<u8> {
+        let mut chunk = Vec::new();
+        chunk.extend_from_slice(&(body.len() as u32 + 6).to_le_bytes());
+        chunk.extend_from_slice(&kind.to_le_bytes());
+        chunk.extend_from_slice(body);
+        chunk
+    }
+
+    fn string(value: &str) -> Vec<u8> {
+        let mut bytes = (value.len() as u16).to_le_bytes().to_vec();
+        bytes.extend_from_slice(value.as_bytes());
+        bytes
+    }
+
+    fn layer_chunk(name: &str, kind: u16, child_level: u16) -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&0u16.to_le_bytes());
+        body.extend_from_slice(&kind.to_le_bytes());
+        body.extend_from_slice(&child_level.to_le_bytes());
+        body.extend_from_slice(&[0; 4]);
+        body.extend_from_slice(&0u16.to_le_bytes());
+        body.push(255);
+        body.extend_from_slice(&[0; 3]);
+        body.extend_from_slice(&string(name));
+        chunk(CHUNK_LAYER, &body)
+    }
+
+    fn cel_header(layer: u16, x: i16, y: i16, cel_type: u16) -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&layer.to_le_bytes());
+        body.extend_from_slice(&x.to_le_bytes());
+        body.extend_from_slice(&y.to_le_bytes());
+        body.push(255);
+        body.extend_from_slice(&cel_type.to_le_bytes());
+        body.extend_from_slice(&[0; 7]);
+        body
+    }
+
+    /// Raw RGBA cel of `width` x `height` pixels of one colour.
+    fn image_cel(layer: u16, x: i16, y: i16, size: (u16, u16), color: [u8; 4]) -> Vec<u8> {
+        let mut body = cel_header(layer, x, y, 0);
+        body.extend_from_slice(&size.0.to_le_bytes());
+        body.extend_from_slice(&size.1.to_le_bytes());
+        for _ in 0..size.0 as usize * size.1 as usize {
+            body.extend_from_slice(&color);
+        }
+        chunk(CHUNK_CEL, &body)
+    }
+
+    fn linked_cel(layer: u16, frame: u16) -> Vec<u8> {
+        let mut body = cel_header(layer, 0, 0, 1);
+        body.extend_from_slice(&frame.to_le_bytes());
+        chunk(CHUNK_CEL, &body)
+    }
+
+    fn tags_chunk(tags: &[(&str, u16, u16, u8)]) -> Vec<u8> {
+        let mut body = (tags.len() as u16).to_le_bytes().to_vec();
+        body.extend_from_slice(&[0; 8]);
+        for (name, from, to, direction) in tags {
+            body.extend_from_slice(&from.to_le_bytes());
+            body.extend_from_slice(&to.to_le_bytes());
+            body.push(*direction);
+            body.extend_from_slice(&[0; 12]);
+            body.extend_from_slice(&string(name));
+        }
+        chunk(CHUNK_TAGS, &body)
+    }
+
+    /// 32-bit file whose frames are given as (duration, chunks).
+    fn aseprite(width: u16, height: u16, frames: &[(u16, Vec<Vec<u8>>)]) -> Vec<u8> {
+        let mut data = vec![0u8; 128];
+        data[4..6].copy_from_slice(&HEADER_MAGIC.to_le_bytes());
+        data[6..8].copy_from_slice(&(frames.len() as u16).to_le_bytes());
+        data[8..10].copy_from_slice(&width.to_le_bytes());
+        data[10..12].copy_from_slice(&height.to_le_bytes());
+        data[12..14].copy_from_slice(&32u16.to_le_bytes());
+        data[14..18].copy_from_slice(&HEADER_FLAG_LAYER_OPACITY.to_le_bytes());
+
+        for (duration_ms, chunks) in frames {
+            let body: Vec<u8> = chunks.concat();
+            data.extend_from_slice(&(body.len() as u32 + 16).to_le_bytes());
+            data.extend_from_slice(&FRAME_MAGIC.to_le_bytes());
+            data.extend_from_slice(&(chunks.len() as u16).to_le_bytes());
+            data.extend_from_slice(&duration_ms.to_le_bytes());
+            data.extend_from_slice(&[0; 2]);
+            data.extend_from_slice(&(chunks.len() as u32).to_le_bytes());
+            data.extend_from_slice(&body);
+        }
+
+        let size = data.len() as u32;
+        data[0..4].copy_from_slice(&size.to_le_bytes());
+        data
+    }
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+
+    #[test]
+    fn reads_groups_cels_and_linked_frames() {
+        let data = aseprite(
+            3,
+            2,
+            &[
+                (
+                    100,
+                    vec![
+                        layer_chunk("Hats", LAYER_GROUP, 0),
+                        layer_chunk("Crown", LAYER_IMAGE, 1),
+                        layer_chunk("Body", LAYER_IMAGE, 0),
+                        image_cel(1, 1, 0, (2, 1), RED),
+                    ],
+                ),
+                (150, vec![linked_cel(1, 0)]),
+            ],
+        );
+
+        let document = read_aseprite(&data).unwrap();
+        assert_eq!((document.width, document.height), (3, 2));
+        // Body has no cel and is left out.
+        assert_eq!(document.nodes.len(), 1);
+
+        let DocumentNode::Group { name, children, .. } = &document.nodes[0] else {
+            panic!("Hats should be a group");
+        };
+        assert_eq!(name, "Hats");
+        let [DocumentNode::Image { name, frames, .. }] = children.as_slice() else {
+            panic!("Hats should hold one image");
+        };
+        assert_eq!(name, "Crown");
+        assert_eq!(
+            frames.iter().map(|f| f.duration_ms).collect::<Vec<_>>(),
+            vec![100, 150]
+        );
+        for frame in frames {
+            assert_eq!(frame.image.get_pixel(1, 0).0, RED);
+            assert_eq!(frame.image.get_pixel(2, 0).0, RED);
+            assert_eq!(frame.image.get_pixel(0, 0).0, [0, 0, 0, 0]);
+            assert_eq!(frame.image.get_pixel(1, 1).0, [0, 0, 0, 0]);
+        }
+    }
+
+    #[test]
+    fn tags_become_variants_of_the_layer() {
+        let data = aseprite(
+            1,
+            1,
+            &[
+                (
+                    10,
+                    vec![
+                        layer_chunk("Eyes", LAYER_IMAGE, 0),
+                        image_cel(0, 0, 0, (1, 1), RED),
+                        tags_chunk(&[("Blink", 0, 1, 0), ("Stare", 1, 1, 0)]),
+                    ],
+                ),
+                (20, vec![image_cel(0, 0, 0, (1, 1), [0, 0, 255, 255])]),
+            ],
+        );
+
+        let document = read_aseprite(&data).unwrap();
+        let variants: Vec<(&str, Vec<u32>)> = document
+            .nodes
+            .iter()
+            .map(|node| match node {
+                DocumentNode::Image { name, frames, .. } => (
+                    name.as_str(),
+                    frames.iter().map(|f| f.duration_ms).collect(),
+                ),
+                DocumentNode::Group { .. } => panic!("unexpected group"),
+            })
+            .collect();
+
+        assert_eq!(
+            variants,
+            vec![("Eyes#Blink", vec![10, 20]), ("Eyes#Stare", vec![20])]
+        );
+    }
+
+    #[test]
+    fn tag_directions_order_frames() {
+        let tag = |direction| AseTag {
+            name: String::new(),
+            from: 1,
+            to: 3,
+            direction,
+        };
+
+        assert_eq!(tag_frames(&tag(0), 5), vec![1, 2, 3]);
+        assert_eq!(tag_frames(&tag(1), 5), vec![3, 2, 1]);
+        assert_eq!(tag_frames(&tag(2), 5), vec![1, 2, 3, 2]);
+        assert_eq!(tag_frames(&tag(3), 5), vec![3, 2, 1, 2]);
+        // A tag past the last frame is clamped to the timeline.
+        assert_eq!(tag_frames(&tag(0), 3), vec![1, 2]);
+    }
+
+    #[test]
+    fn rejects_other_files_and_truncated_chunks() {
+        assert!(read_aseprite(&[0; 128]).is_err());
+        assert!(read_aseprite(&aseprite(20_000, 20_000, &[])).is_err());
+
+        let mut data = aseprite(1, 1, &[(10, vec![layer_chunk("Eyes", LAYER_IMAGE, 0)])]);
+        data.truncate(data.len() - 3);
+        assert!(read_aseprite(&data).is_err());
+    }
+
+    #[test]
+    fn rejects_oversized_palettes() {
+        let palette = |size: u32, last: u32| {
+            let mut body = Vec::new();
+            body.extend_from_slice(&size.to_le_bytes());
+            body.extend_from_slice(&0u32.to_le_bytes());
+            body.extend_from_slice(&last.to_le_bytes());
+            body.extend_from_slice(&[0; 8]);
+            for _ in 0..=last.min(1) {
+                body.extend_from_slice(&0u16.to_le_bytes());
+                body.extend_from_slice(&RED);
+            }
+            chunk(CHUNK_PALETTE, &body)
+        };
+
+        assert!(read_aseprite(&aseprite(1, 1, &[(10, vec![palette(2, 1)])])).is_ok());
+        let error = read_aseprite(&aseprite(1, 1, &[(10, vec![palette(u32::MAX, 1)])]))
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("palette"));
+        assert!(read_aseprite(&aseprite(1, 1, &[(10, vec![palette(256, 256)])])).is_err());
+    }
+
+    #[test]
+    fn rejects_groups_nested_past_the_limit() {
+        let data = aseprite(
+            1,
+            1,
+            &[(10, vec![layer_chunk("Deep", LAYER_GROUP, u16::MAX)])],
+        );
+        assert!(read_aseprite(&data).is_err());
+
+        let groups: Vec<Vec<u8>> = (0..=MAX_GROUP_DEPTH + 1)
+            .map(|level| layer_chunk("Group", LAYER_GROUP, level))
+            .collect();
+        let error = read_aseprite(&aseprite(1, 1, &[(10, groups)]))
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("nested"));
+    }
+}
@@ -0,0 +1,565 @@
+pub mod aseprite;
+pub mod psd;
+pub mod reader;
+
+use anyhow::{anyhow, Context, Result};
+use image::{imageops, RgbaImage};
+use serde::Serialize;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+use tauri::State;
+use tokio::task::spawn_blocking;
+use tracing;
+use webp_animation::{Encoder, EncoderOptions, EncodingConfig, EncodingType};
+
+use crate::{
+    filesystem::{
+        projects::ProjectRegistry,
+        storage::{load_storage, save_storage},
+        trait_files::split_variant,
+    },
+    types::{
+        BlendProperties, LayerConfig, LayerSetConfig, RarityConfigStorage, SetConfig, TraitConfig,
+    },
+};
+
+const DEFAULT_SET_ID: &str = "set1";
+
+/// Largest canvas a document may have, four bytes per pixel for every
+/// trait written at full canvas size.
+pub const MAX_CANVAS_PIXELS: u64 = 100_000_000;
+
+/// One frame of a document layer. `image` covers the layer's pixels only and
+/// is drawn at `left`/`top` on the document canvas.
+pub struct DocumentFrame {
+    pub image: RgbaImage,
+    pub left: u32,
+    pub top: u32,
+    pub duration_ms: u32,
+}
+
+impl DocumentFrame {
+    /// The frame on a transparent canvas of the document size.
+    pub fn on_canvas(&self, width: u32, height: u32) -> Cow<'_, RgbaImage> {
+        if (self.left, self.top) == (0, 0) && self.image.dimensions() == (width, height) {
+            return Cow::Borrowed(&self.image);
+        }
+        let mut canvas = RgbaImage::new(width, height);
+        imageops::replace(&mut canvas, &self.image, self.left as i64, self.top as i64);
+        Cow::Owned(canvas)
+    }
+}
+
+/// Refuses canvases that could not be written out at full size.
+pub fn check_canvas(width: u32, height: u32, max_side: u32) -> Result<()> {
+    if width == 0
+        || height == 0
+        || width > max_side
+        || height > max_side
+        || width as u64 * height as u64 > MAX_CANVAS_PIXELS
+    {
+        return Err(anyhow!(
+            "Unsupported canvas size {}x{}, at most {} pixels per side and {} pixels in total",
+            width,
+            height,
+            max_side,
+            MAX_CANVAS_PIXELS
+        ));
+    }
+    Ok(())
+}
+
+pub enum DocumentNode {
+    Group {
+        name: String,
+        blend: BlendProperties,
+        children: Vec<DocumentNode>,
+    },
+    Image {
+        name: String,
+        blend: BlendProperties,
+        frames: Vec<DocumentFrame>,
+    },
+}
+
+/// Layer tree of a source document, bottom node first.
+pub struct LayeredDocument {
+    pub width: u32,
+    pub height: u32,
+    pub nodes: Vec<DocumentNode>,
+}
+
+struct ImportedTrait {
+    /// Path inside the layer folder without extension, e.g. `Rare/Crown`.
+    path: String,
+    blend: BlendProperties,
+    frames: Vec<DocumentFrame>,
+}
+
+struct ImportedLayer {
+    name: String,
+    traits: Vec<ImportedTrait>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentImportSummary {
+    pub width: u32,
+    pub height: u32,
+    pub layers: Vec<String>,
+    pub trait_count: usize,
+    pub animated_trait_count: usize,
+}
+
+/// Turns a layered PSD or Aseprite file into the layer/trait folder tree read
+/// by `get_layers_content`. Top-level groups become layers, their layers
+/// become traits and nested groups become trait subfolders. Every trait is
+/// written at full canvas size, one at a time, so its pixels keep their
+/// document position.
+/// The blend mode and opacity of each source layer seed the rarity config.
+#[tauri::command]
+pub async fn import_layered_document(
+    source_path: String,
+    output_folder: String,
+    set_id: Option<String>,
+    registry: State<'_, ProjectRegistry>,
+) -> Result<DocumentImportSummary, String> {
+    let storage_files = registry.storage_files();
+    let set_id = set_id.unwrap_or_else(|| DEFAULT_SET_ID.to_string());
+
+    tracing::info!("[Import] Importing {} into {}", source_path, output_folder);
+
+    let (summary, layers) = spawn_blocking(move || -> Result<_> {
+        let data =
+            fs::read(&source_path).with_context(|| format!("Failed to read {}", source_path))?;
+
+        let extension = Path::new(&source_path)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let document = match extension.as_str() {
+            "psd" => psd::read_psd(&data)?,
+            "aseprite" | "ase" => aseprite::read_aseprite(&data)?,
+            other => return Err(anyhow!("Unsupported document type: .{}", other)),
+        };
+
+        let (width, height) = (document.width, document.height);
+        let layers = into_layers(document.nodes);
+        let summary = write_layers(Path::new(&output_folder), &layers, width, height)?;
+
+        // The pixels are no longer needed once written.
+        let layers: Vec<(String, Vec<(String, BlendProperties)>)> = layers
+            .into_iter()
+            .map(|layer| {
+                let traits = layer
+                    .traits
+                    .into_iter()
+                    .map(|imported| (imported.path, imported.blend))
+                    .collect();
+                (layer.name, traits)
+            })
+            .collect();
+
+        Ok((summary, layers))
+    })
+    .await
+    .map_err(|e| format!("Import task failed: {}", e))?
+    .map_err(|e| {
+        tracing::error!("[Import] {:#}", e);
+        format!("{:#}", e)
+    })?;
+
+    let mut storage = load_storage::<RarityConfigStorage>(&storage_files.rarity_config)
+        .await?
+        .unwrap_or_default();
+    seed_rarity_config(&mut storage, &layers, &set_id);
+    save_storage(&storage_files.rarity_config, &storage).await?;
+
+    tracing::info!(
+        "[Import] Imported {} layers and {} traits ({} animated)",
+        summary.layers.len(),
+        summary.trait_count,
+        summary.animated_trait_count
+    );
+
+    Ok(summary)
+}
+
+/// Top-level groups become layers. A top-level layer outside any group becomes
+/// a layer holding a single trait of the same name.
+fn into_layers(nodes: Vec<DocumentNode>) -> Vec<ImportedLayer> {
+    let mut layer_names = HashSet::new();
+    let mut layers = Vec::new();
+
+    for node in nodes {
+        let (name, traits) = match node {
+            DocumentNode::Group {
+                name,
+                blend,
+                children,
+            } => {
+                let mut traits = Vec::new();
+                collect_traits(children, "", blend.opacity, &mut traits);
+                (name, traits)
+            }
+            DocumentNode::Image {
+                name,
+                blend,
+                frames,
+            } => {
+                let path = sanitize_name(&name);
+                (
+                    name,
+                    vec![ImportedTrait {
+                        path,
+                        blend,
+                        frames,
+                    }],
+                )
+            }
+        };
+
+        if traits.is_empty() {
+            tracing::warn!("[Import] Skipping empty layer '{}'", name);
+            continue;
+        }
+
+        layers.push(ImportedLayer {
+            name: unique_name(&sanitize_name(&name), &mut layer_names),
+            traits,
+        });
+    }
+
+    layers
+}
+
+fn collect_traits(
+    nodes: Vec<DocumentNode>,
+    prefix: &str,
+    opacity: f32,
+    traits: &mut Vec<ImportedTrait>,
+) {
+    let mut names = HashSet::new();
+
+    for node in nodes {
+        match node {
+            DocumentNode::Group {
+                name,
+                blend,
+                children,
+            } => {
+                let group = unique_name(&sanitize_name(&name), &mut names);
+                collect_traits(
+                    children,
+                    &format!("{}{}/", prefix, group),
+                    opacity * blend.opacity,
+                    traits,
+                );
+            }
+            DocumentNode::Image {
+                name,
+                blend,
+                frames,
+            } => {
+                let name = unique_name(&sanitize_name(&name), &mut names);
+                traits.push(ImportedTrait {
+                    path: format!("{}{}", prefix, name),
+                    blend: BlendProperties {
+                        mode: blend.mode,
+                        opacity: blend.opacity * opacity,
+                    },
+                    frames,
+                });
+            }
+        }
+    }
+}
+
+fn sanitize_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let cleaned = cleaned.trim().trim_start_matches('.').trim_end_matches('.');
+
+    if cleaned.is_empty() {
+        "Untitled".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+fn unique_name(name: &str, taken: &mut HashSet<String>) -> String {
+    let mut candidate = name.to_string();
+    let mut index = 2;
+    while !taken.insert(candidate.to_lowercase()) {
+        candidate = format!("{} {}", name, index);
+        index += 1;
+    }
+    candidate
+}
+
+fn write_layers(
+    output_folder: &Path,
+    layers: &[ImportedLayer],
+    width: u32,
+    height: u32,
+) -> Result<DocumentImportSummary> {
+    let mut trait_count = 0;
+    let mut animated_trait_count = 0;
+
+    for layer in layers {
+        for imported in &layer.traits {
+            let layer_dir = output_folder.join(&layer.name);
+            // Trait names may contain dots, so extensions are appended, not set.
+            let file_path =
+                |extension: &str| layer_dir.join(format!("{}.{}", imported.path, extension));
+            if let Some(parent) = file_path("png").parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+
+            match imported.frames.as_slice() {
+                [] => continue,
+                [frame] => {
+                    let path = file_path("png");
+                    frame
+                        .on_canvas(width, height)
+                        .save_with_format(&path, image::ImageFormat::Png)
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                }
+                frames => {
+                    let path = file_path("webp");
+                    write_animated_webp(&path, frames, width, height)?;
+                    animated_trait_count += 1;
+                }
+            }
+            trait_count += 1;
+        }
+    }
+
+    Ok(DocumentImportSummary {
+        width,
+        height,
+        layers: layers.iter().map(|layer| layer.name.clone()).collect(),
+        trait_count,
+        animated_trait_count,
+    })
+}
+
+fn write_animated_webp(
+    path: &Path,
+    frames: &[DocumentFrame],
+    width: u32,
+    height: u32,
+) -> Result<()> {
+    let options = EncoderOptions {
+        encoding_config: Some(EncodingConfig {
+            encoding_type: EncodingType::Lossless,
+            quality: 100.0,
+            method: 4,
+        }),
+        ..Default::default()
+    };
+    let mut encoder = Encoder::new_with_options((width, height), options)
+        .map_err(|e| anyhow!("Failed to create WebP encoder: {:?}", e))?;
+
+    let mut timestamp_ms = 0i32;
+    for frame in frames {
+        encoder
+            .add_frame(frame.on_canvas(width, height).as_raw(), timestamp_ms)
+            .map_err(|e| anyhow!("Failed to encode WebP frame: {:?}", e))?;
+        timestamp_ms += frame.duration_ms.max(1) as i32;
+    }
+
+    let data = encoder
+        .finalize(timestamp_ms)
+        .map_err(|e| anyhow!("Failed to finalize WebP: {:?}", e))?;
+    fs::write(path, &*data).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Adds the imported layers and traits to the rarity config. Traits that
+/// already exist keep their rarity and only take the blend settings of the
+/// document; new traits get an even share of their layer, see
+/// `share_with_new_traits`.
+fn seed_rarity_config(
+    storage: &mut RarityConfigStorage,
+    layers: &[(String, Vec<(String, BlendProperties)>)],
+    set_id: &str,
+) {
+    let config = &mut storage.rarity_config_storage;
+
+    for (z_index, (layer_name, traits)) in layers.iter().enumerate() {
+        let layer = config
+            .layers
+            .entry(layer_name.clone())
+            .or_insert_with(|| LayerConfig {
+                sets: HashMap::new(),
+                locked: None,
+                traits: HashMap::new(),
+                default_blend: BlendProperties::default(),
+                playback: None,
+                groups: HashMap::new(),
+                parts: HashMap::new(),
+                trait_type: None,
+                localized_trait_types: HashMap::new(),
+            });
+        layer
+            .sets
+            .entry(set_id.to_string())
+            .or_insert_with(|| LayerSetConfig {
+                active: true,
+                include_in_metadata: None,
+            });
+
+        // Variants share one rarity entry, which takes the first variant's blend.
+        let mut trait_blends: Vec<(&str, &BlendProperties)> = Vec::new();
+        for (path, blend) in traits {
+            let trait_name = split_variant(path).0;
+            if !trait_blends.iter().any(|(name, _)| *name == trait_name) {
+                trait_blends.push((trait_name, blend));
+            }
+        }
+        let mut added = Vec::new();
+        for (trait_name, blend) in trait_blends {
+            let trait_config = layer
+                .traits
+                .entry(trait_name.to_string())
+                .or_insert_with(|| TraitConfig {
+                    sets: HashMap::new(),
+                    variants: HashMap::new(),
+                    display_name: None,
+                    localized_names: HashMap::new(),
+                });
+
+            let set = trait_config
+                .sets
+                .entry(set_id.to_string())
+                .or_insert_with(|| {
+                    added.push(trait_name.to_string());
+                    SetConfig {
+                        blend: blend.clone(),
+                        z_index: z_index as i32,
+                        enabled: true,
+                        value: 0.0,
+                        include_in_metadata: None,
+                        offset_x: None,
+                        offset_y: None,
+                    }
+                });
+            set.blend = blend.clone();
+            // Pixels are written at their document position, so no extra
+            // offset is needed.
+            set.offset_x = Some(0);
+            set.offset_y = Some(0);
+        }
+        share_with_new_traits(layer, set_id, &added);
+    }
+}
+
+/// Gives every trait in `added` the share an even split of the layer would
+/// give it, and scales the other traits of the set down so the layer still
+/// sums to 100 with their proportions kept.
+fn share_with_new_traits(layer: &mut LayerConfig, set_id: &str, added: &[String]) {
+    if added.is_empty() {
+        return;
+    }
+    let existing: f32 = layer
+        .traits
+        .iter()
+        .filter(|(name, _)| !added.contains(name))
+        .filter_map(|(_, trait_config)| trait_config.sets.get(set_id))
+        .map(|set| set.value)
+        .sum();
+    let share = if existing > 0.0 {
+        let count = layer
+            .traits
+            .values()
+            .filter(|trait_config| trait_config.sets.contains_key(set_id))
+            .count();
+        100.0 / count as f32
+    } else {
+        100.0 / added.len() as f32
+    };
+    let scale = if existing > 0.0 {
+        (100.0 - share * added.len() as f32) / existing
+    } else {
+        0.0
+    };
+
+    for (name, trait_config) in layer.traits.iter_mut() {
+        if let Some(set) = trait_config.sets.get_mut(set_id) {
+            set.value = if added.contains(name) {
+                share
+            } else {
+                set.value * scale
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn import(storage: &mut RarityConfigStorage, traits: &[&str]) {
+        let traits = traits
+            .iter()
+            .map(|name| (name.to_string(), BlendProperties::default()))
+            .collect();
+        seed_rarity_config(storage, &[("Hats".to_string(), traits)], DEFAULT_SET_ID);
+    }
+
+    fn values(storage: &RarityConfigStorage) -> Vec<(String, f32)> {
+        let mut values: Vec<(String, f32)> = storage.rarity_config_storage.layers["Hats"]
+            .traits
+            .iter()
+            .map(|(name, config)| (name.clone(), config.sets[DEFAULT_SET_ID].value))
+            .collect();
+        values.sort_by(|a, b| a.0.cmp(&b.0));
+        values
+    }
+
+    #[test]
+    fn new_layers_split_their_rarity_evenly() {
+        let mut storage = RarityConfigStorage::default();
+        import(&mut storage, &["Cap", "Crown#Gold", "Crown#Silver", "Hood"]);
+
+        let expected = 100.0 / 3.0;
+        for (name, value) in values(&storage) {
+            assert!((value - expected).abs() < 1e-4, "{} has {}", name, value);
+        }
+    }
+
+    #[test]
+    fn traits_added_to_a_layer_take_an_even_share() {
+        let mut storage = RarityConfigStorage::default();
+        import(&mut storage, &["Cap", "Crown"]);
+        let layer = storage
+            .rarity_config_storage
+            .layers
+            .get_mut("Hats")
+            .unwrap();
+        for (name, value) in [("Cap", 60.0), ("Crown", 40.0)] {
+            let trait_config = layer.traits.get_mut(name).unwrap();
+            trait_config.sets.get_mut(DEFAULT_SET_ID).unwrap().value = value;
+        }
+
+        import(&mut storage, &["Crown", "Hood"]);
+
+        let values = values(&storage);
+        let expected = [("Cap", 40.0), ("Crown", 80.0 / 3.0), ("Hood", 100.0 / 3.0)];
+        for ((name, value), (expected_name, expected_value)) in values.iter().zip(expected) {
+            assert_eq!(name, expected_name);
+            assert!((value - expected_value).abs() < 1e-3, "{}: {}", name, value);
+        }
+    }
+}
@@ -0,0 +1,607 @@
+use anyhow::{anyhow, bail, Context, Result};
+use flate2::read::ZlibDecoder;
+use image::RgbaImage;
+use std::{collections::HashMap, io::Read};
+
+use super::{check_canvas, reader::ByteReader, DocumentFrame, DocumentNode, LayeredDocument};
+use crate::types::{BlendMode, BlendProperties};
+
+const SECTION_OPEN_FOLDER: u32 = 1;
+const SECTION_CLOSED_FOLDER: u32 = 2;
+const SECTION_DIVIDER: u32 = 3;
+/// Largest side of a Photoshop document, larger layers are corrupt.
+const MAX_LAYER_SIDE: i32 = 30_000;
+
+struct LayerRecord {
+    name: String,
+    top: i32,
+    left: i32,
+    width: usize,
+    height: usize,
+    channels: Vec<(i16, usize)>,
+    blend_key: [u8; 4],
+    opacity: u8,
+    section: Option<u32>,
+}
+
+impl LayerRecord {
+    fn blend(&self) -> BlendProperties {
+        BlendProperties {
+            mode: blend_mode(&self.blend_key),
+            opacity: self.opacity as f32 / 255.0,
+        }
+    }
+}
+
+/// Reads an 8-bit RGB Photoshop document. Groups become `Group` nodes and
+/// pixel layers become single-frame `Image` nodes placed on the canvas.
+pub fn read_psd(data: &[u8]) -> Result<LayeredDocument> {
+    let mut reader = ByteReader::big_endian(data);
+
+    if reader.bytes(4)? != b"8BPS" {
+        bail!("Not a Photoshop document");
+    }
+    let version = reader.u16()?;
+    if version != 1 {
+        bail!("Large documents (PSB) are not supported");
+    }
+    reader.skip(6)?;
+    let _channels = reader.u16()?;
+    let height = reader.u32()?;
+    let width = reader.u32()?;
+    let depth = reader.u16()?;
+    let color_mode = reader.u16()?;
+    if depth != 8 {
+        bail!("Only 8-bit documents are supported, found {}-bit", depth);
+    }
+    if color_mode != 3 {
+        bail!("Only RGB documents are supported");
+    }
+    check_canvas(width, height, MAX_LAYER_SIDE as u32)?;
+
+    let color_mode_len = reader.u32()? as usize;
+    reader.skip(color_mode_len)?;
+    let resources_len = reader.u32()? as usize;
+    reader.skip(resources_len)?;
+
+    let _layer_and_mask_len = reader.u32()?;
+    let layer_info_len = reader.u32()?;
+    if layer_info_len == 0 {
+        bail!("The document has no layers");
+    }
+
+    // A negative count means the first alpha channel holds merged transparency.
+    let layer_count = reader.i16()?.unsigned_abs() as usize;
+    let records = (0..layer_count)
+        .map(|_| read_layer_record(&mut reader))
+        .collect::<Result<Vec<_>>>()?;
+
+    // Records run bottom to top; a divider opens a group and its folder
+    // record closes it.
+    let mut root = Vec::new();
+    let mut groups: Vec<Vec<DocumentNode>> = Vec::new();
+    for record in &records {
+        let channels = read_channels(&mut reader, record)
+            .with_context(|| format!("Failed to read pixels of layer '{}'", record.name))?;
+
+        match record.section {
+            Some(SECTION_DIVIDER) => groups.push(Vec::new()),
+            Some(SECTION_OPEN_FOLDER) | Some(SECTION_CLOSED_FOLDER) => {
+                let children = groups.pop().unwrap_or_default();
+                current_level(&mut root, &mut groups).push(DocumentNode::Group {
+                    name: record.name.clone(),
+                    blend: record.blend(),
+                    children,
+                });
+            }
+            _ => {
+                let frame = compose_layer(record, &channels, width, height);
+                current_level(&mut root, &mut groups).push(DocumentNode::Image {
+                    name: record.name.clone(),
+                    blend: record.blend(),
+                    frames: vec![frame],
+                });
+            }
+        }
+    }
+
+    // Unclosed groups are flattened into their parent.
+    while let Some(orphans) = groups.pop() {
+        current_level(&mut root, &mut groups).extend(orphans);
+    }
+
+    Ok(LayeredDocument {
+        width,
+        height,
+        nodes: root,
+    })
+}
+
+/// Nodes of the innermost open group, or of the document itself.
+fn current_level<'a>(
+    root: &'a mut Vec<DocumentNode>,
+    groups: &'a mut [Vec<DocumentNode>],
+) -> &'a mut Vec<DocumentNode> {
+    groups.last_mut().unwrap_or(root)
+}
+
+/// Size of a layer between two edges, rejecting inverted or oversized bounds.
+fn layer_extent(start: i32, end: i32) -> Option<usize> {
+    let extent = end.checked_sub(start)?;
+    (0..=MAX_LAYER_SIDE)
+        .contains(&extent)
+        .then_some(extent as usize)
+}
+
+fn read_layer_record(reader: &mut ByteReader) -> Result<LayerRecord> {
+    let top = reader.i32()?;
+    let left = reader.i32()?;
+    let bottom = reader.i32()?;
+    let right = reader.i32()?;
+
+    let channel_count = reader.u16()?;
+    let channels = (0..channel_count)
+        .map(|_| Ok((reader.i16()?, reader.u32()? as usize)))
+        .collect::<Result<Vec<_>>>()?;
+
+    if reader.bytes(4)? != b"8BIM" {
+        bail!("Invalid layer blend signature");
+    }
+    let mut blend_key = reader.array::<4>()?;
+    let opacity = reader.u8()?;
+    let _clipping = reader.u8()?;
+    let _flags = reader.u8()?;
+    reader.skip(1)?;
+
+    let extra_len = reader.u32()? as usize;
+    let extra_end = reader.position() + extra_len;
+
+    let mask_len = reader.u32()? as usize;
+    reader.skip(mask_len)?;
+    let ranges_len = reader.u32()? as usize;
+    reader.skip(ranges_len)?;
+
+    let name_len = reader.u8()? as usize;
+    let mut name = String::from_utf8_lossy(reader.bytes(name_len)?).into_owned();
+    let padded_len = (name_len + 1).div_ceil(4) * 4;
+    reader.skip(padded_len - name_len - 1)?;
+
+    let mut section = None;
+    while reader.position() + 12 <= extra_end {
+        let signature = reader.bytes(4)?;
+        if signature != b"8BIM" && signature != b"8B64" {
+            break;
+        }
+        let key = reader.array::<4>()?;
+        let len = reader.u32()? as usize;
+        let block_end = reader.position() + len;
+
+        match &key {
+            b"luni" => {
+                let units = reader.u32()? as usize;
+                let utf16 = (0..units)
+                    .map(|_| reader.u16())
+                    .collect::<Result<Vec<_>>>()?;
+                name = String::from_utf16_lossy(&utf16)
+                    .trim_end_matches('\0')
+                    .to_string();
+            }
+            b"lsct" => {
+                section = Some(reader.u32()?);
+                if len >= 12 {
+                    reader.skip(4)?;
+                    blend_key = reader.array::<4>()?;
+                }
+            }
+            _ => {}
+        }
+
+        reader.seek(block_end)?;
+    }
+    reader.seek(extra_end)?;
+
+    let (Some(width), Some(height)) = (layer_extent(left, right), layer_extent(top, bottom)) else {
+        bail!(
+            "Invalid bounds of layer '{}': ({}, {}) to ({}, {})",
+            name,
+            left,
+            top,
+            right,
+            bottom
+        );
+    };
+
+    Ok(LayerRecord {
+        name,
+        top,
+        left,
+        width,
+        height,
+        channels,
+        blend_key,
+        opacity,
+        section,
+    })
+}
+
+/// Decoded colour and alpha channels of a layer, keyed by channel id.
+fn read_channels(reader: &mut ByteReader, record: &LayerRecord) -> Result<HashMap<i16, Vec<u8>>> {
+    let (width, height) = (record.width, record.height);
+    let mut channels = HashMap::new();
+
+    for &(id, len) in &record.channels {
+        let end = reader.position() + len;
+        // User and vector masks have their own bounds and are not needed.
+        if id >= -1 && len >= 2 && width > 0 && height > 0 {
+            let compression = reader.u16()?;
+            let data = reader.bytes(len - 2)?;
+            channels.insert(id, decode_channel(compression, data, width, height)?);
+        }
+        reader.seek(end)?;
+    }
+
+    Ok(channels)
+}
+
+fn decode_channel(compression: u16, data: &[u8], width: usize, height: usize) -> Result<Vec<u8>> {
+    let size = width * height;
+
+    let mut pixels = match compression {
+        0 => data.to_vec(),
+        1 => {
+            let mut counts = ByteReader::big_endian(data);
+            let row_lengths = (0..height)
+                .map(|_| counts.u16().map(|len| len as usize))
+                .collect::<Result<Vec<_>>>()?;
+
+            let mut pixels = Vec::with_capacity(size);
+            for row_length in row_lengths {
+                unpack_bits(counts.bytes(row_length)?, width, &mut pixels)?;
+            }
+            pixels
+        }
+        2 | 3 => {
+            let mut pixels = Vec::with_capacity(size);
+            ZlibDecoder::new(data)
+                .read_to_end(&mut pixels)
+                .context("Failed to inflate channel data")?;
+            if compression == 3 {
+                for row in pixels.chunks_mut(width) {
+                    for x in 1..row.len() {
+                        row[x] = row[x].wrapping_add(row[x - 1]);
+                    }
+                }
+            }
+            pixels
+        }
+        other => bail!("Unknown channel compression {}", other),
+    };
+
+    pixels.resize(size, 0);
+    Ok(pixels)
+}
+
+/// Appends one PackBits-encoded row of `width` bytes to `out`.
+fn unpack_bits(src: &[u8], width: usize, out: &mut Vec<u8>) -> Result<()> {
+    let target = out.len() + width;
+    let mut i = 0;
+
+    while i < src.len() && out.len() < target {
+        let header = src[i] as i8;
+        i += 1;
+
+        if header >= 0 {
+            let count = header as usize + 1;
+            let literal = src
+                .get(i..i + count)
+                .ok_or_else(|| anyhow!("Truncated PackBits literal"))?;
+            out.extend_from_slice(literal);
+            i += count;
+        } else if header != -128 {
+            let count = (1 - header as isize) as usize;
+            let value = *src
+                .get(i)
+                .ok_or_else(|| anyhow!("Truncated PackBits run"))?;
+            out.extend(std::iter::repeat_n(value, count));
+            i += 1;
+        }
+    }
+
+    out.resize(target, 0);
+    Ok(())
+}
+
+/// The layer's pixels clipped to the canvas, placed at their document
+/// position. Only the layer bounds are kept, the full canvas is drawn when
+/// the trait is written.
+fn compose_layer(
+    record: &LayerRecord,
+    channels: &HashMap<i16, Vec<u8>>,
+    width: u32,
+    height: u32,
+) -> DocumentFrame {
+    let (record_left, record_top) = (record.left as i64, record.top as i64);
+    let left = record_left.clamp(0, width as i64);
+    let top = record_top.clamp(0, height as i64);
+    let right = (record_left + record.width as i64).clamp(left, width as i64);
+    let bottom = (record_top + record.height as i64).clamp(top, height as i64);
+
+    let mut image = RgbaImage::new((right - left) as u32, (bottom - top) as u32);
+    for canvas_y in top..bottom {
+        let y = (canvas_y - record_top) as usize;
+        for canvas_x in left..right {
+            let x = (canvas_x - record_left) as usize;
+
+            let index = y * record.width + x;
+            let value = |id: i16, default: u8| {
+                channels
+                    .get(&id)
+                    .and_then(|channel| channel.get(index))
+                    .copied()
+                    .unwrap_or(default)
+            };
+            image.put_pixel(
+                (canvas_x - left) as u32,
+                (canvas_y - top) as u32,
+                image::Rgba([value(0, 0), value(1, 0), value(2, 0), value(-1, 255)]),
+            );
+        }
+    }
+
+    DocumentFrame {
+        image,
+        left: left as u32,
+        top: top as u32,
+        duration_ms: 0,
+    }
+}
+
+fn blend_mode(key: &[u8; 4]) -> BlendMode {
+    match key {
+        b"mul " => BlendMode::Multiply,
+        b"scrn" => BlendMode::Screen,
+        b"over" => BlendMode::Overlay,
+        b"dark" | b"dkCl" => BlendMode::Darken,
+        b"lite" | b"lgCl" => BlendMode::Lighten,
+        b"div " => BlendMode::ColorDodge,
+        b"idiv" => BlendMode::ColorBurn,
+        b"hLit" => BlendMode::HardLight,
+        b"sLit" => BlendMode::SoftLight,
+        b"diff" => BlendMode::Difference,
+        b"smud" => BlendMode::Exclusion,
+        b"hue " => BlendMode::Hue,
+        b"sat " => BlendMode::Saturation,
+        b"colr" => BlendMode::Color,
+        b"lum " => BlendMode::Luminosity,
+        b"lddg" => BlendMode::Lighter,
+        _ => BlendMode::SourceOver,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::ZlibEncoder, Compression};
+    use std::io::Write;
+
+    struct TestLayer {
+        name: &'static str,
+        /// Top, left, bottom, right.
+        bounds: [i32; 4],
+        section: Option<u32>,
+        color: [u8; 4],
+    }
+
+    impl TestLayer {
+        fn image(name: &'static str, bounds: [i32; 4], color: [u8; 4]) -> Self {
+            Self {
+                name,
+                bounds,
+                section: None,
+                color,
+            }
+        }
+
+        fn section(name: &'static str, section: u32) -> Self {
+            Self {
+                name,
+                bounds: [0; 4],
+                section: Some(section),
+                color: [0; 4],
+            }
+        }
+
+        /// Pixels written for the layer, none when its bounds are invalid.
+        fn pixel_count(&self) -> usize {
+            let [top, left, bottom, right] = self.bounds;
+            layer_extent(left, right).unwrap_or(0) * layer_extent(top, bottom).unwrap_or(0)
+        }
+    }
+
+    /// 8-bit RGB document with raw channel data, layers bottom first.
+    fn psd(width: u32, height: u32, layers: &[TestLayer]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(b"8BPS");
+        data.extend_from_slice(&1u16.to_be_bytes());
+        data.extend_from_slice(&[0; 6]);
+        data.extend_from_slice(&3u16.to_be_bytes());
+        data.extend_from_slice(&height.to_be_bytes());
+        data.extend_from_slice(&width.to_be_bytes());
+        data.extend_from_slice(&8u16.to_be_bytes());
+        data.extend_from_slice(&3u16.to_be_bytes());
+        data.extend_from_slice(&0u32.to_be_bytes());
+        data.extend_from_slice(&0u32.to_be_bytes());
+        data.extend_from_slice(&0u32.to_be_bytes());
+        data.extend_from_slice(&1u32.to_be_bytes());
+        data.extend_from_slice(&(layers.len() as i16).to_be_bytes());
+
+        for layer in layers {
+            for edge in layer.bounds {
+                data.extend_from_slice(&edge.to_be_bytes());
+            }
+            data.extend_from_slice(&4u16.to_be_bytes());
+            for id in [0i16, 1, 2, -1] {
+                data.extend_from_slice(&id.to_be_bytes());
+                data.extend_from_slice(&(layer.pixel_count() as u32 + 2).to_be_bytes());
+            }
+            data.extend_from_slice(b"8BIMnorm");
+            data.extend_from_slice(&[255, 0, 0, 0]);
+
+            let mut extra = Vec::new();
+            extra.extend_from_slice(&0u32.to_be_bytes());
+            extra.extend_from_slice(&0u32.to_be_bytes());
+            extra.push(layer.name.len() as u8);
+            extra.extend_from_slice(layer.name.as_bytes());
+            while (extra.len() - 8) % 4 != 0 {
+                extra.push(0);
+            }
+            if let Some(section) = layer.section {
+                extra.extend_from_slice(b"8BIMlsct");
+                extra.extend_from_slice(&4u32.to_be_bytes());
+                extra.extend_from_slice(&section.to_be_bytes());
+            }
+            data.extend_from_slice(&(extra.len() as u32).to_be_bytes());
+            data.extend_from_slice(&extra);
+        }
+
+        for layer in layers {
+            for channel in [0, 1, 2, 3] {
+                data.extend_from_slice(&0u16.to_be_bytes());
+                data.extend(vec![layer.color[channel]; layer.pixel_count()]);
+            }
+        }
+
+        data
+    }
+
+    fn node_names(nodes: &[DocumentNode]) -> Vec<String> {
+        nodes
+            .iter()
+            .map(|node| match node {
+                DocumentNode::Group { name, children, .. } => {
+                    format!("{}[{}]", name, node_names(children).join(","))
+                }
+                DocumentNode::Image { name, .. } => name.clone(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reads_groups_and_places_layers_on_the_canvas() {
+        let red = [255, 0, 0, 255];
+        let document = read_psd(&psd(
+            4,
+            4,
+            &[
+                TestLayer::image("Body", [0, 0, 4, 4], [0, 0, 255, 255]),
+                TestLayer::section("</Layer group>", SECTION_DIVIDER),
+                TestLayer::image("Crown", [1, 2, 3, 5], red),
+                TestLayer::section("Hats", SECTION_OPEN_FOLDER),
+            ],
+        ))
+        .unwrap();
+
+        assert_eq!((document.width, document.height), (4, 4));
+        assert_eq!(node_names(&document.nodes), vec!["Body", "Hats[Crown]"]);
+
+        let DocumentNode::Group { children, .. } = &document.nodes[1] else {
+            panic!("Hats should be a group");
+        };
+        let DocumentNode::Image { frames, .. } = &children[0] else {
+            panic!("Crown should be an image");
+        };
+        // Only the part of the layer inside the canvas is kept.
+        assert_eq!(frames[0].image.dimensions(), (2, 2));
+        assert_eq!((frames[0].left, frames[0].top), (2, 1));
+        let image = frames[0].on_canvas(4, 4);
+        assert_eq!(image.get_pixel(2, 1).0, red);
+        assert_eq!(image.get_pixel(3, 2).0, red);
+        assert_eq!(image.get_pixel(1, 1).0, [0, 0, 0, 0]);
+        assert_eq!(image.get_pixel(2, 3).0, [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn unclosed_groups_are_flattened_and_stray_folders_are_empty() {
+        let document = read_psd(&psd(
+            2,
+            2,
+            &[
+                TestLayer::section("Stray", SECTION_CLOSED_FOLDER),
+                TestLayer::section("</Layer group>", SECTION_DIVIDER),
+                TestLayer::image("Orphan", [0, 0, 1, 1], [1, 2, 3, 255]),
+            ],
+        ))
+        .unwrap();
+
+        assert_eq!(node_names(&document.nodes), vec!["Stray[]", "Orphan"]);
+    }
+
+    #[test]
+    fn rejects_inverted_and_overflowing_layer_bounds() {
+        for bounds in [[2, 0, 1, 1], [0, i32::MIN, 1, i32::MAX], [0, 0, 1, 40_000]] {
+            let layer = TestLayer {
+                name: "Bad",
+                bounds,
+                section: None,
+                color: [0; 4],
+            };
+            let error = read_psd(&psd(2, 2, &[layer])).err().unwrap();
+            assert!(error.to_string().contains("Invalid bounds"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn rejects_oversized_canvases() {
+        for (width, height) in [(0, 2), (40_000, 2), (20_000, 20_000)] {
+            let error = read_psd(&psd(width, height, &[])).err().unwrap();
+            assert!(error.to_string().contains("canvas size"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn unpack_bits_expands_literals_and_runs() {
+        let mut out = vec![7];
+        unpack_bits(&[0x02, 1, 2, 3, 0xFE, 9, 0x80], 7, &mut out).unwrap();
+        assert_eq!(out, vec![7, 1, 2, 3, 9, 9, 9, 0]);
+
+        let mut out = Vec::new();
+        unpack_bits(&[0xFD, 4, 0x01, 5, 6], 3, &mut out).unwrap();
+        assert_eq!(out, vec![4, 4, 4]);
+    }
+
+    #[test]
+    fn unpack_bits_rejects_truncated_data() {
+        assert!(unpack_bits(&[0x03, 1, 2], 4, &mut Vec::new()).is_err());
+        assert!(unpack_bits(&[0xFF], 4, &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn decode_channel_reads_every_compression() {
+        assert_eq!(
+            decode_channel(0, &[1, 2, 3], 2, 2).unwrap(),
+            vec![1, 2, 3, 0]
+        );
+
+        // Two rows of PackBits, each preceded by its byte count.
+        let rle = [0, 2, 0, 3, 0xFF, 5, 0x01, 6, 7];
+        assert_eq!(decode_channel(1, &rle, 2, 2).unwrap(), vec![5, 5, 6, 7]);
+
+        let zip = |bytes: &[u8]| {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(bytes).unwrap();
+            encoder.finish().unwrap()
+        };
+        assert_eq!(
+            decode_channel(2, &zip(&[1, 2, 3, 4]), 2, 2).unwrap(),
+            vec![1, 2, 3, 4]
+        );
+        // Prediction stores each byte as the difference to the previous one
+        // of its row.
+        assert_eq!(
+            decode_channel(3, &zip(&[10, 1, 20, 255]), 2, 2).unwrap(),
+            vec![10, 11, 20, 19]
+        );
+
+        assert!(decode_channel(9, &[], 1, 1).is_err());
+    }
+}
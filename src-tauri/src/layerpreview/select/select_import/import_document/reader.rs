@@ -0,0 +1,88 @@
+use anyhow::{anyhow, Result};
+
+/// Bounds-checked cursor over a document buffer.
+pub struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    little_endian: bool,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn big_endian(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            little_endian: false,
+        }
+    }
+
+    pub fn little_endian(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            little_endian: true,
+        }
+    }
+
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub fn seek(&mut self, pos: usize) -> Result<()> {
+        if pos > self.data.len() {
+            return Err(anyhow!("Unexpected end of file at offset {}", pos));
+        }
+        self.pos = pos;
+        Ok(())
+    }
+
+    pub fn skip(&mut self, count: usize) -> Result<()> {
+        self.seek(self.pos + count)
+    }
+
+    pub fn bytes(&mut self, count: usize) -> Result<&'a [u8]> {
+        let end = self.pos + count;
+        let bytes = self
+            .data
+            .get(self.pos..end)
+            .ok_or_else(|| anyhow!("Unexpected end of file at offset {}", self.pos))?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    pub fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.bytes(N)?);
+        Ok(array)
+    }
+
+    pub fn u8(&mut self) -> Result<u8> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16> {
+        let bytes = self.array()?;
+        Ok(if self.little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    }
+
+    pub fn i16(&mut self) -> Result<i16> {
+        Ok(self.u16()? as i16)
+    }
+
+    pub fn u32(&mut self) -> Result<u32> {
+        let bytes = self.array()?;
+        Ok(if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    pub fn i32(&mut self) -> Result<i32> {
+        Ok(self.u32()? as i32)
+    }
+}
@@ -1,5 +1,6 @@
 pub mod check_animated;
 pub mod get_layer_names;
 pub mod get_layers_content;
+pub mod import_document;
 pub mod select;
 pub mod utils;
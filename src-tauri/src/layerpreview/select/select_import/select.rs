@@ -1,18 +1,21 @@
 use crate::types::{ImageDimensions, ProjectSetupState, SpritesheetLayout};
 use crate::{
     filesystem::{
-        constants::StorageFiles,
         folderhash::{calculate_folder_hash, get_previous_hash, save_folder_hash},
         persist::{load_projectsetup_state, save_storage_command},
+        projects::ProjectRegistry,
     },
-    layerpreview::select::select_import::{
-        get_layers_content::get_layers_content,
-        utils::{
-            base_dimensions::get_base_dimensions, config_files::setup_config_files,
-            create_directories::setup_directories, error_dialog::show_error_dialog,
-            notify::notify_processing_started, process_animated::process_animated_layers,
-            structs::LayerContent,
+    layerpreview::{
+        select::select_import::{
+            get_layers_content::get_layers_content,
+            utils::{
+                base_dimensions::get_base_dimensions, config_files::setup_config_files,
+                create_directories::setup_directories, error_dialog::show_error_dialog,
+                notify::notify_processing_started, process_animated::process_animated_layers,
+                structs::LayerContent,
+            },
         },
+        validation::watcher::start_layer_watcher,
     },
 };
 use anyhow::Result;
@@ -58,13 +61,14 @@ pub async fn select_and_load_folder_data(
             msg
         })?;
 
-    let previous_hash = get_previous_hash(folder_path.clone(), app_handle.state::<StorageFiles>())
-        .await
-        .map_err(|e| {
-            let msg = format!("Failed to get previous hash: {}", e);
-            tracing::error!("{}", msg);
-            msg
-        })?;
+    let previous_hash =
+        get_previous_hash(folder_path.clone(), app_handle.state::<ProjectRegistry>())
+            .await
+            .map_err(|e| {
+                let msg = format!("Failed to get previous hash: {}", e);
+                tracing::error!("{}", msg);
+                msg
+            })?;
 
     if let Some(hash) = previous_hash {
         if hash == current_hash {
@@ -89,7 +93,10 @@ pub async fn select_and_load_folder_data(
         .path()
         .app_data_dir()
         .map_err(|e| format!("Failed to get app data directory: {}", e))?;
-    let config_dir = app_data_dir.join("config");
+    let config_dir = app_handle
+        .state::<ProjectRegistry>()
+        .storage_files()
+        .get_config_dir();
 
     tracing::info!("Setting up directories and config files");
     setup_directories(&[&app_data_dir, &config_dir]).await?;
@@ -99,7 +106,7 @@ pub async fn select_and_load_folder_data(
     save_folder_hash(
         folder_path.clone(),
         current_hash,
-        app_handle.state::<StorageFiles>(),
+        app_handle.state::<ProjectRegistry>(),
     )
     .await
     .map_err(|e| {
@@ -124,6 +131,7 @@ pub async fn select_and_load_folder_data(
         layers.len(),
         is_animated_collection
     );
+    start_layer_watcher(app_handle.clone(), PathBuf::from(&folder_path));
 
     Ok(Some(InitialFolderData {
         folder_path,
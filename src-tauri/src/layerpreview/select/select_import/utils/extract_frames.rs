@@ -1,14 +1,41 @@
-use crate::layerpreview::{
-    animations::commands::extract_frames,
-    select::select_import::{check_animated::check_animated_images, utils::structs::LayerContent},
+use crate::{
+    filesystem::{
+        manifest::{FolderManifest, ManifestDiff},
+        persist::load_rarity_config,
+        trait_files::{scan_trait_files, TraitFile},
+        utils::normalize_path,
+    },
+    layerpreview::{
+        animations::{
+            commands::extract_frames,
+            get_animated_frames_dir, probe_frame_durations,
+            timeline::{AnimationTimeline, PlaybackSequence},
+            utils::{reset_animation_state, set_animation_timeline},
+            RAYON_POOL,
+        },
+        select::select_import::{
+            check_animated::check_animated_images, utils::structs::LayerContent,
+        },
+    },
+    types::LayerPlayback,
 };
 use anyhow::Result;
 use futures::future::join_all;
-use std::{fs::read_dir, path::PathBuf};
+use rayon::prelude::*;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+use tauri::Manager;
 use tracing;
 
-const SUPPORTED_IMAGE_EXTENSIONS: &[&str] =
-    &["gif", "webp", "png", "mp4", "webm", "mov", "avi", "mkv"];
+struct AnimatedFile {
+    layer_name: String,
+    path: PathBuf,
+    image_name: String,
+    /// Path relative to the project folder, as stored in the manifest.
+    key: String,
+}
 
 pub async fn extract_animation_frames(
     folder_path: &str,
@@ -16,6 +43,7 @@ pub async fn extract_animation_frames(
     app_handle: &tauri::AppHandle,
     project_id: &str,
     total_files: u32,
+    manifest: &mut FolderManifest,
 ) -> Result<(), String> {
     tracing::info!(
         "Starting animation frame extraction for {} layers, {} total files",
@@ -23,7 +51,130 @@ pub async fn extract_animation_frames(
         total_files
     );
 
-    let mut futures = Vec::new();
+    let layer_playback = load_layer_playback(app_handle).await;
+    let animated_files = collect_animated_files(folder_path, layers).await?;
+    let timeline = build_timeline(&animated_files, &layer_playback, manifest).await?;
+
+    let base_dir = get_animated_frames_dir(app_handle, project_id)?;
+    if let Err(e) = timeline.save(&base_dir) {
+        tracing::warn!("Failed to persist animation timeline: {}", e);
+    }
+    set_animation_timeline(timeline).await;
+
+    run_extraction(
+        app_handle,
+        project_id,
+        animated_files,
+        &layer_playback,
+        total_files,
+        manifest,
+    )
+    .await?;
+
+    tracing::info!(
+        "Animation frame extraction completed for {} layers",
+        layers.len()
+    );
+    Ok(())
+}
+
+/// Brings existing spritesheets up to date with `diff`. Only added or changed
+/// animated files are extracted again; a change of the shared timeline still
+/// rebuilds every trait, since all sheets must share the same frame count.
+pub async fn refresh_animation_frames(
+    folder_path: &str,
+    layers: &[LayerContent],
+    app_handle: &tauri::AppHandle,
+    project_id: &str,
+    total_files: u32,
+    diff: &ManifestDiff,
+    manifest: &mut FolderManifest,
+) -> Result<(), String> {
+    let layer_playback = load_layer_playback(app_handle).await;
+    let animated_files = collect_animated_files(folder_path, layers).await?;
+    let timeline = build_timeline(&animated_files, &layer_playback, manifest).await?;
+
+    let base_dir = get_animated_frames_dir(app_handle, project_id)?;
+    if AnimationTimeline::load(&base_dir).as_ref() != Some(&timeline) {
+        tracing::info!("⏱️ Animation timeline changed, rebuilding every spritesheet");
+        return extract_animation_frames(
+            folder_path,
+            layers,
+            app_handle,
+            project_id,
+            total_files,
+            manifest,
+        )
+        .await;
+    }
+
+    let spritesheets_dir = base_dir.join("spritesheets");
+    for key in diff.removed.iter().chain(&diff.changed) {
+        if let Some(trait_dir) = trait_spritesheet_dir(&spritesheets_dir, key) {
+            if trait_dir.exists() {
+                if let Err(e) = std::fs::remove_dir_all(&trait_dir) {
+                    tracing::warn!(
+                        "Failed to remove spritesheets of {}: {}",
+                        trait_dir.display(),
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    let modified = diff.modified();
+    let affected: Vec<AnimatedFile> = animated_files
+        .into_iter()
+        .filter(|file| modified.contains(file.key.as_str()))
+        .collect();
+
+    tracing::info!(
+        "🔄 Refreshing {} of {} animated files ({} removed)",
+        affected.len(),
+        total_files,
+        diff.removed.len()
+    );
+
+    // Removals alone leave the remaining sheets and their layout as they are.
+    if affected.is_empty() {
+        set_animation_timeline(timeline).await;
+        return Ok(());
+    }
+
+    reset_animation_state().await;
+    set_animation_timeline(timeline).await;
+
+    run_extraction(
+        app_handle,
+        project_id,
+        affected,
+        &layer_playback,
+        total_files,
+        manifest,
+    )
+    .await
+}
+
+async fn load_layer_playback(app_handle: &tauri::AppHandle) -> HashMap<String, LayerPlayback> {
+    match load_rarity_config(app_handle.state()).await {
+        Ok(rarity_config) => rarity_config
+            .layers
+            .into_iter()
+            .filter_map(|(layer, config)| config.playback.map(|playback| (layer, playback)))
+            .collect(),
+        Err(e) => {
+            tracing::warn!("Failed to load layer playback settings: {}", e);
+            HashMap::new()
+        }
+    }
+}
+
+async fn collect_animated_files(
+    folder_path: &str,
+    layers: &[LayerContent],
+) -> Result<Vec<AnimatedFile>, String> {
+    let mut animated_files = Vec::new();
 
     for layer in layers {
         let layer_path = PathBuf::from(folder_path).join(&layer.name);
@@ -38,52 +189,157 @@ pub async fn extract_animation_frames(
             continue;
         }
 
-        if let Ok(entries) = read_dir(&layer_path) {
-            for entry in entries.flatten() {
-                if let Some(ext) = entry.path().extension() {
-                    let ext = ext.to_string_lossy().to_lowercase();
-                    if SUPPORTED_IMAGE_EXTENSIONS.contains(&ext.as_str()) {
-                        if let Some(name) = entry.path().file_stem() {
-                            let image_name = name.to_string_lossy().to_string();
-                            tracing::debug!(
-                                "Found animated image: {} ({}) in layer {}",
-                                image_name,
-                                ext,
-                                layer.name
-                            );
-
-                            let app_handle_clone = app_handle.clone();
-                            let path_string = entry.path().to_string_lossy().to_string();
-
-                            futures.push(extract_frames(
-                                app_handle_clone,
-                                project_id.to_string(),
-                                path_string,
-                                layer.name.clone(),
-                                image_name,
-                                0,
-                                true,
-                                total_files,
-                            ));
-                        }
-                    }
-                }
-            }
+        for file in scan_trait_files(&layer_path) {
+            tracing::debug!(
+                "Found animated image: {} in layer {}",
+                file.relative_path,
+                layer.name
+            );
+
+            animated_files.push(AnimatedFile {
+                layer_name: layer.name.clone(),
+                path: layer_path.join(&file.relative_path),
+                key: normalize_path(&format!("{}/{}", layer.name, file.relative_path)),
+                image_name: file.key,
+            });
         }
     }
 
+    Ok(animated_files)
+}
+
+/// Builds the shared timeline, probing only files whose frame timing is not
+/// already cached in `manifest`, and records the newly probed timing there.
+async fn build_timeline(
+    animated_files: &[AnimatedFile],
+    layer_playback: &HashMap<String, LayerPlayback>,
+    manifest: &mut FolderManifest,
+) -> Result<AnimationTimeline, String> {
+    let to_probe: Vec<(usize, PathBuf)> = animated_files
+        .iter()
+        .enumerate()
+        .filter(|(_, file)| manifest.frame_durations(&file.key).is_none())
+        .map(|(index, file)| (index, file.path.clone()))
+        .collect();
+
+    tracing::debug!(
+        "Probing frame timing of {} of {} animated files",
+        to_probe.len(),
+        animated_files.len()
+    );
+
+    let probed = tokio::task::spawn_blocking(move || {
+        RAYON_POOL.install(|| {
+            to_probe
+                .into_par_iter()
+                .map(|(index, path)| {
+                    let durations = probe_frame_durations(&path)
+                        .map_err(|e| {
+                            tracing::warn!("Failed to probe timing of {}: {}", path.display(), e)
+                        })
+                        .ok();
+                    (index, durations)
+                })
+                .collect::<Vec<_>>()
+        })
+    })
+    .await
+    .map_err(|e| format!("Frame timing probe failed: {}", e))?;
+
+    let mut probed: HashMap<usize, Option<Vec<u32>>> = probed.into_iter().collect();
+
+    let sequences: Vec<PlaybackSequence> = animated_files
+        .iter()
+        .enumerate()
+        .map(|(index, file)| {
+            let durations = match probed.remove(&index) {
+                Some(Some(durations)) => {
+                    manifest.set_frame_durations(&file.key, durations.clone());
+                    durations
+                }
+                Some(None) => Vec::new(),
+                None => manifest
+                    .frame_durations(&file.key)
+                    .map(<[u32]>::to_vec)
+                    .unwrap_or_default(),
+            };
+            let playback = layer_playback
+                .get(&file.layer_name)
+                .cloned()
+                .unwrap_or_default();
+            PlaybackSequence::new(&durations, &playback)
+        })
+        .collect();
+
+    let timeline = AnimationTimeline::build(&sequences, 0);
+    tracing::info!(
+        "⏱️ Animation timeline: {} frames over {} ms (variable timing: {})",
+        timeline.frame_count(),
+        timeline.loop_duration_ms(),
+        timeline.is_variable()
+    );
+
+    Ok(timeline)
+}
+
+/// Extracts every file. Failed files are dropped from `manifest`, which is
+/// saved before the error is returned, so the next reload sees them as added
+/// and extracts only them again.
+async fn run_extraction(
+    app_handle: &tauri::AppHandle,
+    project_id: &str,
+    animated_files: Vec<AnimatedFile>,
+    layer_playback: &HashMap<String, LayerPlayback>,
+    total_files: u32,
+    manifest: &mut FolderManifest,
+) -> Result<(), String> {
+    let keys: Vec<String> = animated_files.iter().map(|file| file.key.clone()).collect();
+    let futures = animated_files.into_iter().map(|file| {
+        let playback = layer_playback.get(&file.layer_name).cloned();
+        extract_frames(
+            app_handle.clone(),
+            project_id.to_string(),
+            file.path.to_string_lossy().to_string(),
+            file.layer_name,
+            file.image_name,
+            0,
+            true,
+            total_files,
+            playback,
+        )
+    });
+
     let results = join_all(futures).await;
 
-    for (i, result) in results.iter().enumerate() {
+    let mut failures = Vec::new();
+    for (key, result) in keys.into_iter().zip(results) {
         match result {
-            Ok(_) => tracing::info!("extract_frames {} succeeded", i),
-            Err(e) => tracing::warn!("extract_frames {} failed but continuing: {}", i, e),
+            Ok(_) => tracing::info!("extract_frames {} succeeded", key),
+            Err(e) => {
+                tracing::warn!("extract_frames {} failed but continuing: {}", key, e);
+                manifest.files.remove(&key);
+                failures.push(format!("{}: {}", key, e));
+            }
         }
     }
 
-    tracing::info!(
-        "Animation frame extraction completed for {} layers",
-        layers.len()
-    );
-    Ok(())
+    if failures.is_empty() {
+        return Ok(());
+    }
+
+    manifest.save(&get_animated_frames_dir(app_handle, project_id)?)?;
+    Err(format!(
+        "Failed to extract frames of {} animated files:\n{}",
+        failures.len(),
+        failures.join("\n")
+    ))
+}
+
+/// Spritesheet folder of a `<layer>/<path>` manifest key, if it names a
+/// supported animated file of a layer.
+fn trait_spritesheet_dir(spritesheets_dir: &Path, key: &str) -> Option<PathBuf> {
+    let (layer_name, relative_path) = key.split_once('/')?;
+    let file = TraitFile::parse(relative_path)?;
+
+    Some(spritesheets_dir.join(layer_name).join(file.key))
 }
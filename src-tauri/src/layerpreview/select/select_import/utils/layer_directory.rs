@@ -1,10 +1,13 @@
-use crate::layerpreview::select::select_import::{
-    check_animated::check_animated_images,
-    utils::{image_file::process_image_file, structs::LayerContent},
+use crate::{
+    filesystem::trait_files::scan_trait_files,
+    layerpreview::select::select_import::{
+        check_animated::check_animated_images,
+        utils::{image_file::process_image_file, structs::LayerContent},
+    },
 };
 use anyhow::Result;
 use futures::future::join_all;
-use tokio::fs::{read_dir, DirEntry};
+use tokio::{fs::DirEntry, task::spawn_blocking};
 
 pub async fn process_layer_directory(entry: DirEntry) -> Result<Option<LayerContent>, String> {
     let layer_name = entry.file_name().to_string_lossy().to_string();
@@ -13,35 +16,27 @@ pub async fn process_layer_directory(entry: DirEntry) -> Result<Option<LayerCont
     let mut image_futures = Vec::with_capacity(32);
     let mut base_dimensions = None;
 
-    let mut subdir_stream = match read_dir(&layer_path).await {
-        Ok(s) => s,
-        Err(_) => return Ok(None),
-    };
-
-    while let Ok(Some(subentry)) = subdir_stream.next_entry().await {
-        if !matches!(subentry.file_type().await, Ok(ft) if ft.is_file()) {
-            continue;
-        }
+    if !layer_path.is_dir() {
+        return Ok(None);
+    }
 
-        let ext = subentry
-            .path()
-            .extension()
-            .and_then(|e| e.to_str())
-            .map(|e| e.to_ascii_lowercase());
+    let scan_path = layer_path.clone();
+    let trait_files = spawn_blocking(move || scan_trait_files(&scan_path))
+        .await
+        .map_err(|e| format!("Failed to scan layer {}: {}", layer_name, e))?;
 
-        if matches!(
-            ext.as_deref(),
-            Some("png")
-                | Some("webp")
-                | Some("gif")
-                | Some("mp4")
-                | Some("webm")
-                | Some("mov")
-                | Some("avi")
-                | Some("mkv")
-        ) {
-            image_futures.push(process_image_file(subentry.path()));
-        }
+    // Linked parts are drawn with their trait and are not listed on their own.
+    for file in trait_files.into_iter().filter(|file| file.part.is_none()) {
+        let path = layer_path.join(&file.relative_path);
+        image_futures.push(async move {
+            // Nested traits are named by their path inside the layer.
+            process_image_file(path).await.map(|result| {
+                result.map(|(mut metadata, dimensions)| {
+                    metadata.name = file.relative_path;
+                    (metadata, dimensions)
+                })
+            })
+        });
     }
 
     let image_results = join_all(image_futures).await;
@@ -1,7 +1,9 @@
 use crate::{
+    filesystem::{manifest::FolderManifest, trait_files::scan_trait_files},
     layerpreview::{
         animations::{
             commands::get_spritesheet_metadata,
+            get_animated_frames_dir,
             utils::{reset_animation_state, set_total_traits_to_process},
             GLOBAL_MAX_FRAMES,
         },
@@ -14,7 +16,7 @@ use crate::{
 };
 
 use anyhow::Result;
-use std::{fs::read_dir, path::PathBuf, sync::atomic::Ordering};
+use std::{path::PathBuf, sync::atomic::Ordering};
 use tracing;
 pub async fn process_animated_layers(
     folder_path: &str,
@@ -75,8 +77,26 @@ pub async fn process_animated_layers(
         let total_files = count_total_animated_files(folder_path, layers);
         tracing::info!("Total animated files to process: {}", total_files);
 
+        let root = path_buf.clone();
+        let mut manifest = tokio::task::spawn_blocking(move || FolderManifest::scan(&root, None))
+            .await
+            .map_err(|e| format!("Failed to scan layer folder: {}", e))?;
+
         tracing::info!("Starting frame extraction for {} files", total_files);
-        extract_animation_frames(folder_path, layers, app_handle, project_id, total_files).await?;
+        extract_animation_frames(
+            folder_path,
+            layers,
+            app_handle,
+            project_id,
+            total_files,
+            &mut manifest,
+        )
+        .await?;
+
+        let animated_dir = get_animated_frames_dir(app_handle, project_id)?;
+        if let Err(e) = manifest.save(&animated_dir) {
+            tracing::warn!("Failed to save layer manifest: {}", e);
+        }
 
         let raw_count = GLOBAL_MAX_FRAMES.load(Ordering::SeqCst);
         frame_count = Some(raw_count);
@@ -98,6 +118,10 @@ pub async fn process_animated_layers(
                 total_sheets: metadata.total_sheets,
                 frames_per_sheet: metadata.frames_per_sheet,
                 total_frames: metadata.total_frames,
+                cell_x: metadata.cell_x,
+                cell_y: metadata.cell_y,
+                cell_width: metadata.cell_width,
+                cell_height: metadata.cell_height,
             });
         } else {
             tracing::warn!("Failed to retrieve spritesheet metadata");
@@ -121,26 +145,8 @@ fn count_total_animated_files(folder_path: &str, layers: &[LayerContent]) -> u32
     for layer in layers {
         let layer_path = PathBuf::from(folder_path).join(&layer.name);
         if layer_path.exists() {
-            let mut layer_file_count = 0;
-            if let Ok(entries) = read_dir(&layer_path) {
-                for entry in entries.flatten() {
-                    if let Some(ext) = entry.path().extension() {
-                        let ext_lower = ext.to_string_lossy().to_lowercase();
-                        if ext_lower == "gif"
-                            || ext_lower == "webp"
-                            || ext_lower == "png"
-                            || ext_lower == "mp4"
-                            || ext_lower == "webm"
-                            || ext_lower == "mov"
-                            || ext_lower == "avi"
-                            || ext_lower == "mkv"
-                        {
-                            total_files += 1;
-                            layer_file_count += 1;
-                        }
-                    }
-                }
-            }
+            let layer_file_count = scan_trait_files(&layer_path).len() as u32;
+            total_files += layer_file_count;
             if layer_file_count > 0 {
                 tracing::debug!("Layer {}: {} animated files", layer.name, layer_file_count);
             }
@@ -1,4 +1,4 @@
-use crate::layerpreview::animations::commands::get_spritesheets_path;
+use crate::{layerpreview::animations::commands::get_spritesheets_path, types::SpritesheetLayout};
 use std::path::PathBuf;
 #[tauri::command]
 pub async fn get_spritesheet_image_path(
@@ -19,3 +19,18 @@ pub async fn get_spritesheet_image_path(
 
     Ok(path.to_string_lossy().to_string())
 }
+
+/// Layout the spritesheets of a trait were packed with, `None` when they use
+/// the project layout.
+#[tauri::command]
+pub async fn get_trait_spritesheet_layout(
+    app_handle: tauri::AppHandle,
+    project_id: String,
+    layer_trait_path: String,
+) -> Result<Option<SpritesheetLayout>, String> {
+    let spritesheets_dir = get_spritesheets_path(app_handle, project_id).await?;
+
+    Ok(SpritesheetLayout::read_from(
+        &PathBuf::from(&spritesheets_dir).join(&layer_trait_path),
+    ))
+}
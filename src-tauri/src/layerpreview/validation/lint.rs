@@ -0,0 +1,742 @@
+use dashmap::DashMap;
+use image::{imageops::FilterType, DynamicImage};
+use once_cell::sync::Lazy;
+use rayon::prelude::*;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fs,
+    io::Cursor,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use tracing;
+
+use crate::{
+    filesystem::trait_files::{scan_trait_files, TraitFile},
+    layerpreview::{
+        animations::timeline::{common_loop_duration, DEFAULT_FRAME_DURATION_MS},
+        select::select_import::utils::frame_count::detect_frame_count,
+    },
+};
+
+/// Files above this size slow every generation down and are usually exported
+/// without compression.
+const OVERSIZED_FILE_BYTES: u64 = 20 * 1024 * 1024;
+
+/// Perceptual hashes this close are reported as likely the same artwork.
+const SIMILAR_HASH_DISTANCE: u32 = 2;
+
+const VIDEO_EXTENSIONS: [&str; 5] = ["mp4", "webm", "mov", "avi", "mkv"];
+
+/// Inspected assets by path. An entry is reused while the file keeps its
+/// size and modification time, so starting a generation only decodes and
+/// hashes the files that changed since the last lint.
+static ASSET_CACHE: Lazy<DashMap<PathBuf, CachedAsset>> = Lazy::new(DashMap::new);
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LintDiagnostic {
+    pub severity: LintSeverity,
+    pub code: String,
+    pub layer: String,
+    /// Path relative to the layer folder, `None` for layer-wide issues.
+    pub file: Option<String>,
+    pub message: String,
+    pub hint: String,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LintReport {
+    pub files_scanned: usize,
+    pub errors: usize,
+    pub warnings: usize,
+    pub diagnostics: Vec<LintDiagnostic>,
+}
+
+impl LintReport {
+    pub fn has_errors(&self) -> bool {
+        self.errors > 0
+    }
+
+    fn push(
+        &mut self,
+        severity: LintSeverity,
+        code: &str,
+        asset: (&str, Option<&str>),
+        message: String,
+        hint: &str,
+    ) {
+        match severity {
+            LintSeverity::Error => self.errors += 1,
+            LintSeverity::Warning => self.warnings += 1,
+            LintSeverity::Info => {}
+        }
+        self.diagnostics.push(LintDiagnostic {
+            severity,
+            code: code.to_string(),
+            layer: asset.0.to_string(),
+            file: asset.1.map(str::to_string),
+            message,
+            hint: hint.to_string(),
+        });
+    }
+}
+
+struct CachedAsset {
+    size: u64,
+    modified: SystemTime,
+    info: AssetInfo,
+}
+
+/// What a single file looks like, gathered in one read.
+#[derive(Clone)]
+struct AssetInfo {
+    layer: String,
+    file: TraitFile,
+    size: u64,
+    dimensions: Option<(u32, u32)>,
+    frame_count: u32,
+    sixteen_bit: bool,
+    foreign_profile: bool,
+    empty_alpha: bool,
+    pixel_hash: Option<String>,
+    perceptual_hash: Option<u64>,
+    error: Option<String>,
+}
+
+impl AssetInfo {
+    fn read(layer: &str, layer_path: &Path, file: TraitFile) -> Self {
+        let path = layer_path.join(&file.relative_path);
+        let metadata = fs::metadata(&path).ok();
+        let size = metadata.as_ref().map_or(0, |m| m.len());
+        let modified = metadata.and_then(|m| m.modified().ok());
+
+        if let Some(cached) = ASSET_CACHE.get(&path) {
+            if cached.size == size && Some(cached.modified) == modified {
+                return cached.info.clone();
+            }
+        }
+
+        let info = Self::inspect_file(layer, &path, file, size);
+        if let Some(modified) = modified {
+            ASSET_CACHE.insert(
+                path,
+                CachedAsset {
+                    size,
+                    modified,
+                    info: info.clone(),
+                },
+            );
+        }
+        info
+    }
+
+    fn inspect_file(layer: &str, path: &Path, file: TraitFile, size: u64) -> Self {
+        let mut info = Self {
+            layer: layer.to_string(),
+            size,
+            file,
+            dimensions: None,
+            frame_count: 1,
+            sixteen_bit: false,
+            foreign_profile: false,
+            empty_alpha: false,
+            pixel_hash: None,
+            perceptual_hash: None,
+            error: None,
+        };
+        if is_video(path) {
+            return info;
+        }
+        if let Err(e) = info.inspect(path) {
+            info.error = Some(e);
+        }
+        info
+    }
+
+    fn location(&self) -> (&str, Option<&str>) {
+        (&self.layer, Some(&self.file.relative_path))
+    }
+
+    fn inspect(&mut self, path: &Path) -> Result<(), String> {
+        let data = fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+
+        self.frame_count = detect_frame_count(&data, &path.to_path_buf())?;
+        if has_extension(path, "png") {
+            let decoder = png::Decoder::new(Cursor::new(&data));
+            let reader = decoder
+                .read_info()
+                .map_err(|e| format!("Invalid PNG: {}", e))?;
+            let png_info = reader.info();
+            self.sixteen_bit = png_info.bit_depth == png::BitDepth::Sixteen;
+            self.foreign_profile = png_info.srgb.is_none()
+                && png_info
+                    .icc_profile
+                    .as_ref()
+                    .is_some_and(|profile| !is_srgb_profile(profile));
+            if let Some(animation) = &png_info.animation_control {
+                self.frame_count = animation.num_frames;
+            }
+        }
+
+        let image =
+            image::load_from_memory(&data).map_err(|e| format!("Failed to decode image: {}", e))?;
+        let rgba = image.to_rgba8();
+        self.dimensions = Some(rgba.dimensions());
+        // The first frame of an animation may be blank on purpose.
+        self.empty_alpha = self.frame_count <= 1 && rgba.pixels().all(|pixel| pixel[3] == 0);
+
+        let mut hasher = Sha256::new();
+        hasher.update(rgba.width().to_le_bytes());
+        hasher.update(rgba.height().to_le_bytes());
+        hasher.update(rgba.as_raw());
+        self.pixel_hash = Some(format!("{:x}", hasher.finalize()));
+        self.perceptual_hash = Some(difference_hash(&image));
+        Ok(())
+    }
+}
+
+/// 64-bit difference hash of the image composited on black, so the shape
+/// of a mostly transparent trait drives the hash rather than hidden pixels.
+fn difference_hash(image: &DynamicImage) -> u64 {
+    let small = image.resize_exact(9, 8, FilterType::Triangle).to_rgba8();
+    let luma = |x: u32, y: u32| {
+        let [r, g, b, a] = small.get_pixel(x, y).0;
+        (r as u32 * 299 + g as u32 * 587 + b as u32 * 114) * a as u32
+    };
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash = (hash << 1) | (luma(x, y) > luma(x + 1, y)) as u64;
+        }
+    }
+    hash
+}
+
+/// ICC profiles embed their description as plain text, which is enough to
+/// tell the common sRGB profiles apart from Display P3, Adobe RGB and the like.
+fn is_srgb_profile(profile: &[u8]) -> bool {
+    profile.windows(4).any(|window| window == b"sRGB")
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.to_string_lossy().eq_ignore_ascii_case(extension))
+}
+
+fn is_video(path: &Path) -> bool {
+    VIDEO_EXTENSIONS
+        .iter()
+        .any(|extension| has_extension(path, extension))
+}
+
+/// Same layer folders as `read_layers`.
+fn layer_names(folder: &Path) -> Result<Vec<String>, String> {
+    let entries = fs::read_dir(folder).map_err(|e| format!("Failed to read directory: {}", e))?;
+    let mut layers: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .filter(|name| !name.starts_with('.') && name != "frames" && name != "spritesheets")
+        .collect();
+    layers.sort();
+    Ok(layers)
+}
+
+/// Most frequent value, ties going to the smallest one so reports are stable.
+fn most_common<T: Ord + Copy + std::hash::Hash>(values: impl Iterator<Item = T>) -> Option<T> {
+    let mut counts: HashMap<T, usize> = HashMap::new();
+    for value in values {
+        *counts.entry(value).or_insert(0) += 1;
+    }
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+        .map(|(value, _)| value)
+}
+
+/// Checks every file of every layer for the asset problems that usually make
+/// a generation fail or produce broken images.
+pub fn lint_folder(folder: &Path) -> Result<LintReport, String> {
+    let layers = layer_names(folder)?;
+    let files: Vec<(String, TraitFile)> = layers
+        .iter()
+        .flat_map(|layer| {
+            scan_trait_files(&folder.join(layer))
+                .into_iter()
+                .map(move |file| (layer.clone(), file))
+        })
+        .collect();
+
+    let assets: Vec<AssetInfo> = files
+        .into_par_iter()
+        .map(|(layer, file)| AssetInfo::read(&layer, &folder.join(&layer), file))
+        .collect();
+
+    let mut report = LintReport {
+        files_scanned: assets.len(),
+        ..Default::default()
+    };
+    check_files(&mut report, &assets);
+    check_dimensions(&mut report, &layers, &assets);
+    check_frame_counts(&mut report, &assets);
+    check_duplicates(&mut report, &layers, &assets);
+
+    report
+        .diagnostics
+        .sort_by(|a, b| b.severity.cmp(&a.severity).then(a.layer.cmp(&b.layer)));
+    tracing::info!(
+        "[Lint] Scanned {} files: {} errors, {} warnings",
+        report.files_scanned,
+        report.errors,
+        report.warnings
+    );
+    Ok(report)
+}
+
+fn check_files(report: &mut LintReport, assets: &[AssetInfo]) {
+    for asset in assets {
+        let at = asset.location();
+
+        if let Some(error) = &asset.error {
+            report.push(
+                LintSeverity::Error,
+                "unreadable",
+                at,
+                error.clone(),
+                "Re-export the file or remove it from the layer.",
+            );
+            continue;
+        }
+        if asset.empty_alpha {
+            report.push(
+                LintSeverity::Warning,
+                "empty_alpha",
+                at,
+                "Every pixel is fully transparent".to_string(),
+                "Use the None trait for blank layers, or re-export with the artwork visible.",
+            );
+        }
+        if asset.sixteen_bit {
+            report.push(
+                LintSeverity::Warning,
+                "sixteen_bit",
+                at,
+                "16-bit PNG, it will be reduced to 8 bits per channel".to_string(),
+                "Export as 8-bit PNG to keep file sizes down and colours predictable.",
+            );
+        }
+        if asset.foreign_profile {
+            report.push(
+                LintSeverity::Warning,
+                "color_profile",
+                at,
+                "Embedded colour profile is not sRGB, colours will shift".to_string(),
+                "Convert the image to sRGB before exporting.",
+            );
+        }
+        if asset.size > OVERSIZED_FILE_BYTES {
+            report.push(
+                LintSeverity::Warning,
+                "oversized",
+                at,
+                format!("File is {:.1} MB", asset.size as f64 / (1024.0 * 1024.0)),
+                "Compress the file or reduce its dimensions.",
+            );
+        }
+    }
+}
+
+/// Every file of a layer should share the layer canvas, and every layer the
+/// collection canvas.
+fn check_dimensions(report: &mut LintReport, layers: &[String], assets: &[AssetInfo]) {
+    let mut layer_canvases: Vec<(&str, (u32, u32))> = Vec::new();
+
+    for layer in layers {
+        let layer_assets: Vec<&AssetInfo> = assets.iter().filter(|a| &a.layer == layer).collect();
+        let Some(canvas) = most_common(layer_assets.iter().filter_map(|a| a.dimensions)) else {
+            continue;
+        };
+        layer_canvases.push((layer.as_str(), canvas));
+
+        for asset in layer_assets {
+            let Some((width, height)) = asset.dimensions.filter(|d| *d != canvas) else {
+                continue;
+            };
+            report.push(
+                LintSeverity::Error,
+                "dimension_mismatch",
+                asset.location(),
+                format!(
+                    "Image is {}x{} while the rest of the layer is {}x{}",
+                    width, height, canvas.0, canvas.1
+                ),
+                "Export every trait of a layer on the same canvas size.",
+            );
+        }
+    }
+
+    let Some(canvas) = most_common(layer_canvases.iter().map(|(_, d)| *d)) else {
+        return;
+    };
+    for (layer, (width, height)) in layer_canvases.into_iter().filter(|(_, d)| *d != canvas) {
+        report.push(
+            LintSeverity::Error,
+            "layer_dimension_mismatch",
+            (layer, None),
+            format!(
+                "Layer canvas is {}x{} while most layers are {}x{}",
+                width, height, canvas.0, canvas.1
+            ),
+            "Export every layer on the collection canvas size.",
+        );
+    }
+}
+
+/// Animated traits are drawn frame by frame together. A frame count that
+/// differs from the others still plays when the timeline can line the loops
+/// up, and breaks the animation when it cannot.
+fn check_frame_counts(report: &mut LintReport, assets: &[AssetInfo]) {
+    let animated: Vec<&AssetInfo> = assets.iter().filter(|a| a.frame_count > 1).collect();
+    let Some(expected) = most_common(animated.iter().map(|a| a.frame_count)) else {
+        return;
+    };
+
+    for asset in animated.into_iter().filter(|a| a.frame_count != expected) {
+        let loops = [expected, asset.frame_count].map(loop_duration_ms);
+        match common_loop_duration(&loops) {
+            Some(common) => report.push(
+                LintSeverity::Warning,
+                "frame_count",
+                asset.location(),
+                format!(
+                    "Animation has {} frames while most have {}, they line up every {} frames",
+                    asset.frame_count,
+                    expected,
+                    common / DEFAULT_FRAME_DURATION_MS as u64
+                ),
+                "Re-export it with the same frame count as the others to keep the loop short.",
+            ),
+            None => report.push(
+                LintSeverity::Error,
+                "frame_count",
+                asset.location(),
+                format!(
+                    "Animation has {} frames while most have {}, the loops cannot be aligned",
+                    asset.frame_count, expected
+                ),
+                "Re-export the animation with the same frame count as the others.",
+            ),
+        }
+    }
+}
+
+fn loop_duration_ms(frame_count: u32) -> u64 {
+    frame_count as u64 * DEFAULT_FRAME_DURATION_MS as u64
+}
+
+/// Identical pixels under different names are reported across the whole
+/// folder, perceptually close images only within a layer.
+fn check_duplicates(report: &mut LintReport, layers: &[String], assets: &[AssetInfo]) {
+    let mut by_pixels: HashMap<&str, Vec<&AssetInfo>> = HashMap::new();
+    for asset in assets {
+        if let Some(hash) = &asset.pixel_hash {
+            by_pixels.entry(hash).or_default().push(asset);
+        }
+    }
+    let mut groups: Vec<Vec<&AssetInfo>> = by_pixels
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .collect();
+    groups.sort_by(|a, b| a[0].file.relative_path.cmp(&b[0].file.relative_path));
+
+    for group in groups {
+        let first = group[0];
+        for duplicate in &group[1..] {
+            report.push(
+                LintSeverity::Warning,
+                "duplicate",
+                duplicate.location(),
+                format!(
+                    "Pixel-identical to {}/{}",
+                    first.layer, first.file.relative_path
+                ),
+                "Remove one of the files, or use a variant if both are wanted.",
+            );
+        }
+    }
+
+    for layer in layers {
+        let hashed: Vec<(&AssetInfo, u64)> = assets
+            .iter()
+            .filter(|a| &a.layer == layer)
+            .filter_map(|a| a.perceptual_hash.map(|hash| (a, hash)))
+            .collect();
+
+        for (index, (asset, hash)) in hashed.iter().enumerate() {
+            let similar = hashed[..index].iter().find(|(other, other_hash)| {
+                other.pixel_hash != asset.pixel_hash
+                    && (hash ^ other_hash).count_ones() <= SIMILAR_HASH_DISTANCE
+            });
+            if let Some((other, _)) = similar {
+                report.push(
+                    LintSeverity::Info,
+                    "similar",
+                    asset.location(),
+                    format!("Looks nearly identical to {}", other.file.relative_path),
+                    "Check that both files are meant to be separate traits.",
+                );
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn lint_layers(folder_path: String) -> Result<LintReport, String> {
+    let folder = PathBuf::from(&folder_path);
+    if !folder.is_dir() {
+        return Err(format!("Folder does not exist: {}", folder_path));
+    }
+
+    tokio::task::spawn_blocking(move || lint_folder(&folder))
+        .await
+        .map_err(|e| format!("Lint task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+
+    fn asset(relative_path: &str, dimensions: (u32, u32)) -> AssetInfo {
+        let (layer, relative_path) = relative_path.split_once('/').unwrap();
+        AssetInfo {
+            layer: layer.to_string(),
+            file: TraitFile::parse(relative_path).unwrap(),
+            size: 1,
+            dimensions: Some(dimensions),
+            frame_count: 1,
+            sixteen_bit: false,
+            foreign_profile: false,
+            empty_alpha: false,
+            pixel_hash: Some(relative_path.to_string()),
+            perceptual_hash: None,
+            error: None,
+        }
+    }
+
+    fn codes(report: &LintReport) -> Vec<(&str, &str, Option<&str>)> {
+        report
+            .diagnostics
+            .iter()
+            .map(|d| (d.code.as_str(), d.layer.as_str(), d.file.as_deref()))
+            .collect()
+    }
+
+    fn gradient(width: u32, height: u32, alpha: u8) -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_fn(width, height, |x, _| {
+            let value = 255 - (x * 255 / (width - 1)) as u8;
+            Rgba([value, value, value, alpha])
+        }))
+    }
+
+    #[test]
+    fn difference_hash_follows_brightness_changes() {
+        // Brightness falls from left to right, so every comparison is set.
+        assert_eq!(difference_hash(&gradient(90, 80, 255)), u64::MAX);
+
+        let flat = DynamicImage::ImageRgba8(RgbaImage::from_pixel(90, 80, Rgba([9, 9, 9, 255])));
+        assert_eq!(difference_hash(&flat), 0);
+    }
+
+    #[test]
+    fn difference_hash_ignores_hidden_pixels() {
+        let hidden_gradient = gradient(90, 80, 0);
+        let hidden_flat =
+            DynamicImage::ImageRgba8(RgbaImage::from_pixel(90, 80, Rgba([200, 10, 10, 0])));
+
+        assert_eq!(difference_hash(&hidden_gradient), 0);
+        assert_eq!(
+            difference_hash(&hidden_gradient),
+            difference_hash(&hidden_flat)
+        );
+    }
+
+    #[test]
+    fn difference_hash_is_stable_across_sizes() {
+        let small = difference_hash(&gradient(90, 80, 255));
+        let large = difference_hash(&gradient(900, 800, 255));
+        assert!((small ^ large).count_ones() <= SIMILAR_HASH_DISTANCE);
+    }
+
+    #[test]
+    fn mismatched_files_and_layers_are_errors() {
+        let layers = vec!["Body".to_string(), "Eyes".to_string(), "Hats".to_string()];
+        let assets = vec![
+            asset("Body/A.png", (100, 100)),
+            asset("Body/B.png", (100, 100)),
+            asset("Body/C.png", (50, 100)),
+            asset("Eyes/A.png", (100, 100)),
+            asset("Hats/A.png", (200, 200)),
+        ];
+        let mut report = LintReport::default();
+
+        check_dimensions(&mut report, &layers, &assets);
+
+        assert_eq!(
+            codes(&report),
+            [
+                ("dimension_mismatch", "Body", Some("C.png")),
+                ("layer_dimension_mismatch", "Hats", None),
+            ]
+        );
+        assert_eq!(report.errors, 2);
+        assert!(report.has_errors());
+    }
+
+    #[test]
+    fn frame_counts_are_compared_between_animations_only() {
+        let mut assets = vec![
+            asset("Body/A.gif", (10, 10)),
+            asset("Body/B.gif", (10, 10)),
+            asset("Eyes/A.gif", (10, 10)),
+            asset("Hats/A.png", (10, 10)),
+        ];
+        assets[0].frame_count = 12;
+        assets[1].frame_count = 12;
+        assets[2].frame_count = 8;
+        let mut report = LintReport::default();
+
+        check_frame_counts(&mut report, &assets);
+
+        assert_eq!(codes(&report), [("frame_count", "Eyes", Some("A.gif"))]);
+    }
+
+    #[test]
+    fn frame_counts_are_errors_only_when_the_loops_cannot_align() {
+        let mut assets = vec![
+            asset("Body/A.gif", (10, 10)),
+            asset("Body/B.gif", (10, 10)),
+            asset("Eyes/A.gif", (10, 10)),
+            asset("Hats/A.gif", (10, 10)),
+        ];
+        assets[0].frame_count = 12;
+        assets[1].frame_count = 12;
+        assets[2].frame_count = 8;
+        assets[3].frame_count = 601;
+        let mut report = LintReport::default();
+
+        check_frame_counts(&mut report, &assets);
+
+        let severities: Vec<(&str, LintSeverity)> = report
+            .diagnostics
+            .iter()
+            .map(|d| (d.layer.as_str(), d.severity))
+            .collect();
+        assert_eq!(
+            severities,
+            [
+                ("Eyes", LintSeverity::Warning),
+                ("Hats", LintSeverity::Error)
+            ]
+        );
+        assert!(report.diagnostics[0].message.contains("every 24 frames"));
+    }
+
+    #[test]
+    fn unchanged_files_are_not_inspected_again() {
+        let folder =
+            std::env::temp_dir().join(format!("blendgine_lint_cache_{}", std::process::id()));
+        let layer = folder.join("Body");
+        fs::create_dir_all(&layer).unwrap();
+        let path = layer.join("A.png");
+        RgbaImage::from_pixel(4, 4, Rgba([1, 2, 3, 255]))
+            .save(&path)
+            .unwrap();
+
+        let first = AssetInfo::read("Body", &layer, TraitFile::parse("A.png").unwrap());
+        assert_eq!(first.dimensions, Some((4, 4)));
+
+        // A stale cache entry with the file's size and mtime is served as is.
+        ASSET_CACHE.get_mut(&path).unwrap().info.dimensions = Some((1, 1));
+        let cached = AssetInfo::read("Body", &layer, TraitFile::parse("A.png").unwrap());
+        assert_eq!(cached.dimensions, Some((1, 1)));
+
+        RgbaImage::from_pixel(8, 8, Rgba([1, 2, 3, 255]))
+            .save(&path)
+            .unwrap();
+        let changed = AssetInfo::read("Body", &layer, TraitFile::parse("A.png").unwrap());
+        assert_eq!(changed.dimensions, Some((8, 8)));
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn duplicates_span_layers_and_similar_images_stay_within_one() {
+        let layers = vec!["Body".to_string(), "Eyes".to_string()];
+        let mut assets = vec![
+            asset("Body/A.png", (10, 10)),
+            asset("Body/B.png", (10, 10)),
+            asset("Body/C.png", (10, 10)),
+            asset("Eyes/A.png", (10, 10)),
+        ];
+        assets[3].pixel_hash = assets[0].pixel_hash.clone();
+        assets[0].perceptual_hash = Some(0b1111);
+        assets[1].perceptual_hash = Some(0b0111);
+        assets[2].perceptual_hash = Some(0b1111_0000_0000);
+        assets[3].perceptual_hash = Some(0b1111);
+        let mut report = LintReport::default();
+
+        check_duplicates(&mut report, &layers, &assets);
+
+        assert_eq!(
+            codes(&report),
+            [
+                ("duplicate", "Eyes", Some("A.png")),
+                ("similar", "Body", Some("B.png")),
+            ]
+        );
+        assert_eq!(report.warnings, 1);
+        assert_eq!(report.errors, 0);
+    }
+
+    #[test]
+    fn unreadable_files_skip_the_other_file_checks() {
+        let mut broken = asset("Body/A.png", (10, 10));
+        broken.error = Some("Invalid PNG".to_string());
+        broken.empty_alpha = true;
+        let mut heavy = asset("Body/B.png", (10, 10));
+        heavy.size = OVERSIZED_FILE_BYTES + 1;
+        heavy.sixteen_bit = true;
+        let mut report = LintReport::default();
+
+        check_files(&mut report, &[broken, heavy]);
+
+        assert_eq!(
+            codes(&report),
+            [
+                ("unreadable", "Body", Some("A.png")),
+                ("sixteen_bit", "Body", Some("B.png")),
+                ("oversized", "Body", Some("B.png")),
+            ]
+        );
+    }
+
+    #[test]
+    fn srgb_profiles_are_recognised() {
+        assert!(is_srgb_profile(b"....desc....sRGB IEC61966-2.1...."));
+        assert!(!is_srgb_profile(b"....desc....Display P3...."));
+    }
+}
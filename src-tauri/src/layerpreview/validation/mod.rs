@@ -1,2 +1,5 @@
+pub mod lint;
+pub mod reconcile;
 pub mod reload;
 pub mod validate;
+pub mod watcher;
@@ -0,0 +1,414 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::Path,
+};
+use tracing;
+
+use crate::{
+    filesystem::{
+        constants::StorageFiles,
+        rename::{update_json, update_trait_references, RuleMap},
+        rename_transaction::{read_json, RenameTransaction},
+        trait_files::{scan_trait_files, TraitFile},
+    },
+    layerpreview::validation::watcher::LayersChanged,
+    types::{
+        BlendProperties, ForcedCombinationsBySets, IncompatibilitiesBySets, LayerConfig,
+        LayerSetConfig, RarityConfig, RarityConfigStorage, SetConfig, SetsStorage, TraitConfig,
+    },
+};
+
+const DEFAULT_SET_ID: &str = "set1";
+
+/// Brings the project configs in line with a change of the layer folder:
+/// renamed files keep their rarity and rules, new traits and layers get
+/// default weights and removed ones are pruned. All files change or none do.
+pub fn reconcile_layers(
+    storage_files: &StorageFiles,
+    folder: &Path,
+    changes: &LayersChanged,
+) -> Result<(), String> {
+    let mut transaction = RenameTransaction::new();
+
+    match reconcile_in_transaction(&mut transaction, storage_files, folder, changes) {
+        Ok(()) => {
+            transaction.commit();
+            Ok(())
+        }
+        Err(e) => {
+            transaction.rollback();
+            Err(e)
+        }
+    }
+}
+
+fn reconcile_in_transaction(
+    transaction: &mut RenameTransaction,
+    storage_files: &StorageFiles,
+    folder: &Path,
+    changes: &LayersChanged,
+) -> Result<(), String> {
+    for renamed in &changes.renamed {
+        let (Some((layer, old)), Some((_, new))) =
+            (split_layer(&renamed.from), split_layer(&renamed.to))
+        else {
+            continue;
+        };
+        update_trait_references(
+            transaction,
+            storage_files,
+            &folder.join(layer),
+            layer,
+            &old,
+            &new,
+        )?;
+    }
+
+    // Variants and parts share a trait name, so a trait only comes or goes
+    // with the first or last of its files.
+    let mut current_traits: HashMap<&str, HashSet<String>> = HashMap::new();
+    let mut still_present = |layer: &str, trait_name: &str| {
+        current_traits
+            .entry(layer)
+            .or_insert_with(|| {
+                scan_trait_files(&folder.join(layer))
+                    .into_iter()
+                    .map(|file| file.trait_name)
+                    .collect()
+            })
+            .contains(trait_name)
+    };
+
+    let mut added: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for path in &changes.added {
+        if let Some((layer, file)) = split_layer(path) {
+            let traits = added.entry(layer.to_string()).or_default();
+            if file.part.is_none() && !traits.contains(&file.trait_name) {
+                traits.push(file.trait_name);
+            }
+        }
+    }
+
+    let mut removed: Vec<(String, String)> = Vec::new();
+    for path in &changes.removed {
+        let Some((layer, file)) = split_layer(path) else {
+            continue;
+        };
+        if changes
+            .removed_layers
+            .iter()
+            .any(|removed| removed == layer)
+            || still_present(layer, &file.trait_name)
+        {
+            continue;
+        }
+        if !removed.contains(&(layer.to_string(), file.trait_name.clone())) {
+            removed.push((layer.to_string(), file.trait_name));
+        }
+    }
+
+    let mut sets_storage: SetsStorage =
+        read_json(&storage_files.ordered_layers)?.unwrap_or_default();
+    if !changes.added_layers.is_empty() || !changes.removed_layers.is_empty() {
+        let updated = update_ordered_layers(&mut sets_storage, changes);
+        if updated {
+            transaction.write_json(&storage_files.ordered_layers, &sets_storage)?;
+        }
+    }
+
+    update_json(
+        transaction,
+        &storage_files.rarity_config,
+        |config: &mut RarityConfigStorage| {
+            let config = &mut config.rarity_config_storage;
+            let mut updated = false;
+
+            for layer in &changes.removed_layers {
+                updated |= config.layers.remove(layer).is_some();
+            }
+            for (layer, traits) in &added {
+                updated |= add_traits(config, &sets_storage, layer, traits);
+            }
+            for (layer, trait_name) in &removed {
+                updated |= remove_trait(config, layer, trait_name);
+            }
+            updated
+        },
+    )?;
+
+    if changes.removed_layers.is_empty() && removed.is_empty() {
+        return Ok(());
+    }
+    update_json(
+        transaction,
+        &storage_files.incompatibility,
+        |rules: &mut IncompatibilitiesBySets| {
+            rules.sets.values_mut().fold(false, |updated, set| {
+                prune_rules(
+                    &mut set.incompatibilities,
+                    &changes.removed_layers,
+                    &removed,
+                ) || updated
+            })
+        },
+    )?;
+    update_json(
+        transaction,
+        &storage_files.forced_combination,
+        |rules: &mut ForcedCombinationsBySets| {
+            rules.sets.values_mut().fold(false, |updated, set| {
+                prune_rules(
+                    &mut set.forced_combinations,
+                    &changes.removed_layers,
+                    &removed,
+                ) || updated
+            })
+        },
+    )
+}
+
+/// Splits a folder-relative path into its layer and trait file.
+fn split_layer(path: &str) -> Option<(&str, TraitFile)> {
+    let (layer, relative) = path.split_once('/')?;
+    Some((layer, TraitFile::parse(relative)?))
+}
+
+fn update_ordered_layers(storage: &mut SetsStorage, changes: &LayersChanged) -> bool {
+    let mut updated = false;
+
+    for set_info in storage.sets.values_mut() {
+        let before = set_info.layers.clone();
+        set_info
+            .layers
+            .retain(|layer| !changes.removed_layers.contains(layer));
+
+        for layer in &changes.added_layers {
+            if !set_info.layers.contains(layer) {
+                set_info.layers.push(layer.clone());
+            }
+        }
+        updated |= set_info.layers != before;
+    }
+
+    updated
+}
+
+/// Adds the new traits of a layer to every set of the layer. A new trait gets
+/// the average weight of the enabled traits, then the set is scaled back to
+/// its previous total so the other traits keep their relative odds.
+fn add_traits(
+    config: &mut RarityConfig,
+    sets_storage: &SetsStorage,
+    layer: &str,
+    traits: &[String],
+) -> bool {
+    if traits.is_empty() {
+        return false;
+    }
+    let layer_config = config
+        .layers
+        .entry(layer.to_string())
+        .or_insert_with(|| new_layer_config(sets_storage));
+    let blend = layer_config.default_blend.clone();
+    let new_traits: Vec<&String> = traits
+        .iter()
+        .filter(|name| !layer_config.traits.contains_key(*name))
+        .collect();
+    if new_traits.is_empty() {
+        return false;
+    }
+
+    let totals = set_totals(layer_config);
+    for (set_id, total) in &totals {
+        let enabled: Vec<&SetConfig> = layer_config
+            .traits
+            .values()
+            .filter_map(|trait_config| trait_config.sets.get(set_id))
+            .filter(|set| set.enabled)
+            .collect();
+        let value = if enabled.is_empty() {
+            100.0 / new_traits.len() as f32
+        } else {
+            total / enabled.len() as f32
+        };
+        let z_index = enabled
+            .first()
+            .map(|set| set.z_index)
+            .unwrap_or_else(|| default_z_index(sets_storage, set_id, layer));
+
+        for name in &new_traits {
+            layer_config
+                .traits
+                .entry(name.to_string())
+                .or_insert_with(|| TraitConfig {
+                    sets: HashMap::new(),
+                    variants: HashMap::new(),
+                    display_name: None,
+                    localized_names: HashMap::new(),
+                })
+                .sets
+                .insert(
+                    set_id.clone(),
+                    SetConfig {
+                        blend: blend.clone(),
+                        z_index,
+                        enabled: true,
+                        value,
+                        include_in_metadata: None,
+                        offset_x: None,
+                        offset_y: None,
+                    },
+                );
+        }
+    }
+    rebalance(layer_config, &totals);
+
+    tracing::info!(
+        "[Watcher] Added {} traits to layer '{}'",
+        new_traits.len(),
+        layer
+    );
+    true
+}
+
+fn remove_trait(config: &mut RarityConfig, layer: &str, trait_name: &str) -> bool {
+    let Some(layer_config) = config.layers.get_mut(layer) else {
+        return false;
+    };
+    let totals = set_totals(layer_config);
+    if layer_config.traits.remove(trait_name).is_none() {
+        return false;
+    }
+    rebalance(layer_config, &totals);
+
+    tracing::info!(
+        "[Watcher] Removed trait '{}' from layer '{}'",
+        trait_name,
+        layer
+    );
+    true
+}
+
+fn new_layer_config(sets_storage: &SetsStorage) -> LayerConfig {
+    let mut set_ids: Vec<String> = sets_storage.sets.keys().cloned().collect();
+    if set_ids.is_empty() {
+        set_ids.push(DEFAULT_SET_ID.to_string());
+    }
+
+    LayerConfig {
+        sets: set_ids
+            .into_iter()
+            .map(|set_id| {
+                (
+                    set_id,
+                    LayerSetConfig {
+                        active: true,
+                        include_in_metadata: None,
+                    },
+                )
+            })
+            .collect(),
+        locked: None,
+        traits: HashMap::new(),
+        default_blend: BlendProperties::default(),
+        playback: None,
+        groups: HashMap::new(),
+        parts: HashMap::new(),
+        trait_type: None,
+        localized_trait_types: HashMap::new(),
+    }
+}
+
+/// Same default as a freshly imported layer: a hundred per layer position.
+fn default_z_index(sets_storage: &SetsStorage, set_id: &str, layer: &str) -> i32 {
+    let layers = sets_storage
+        .sets
+        .get(set_id)
+        .map(|set_info| set_info.layers.as_slice())
+        .unwrap_or_default();
+    let index = layers
+        .iter()
+        .position(|name| name == layer)
+        .unwrap_or(layers.len());
+    index as i32 * 100
+}
+
+/// Sum of the enabled trait weights of every set of the layer, 100 when the
+/// set has no weight yet.
+fn set_totals(layer_config: &LayerConfig) -> HashMap<String, f32> {
+    layer_config
+        .sets
+        .keys()
+        .map(|set_id| {
+            let total: f32 = layer_config
+                .traits
+                .values()
+                .filter_map(|trait_config| trait_config.sets.get(set_id))
+                .filter(|set| set.enabled)
+                .map(|set| set.value)
+                .sum();
+            let total = if total > 0.0 { total } else { 100.0 };
+            (set_id.clone(), total)
+        })
+        .collect()
+}
+
+fn rebalance(layer_config: &mut LayerConfig, totals: &HashMap<String, f32>) {
+    for (set_id, target) in totals {
+        let current: f32 = layer_config
+            .traits
+            .values()
+            .filter_map(|trait_config| trait_config.sets.get(set_id))
+            .filter(|set| set.enabled)
+            .map(|set| set.value)
+            .sum();
+        if current <= 0.0 {
+            continue;
+        }
+
+        let scale = target / current;
+        for set in layer_config
+            .traits
+            .values_mut()
+            .filter_map(|trait_config| trait_config.sets.get_mut(set_id))
+            .filter(|set| set.enabled)
+        {
+            set.value *= scale;
+        }
+    }
+}
+
+fn prune_rules(
+    rules: &mut RuleMap,
+    removed_layers: &[String],
+    removed_traits: &[(String, String)],
+) -> bool {
+    let mut updated = false;
+
+    for layer in removed_layers {
+        updated |= rules.remove(layer).is_some();
+    }
+    for (layer, trait_name) in removed_traits {
+        if let Some(trait_rules) = rules.get_mut(layer) {
+            updated |= trait_rules.remove(trait_name).is_some();
+        }
+    }
+
+    for targets in rules
+        .values_mut()
+        .flat_map(|trait_rules| trait_rules.values_mut())
+    {
+        for layer in removed_layers {
+            updated |= targets.remove(layer).is_some();
+        }
+        for (layer, trait_name) in removed_traits {
+            if let Some(traits) = targets.get_mut(layer) {
+                let before = traits.len();
+                traits.retain(|name| name != trait_name);
+                updated |= traits.len() != before;
+            }
+        }
+    }
+
+    updated
+}
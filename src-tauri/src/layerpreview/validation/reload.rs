@@ -1,6 +1,12 @@
-use crate::filesystem::persist::{load_projectsetup_state, save_projectsetup_state};
-use crate::layerpreview::animations::commands::extract_frames;
-use crate::layerpreview::select::select_import::utils::structs::LayerContent;
+use crate::filesystem::{
+    manifest::{FolderManifest, ManifestDiff},
+    persist::{load_projectsetup_state, save_projectsetup_state},
+    trait_files::scan_trait_files,
+};
+use crate::layerpreview::select::select_import::utils::{
+    extract_frames::{extract_animation_frames, refresh_animation_frames},
+    structs::LayerContent,
+};
 use crate::layerpreview::{
     animations::{commands::get_spritesheet_metadata, reset_animation_state, GLOBAL_MAX_FRAMES},
     select::select_import::{
@@ -10,22 +16,33 @@ use crate::layerpreview::{
 };
 use crate::types::SpritesheetLayout;
 use anyhow::Result;
-use futures::future::join_all;
-use std::fs::read_dir;
 use std::{fs, path::PathBuf, sync::atomic::Ordering};
 use tauri::Manager;
 use tracing;
 
-#[tauri::command]
+/// How much of the spritesheet cache a reload has to rebuild.
+#[derive(Debug)]
+pub enum SpritesheetRebuild {
+    Skip,
+    Full,
+    /// Only the traits touched by the diff are extracted again.
+    Partial(ManifestDiff),
+}
+
 pub async fn reload_folder_data(
     app_handle: tauri::AppHandle,
     folder_path: String,
-    should_recreate_spritesheets: bool,
+    rebuild: &SpritesheetRebuild,
+    manifest: &mut FolderManifest,
 ) -> Result<InitialFolderData, String> {
     tracing::info!(
-        "Reloading folder data: {} (recreate spritesheets: {})",
+        "Reloading folder data: {} (spritesheet rebuild: {})",
         folder_path,
-        should_recreate_spritesheets
+        match rebuild {
+            SpritesheetRebuild::Skip => "skip",
+            SpritesheetRebuild::Full => "full",
+            SpritesheetRebuild::Partial(_) => "partial",
+        }
     );
 
     if let Err(e) = fs::metadata(&folder_path) {
@@ -86,10 +103,7 @@ pub async fn reload_folder_data(
             })?
             .to_string();
 
-        if should_recreate_spritesheets {
-            tracing::info!("Recreating spritesheets for animated collection");
-            reset_animation_state().await;
-
+        if !matches!(rebuild, SpritesheetRebuild::Skip) {
             let total_animated_files = count_total_animated_files(&folder_path, &layers).await?;
 
             tracing::info!(
@@ -98,41 +112,36 @@ pub async fn reload_folder_data(
                 animated_layers_count
             );
 
-            let mut futures = Vec::new();
-
-            for layer in &layers {
-                let layer_path = PathBuf::from(&folder_path).join(&layer.name);
-                if check_animated_images(layer_path.to_string_lossy().to_string()).await? {
-                    let app_handle_clone = app_handle.clone();
-                    let path_string = layer_path.to_string_lossy().to_string();
-                    let layer_name = layer.name.clone();
-
-                    let path_buf = PathBuf::from(&path_string);
-                    let image_name = path_buf
-                        .file_stem()
-                        .and_then(|name| name.to_str())
-                        .ok_or_else(|| {
-                            let msg = "Failed to get image name".to_string();
-                            tracing::error!("{}", msg);
-                            msg
-                        })?;
-
-                    futures.push(extract_frames(
-                        app_handle_clone,
-                        project_id.to_string(),
-                        path_string,
-                        layer_name.to_string(),
-                        image_name.to_string(),
-                        0,
-                        true,
-                        total_animated_files,
-                    ));
-                }
-            }
-
-            let results = join_all(futures).await;
-            for result in results {
-                result?;
+            if let SpritesheetRebuild::Partial(diff) = rebuild {
+                tracing::info!(
+                    "Updating spritesheets: {} added, {} changed, {} removed files",
+                    diff.added.len(),
+                    diff.changed.len(),
+                    diff.removed.len()
+                );
+                refresh_animation_frames(
+                    &folder_path,
+                    &layers,
+                    &app_handle,
+                    &project_id,
+                    total_animated_files,
+                    diff,
+                    manifest,
+                )
+                .await?;
+            } else {
+                tracing::info!("Recreating spritesheets for animated collection");
+                reset_animation_state().await;
+
+                extract_animation_frames(
+                    &folder_path,
+                    &layers,
+                    &app_handle,
+                    &project_id,
+                    total_animated_files,
+                    manifest,
+                )
+                .await?;
             }
 
             let raw_count = GLOBAL_MAX_FRAMES.load(Ordering::SeqCst);
@@ -154,6 +163,10 @@ pub async fn reload_folder_data(
                     total_sheets: metadata.total_sheets,
                     frames_per_sheet: metadata.frames_per_sheet,
                     total_frames: metadata.total_frames,
+                    cell_x: metadata.cell_x,
+                    cell_y: metadata.cell_y,
+                    cell_width: metadata.cell_width,
+                    cell_height: metadata.cell_height,
                 };
                 spritesheet_layout = Some(layout);
 
@@ -170,7 +183,9 @@ pub async fn reload_folder_data(
 
                 tracing::info!("Project setup state saved successfully");
             } else {
-                tracing::warn!("Failed to retrieve spritesheet metadata");
+                tracing::warn!(
+                    "Failed to retrieve spritesheet metadata, keeping the previous layout"
+                );
             }
         } else {
             max_frames = persisted_state.max_frames;
@@ -205,24 +220,7 @@ async fn count_total_animated_files(
     for layer in layers {
         let layer_path = PathBuf::from(folder_path).join(&layer.name);
         if layer_path.exists() {
-            if let Ok(entries) = read_dir(&layer_path) {
-                for entry in entries.flatten() {
-                    if let Some(ext) = entry.path().extension() {
-                        let ext_lower = ext.to_string_lossy().to_lowercase();
-                        if ext_lower == "gif"
-                            || ext_lower == "webp"
-                            || ext_lower == "png"
-                            || ext_lower == "mp4"
-                            || ext_lower == "webm"
-                            || ext_lower == "mov"
-                            || ext_lower == "avi"
-                            || ext_lower == "mkv"
-                        {
-                            total_files += 1;
-                        }
-                    }
-                }
-            }
+            total_files += scan_trait_files(&layer_path).len() as u32;
         }
     }
 
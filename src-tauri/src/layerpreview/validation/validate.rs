@@ -1,12 +1,15 @@
 use crate::layerpreview::{
-    animations::commands::get_spritesheets_path, select::select_import::select::InitialFolderData,
+    animations::get_animated_frames_dir, select::select_import::select::InitialFolderData,
 };
 use crate::{
     filesystem::{
-        folderhash::{calculate_folder_hash, get_previous_hash, save_folder_hash},
+        manifest::FolderManifest,
         persist::{load_projectsetup_state, save_projectsetup_state},
     },
-    layerpreview::validation::reload::reload_folder_data,
+    layerpreview::validation::{
+        reload::{reload_folder_data, SpritesheetRebuild},
+        watcher::start_layer_watcher,
+    },
 };
 use anyhow::Result;
 use std::path::PathBuf;
@@ -39,82 +42,83 @@ pub async fn validate_and_reload_layers(
         })?
         .unwrap_or_default();
 
-    let current_hash = calculate_folder_hash(folder_path.clone())
-        .await
-        .map_err(|e| e.to_string())?;
+    let mut tracked = if persisted_state.is_animated_collection {
+        let project_id = folder_path_buf
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| {
+                let msg = "Failed to get folder name".to_string();
+                tracing::error!("{}", msg);
+                msg
+            })?
+            .to_string();
 
-    tracing::debug!("Calculated current folder hash: {}", current_hash);
+        let animated_dir = get_animated_frames_dir(&app_handle, &project_id)?;
+        let previous = FolderManifest::load(&animated_dir);
 
-    let previous_hash = get_previous_hash(folder_path.clone(), app_handle.state()).await;
+        let root = folder_path_buf.clone();
+        let scan_previous = previous.clone();
+        let manifest = tokio::task::spawn_blocking(move || {
+            FolderManifest::scan(&root, scan_previous.as_ref())
+        })
+        .await
+        .map_err(|e| {
+            let msg = format!("Failed to scan layer folder: {}", e);
+            tracing::error!("{}", msg);
+            msg
+        })?;
+
+        Some((animated_dir, previous, manifest))
+    } else {
+        None
+    };
 
-    let should_recreate_spritesheets = match previous_hash {
-        Ok(Some(hash)) => {
-            if hash != current_hash {
+    let rebuild = match &tracked {
+        None => {
+            tracing::debug!("Not an animated collection, no spritesheets needed");
+            SpritesheetRebuild::Skip
+        }
+        Some((_, None, _)) => {
+            tracing::info!("No previous manifest found, recreating spritesheets");
+            SpritesheetRebuild::Full
+        }
+        Some((animated_dir, _, _)) if !animated_dir.join("spritesheets").exists() => {
+            tracing::info!("Spritesheets directory doesn't exist, recreating spritesheets");
+            SpritesheetRebuild::Full
+        }
+        Some((_, Some(previous), manifest)) => {
+            let diff = manifest.diff(previous);
+            if diff.is_empty() {
+                tracing::debug!("Layer files unchanged, no recreation needed");
+                SpritesheetRebuild::Skip
+            } else {
                 tracing::info!(
-                    "Folder hash changed ({} -> {}), recreating spritesheets",
-                    hash,
-                    current_hash
+                    "Layer files changed: {} added, {} changed, {} removed",
+                    diff.added.len(),
+                    diff.changed.len(),
+                    diff.removed.len()
                 );
-                true
-            } else if persisted_state.is_animated_collection {
-                let project_id = folder_path_buf
-                    .file_name()
-                    .and_then(|name| name.to_str())
-                    .ok_or_else(|| {
-                        let msg = "Failed to get folder name".to_string();
-                        tracing::error!("{}", msg);
-                        msg
-                    })?
-                    .to_string();
-
-                let spritesheets_dir = get_spritesheets_path(app_handle.clone(), project_id)
-                    .await
-                    .map_err(|e| {
-                        let msg = format!("Failed to get spritesheets directory: {}", e);
-                        tracing::error!("{}", msg);
-                        msg
-                    })?;
-
-                if !PathBuf::from(&spritesheets_dir).exists() {
-                    tracing::info!("Spritesheets directory doesn't exist, recreating spritesheets");
-                    true
-                } else {
-                    tracing::debug!("Spritesheets directory exists, no recreation needed");
-                    false
-                }
-            } else {
-                tracing::debug!("Not an animated collection, no spritesheets needed");
-                false
+                SpritesheetRebuild::Partial(diff)
             }
         }
-        _ => {
-            tracing::info!("No previous hash found, recreating spritesheets");
-            true
-        }
     };
 
-    tracing::info!(
-        "Reloading folder data (recreate spritesheets: {})",
-        should_recreate_spritesheets
-    );
+    let mut untracked = FolderManifest::default();
+    let manifest = match tracked.as_mut() {
+        Some((_, _, manifest)) => manifest,
+        None => &mut untracked,
+    };
 
-    let result = reload_folder_data(
-        app_handle.clone(),
-        folder_path.clone(),
-        should_recreate_spritesheets,
-    )
-    .await?;
-
-    if should_recreate_spritesheets {
-        tracing::info!("Saving new folder hash: {}", current_hash);
-        save_folder_hash(folder_path.clone(), current_hash, app_handle.state())
-            .await
-            .map_err(|e| {
-                let msg = format!("Failed to save folder hash: {}", e);
-                tracing::error!("{}", msg);
-                msg
-            })?;
-        tracing::info!("Folder hash saved successfully");
+    let result =
+        reload_folder_data(app_handle.clone(), folder_path.clone(), &rebuild, manifest).await?;
+
+    if let Some((animated_dir, _, manifest)) = &tracked {
+        manifest.save(animated_dir).map_err(|e| {
+            let msg = format!("Failed to save layer manifest: {}", e);
+            tracing::error!("{}", msg);
+            msg
+        })?;
+        tracing::info!("Layer manifest saved ({} files)", manifest.files.len());
     }
 
     let mut updated_state = persisted_state.clone();
@@ -135,6 +139,7 @@ pub async fn validate_and_reload_layers(
         })?;
 
     tracing::info!("Project state updated successfully");
+    start_layer_watcher(app_handle.clone(), folder_path_buf);
 
     tracing::info!("Validation and reload completed successfully");
     Ok(result)
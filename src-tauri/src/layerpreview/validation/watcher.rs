@@ -0,0 +1,297 @@
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::{
+    collections::BTreeSet,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{sleep, spawn},
+    time::Duration,
+};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing;
+
+use crate::{
+    filesystem::{
+        constants::StorageFiles, manifest::FolderManifest, projects::ProjectRegistry,
+        trait_files::TraitFile,
+    },
+    layerpreview::validation::reconcile::reconcile_layers,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(750);
+
+/// Folder being watched, the config file of the project it belongs to and
+/// the flag that stops its thread.
+static LAYER_WATCHER: Lazy<Mutex<Option<(PathBuf, PathBuf, Arc<AtomicBool>)>>> =
+    Lazy::new(|| Mutex::new(None));
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RenamedFile {
+    pub from: String,
+    pub to: String,
+}
+
+/// Payload of the `layers-changed` event. Paths are relative to the layer
+/// folder, e.g. `Hats/Rare/Crown.png`.
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LayersChanged {
+    pub added_layers: Vec<String>,
+    pub removed_layers: Vec<String>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// Files that disappeared and reappeared elsewhere in the same layer with
+    /// the same content.
+    pub renamed: Vec<RenamedFile>,
+}
+
+impl LayersChanged {
+    pub fn is_empty(&self) -> bool {
+        self.added_layers.is_empty()
+            && self.removed_layers.is_empty()
+            && self.added.is_empty()
+            && self.removed.is_empty()
+            && self.renamed.is_empty()
+    }
+
+    fn between(previous: &FolderManifest, current: &FolderManifest) -> Self {
+        let diff = current.diff(previous);
+        let mut added: Vec<String> = diff.added.into_iter().filter(|p| is_trait(p)).collect();
+        let mut removed: Vec<String> = diff.removed.into_iter().filter(|p| is_trait(p)).collect();
+
+        let mut renamed = Vec::new();
+        removed.retain(|from| {
+            let hash = &previous.files[from].hash;
+            let same_file = added
+                .iter()
+                .position(|to| layer_of(to) == layer_of(from) && &current.files[to].hash == hash);
+            match same_file {
+                Some(index) => {
+                    renamed.push(RenamedFile {
+                        from: from.clone(),
+                        to: added.remove(index),
+                    });
+                    false
+                }
+                None => true,
+            }
+        });
+
+        let previous_layers = layer_names(previous);
+        let current_layers = layer_names(current);
+
+        Self {
+            added_layers: current_layers
+                .difference(&previous_layers)
+                .cloned()
+                .collect(),
+            removed_layers: previous_layers
+                .difference(&current_layers)
+                .cloned()
+                .collect(),
+            added,
+            removed,
+            renamed,
+        }
+    }
+}
+
+fn layer_of(path: &str) -> &str {
+    path.split_once('/').map_or(path, |(layer, _)| layer)
+}
+
+/// Same layer folders as `read_layers`.
+fn is_trait(path: &str) -> bool {
+    path.split_once('/').is_some_and(|(layer, relative)| {
+        !layer.starts_with('.')
+            && layer != "frames"
+            && layer != "spritesheets"
+            && TraitFile::parse(relative).is_some()
+    })
+}
+
+fn layer_names(manifest: &FolderManifest) -> BTreeSet<String> {
+    manifest
+        .files
+        .keys()
+        .filter(|path| is_trait(path))
+        .map(|path| layer_of(path).to_string())
+        .collect()
+}
+
+/// Watches the layer folder and keeps the project configs in sync with it.
+/// A change is only handled once two scans in a row agree, so files that are
+/// still being copied are left alone. The configs updated are those of the
+/// project active when the watcher starts.
+pub fn start_layer_watcher(app_handle: AppHandle, folder: PathBuf) {
+    let storage_files = app_handle.state::<ProjectRegistry>().storage_files();
+    let project_config = storage_files.project_setup.clone();
+
+    let mut watcher = LAYER_WATCHER.lock();
+    if let Some((watched, watched_config, stop)) = watcher.as_ref() {
+        if *watched == folder && *watched_config == project_config && !stop.load(Ordering::Relaxed)
+        {
+            return;
+        }
+        stop.store(true, Ordering::Relaxed);
+    }
+
+    tracing::info!("[Watcher] Watching layer folder {}", folder.display());
+    let stop = Arc::new(AtomicBool::new(false));
+    *watcher = Some((folder.clone(), project_config, stop.clone()));
+
+    spawn(move || watch(app_handle, folder, storage_files, stop));
+}
+
+fn watch(
+    app_handle: AppHandle,
+    folder: PathBuf,
+    storage_files: StorageFiles,
+    stop: Arc<AtomicBool>,
+) {
+    let mut current = FolderManifest::scan(&folder, None);
+    let mut pending: Option<FolderManifest> = None;
+
+    while !stop.load(Ordering::Relaxed) {
+        sleep(POLL_INTERVAL);
+        if !folder.exists() {
+            continue;
+        }
+
+        let scanned = FolderManifest::scan(&folder, Some(pending.as_ref().unwrap_or(&current)));
+        if scanned.files == current.files {
+            pending = None;
+            continue;
+        }
+        if pending.as_ref().map(|p| &p.files) != Some(&scanned.files) {
+            pending = Some(scanned);
+            continue;
+        }
+        pending = None;
+
+        let changes = LayersChanged::between(&current, &scanned);
+        current = scanned;
+        if changes.is_empty() || stop.load(Ordering::Relaxed) {
+            continue;
+        }
+
+        tracing::info!(
+            "[Watcher] Layer folder changed: {} added, {} removed, {} renamed",
+            changes.added.len(),
+            changes.removed.len(),
+            changes.renamed.len()
+        );
+
+        if let Err(e) = reconcile_layers(&storage_files, &folder, &changes) {
+            tracing::error!("[Watcher] Failed to reconcile project config: {}", e);
+        }
+        let _ = app_handle.emit("layers-changed", &changes);
+    }
+
+    tracing::info!("[Watcher] Stopped watching {}", folder.display());
+}
+
+pub fn stop_layer_watcher() {
+    if let Some((_, _, stop)) = LAYER_WATCHER.lock().take() {
+        stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[tauri::command]
+pub async fn watch_layer_folder(app_handle: AppHandle, folder_path: String) -> Result<(), String> {
+    let folder = PathBuf::from(&folder_path);
+    if !folder.is_dir() {
+        return Err(format!("Folder does not exist: {}", folder_path));
+    }
+    start_layer_watcher(app_handle, folder);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn unwatch_layer_folder() -> Result<(), String> {
+    stop_layer_watcher();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filesystem::manifest::ManifestEntry;
+
+    fn manifest(files: &[(&str, &str)]) -> FolderManifest {
+        FolderManifest {
+            files: files
+                .iter()
+                .map(|(path, hash)| {
+                    let entry = ManifestEntry {
+                        size: 1,
+                        modified_ms: 0,
+                        hash: hash.to_string(),
+                        frame_durations_ms: None,
+                    };
+                    (path.to_string(), entry)
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn moved_files_with_the_same_content_are_renames() {
+        let previous = manifest(&[("Hats/Crown.png", "a"), ("Hats/Cap.png", "b")]);
+        let current = manifest(&[("Hats/Rare/Crown.png", "a"), ("Hats/Beanie.png", "c")]);
+
+        let changes = LayersChanged::between(&previous, &current);
+
+        assert_eq!(changes.renamed.len(), 1);
+        assert_eq!(changes.renamed[0].from, "Hats/Crown.png");
+        assert_eq!(changes.renamed[0].to, "Hats/Rare/Crown.png");
+        assert_eq!(changes.added, ["Hats/Beanie.png"]);
+        assert_eq!(changes.removed, ["Hats/Cap.png"]);
+        assert!(changes.added_layers.is_empty() && changes.removed_layers.is_empty());
+    }
+
+    #[test]
+    fn moves_between_layers_are_not_renames() {
+        let previous = manifest(&[("Hats/Crown.png", "a")]);
+        let current = manifest(&[("Eyes/Crown.png", "a")]);
+
+        let changes = LayersChanged::between(&previous, &current);
+
+        assert!(changes.renamed.is_empty());
+        assert_eq!(changes.added, ["Eyes/Crown.png"]);
+        assert_eq!(changes.removed, ["Hats/Crown.png"]);
+        assert_eq!(changes.added_layers, ["Eyes"]);
+        assert_eq!(changes.removed_layers, ["Hats"]);
+    }
+
+    #[test]
+    fn duplicate_content_is_only_renamed_once() {
+        let previous = manifest(&[("Hats/A.png", "same"), ("Hats/B.png", "same")]);
+        let current = manifest(&[("Hats/C.png", "same"), ("Hats/B.png", "same")]);
+
+        let changes = LayersChanged::between(&previous, &current);
+
+        assert_eq!(changes.renamed.len(), 1);
+        assert_eq!(changes.renamed[0].from, "Hats/A.png");
+        assert_eq!(changes.renamed[0].to, "Hats/C.png");
+        assert!(changes.added.is_empty() && changes.removed.is_empty());
+    }
+
+    #[test]
+    fn caches_and_other_files_are_ignored() {
+        let previous = manifest(&[("Hats/Crown.png", "a")]);
+        let current = manifest(&[
+            ("Hats/Crown.png", "a"),
+            ("Hats/notes.txt", "n"),
+            ("frames/Hats/Crown/0.png", "f"),
+            (".cache/Hats.png", "c"),
+        ]);
+
+        assert!(LayersChanged::between(&previous, &current).is_empty());
+    }
+}
@@ -1,4 +1,7 @@
 use super::utils::*;
+use crate::generation::generate::generate_single::progress::{
+    send_phase_progress, GenerationPhase,
+};
 use crate::types::*;
 use std::{fs, path::Path};
 use tauri::AppHandle;
@@ -85,7 +88,7 @@ pub async fn read_folder(folder_path: String) -> Result<FolderContent, String> {
     }
 
     // Vérifier les extensions des fichiers images
-    let valid_image_extensions = [".png", ".jpg", ".jpeg", ".webp", ".gif", ".mp4", ".webm"];
+    let valid_image_extensions = [".png", ".jpg", ".jpeg", ".webp", ".gif", ".apng", ".mp4", ".webm"];
     let all_images_valid = image_files.iter().all(|file| {
         let extension = Path::new(file)
             .extension()
@@ -198,7 +201,7 @@ pub async fn validate_legendary_nfts_folder(
 
             match extension.as_deref() {
                 Some("png") | Some("jpg") | Some("jpeg") | Some("webp") | Some("gif")
-                | Some("mp4") | Some("webm") => {
+                | Some("apng") | Some("mp4") | Some("webm") => {
                     images_folder_path = Some(subfolder_path.clone());
                     image_count = files.len();
                 }
@@ -242,7 +245,7 @@ pub async fn validate_legendary_nfts_folder(
     let mut image_files = fs::read_dir(images_folder)
         .map_err(|e| e.to_string())?
         .filter_map(Result::ok);
-    let valid_image_extensions = [".png", ".jpg", ".jpeg", ".webp", ".gif", ".mp4", ".webm"];
+    let valid_image_extensions = [".png", ".jpg", ".jpeg", ".webp", ".gif", ".apng", ".mp4", ".webm"];
     let all_images_valid = image_files.all(|file| {
         let extension = file
             .path()
@@ -288,6 +291,7 @@ pub async fn validate_legendary_nfts_folder(
 
 #[tauri::command]
 pub async fn mix_legendary_nfts(
+    app: AppHandle,
     legendary_folder: String,
     export_folder: String,
 ) -> Result<MixingResult, String> {
@@ -345,6 +349,8 @@ pub async fn mix_legendary_nfts(
 
         let mut legendary_indices = Vec::new();
         let mut updated_global_metadata = global_metadata;
+        let legendary_total = legendary_image_files.len().min(legendary_metadata_files.len()) as u32;
+        send_phase_progress(&app, GenerationPhase::LegendaryMixing, 0, legendary_total);
 
         for (i, (legendary_image_file, legendary_metadata_file)) in 
             legendary_image_files.iter().zip(legendary_metadata_files.iter()).enumerate() {
@@ -398,6 +404,8 @@ pub async fn mix_legendary_nfts(
                 updated_global_metadata.items[random_index] = legendary_metadata.clone();
                 legendary_indices.push(random_index);
             }
+
+            send_phase_progress(&app, GenerationPhase::LegendaryMixing, i as u32 + 1, legendary_total);
         }
 
         fs::write(
@@ -27,7 +27,7 @@ pub async fn find_image_and_metadata_folders(base_folder: &Path) -> Result<(Path
 
                 match extension {
                     Some(ext)
-                        if ["png", "jpg", "jpeg", "webp", "gif", "mp4", "webm"]
+                        if ["png", "jpg", "jpeg", "webp", "gif", "apng", "mp4", "webm"]
                             .contains(&ext.as_str()) =>
                     {
                         images_folder = Some(path.clone());
@@ -20,7 +20,7 @@ mod window_manager;
 use tauri::Manager;
 use theme::{get_color_theme, get_theme, init_theme, set_color_theme, set_theme};
 use tracing;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer};
 
 use window_manager::{
     layer_order_zoom_window::{
@@ -42,7 +42,7 @@ use window_manager::{
 };
 
 use filesystem::{
-    constants::StorageFiles, folderhash::*, persist::*, rarity::*, rename::*, temp_dir::*, utils::*,
+    folderhash::*, persist::*, projects::*, rarity::*, rename::*, temp_dir::*, utils::*,
 };
 
 use layerpreview::{
@@ -51,22 +51,36 @@ use layerpreview::{
         select_import::{
             check_animated::check_animated_images,
             get_layer_names::get_layer_image_names,
+            import_document::import_layered_document,
             select::{select_and_load_folder_data, select_folder},
         },
     },
     traitsandlayers::{
         base_dimensions::get_base_dimensions, image_dimensions::get_image_dimensions,
         image_path::get_layer_image_path, read_layers::read_layers, read_traits::read_traits,
-        spritesheet_path::get_spritesheet_image_path,
+        spritesheet_path::{get_spritesheet_image_path, get_trait_spritesheet_layout},
+    },
+    validation::{
+        lint::lint_layers,
+        validate::validate_and_reload_layers,
+        watcher::{unwatch_layer_folder, watch_layer_folder},
     },
-    validation::validate::validate_and_reload_layers,
 };
 
 use generation::{
     generate::pausecancel::{
         cancel_nft_generation, get_generation_status, toggle_generation_pause, WINDOW,
     },
+    contact_sheet::export_contact_sheets,
     generation_main::*,
+    job_queue::{
+        cancel_generation_job, clear_finished_generation_jobs, enqueue_generation_job,
+        get_generation_job, get_generation_job_log, is_job_event, list_generation_jobs,
+        move_generation_job, run_job_queue, JobLogWriter, JobQueue,
+    },
+    package::package_collection,
+    preview::render_preview,
+    simulate::simulate_generation,
 };
 
 use editmetadata::editmetadata::*;
@@ -85,6 +99,12 @@ fn main() {
                 .unwrap_or_else(|_| "trace".into()),
         )
         .with(tracing_subscriber::fmt::layer())
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(|| JobLogWriter)
+                .with_filter(tracing_subscriber::filter::dynamic_filter_fn(is_job_event)),
+        )
         .init();
 
     init_theme();
@@ -184,8 +204,18 @@ fn main() {
             get_rarity_data,
             save_storage_command,
             load_storage_command,
+            // Project commands
+            list_projects,
+            get_active_project,
+            create_project,
+            open_project,
+            duplicate_project,
+            delete_project,
             //
             rename_item,
+            set_display_name,
+            get_rename_journal,
+            undo_rename,
             is_folder_empty,
             get_image_dimensions,
             validate_rarity_config,
@@ -217,14 +247,19 @@ fn main() {
             // layerpreview
             select_folder,
             select_and_load_folder_data,
+            import_layered_document,
             select_export_folder,
             get_layer_image_names,
             get_layer_image_path,
             get_spritesheet_image_path,
+            get_trait_spritesheet_layout,
             check_animated_images,
             get_spritesheets_path,
             extract_frames,
             validate_and_reload_layers,
+            watch_layer_folder,
+            unwatch_layer_folder,
+            lint_layers,
             read_layers,
             read_traits,
             // nft generation
@@ -232,6 +267,17 @@ fn main() {
             cancel_nft_generation,
             toggle_generation_pause,
             get_generation_status,
+            render_preview,
+            simulate_generation,
+            export_contact_sheets,
+            package_collection,
+            enqueue_generation_job,
+            list_generation_jobs,
+            get_generation_job,
+            move_generation_job,
+            cancel_generation_job,
+            clear_finished_generation_jobs,
+            get_generation_job_log,
             get_spritesheet_metadata,
             // window communication
             emit_to_window,
@@ -241,17 +287,16 @@ fn main() {
 }
 
 async fn setup_app(app_handle: tauri::AppHandle) -> anyhow::Result<()> {
-    let storage_files = StorageFiles::new(&app_handle)
-        .map_err(|e| anyhow::anyhow!("Failed to create storage files: {}", e))?;
+    let registry = ProjectRegistry::load(&app_handle)
+        .map_err(|e| anyhow::anyhow!("Failed to load project registry: {}", e))?;
 
-    if !storage_files.check_files_exist() {
-        let config_dir = storage_files.get_config_dir();
-        tokio::fs::create_dir_all(&config_dir)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to create config directory: {}", e))?;
-    }
+    app_handle.manage(registry);
+
+    let job_queue = JobQueue::load(&app_handle)
+        .map_err(|e| anyhow::anyhow!("Failed to load generation job queue: {}", e))?;
 
-    app_handle.manage(storage_files);
+    app_handle.manage(job_queue);
+    tauri::async_runtime::spawn(run_job_queue(app_handle.clone()));
 
     if let Err(e) = cleanup_old_temp_dirs() {
         tracing::warn!("Failed to cleanup old temp directories: {}", e);
@@ -24,6 +24,15 @@ pub struct ProjectSetupState {
     pub spritesheet_layout: Option<SpritesheetLayout>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectInfo {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+    pub last_opened_at: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Preferences {
     pub dark_mode: bool,
@@ -47,6 +56,9 @@ pub struct ImageSetupState {
     pub solana_config: Option<SolanaMetadataConfig>,
     pub animation_quality: Option<AnimationQualityConfig>,
     pub resize_config: Option<ResizeConfig>,
+    /// Archive written after each run of the project.
+    #[serde(default)]
+    pub packaging: PackagingConfig,
 }
 
 impl Default for ImageSetupState {
@@ -65,6 +77,7 @@ impl Default for ImageSetupState {
             solana_config: Some(SolanaMetadataConfig::default()),
             animation_quality: Some(AnimationQualityConfig::default()),
             resize_config: Some(ResizeConfig::default()),
+            packaging: PackagingConfig::default(),
         }
     }
 }
@@ -88,6 +101,25 @@ pub struct SetConfig {
 #[serde(rename_all = "camelCase")]
 pub struct TraitConfig {
     pub sets: HashMap<String, SetConfig>,
+    /// Weights of the `Name#Variant` files of this trait. Variants without a
+    /// weight are picked uniformly.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub variants: HashMap<String, f32>,
+    /// Value written in metadata instead of the file name. Renaming it never
+    /// touches the files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// Display names per locale, e.g. `fr` -> `Chapeau rouge`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub localized_names: HashMap<String, String>,
+}
+
+/// Rarity weight of a trait subfolder per set. The weight is shared among the
+/// traits and subgroups of the folder in proportion to their own values.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupConfig {
+    pub sets: HashMap<String, f32>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
@@ -98,6 +130,45 @@ pub struct LayerSetConfig {
     pub include_in_metadata: Option<bool>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum PlaybackMode {
+    Loop,
+    PingPong,
+    HoldLastFrame,
+}
+
+impl Default for PlaybackMode {
+    fn default() -> Self {
+        Self::Loop
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LayerPlayback {
+    #[serde(default)]
+    pub mode: PlaybackMode,
+    #[serde(default)]
+    pub start_offset: u32,
+    #[serde(default = "default_playback_speed")]
+    pub speed: f32,
+}
+
+fn default_playback_speed() -> f32 {
+    1.0
+}
+
+impl Default for LayerPlayback {
+    fn default() -> Self {
+        Self {
+            mode: PlaybackMode::Loop,
+            start_offset: 0,
+            speed: default_playback_speed(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct LayerConfig {
@@ -106,6 +177,29 @@ pub struct LayerConfig {
     pub locked: Option<bool>,
     pub traits: HashMap<String, TraitConfig>,
     pub default_blend: BlendProperties,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub playback: Option<LayerPlayback>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub groups: HashMap<String, GroupConfig>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub parts: HashMap<String, TraitPartConfig>,
+    /// `trait_type` written in metadata instead of the layer folder name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trait_type: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub localized_trait_types: HashMap<String, String>,
+}
+
+/// Where the `Name__part` files of a layer are drawn. A part goes directly
+/// below or above the anchor layer; without an anchor, `back` parts sit just
+/// below their own layer and every other part just above it.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TraitPartConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub below_layer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub above_layer: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -193,6 +287,19 @@ pub struct ForcedCombinationsBySets {
 pub struct NFTTrait {
     pub trait_type: String,
     pub value: String,
+    /// File key of the chosen variant, e.g. `Rare/Crown#Gold`, when it differs
+    /// from `value`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Linked parts of the chosen file, e.g. `back` for `Long__back.png`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parts: Vec<String>,
+}
+
+impl NFTTrait {
+    pub fn file_key(&self) -> &str {
+        self.file.as_deref().unwrap_or(&self.value)
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -234,6 +341,96 @@ pub struct NFTGenerationArgs {
     pub resize_config: Option<ResizeConfig>,
     pub total_frames_count: Option<u32>,
     pub spritesheet_layout: Option<SpritesheetLayout>,
+    /// Locale of the display names written in metadata.
+    #[serde(default)]
+    pub metadata_locale: Option<String>,
+    /// Starts the run even when `lint_layers` reports errors.
+    #[serde(default)]
+    pub ignore_lint_errors: bool,
+    /// Continues the run stopped in the export folder instead of clearing it.
+    #[serde(default)]
+    pub resume: bool,
+    #[serde(default)]
+    pub failure_policy: FailurePolicy,
+    #[serde(default)]
+    pub resource_limits: ResourceLimits,
+    #[serde(default)]
+    pub packaging: PackagingConfig,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum PackageFormat {
+    #[default]
+    Zip,
+    TarZst,
+}
+
+/// Archive of the collection folder written after a run.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PackagingConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub format: PackageFormat,
+    /// Splits the archive into volumes of at most this size.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_volume_mb: Option<u64>,
+}
+
+/// Worker counts and memory budgets of a run, picked from the machine when unset.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceLimits {
+    pub generation_workers: Option<u32>,
+    pub save_workers: Option<u32>,
+    pub memory_budget_mb: Option<u32>,
+    /// GPU memory items may hold at once, unbounded when unset.
+    pub vram_budget_mb: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum FailureMode {
+    /// Renders the same traits again.
+    #[default]
+    Retry,
+    /// Selects a new combination for the same index.
+    Replace,
+    /// Stops the run at the first failed item.
+    FailFast,
+}
+
+/// What generation does when an item errors or times out.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FailurePolicy {
+    #[serde(default)]
+    pub mode: FailureMode,
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// Delay before the second attempt, doubled for each one after it.
+    #[serde(default = "default_backoff_ms")]
+    pub backoff_ms: u64,
+}
+
+fn default_max_attempts() -> u32 {
+    3
+}
+
+fn default_backoff_ms() -> u64 {
+    500
+}
+
+impl Default for FailurePolicy {
+    fn default() -> Self {
+        Self {
+            mode: FailureMode::Retry,
+            max_attempts: default_max_attempts(),
+            backoff_ms: default_backoff_ms(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -542,6 +739,21 @@ impl Default for WebMSettings {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct APNGSettings {
+    pub interpolation: AnimationInterpolationSettings,
+    pub autoloop: bool,
+}
+
+impl Default for APNGSettings {
+    fn default() -> Self {
+        Self {
+            interpolation: AnimationInterpolationSettings::default(),
+            autoloop: true,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct AnimationQualityConfig {
@@ -558,6 +770,20 @@ impl Default for AnimationQualityConfig {
     }
 }
 
+impl AnimationQualityConfig {
+    pub fn interpolation_for(&self, format: &str) -> Option<&AnimationInterpolationSettings> {
+        let settings = &self.format_specific_settings;
+        match format {
+            "gif" => Some(&settings.gif.interpolation),
+            "webp" => Some(&settings.webp.interpolation),
+            "mp4" => Some(&settings.mp4.interpolation),
+            "webm" => Some(&settings.webm.interpolation),
+            "apng" => Some(&settings.apng.interpolation),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct FormatSpecificSettings {
@@ -565,6 +791,8 @@ pub struct FormatSpecificSettings {
     pub gif: GIFSettings,
     pub mp4: MP4Settings,
     pub webm: WebMSettings,
+    #[serde(default)]
+    pub apng: APNGSettings,
 }
 
 impl Default for FormatSpecificSettings {
@@ -574,6 +802,7 @@ impl Default for FormatSpecificSettings {
             gif: GIFSettings::default(),
             mp4: MP4Settings::default(),
             webm: WebMSettings::default(),
+            apng: APNGSettings::default(),
         }
     }
 }
@@ -588,6 +817,16 @@ pub struct SpritesheetLayout {
     pub total_sheets: u32,
     pub frames_per_sheet: u32,
     pub total_frames: u32,
+    /// Part of the frame every cell holds. Cells of layouts saved before
+    /// cells were trimmed hold the whole frame and leave these at 0.
+    #[serde(default)]
+    pub cell_x: u32,
+    #[serde(default)]
+    pub cell_y: u32,
+    #[serde(default)]
+    pub cell_width: u32,
+    #[serde(default)]
+    pub cell_height: u32,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::{sync::Mutex, thread, time::Duration};
 use tauri::{Emitter, Manager, Runtime, Theme, WebviewUrl, WebviewWindow, WindowEvent};
 
-use crate::filesystem::{constants::StorageFiles, storage::load_storage};
+use crate::filesystem::{projects::ProjectRegistry, storage::load_storage};
 use crate::types::Preferences;
 use crate::window_manager::screen_utils::get_secondary_screen_config;
 
@@ -50,7 +50,7 @@ pub async fn open_layer_order_zoom_window<R: Runtime>(
 ) -> Result<(), String> {
     let layer_order_zoom_window_id = LAYER_ORDER_ZOOM_WINDOW.get_or_init(|| Mutex::new(None));
 
-    let storage_files = app_handle.state::<StorageFiles>();
+    let storage_files = app_handle.state::<ProjectRegistry>().storage_files();
 
     let preferences = load_storage::<Preferences>(&storage_files.preferences)
         .await
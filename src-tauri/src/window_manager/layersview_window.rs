@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
 use tauri::{Emitter, Manager, Runtime, Theme, WebviewUrl, WebviewWindow, WindowEvent};
 
-use crate::filesystem::{constants::StorageFiles, storage};
+use crate::filesystem::{projects::ProjectRegistry, storage};
 use crate::types::Preferences;
 use crate::window_manager::screen_utils::get_secondary_screen_config;
 
@@ -22,7 +22,7 @@ pub async fn open_layersview_window<R: Runtime>(
 ) -> Result<(), String> {
     let layersview_window_id = LAYERSVIEW_WINDOW.get_or_init(|| Mutex::new(None));
 
-    let storage_files = app_handle.state::<StorageFiles>();
+    let storage_files = app_handle.state::<ProjectRegistry>().storage_files();
 
     let preferences = storage::load_storage::<Preferences>(&storage_files.preferences)
         .await
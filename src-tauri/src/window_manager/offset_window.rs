@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::{sync::Mutex, thread, time::Duration};
 use tauri::{Emitter, Manager, Runtime, Theme, WebviewUrl, WebviewWindow, WindowEvent};
 
-use crate::filesystem::{constants::StorageFiles, storage::load_storage};
+use crate::filesystem::{projects::ProjectRegistry, storage::load_storage};
 use crate::types::Preferences;
 use crate::window_manager::screen_utils::calculate_center_position;
 
@@ -27,7 +27,7 @@ pub async fn open_offset_window<R: Runtime>(
 ) -> Result<(), String> {
     let offset_window_id = OFFSET_WINDOW.get_or_init(|| Mutex::new(None));
 
-    let storage_files = app_handle.state::<StorageFiles>();
+    let storage_files = app_handle.state::<ProjectRegistry>().storage_files();
 
     let preferences = load_storage::<Preferences>(&storage_files.preferences)
         .await
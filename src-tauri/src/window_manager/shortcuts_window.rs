@@ -2,7 +2,7 @@ use once_cell::sync::OnceCell;
 use std::sync::Mutex;
 use tauri::{Emitter, Manager, Runtime, Theme, WebviewUrl, WebviewWindow, WindowEvent};
 
-use crate::filesystem::{constants::StorageFiles, storage::load_storage};
+use crate::filesystem::{projects::ProjectRegistry, storage::load_storage};
 use crate::types::Preferences;
 use crate::window_manager::screen_utils::get_secondary_screen_config;
 
@@ -14,7 +14,7 @@ pub async fn open_shortcuts_window<R: Runtime>(
 ) -> Result<(), String> {
     let shortcuts_window_id = SHORTCUTS_WINDOW.get_or_init(|| Mutex::new(None));
 
-    let storage_files = app_handle.state::<StorageFiles>();
+    let storage_files = app_handle.state::<ProjectRegistry>().storage_files();
 
     let preferences = load_storage::<Preferences>(&storage_files.preferences)
         .await